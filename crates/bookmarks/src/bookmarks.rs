@@ -0,0 +1,424 @@
+use std::sync::Arc;
+
+use editor::{
+    actions::{LabelBookmark, SelectAll},
+    bookmarks::{BookmarkEntry, BookmarkStore},
+    scroll::Autoscroll,
+    Editor, EditorEvent, EditorMode, MultibufferSelectionMode,
+};
+use futures::future::join_all;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, div, rems, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    Render, SharedString, Subscription, Task, WeakEntity, Window,
+};
+use language::{Bias, Location, Point};
+use picker::{Picker, PickerDelegate};
+use theme::ActiveTheme;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(bookmarks, [Toggle]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(BookmarksView::register).detach();
+    cx.observe_new(BookmarkLabelPrompt::register).detach();
+}
+
+/// Lists the bookmarks of all files in the workspace.
+pub struct BookmarksView {
+    picker: Entity<Picker<BookmarksViewDelegate>>,
+}
+
+impl ModalView for BookmarksView {}
+
+impl EventEmitter<DismissEvent> for BookmarksView {}
+
+impl Focusable for BookmarksView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for BookmarksView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl BookmarksView {
+    fn register(workspace: &mut Workspace, _: Option<&mut Window>, _: &mut Context<Workspace>) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            let Some(workspace_id) = workspace.database_id() else {
+                return;
+            };
+            let Some(store) = BookmarkStore::global(cx) else {
+                return;
+            };
+            let bookmarks = store.read(cx).workspace_bookmarks(workspace_id, cx);
+            let workspace_handle = cx.entity().downgrade();
+            workspace.toggle_modal(window, cx, |window, cx| {
+                BookmarksView::new(workspace_handle, bookmarks, window, cx)
+            });
+        });
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        bookmarks: Vec<BookmarkEntry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let project = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone());
+        let candidates = bookmarks
+            .iter()
+            .enumerate()
+            .map(|(id, bookmark)| {
+                // Show paths relative to their worktree, like the rest of the workspace.
+                let path = project
+                    .as_ref()
+                    .and_then(|project| project.read(cx).find_project_path(&bookmark.abs_path, cx))
+                    .map_or_else(
+                        || bookmark.abs_path.to_string_lossy().to_string(),
+                        |project_path| project_path.path.to_string_lossy().to_string(),
+                    );
+                let location = format!("{path}:{}", bookmark.row + 1);
+                let string = match &bookmark.label {
+                    Some(label) => format!("{label} {location}"),
+                    None => location,
+                };
+                StringMatchCandidate::new(id, &string)
+            })
+            .collect();
+
+        let delegate = BookmarksViewDelegate {
+            bookmarks_view: cx.entity().downgrade(),
+            workspace,
+            bookmarks,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| {
+            Picker::uniform_list(delegate, window, cx).max_height(Some(vh(0.75, window)))
+        });
+        Self { picker }
+    }
+}
+
+struct BookmarksViewDelegate {
+    bookmarks_view: WeakEntity<BookmarksView>,
+    workspace: WeakEntity<Workspace>,
+    bookmarks: Vec<BookmarkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for BookmarksViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    /// Opens the selected bookmark, or all matching bookmarks in a multibuffer when `secondary`.
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        if secondary {
+            let bookmarks = self
+                .matches
+                .iter()
+                .map(|mat| self.bookmarks[mat.candidate_id].clone())
+                .collect::<Vec<_>>();
+            if bookmarks.is_empty() {
+                return;
+            }
+            open_bookmarks_in_multibuffer(workspace, bookmarks, window, cx);
+        } else {
+            let Some(mat) = self.matches.get(self.selected_index) else {
+                return;
+            };
+            let bookmark = self.bookmarks[mat.candidate_id].clone();
+            open_bookmark(workspace, bookmark, window, cx);
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.bookmarks_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Book).color(Color::Accent))
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+fn open_bookmark(
+    workspace: Entity<Workspace>,
+    bookmark: BookmarkEntry,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    workspace.update(cx, |workspace, cx| {
+        let open_task = workspace.open_abs_path(bookmark.abs_path, true, window, cx);
+        cx.spawn_in(window, |_, mut cx| async move {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update_in(&mut cx, |editor, window, cx| {
+                    let position = Point::new(bookmark.row, 0);
+                    editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    });
+}
+
+fn open_bookmarks_in_multibuffer(
+    workspace: Entity<Workspace>,
+    bookmarks: Vec<BookmarkEntry>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    workspace.update(cx, |workspace, cx| {
+        let project = workspace.project().clone();
+        let buffer_tasks = bookmarks
+            .into_iter()
+            .map(|bookmark| {
+                let buffer = project.update(cx, |project, cx| {
+                    project.open_local_buffer(&bookmark.abs_path, cx)
+                });
+                async move { anyhow::Ok((buffer.await?, bookmark.row)) }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let buffers = join_all(buffer_tasks)
+                .await
+                .into_iter()
+                .filter_map(|buffer| buffer.log_err())
+                .collect::<Vec<_>>();
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let locations = buffers
+                    .into_iter()
+                    .map(|(buffer, row)| {
+                        let snapshot = buffer.read(cx).snapshot();
+                        let start = snapshot.clip_point(Point::new(row, 0), Bias::Left);
+                        let end = Point::new(start.row, snapshot.line_len(start.row));
+                        let range = snapshot.anchor_before(start)..snapshot.anchor_after(end);
+                        Location { buffer, range }
+                    })
+                    .collect::<Vec<_>>();
+                if !locations.is_empty() {
+                    Editor::open_locations_in_multibuffer(
+                        workspace,
+                        locations,
+                        "Bookmarks".to_string(),
+                        false,
+                        MultibufferSelectionMode::First,
+                        window,
+                        cx,
+                    );
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    });
+}
+
+/// Prompts for the label of the bookmark on the line of the cursor.
+pub struct BookmarkLabelPrompt {
+    label_editor: Entity<Editor>,
+    active_editor: Entity<Editor>,
+    _subscription: Subscription,
+}
+
+impl ModalView for BookmarkLabelPrompt {}
+
+impl EventEmitter<DismissEvent> for BookmarkLabelPrompt {}
+
+impl Focusable for BookmarkLabelPrompt {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.label_editor.focus_handle(cx)
+    }
+}
+
+impl BookmarkLabelPrompt {
+    fn register(editor: &mut Editor, _: Option<&mut Window>, cx: &mut Context<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let handle = cx.entity().downgrade();
+        editor
+            .register_action(move |_: &LabelBookmark, window, cx| {
+                let Some(editor) = handle.upgrade() else {
+                    return;
+                };
+                let Some(workspace) = editor.read(cx).workspace() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        BookmarkLabelPrompt::new(editor, window, cx)
+                    });
+                })
+            })
+            .detach();
+    }
+
+    fn new(active_editor: Entity<Editor>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let label = active_editor
+            .update(cx, |editor, cx| editor.bookmark_at_cursor(cx))
+            .and_then(|(_, label)| label);
+        let label_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Bookmark label", cx);
+            if let Some(label) = label {
+                editor.set_text(label, window, cx);
+                editor.select_all(&SelectAll, window, cx);
+            }
+            editor
+        });
+        let subscription = cx.subscribe_in(&label_editor, window, |_, _, event, _, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            label_editor,
+            active_editor,
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let label = self.label_editor.read(cx).text(cx);
+        let label = label.trim();
+        let label = (!label.is_empty()).then(|| SharedString::from(label.to_string()));
+        self.active_editor.update(cx, |editor, cx| {
+            editor.set_bookmark_label(label, cx);
+            window.focus(&editor.focus_handle(cx));
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for BookmarkLabelPrompt {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("BookmarkLabelPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.label_editor.clone()),
+            )
+            .child(
+                h_flex().px_2().py_1().child(
+                    Label::new(
+                        "Label the bookmark on this line, or leave empty to remove the label",
+                    )
+                    .color(Color::Muted),
+                ),
+            )
+    }
+}
@@ -69,6 +69,12 @@ pub struct DiffHunk {
     pub diff_base_byte_range: Range<usize>,
     pub secondary_status: DiffHunkSecondaryStatus,
     pub secondary_diff_base_byte_range: Option<Range<usize>>,
+    /// The spans within [`Self::buffer_range`] that changed, for modified hunks small enough
+    /// to be diffed word by word.
+    pub buffer_word_diffs: Arc<[Range<Anchor>]>,
+    /// The spans within [`Self::diff_base_byte_range`] that changed, for modified hunks small
+    /// enough to be diffed word by word.
+    pub base_word_diffs: Arc<[Range<usize>]>,
 }
 
 /// We store [`InternalDiffHunk`]s internally so we don't need to store the additional row range.
@@ -76,6 +82,8 @@ pub struct DiffHunk {
 struct InternalDiffHunk {
     buffer_range: Range<Anchor>,
     diff_base_byte_range: Range<usize>,
+    buffer_word_diffs: Arc<[Range<Anchor>]>,
+    base_word_diffs: Arc<[Range<usize>]>,
 }
 
 impl sum_tree::Item for InternalDiffHunk {
@@ -291,11 +299,15 @@ impl BufferDiffInner {
             [
                 (
                     &hunk.buffer_range.start,
-                    (hunk.buffer_range.start, hunk.diff_base_byte_range.start),
+                    (
+                        hunk.buffer_range.start,
+                        hunk.diff_base_byte_range.start,
+                        hunk,
+                    ),
                 ),
                 (
                     &hunk.buffer_range.end,
-                    (hunk.buffer_range.end, hunk.diff_base_byte_range.end),
+                    (hunk.buffer_range.end, hunk.diff_base_byte_range.end, hunk),
                 ),
             ]
         });
@@ -308,8 +320,8 @@ impl BufferDiffInner {
 
         let mut summaries = buffer.summaries_for_anchors_with_payload::<Point, _, _>(anchor_iter);
         iter::from_fn(move || loop {
-            let (start_point, (start_anchor, start_base)) = summaries.next()?;
-            let (mut end_point, (mut end_anchor, end_base)) = summaries.next()?;
+            let (start_point, (start_anchor, start_base, hunk)) = summaries.next()?;
+            let (mut end_point, (mut end_anchor, end_base, _)) = summaries.next()?;
 
             if !start_anchor.is_valid(buffer) {
                 continue;
@@ -353,6 +365,8 @@ impl BufferDiffInner {
                 buffer_range: start_anchor..end_anchor,
                 secondary_status,
                 secondary_diff_base_byte_range,
+                buffer_word_diffs: hunk.buffer_word_diffs.clone(),
+                base_word_diffs: hunk.base_word_diffs.clone(),
             });
         })
     }
@@ -388,6 +402,8 @@ impl BufferDiffInner {
                 // The secondary status is not used by callers of this method.
                 secondary_status: DiffHunkSecondaryStatus::None,
                 secondary_diff_base_byte_range: None,
+                buffer_word_diffs: hunk.buffer_word_diffs.clone(),
+                base_word_diffs: hunk.base_word_diffs.clone(),
             })
         })
     }
@@ -484,6 +500,8 @@ fn compute_hunks(
                 InternalDiffHunk {
                     buffer_range: buffer.anchor_before(0)..buffer.anchor_before(0),
                     diff_base_byte_range: 0..diff_base.len() - 1,
+                    buffer_word_diffs: Arc::default(),
+                    base_word_diffs: Arc::default(),
                 },
                 &buffer,
             );
@@ -493,13 +511,14 @@ fn compute_hunks(
         if let Some(patch) = patch {
             let mut divergence = 0;
             for hunk_index in 0..patch.num_hunks() {
-                let hunk = process_patch_hunk(
+                let mut hunk = process_patch_hunk(
                     &patch,
                     hunk_index,
                     &diff_base_rope,
                     &buffer,
                     &mut divergence,
                 );
+                compute_word_diffs(&mut hunk, &diff_base, &buffer_text, &buffer);
                 tree.push(hunk, &buffer);
             }
         }
@@ -581,9 +600,43 @@ fn process_patch_hunk(
     InternalDiffHunk {
         buffer_range,
         diff_base_byte_range,
+        buffer_word_diffs: Arc::default(),
+        base_word_diffs: Arc::default(),
     }
 }
 
+/// Finds the changed words within a modified hunk, so that small edits within a line can be
+/// highlighted more precisely than the lines containing them.
+fn compute_word_diffs(
+    hunk: &mut InternalDiffHunk,
+    diff_base: &str,
+    buffer_text: &str,
+    buffer: &text::BufferSnapshot,
+) {
+    let buffer_byte_range = hunk.buffer_range.to_offset(buffer);
+    let Some((base_word_diffs, buffer_word_diffs)) = language::word_diff_ranges(
+        &diff_base[hunk.diff_base_byte_range.clone()],
+        &buffer_text[buffer_byte_range.clone()],
+        language::DiffOptions::default(),
+    ) else {
+        return;
+    };
+
+    let base_start = hunk.diff_base_byte_range.start;
+    hunk.base_word_diffs = base_word_diffs
+        .into_iter()
+        .map(|range| base_start + range.start..base_start + range.end)
+        .collect();
+    let buffer_start = buffer_byte_range.start;
+    hunk.buffer_word_diffs = buffer_word_diffs
+        .into_iter()
+        .map(|range| {
+            buffer.anchor_before(buffer_start + range.start)
+                ..buffer.anchor_after(buffer_start + range.end)
+        })
+        .collect();
+}
+
 impl std::fmt::Debug for BufferDiff {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BufferChangeSet")
@@ -680,6 +733,8 @@ impl BufferDiff {
             InternalDiffHunk {
                 buffer_range: Anchor::MIN..Anchor::MAX,
                 diff_base_byte_range: 0..0,
+                buffer_word_diffs: Arc::default(),
+                base_word_diffs: Arc::default(),
             },
             &base_text,
         );
@@ -876,8 +931,9 @@ impl BufferDiff {
             let Some(this) = this.upgrade() else {
                 return;
             };
-            this.update(&mut cx, |this, _| {
-                this.set_state(snapshot, &buffer);
+            this.update(&mut cx, |this, cx| {
+                let changed_range = this.set_state(snapshot, &buffer);
+                cx.emit(BufferDiffEvent::DiffChanged { changed_range });
             })
             .log_err();
             drop(complete_on_drop)
@@ -1113,6 +1169,50 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_buffer_diff_word_diffs(cx: &mut gpui::TestAppContext) {
+        let diff_base = "
+            fn main() {
+                let count = 1;
+                println!(\"{count}\");
+            }
+        "
+        .unindent();
+
+        let buffer_text = "
+            fn main() {
+                let total = 1;
+                println!(\"{total}\");
+            }
+            fn other() {}
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text);
+        let diff = BufferDiff::build_sync(buffer.clone(), diff_base.clone(), cx);
+        let hunks = diff
+            .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &buffer, None)
+            .collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 2);
+
+        let base_words = hunks[0]
+            .base_word_diffs
+            .iter()
+            .map(|range| &diff_base[range.clone()])
+            .collect::<Vec<_>>();
+        let buffer_words = hunks[0]
+            .buffer_word_diffs
+            .iter()
+            .map(|range| buffer.text_for_range(range.clone()).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(base_words, ["count", "count"]);
+        assert_eq!(buffer_words, ["total", "total"]);
+
+        // Pure additions are highlighted as whole lines.
+        assert!(hunks[1].base_word_diffs.is_empty());
+        assert!(hunks[1].buffer_word_diffs.is_empty());
+    }
+
     #[gpui::test]
     async fn test_buffer_diff_with_secondary(cx: &mut gpui::TestAppContext) {
         let head_text = "
@@ -0,0 +1,229 @@
+use std::cmp::Reverse;
+
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Editor, EditorMode, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT};
+use gpui::{actions, App, AppContext as _, Context, Entity, Task, Window};
+use hierarchy_view::{HierarchyDelegate, HierarchySymbol, HierarchyView};
+use language::OffsetRangeExt as _;
+use project::{CallHierarchyCall, Location, Project};
+
+actions!(call_hierarchy, [ShowIncomingCalls, ShowOutgoingCalls]);
+
+pub type CallHierarchyView = HierarchyView<CallHierarchyDelegate>;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(register).detach();
+}
+
+fn register(editor: &mut Editor, _: Option<&mut Window>, cx: &mut Context<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+
+    let handle = cx.entity().downgrade();
+    editor
+        .register_action({
+            let handle = handle.clone();
+            move |_: &ShowIncomingCalls, window, cx| {
+                if let Some(editor) = handle.upgrade() {
+                    deploy(editor, CallHierarchyDirection::Incoming, window, cx);
+                }
+            }
+        })
+        .detach();
+    editor
+        .register_action(move |_: &ShowOutgoingCalls, window, cx| {
+            if let Some(editor) = handle.upgrade() {
+                deploy(editor, CallHierarchyDirection::Outgoing, window, cx);
+            }
+        })
+        .detach();
+}
+
+fn deploy(
+    editor: Entity<Editor>,
+    direction: CallHierarchyDirection,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    hierarchy_view::deploy::<CallHierarchyDelegate>(
+        editor,
+        |project, buffer, position, cx| {
+            let prepare = project.prepare_call_hierarchy(buffer, position, cx);
+            // Roots aren't called from anywhere, so they have no call sites.
+            cx.background_spawn(async move {
+                Ok(prepare
+                    .await?
+                    .into_iter()
+                    .map(|item| CallHierarchyCall {
+                        item,
+                        call_sites: Vec::new(),
+                    })
+                    .collect())
+            })
+        },
+        move |project, window, cx| CallHierarchyDelegate::new(project, direction, window, cx),
+        window,
+        cx,
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallHierarchyDirection {
+    /// Show the callers of the symbol.
+    Incoming,
+    /// Show the functions called by the symbol.
+    Outgoing,
+}
+
+impl CallHierarchyDirection {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Incoming => "Incoming Calls",
+            Self::Outgoing => "Outgoing Calls",
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::Incoming => Self::Outgoing,
+            Self::Outgoing => Self::Incoming,
+        }
+    }
+}
+
+/// Loads the callers (or callees) of a symbol from the language server, and shows the call
+/// sites of the selected node in a multibuffer next to the tree.
+pub struct CallHierarchyDelegate {
+    project: Entity<Project>,
+    direction: CallHierarchyDirection,
+    call_sites: Entity<MultiBuffer>,
+    call_sites_editor: Entity<Editor>,
+}
+
+impl CallHierarchyDelegate {
+    pub fn new(
+        project: Entity<Project>,
+        direction: CallHierarchyDirection,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let capability = project.read(cx).capability();
+        let call_sites = cx.new(|_| MultiBuffer::new(capability));
+        let call_sites_editor = cx.new(|cx| {
+            Editor::for_multibuffer(call_sites.clone(), Some(project.clone()), true, window, cx)
+        });
+        Self {
+            project,
+            direction,
+            call_sites,
+            call_sites_editor,
+        }
+    }
+}
+
+impl HierarchyDelegate for CallHierarchyDelegate {
+    type Node = CallHierarchyCall;
+
+    fn direction_label(&self) -> &'static str {
+        self.direction.label()
+    }
+
+    fn toggle_direction(&mut self) {
+        self.direction = self.direction.opposite();
+    }
+
+    fn load_children(
+        &self,
+        call: &CallHierarchyCall,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.project.update(cx, |project, cx| match self.direction {
+            CallHierarchyDirection::Incoming => project.incoming_calls(&call.item, cx),
+            CallHierarchyDirection::Outgoing => project.outgoing_calls(&call.item, cx),
+        })
+    }
+
+    fn symbol(&self, call: &CallHierarchyCall, _: &App) -> HierarchySymbol {
+        HierarchySymbol {
+            name: call.item.name().to_string().into(),
+            detail: call.item.lsp_item.detail.clone().map(Into::into),
+            buffer: call.item.buffer.clone(),
+            selection_range: call.item.selection_range.clone(),
+        }
+    }
+
+    fn key_context(&self) -> &'static str {
+        "CallHierarchy"
+    }
+
+    fn telemetry_event_text(&self) -> &'static str {
+        "Call Hierarchy Opened"
+    }
+
+    fn clone_on_split(&self, window: &mut Window, cx: &mut App) -> Self {
+        Self::new(self.project.clone(), self.direction, window, cx)
+    }
+
+    /// Replaces the contents of the call sites multibuffer with the call sites of the selected
+    /// node. Roots have no call sites, so their definition is shown instead.
+    fn selected_node_changed(
+        &mut self,
+        call: &CallHierarchyCall,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let mut locations = if call.call_sites.is_empty() {
+            vec![Location {
+                buffer: call.item.buffer.clone(),
+                range: call.item.selection_range.clone(),
+            }]
+        } else {
+            call.call_sites.clone()
+        };
+        locations.sort_by_key(|location| location.buffer.read(cx).remote_id());
+
+        let ranges = self.call_sites.update(cx, |multibuffer, cx| {
+            multibuffer.clear(cx);
+            let mut ranges = Vec::new();
+            let mut locations = locations.into_iter().peekable();
+            while let Some(location) = locations.next() {
+                let buffer = location.buffer.read(cx);
+                let mut ranges_for_buffer = vec![location.range.to_offset(buffer)];
+                while let Some(next_location) = locations.peek() {
+                    if next_location.buffer == location.buffer {
+                        ranges_for_buffer.push(next_location.range.to_offset(buffer));
+                        locations.next();
+                    } else {
+                        break;
+                    }
+                }
+                ranges_for_buffer.sort_by_key(|range| (range.start, Reverse(range.end)));
+                ranges.extend(multibuffer.push_excerpts_with_context_lines(
+                    location.buffer.clone(),
+                    ranges_for_buffer,
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                ));
+            }
+            ranges
+        });
+
+        self.call_sites_editor.update(cx, |editor, cx| {
+            editor.highlight_background::<Self>(
+                &ranges,
+                |theme| theme.editor_highlighted_line_background,
+                cx,
+            );
+            if let Some(first_range) = ranges.first() {
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |selections| {
+                    selections.select_anchor_ranges([first_range.start..first_range.start]);
+                });
+            }
+        });
+    }
+
+    fn details_editor(&self) -> Option<&Entity<Editor>> {
+        Some(&self.call_sites_editor)
+    }
+}
@@ -5153,6 +5153,263 @@ async fn test_document_highlights(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_call_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn a() { b() }\nfn b() { c() }\nfn c() {}\n",
+            }),
+        )
+        .await;
+
+    client_a.language_registry().add(rust_lang());
+    let mut fake_language_servers = client_a.language_registry().register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    // Open the file on client B.
+    let (buffer_b, _handle) = project_b
+        .update(cx_b, |p, cx| {
+            p.open_buffer_with_lsp((worktree_id, "main.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn function_item(name: &str, row: u32, len: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.into(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/root-1/main.rs").unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, len)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, 3),
+                lsp::Position::new(row, 4),
+            ),
+            data: None,
+        }
+    }
+
+    // Request the call hierarchy as the guest.
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    fake_language_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 3)
+            );
+            Ok(Some(vec![function_item("b", 1, 14)]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: function_item("a", 0, 14),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 9),
+                    lsp::Position::new(0, 10),
+                )],
+            }]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: function_item("c", 2, 9),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(1, 9),
+                    lsp::Position::new(1, 10),
+                )],
+            }]))
+        },
+    );
+
+    let items = project_b
+        .update(cx_b, |p, cx| p.prepare_call_hierarchy(&buffer_b, 18, cx))
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.into_iter().next().unwrap();
+    assert_eq!(item.name(), "b");
+    assert_eq!(item.buffer, buffer_b);
+
+    let incoming = project_b
+        .update(cx_b, |p, cx| p.incoming_calls(&item, cx))
+        .await
+        .unwrap();
+    let outgoing = project_b
+        .update(cx_b, |p, cx| p.outgoing_calls(&item, cx))
+        .await
+        .unwrap();
+    buffer_b.read_with(cx_b, |buffer, _| {
+        let summary = |calls: Vec<project::CallHierarchyCall>| {
+            calls
+                .into_iter()
+                .map(|call| {
+                    let call_sites = call
+                        .call_sites
+                        .iter()
+                        .map(|site| site.range.to_offset(buffer))
+                        .collect::<Vec<_>>();
+                    (call.item.name().to_string(), call_sites)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(incoming), [("a".to_string(), vec![9..10])]);
+        assert_eq!(summary(outgoing), [("c".to_string(), vec![24..25])]);
+    });
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_type_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "trait Shape {}\nstruct Square;\nimpl Shape for Square {}\n",
+            }),
+        )
+        .await;
+
+    client_a.language_registry().add(rust_lang());
+    let mut fake_language_servers = client_a
+        .language_registry()
+        .register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    // Open the file on client B.
+    let (buffer_b, _handle) = project_b
+        .update(cx_b, |p, cx| {
+            p.open_buffer_with_lsp((worktree_id, "main.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn type_item(name: &str, row: u32, column: u32) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.into(),
+            kind: lsp::SymbolKind::STRUCT,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/root-1/main.rs").unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 14)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, column),
+                lsp::Position::new(row, column + name.len() as u32),
+            ),
+            data: None,
+        }
+    }
+
+    // Request the type hierarchy as the guest.
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    fake_language_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 7)
+            );
+            Ok(Some(vec![type_item("Square", 1, 7)]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Square");
+            Ok(Some(vec![type_item("Shape", 0, 6)]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Shape");
+            Ok(Some(vec![type_item("Square", 1, 7)]))
+        },
+    );
+
+    let items = project_b
+        .update(cx_b, |p, cx| p.prepare_type_hierarchy(&buffer_b, 22, cx))
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let square = items.into_iter().next().unwrap();
+    assert_eq!(square.name(), "Square");
+    assert_eq!(square.buffer, buffer_b);
+
+    let supertypes = project_b
+        .update(cx_b, |p, cx| p.supertypes(&square, cx))
+        .await
+        .unwrap();
+    let subtypes = project_b
+        .update(cx_b, |p, cx| p.subtypes(&supertypes[0], cx))
+        .await
+        .unwrap();
+    buffer_b.read_with(cx_b, |buffer, _| {
+        let summary = |items: Vec<project::TypeHierarchyItem>| {
+            items
+                .into_iter()
+                .map(|item| {
+                    (
+                        item.name().to_string(),
+                        item.selection_range.to_offset(buffer),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(supertypes), [("Shape".to_string(), 6..11)]);
+        assert_eq!(summary(subtypes), [("Square".to_string(), 22..28)]);
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_lsp_hover(
     executor: BackgroundExecutor,
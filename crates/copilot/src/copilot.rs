@@ -19,8 +19,8 @@ use http_client::HttpClient;
 use language::language_settings::CopilotSettings;
 use language::{
     language_settings::{all_language_settings, language_settings, EditPredictionProvider},
-    point_from_lsp, point_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, Language, PointUtf16,
-    ToPointUtf16,
+    point_to_lsp_encoded, range_from_lsp_encoded, Anchor, Bias, Buffer, BufferSnapshot, Language,
+    PointUtf16, ToPointUtf16,
 };
 use lsp::{LanguageServer, LanguageServerBinary, LanguageServerId, LanguageServerName};
 use node_runtime::NodeRuntime;
@@ -225,11 +225,12 @@ impl RegisteredBuffer {
             self.pending_buffer_change = cx.spawn(move |copilot, mut cx| async move {
                 prev_pending_change.await;
 
-                let old_version = copilot
+                let (old_snapshot, encoding) = copilot
                     .update(&mut cx, |copilot, _| {
                         let server = copilot.server.as_authenticated().log_err()?;
+                        let encoding = server.lsp.position_encoding();
                         let buffer = server.registered_buffers.get_mut(&id)?;
-                        Some(buffer.snapshot.version.clone())
+                        Some((buffer.snapshot.clone(), encoding))
                     })
                     .ok()??;
                 let new_snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot()).ok()?;
@@ -238,19 +239,36 @@ impl RegisteredBuffer {
                     .background_spawn({
                         let new_snapshot = new_snapshot.clone();
                         async move {
+                            let old_text = old_snapshot.as_rope();
                             new_snapshot
-                                .edits_since::<(PointUtf16, usize)>(&old_version)
+                                .edits_since::<(PointUtf16, usize)>(&old_snapshot.version)
                                 .map(|edit| {
-                                    let edit_start = edit.new.start.0;
-                                    let edit_end = edit_start + (edit.old.end.0 - edit.old.start.0);
+                                    let edit_start = point_to_lsp_encoded(
+                                        edit.new.start.0,
+                                        new_snapshot.as_rope(),
+                                        encoding,
+                                    );
+                                    let old_start =
+                                        point_to_lsp_encoded(edit.old.start.0, old_text, encoding);
+                                    let old_end =
+                                        point_to_lsp_encoded(edit.old.end.0, old_text, encoding);
+                                    let edit_end = if old_start.line == old_end.line {
+                                        lsp::Position::new(
+                                            edit_start.line,
+                                            edit_start.character + old_end.character
+                                                - old_start.character,
+                                        )
+                                    } else {
+                                        lsp::Position::new(
+                                            edit_start.line + old_end.line - old_start.line,
+                                            old_end.character,
+                                        )
+                                    };
                                     let new_text = new_snapshot
                                         .text_for_range(edit.new.start.1..edit.new.end.1)
                                         .collect();
                                     lsp::TextDocumentContentChangeEvent {
-                                        range: Some(lsp::Range::new(
-                                            point_to_lsp(edit_start),
-                                            point_to_lsp(edit_end),
-                                        )),
+                                        range: Some(lsp::Range::new(edit_start, edit_end)),
                                         range_length: None,
                                         text: new_text,
                                     }
@@ -911,6 +929,7 @@ impl Copilot {
 
         cx.background_spawn(async move {
             let (version, snapshot) = snapshot.await?;
+            let encoding = lsp.position_encoding();
             let result = lsp
                 .request::<R>(request::GetCompletionsParams {
                     doc: request::GetCompletionsDocument {
@@ -919,7 +938,7 @@ impl Copilot {
                         indent_size: 1,
                         insert_spaces: !hard_tabs,
                         relative_path: relative_path.to_string_lossy().into(),
-                        position: point_to_lsp(position),
+                        position: point_to_lsp_encoded(position, snapshot.as_rope(), encoding),
                         version: version.try_into().unwrap(),
                     },
                 })
@@ -928,10 +947,10 @@ impl Copilot {
                 .completions
                 .into_iter()
                 .map(|completion| {
-                    let start = snapshot
-                        .clip_point_utf16(point_from_lsp(completion.range.start), Bias::Left);
-                    let end =
-                        snapshot.clip_point_utf16(point_from_lsp(completion.range.end), Bias::Left);
+                    let range =
+                        range_from_lsp_encoded(completion.range, snapshot.as_rope(), encoding);
+                    let start = snapshot.clip_point_utf16(range.start, Bias::Left);
+                    let end = snapshot.clip_point_utf16(range.end, Bias::Left);
                     Completion {
                         uuid: completion.uuid,
                         range: snapshot.anchor_before(start)..snapshot.anchor_after(end),
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, Render, Task, WeakEntity, Window,
+};
+use language::{Buffer, Point};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{active_buffer, CompareWithOpenBuffer, DiffSide, DiffView};
+
+/// Lists the project's other open buffers, including unsaved ones, to compare the active file with.
+pub struct OpenBufferPicker {
+    picker: Entity<Picker<OpenBufferPickerDelegate>>,
+}
+
+impl ModalView for OpenBufferPicker {}
+
+impl EventEmitter<DismissEvent> for OpenBufferPicker {}
+
+impl Focusable for OpenBufferPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for OpenBufferPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl OpenBufferPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &CompareWithOpenBuffer,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(buffer) = active_buffer(workspace, cx) else {
+            return;
+        };
+        let buffers = workspace
+            .project()
+            .read(cx)
+            .buffer_store()
+            .read(cx)
+            .buffers()
+            .filter(|other| other != &buffer)
+            .collect::<Vec<_>>();
+
+        let workspace_handle = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            OpenBufferPicker::new(workspace_handle, buffer, buffers, window, cx)
+        });
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        buffer: Entity<Buffer>,
+        buffers: Vec<Entity<Buffer>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let candidates = buffers
+            .iter()
+            .enumerate()
+            .map(|(id, buffer)| StringMatchCandidate::new(id, &buffer_title(buffer, cx)))
+            .collect();
+        let delegate = OpenBufferPickerDelegate {
+            picker: cx.entity().downgrade(),
+            workspace,
+            buffer,
+            buffers,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+/// Returns the path of a buffer's file, or its first line if it has none.
+fn buffer_title(buffer: &Entity<Buffer>, cx: &App) -> String {
+    let buffer = buffer.read(cx);
+    match buffer.file() {
+        Some(file) => file.full_path(cx).to_string_lossy().into_owned(),
+        None => {
+            let first_line = buffer
+                .text_for_range(Point::zero()..Point::new(0, buffer.line_len(0)))
+                .collect::<String>();
+            let first_line = first_line.trim();
+            if first_line.is_empty() {
+                "untitled".to_string()
+            } else {
+                format!("untitled: {first_line}")
+            }
+        }
+    }
+}
+
+struct OpenBufferPickerDelegate {
+    picker: WeakEntity<OpenBufferPicker>,
+    workspace: WeakEntity<Workspace>,
+    buffer: Entity<Buffer>,
+    buffers: Vec<Entity<Buffer>>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for OpenBufferPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Compare with open buffer...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        "No other buffers are open".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let other_buffer = self.buffers[mat.candidate_id].clone();
+        let buffer = self.buffer.clone();
+        workspace.update(cx, |workspace, cx| {
+            let left = DiffSide::for_buffer(buffer, cx);
+            let right = DiffSide::for_buffer(other_buffer, cx);
+            DiffView::open(left, right, workspace, window, cx);
+        });
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
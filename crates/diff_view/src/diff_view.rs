@@ -0,0 +1,800 @@
+mod buffer_picker;
+
+use std::{any::TypeId, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use buffer_diff::BufferDiff;
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
+    Editor, EditorEvent,
+};
+use gpui::{
+    actions, AnyView, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    Hsla, PathPromptOptions, Render, Subscription, Task, Window,
+};
+use language::{language_settings::SoftWrap, Buffer, BufferEvent, Point, ToPoint as _};
+use project::{DirectoryLister, Project};
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use workspace::{
+    item::ItemEvent, searchable::SearchableItemHandle, Item, ItemNavHistory, Workspace,
+};
+
+use buffer_picker::OpenBufferPicker;
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+actions!(
+    diff_view,
+    [
+        CompareWithFile,
+        CompareWithOpenBuffer,
+        CompareWithClipboard,
+        MoveHunkToLeft,
+        MoveHunkToRight
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(compare_with_file)
+            .register_action(compare_with_clipboard)
+            .register_action(OpenBufferPicker::toggle);
+    })
+    .detach();
+}
+
+/// Compares the active file with a file chosen in a prompt.
+fn compare_with_file(
+    workspace: &mut Workspace,
+    _: &CompareWithFile,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(buffer) = active_buffer(workspace, cx) else {
+        return;
+    };
+    let project = workspace.project().clone();
+    let paths = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Project(project.clone()),
+        window,
+        cx,
+    );
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let Some(path) = paths
+            .await
+            .ok()
+            .flatten()
+            .and_then(|paths| paths.into_iter().next())
+        else {
+            return Ok(());
+        };
+        let other_buffer = project
+            .update(&mut cx, |project, cx| {
+                match project.find_project_path(&path, cx) {
+                    Some(project_path) => project.open_buffer(project_path, cx),
+                    None => project.open_local_buffer(&path, cx),
+                }
+            })?
+            .await?;
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let left = DiffSide::for_buffer(buffer, cx);
+            let right = DiffSide::for_buffer(other_buffer, cx);
+            DiffView::open(left, right, workspace, window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Compares the clipboard's text with the selection in the active editor, or with the whole file
+/// when nothing is selected.
+fn compare_with_clipboard(
+    workspace: &mut Workspace,
+    _: &CompareWithClipboard,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(clipboard_text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+        workspace.show_error(&anyhow!("The clipboard doesn't contain any text"), cx);
+        return;
+    };
+    let Some((buffer, selected_text)) = editor.update(cx, |editor, cx| {
+        let buffer = editor.buffer().read(cx).as_singleton()?;
+        let selection = editor.selections.newest::<usize>(cx);
+        let selected_text = (!selection.is_empty()).then(|| {
+            buffer
+                .read(cx)
+                .text_for_range(selection.range())
+                .collect::<String>()
+        });
+        Some((buffer, selected_text))
+    }) else {
+        return;
+    };
+
+    let left = DiffSide::scratch(clipboard_text, "Clipboard", &buffer, cx);
+    let right = match selected_text {
+        Some(selected_text) => {
+            let label = format!("Selection in {}", buffer_label(&buffer, cx));
+            DiffSide::scratch(selected_text, label, &buffer, cx)
+        }
+        None => DiffSide::for_buffer(buffer, cx),
+    };
+    DiffView::open(left, right, workspace, window, cx);
+}
+
+fn active_buffer(workspace: &Workspace, cx: &App) -> Option<Entity<Buffer>> {
+    workspace
+        .active_item_as::<Editor>(cx)?
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .as_singleton()
+}
+
+fn buffer_label(buffer: &Entity<Buffer>, cx: &App) -> SharedString {
+    match buffer.read(cx).file() {
+        Some(file) => file.file_name(cx).to_string_lossy().to_string().into(),
+        None => "untitled".into(),
+    }
+}
+
+/// One of the two texts shown in a [`DiffView`].
+pub struct DiffSide {
+    buffer: Entity<Buffer>,
+    label: SharedString,
+}
+
+impl DiffSide {
+    pub fn for_buffer(buffer: Entity<Buffer>, cx: &App) -> Self {
+        let label = buffer_label(&buffer, cx);
+        Self { buffer, label }
+    }
+
+    /// A side showing text that isn't in any file, highlighted in the language of `buffer`.
+    pub fn scratch(
+        text: String,
+        label: impl Into<SharedString>,
+        buffer: &Entity<Buffer>,
+        cx: &mut App,
+    ) -> Self {
+        let scratch_buffer = cx.new(|cx| {
+            let mut scratch_buffer = Buffer::local(text, cx);
+            if let Some(language_registry) = buffer.read(cx).language_registry() {
+                scratch_buffer.set_language_registry(language_registry);
+            }
+            scratch_buffer.set_language(buffer.read(cx).language().cloned(), cx);
+            scratch_buffer
+        });
+        Self {
+            buffer: scratch_buffer,
+            label: label.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Left,
+    Right,
+}
+
+impl Column {
+    fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+struct SideEditor {
+    buffer: Entity<Buffer>,
+    label: SharedString,
+    editor: Entity<Editor>,
+    padding_blocks: HashSet<CustomBlockId>,
+}
+
+/// A changed region, as ranges in the left and right buffers.
+#[derive(Clone, Debug)]
+struct AlignedHunk {
+    left: Range<language::Anchor>,
+    right: Range<language::Anchor>,
+}
+
+/// Shows two texts side by side, with their changed lines highlighted and padded so that the
+/// lines that stay the same line up, and keeps both sides scrolled to the same place.
+///
+/// The left text is the base of the diff. Both sides can be edited, and hunks can be moved from
+/// one side to the other.
+pub struct DiffView {
+    left: SideEditor,
+    right: SideEditor,
+    diff: Entity<BufferDiff>,
+    hunks: Vec<AlignedHunk>,
+    update_diff_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DiffView {
+    pub fn open(
+        left: DiffSide,
+        right: DiffSide,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let view = cx.new(|cx| DiffView::new(left, right, project, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+        view
+    }
+
+    pub fn new(
+        left: DiffSide,
+        right: DiffSide,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let diff = cx.new(|cx| BufferDiff::new(&right.buffer.read(cx).text_snapshot()));
+        let left = Self::new_side_editor(left, project.clone(), window, cx);
+        let right = Self::new_side_editor(right, project, window, cx);
+
+        let mut subscriptions = Vec::new();
+        for (side, column) in [(&left, Column::Left), (&right, Column::Right)] {
+            subscriptions.push(cx.subscribe(&side.buffer, Self::on_buffer_event));
+            subscriptions.push(cx.subscribe_in(
+                &side.editor,
+                window,
+                move |this, _, event: &EditorEvent, window, cx| {
+                    this.on_editor_event(column, event, window, cx)
+                },
+            ));
+        }
+
+        let mut this = Self {
+            left,
+            right,
+            diff,
+            hunks: Vec::new(),
+            update_diff_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.schedule_diff_update(None, cx);
+        this
+    }
+
+    fn new_side_editor(
+        side: DiffSide,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> SideEditor {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(side.buffer.clone(), Some(project), window, cx);
+            // Wrapped lines take up more rows on one side than on the other.
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor
+        });
+        SideEditor {
+            buffer: side.buffer,
+            label: side.label,
+            editor,
+            padding_blocks: HashSet::default(),
+        }
+    }
+
+    fn side(&self, column: Column) -> &SideEditor {
+        match column {
+            Column::Left => &self.left,
+            Column::Right => &self.right,
+        }
+    }
+
+    fn on_buffer_event(&mut self, _: Entity<Buffer>, event: &BufferEvent, cx: &mut Context<Self>) {
+        if let BufferEvent::Edited = event {
+            self.schedule_diff_update(Some(UPDATE_DEBOUNCE), cx);
+        }
+    }
+
+    fn on_editor_event(
+        &mut self,
+        column: Column,
+        event: &EditorEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            EditorEvent::ScrollPositionChanged { .. } => {
+                self.sync_scroll_position(column, window, cx)
+            }
+            EditorEvent::DirtyChanged | EditorEvent::Saved | EditorEvent::TitleChanged => {
+                cx.emit(event.clone())
+            }
+            _ => {}
+        }
+    }
+
+    /// Scrolls the other side to where the side in `column` is scrolled.
+    fn sync_scroll_position(
+        &mut self,
+        column: Column,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let position = self
+            .side(column)
+            .editor
+            .update(cx, |editor, cx| editor.scroll_position(cx));
+        // Setting the same position again would bounce the change back and forth.
+        self.side(column.other()).editor.update(cx, |editor, cx| {
+            if editor.scroll_position(cx) != position {
+                editor.set_scroll_position(position, window, cx);
+            }
+        });
+    }
+
+    fn schedule_diff_update(&mut self, delay: Option<Duration>, cx: &mut Context<Self>) {
+        self.update_diff_task = cx.spawn(|this, mut cx| async move {
+            if let Some(delay) = delay {
+                cx.background_executor().timer(delay).await;
+            }
+            let Ok((left_version, diff_updated)) = this.update(&mut cx, |this, cx| {
+                let left_version = this.left.buffer.read(cx).version();
+                let left_buffer = this.left.buffer.clone();
+                let right_snapshot = this.right.buffer.read(cx).text_snapshot();
+                let diff_updated = this.diff.update(cx, |diff, cx| {
+                    diff.set_base_text(left_buffer, right_snapshot, cx)
+                });
+                (left_version, diff_updated)
+            }) else {
+                return;
+            };
+            diff_updated.await.ok();
+            this.update(&mut cx, |this, cx| {
+                // The diff's offsets into the left text are only valid for the text it was
+                // computed from. If it changed since, another update is on its way.
+                if this.left.buffer.read(cx).version() == left_version {
+                    this.refresh_hunks(cx);
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn refresh_hunks(&mut self, cx: &mut Context<Self>) {
+        let left_snapshot = self.left.buffer.read(cx).text_snapshot();
+        let right_snapshot = self.right.buffer.read(cx).text_snapshot();
+        self.hunks = self
+            .diff
+            .read(cx)
+            .hunks_intersecting_range(
+                language::Anchor::MIN..language::Anchor::MAX,
+                &right_snapshot,
+                cx,
+            )
+            .map(|hunk| AlignedHunk {
+                left: left_snapshot.anchor_before(hunk.diff_base_byte_range.start)
+                    ..left_snapshot.anchor_after(hunk.diff_base_byte_range.end),
+                right: hunk.buffer_range,
+            })
+            .collect();
+
+        let mut left_rows = Vec::with_capacity(self.hunks.len());
+        let mut right_rows = Vec::with_capacity(self.hunks.len());
+        for hunk in &self.hunks {
+            left_rows.push(row_range(&hunk.left, &left_snapshot));
+            right_rows.push(row_range(&hunk.right, &right_snapshot));
+        }
+        let deleted = cx.theme().colors().version_control_deleted.opacity(0.1);
+        let added = cx.theme().colors().version_control_added.opacity(0.1);
+        update_side_editor(&mut self.left, &left_rows, &right_rows, deleted, cx);
+        update_side_editor(&mut self.right, &right_rows, &left_rows, added, cx);
+        cx.notify();
+    }
+
+    /// Returns the hunk at the newest cursor of the side in `column`.
+    fn hunk_at_cursor(&self, column: Column, cx: &mut Context<Self>) -> Option<AlignedHunk> {
+        let side = self.side(column);
+        let row = side.editor.update(cx, |editor, cx| {
+            editor.selections.newest::<Point>(cx).head().row
+        });
+        let snapshot = side.buffer.read(cx).text_snapshot();
+        self.hunks
+            .iter()
+            .find(|hunk| {
+                let range = match column {
+                    Column::Left => &hunk.left,
+                    Column::Right => &hunk.right,
+                };
+                let rows = row_range(range, &snapshot);
+                rows.contains(&row) || (rows.is_empty() && rows.start == row)
+            })
+            .cloned()
+    }
+
+    /// Replaces the hunk at the cursor in the other side with its text on the side in `from`.
+    fn move_hunk(&mut self, from: Column, window: &mut Window, cx: &mut Context<Self>) {
+        let column = if self.left.editor.focus_handle(cx).is_focused(window) {
+            Column::Left
+        } else {
+            Column::Right
+        };
+        let Some(hunk) = self.hunk_at_cursor(column, cx) else {
+            return;
+        };
+        let (source_range, target_range) = match from {
+            Column::Left => (hunk.left, hunk.right),
+            Column::Right => (hunk.right, hunk.left),
+        };
+        let text = self
+            .side(from)
+            .buffer
+            .read(cx)
+            .text_for_range(source_range)
+            .collect::<String>();
+        self.side(from.other()).buffer.update(cx, |buffer, cx| {
+            buffer.edit([(target_range, text)], None, cx);
+        });
+    }
+
+    fn move_hunk_to_left(
+        &mut self,
+        _: &MoveHunkToLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_hunk(Column::Right, window, cx);
+    }
+
+    fn move_hunk_to_right(
+        &mut self,
+        _: &MoveHunkToRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_hunk(Column::Left, window, cx);
+    }
+
+    fn title(&self) -> SharedString {
+        format!("{} ↔ {}", self.left.label, self.right.label).into()
+    }
+}
+
+/// Returns the rows that a hunk's range covers, where a range ending within a line covers that
+/// line too.
+fn row_range(
+    range: &Range<language::Anchor>,
+    snapshot: &language::TextBufferSnapshot,
+) -> Range<u32> {
+    let start = range.start.to_point(snapshot);
+    let end = range.end.to_point(snapshot);
+    let end_row = if end.column > 0 { end.row + 1 } else { end.row };
+    start.row..end_row.max(start.row)
+}
+
+/// Highlights the changed rows of a side and pads its hunks that are shorter than on the other
+/// side, so that the rows after them line up.
+fn update_side_editor(
+    side: &mut SideEditor,
+    rows: &[Range<u32>],
+    other_rows: &[Range<u32>],
+    color: Hsla,
+    cx: &mut App,
+) {
+    side.editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let max_row = snapshot.max_point().row;
+        editor.clear_row_highlights::<DiffView>();
+        let mut blocks = Vec::new();
+        for (rows, other_rows) in rows.iter().zip(other_rows) {
+            if !rows.is_empty() {
+                let start = snapshot.anchor_before(Point::new(rows.start, 0));
+                let end = snapshot.anchor_before(
+                    snapshot.clip_point(Point::new(rows.end, 0), language::Bias::Left),
+                );
+                editor.highlight_rows::<DiffView>(start..end, color, false, cx);
+            }
+
+            let padding = other_rows.len().saturating_sub(rows.len()) as u32;
+            if padding == 0 {
+                continue;
+            }
+            let placement = if rows.is_empty() && rows.start <= max_row {
+                BlockPlacement::Above(snapshot.anchor_before(Point::new(rows.start, 0)))
+            } else {
+                let row = rows.end.saturating_sub(1).min(max_row);
+                BlockPlacement::Below(snapshot.anchor_before(Point::new(row, 0)))
+            };
+            blocks.push(BlockProperties {
+                placement,
+                height: padding,
+                style: BlockStyle::Fixed,
+                render: Arc::new(|cx: &mut BlockContext| {
+                    div()
+                        .id(cx.block_id)
+                        .size_full()
+                        .bg(cx.theme().colors().editor_subheader_background)
+                        .into_any_element()
+                }),
+                priority: 0,
+            });
+        }
+
+        let old_blocks = std::mem::take(&mut side.padding_blocks);
+        if !old_blocks.is_empty() {
+            editor.remove_blocks(old_blocks, None, cx);
+        }
+        side.padding_blocks = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+    });
+}
+
+impl Render for DiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border_variant;
+        let header = |label: SharedString| {
+            h_flex()
+                .flex_1()
+                .px_2()
+                .py_1()
+                .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+        };
+
+        v_flex()
+            .size_full()
+            .key_context("DiffView")
+            .on_action(cx.listener(Self::move_hunk_to_left))
+            .on_action(cx.listener(Self::move_hunk_to_right))
+            .child(
+                h_flex()
+                    .w_full()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(header(self.left.label.clone()))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new("move-hunk-to-left", IconName::ArrowLeft)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(Tooltip::for_action_title(
+                                        "Move Hunk to Left",
+                                        &MoveHunkToLeft,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.move_hunk(Column::Right, window, cx)
+                                    })),
+                            )
+                            .child(
+                                IconButton::new("move-hunk-to-right", IconName::ArrowRight)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(Tooltip::for_action_title(
+                                        "Move Hunk to Right",
+                                        &MoveHunkToRight,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.move_hunk(Column::Left, window, cx)
+                                    })),
+                            ),
+                    )
+                    .child(header(self.right.label.clone())),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .w_full()
+                    .child(div().flex_1().h_full().child(self.left.editor.clone()))
+                    .child(div().w_px().h_full().bg(border_color))
+                    .child(div().flex_1().h_full().child(self.right.editor.clone())),
+            )
+    }
+}
+
+impl Focusable for DiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.right.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for DiffView {}
+
+impl Item for DiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(self.title())
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let path = |side: &SideEditor| match side.buffer.read(cx).file() {
+            Some(file) => file.full_path(cx).display().to_string(),
+            None => side.label.to_string(),
+        };
+        Some(format!("{} ↔ {}", path(&self.left), path(&self.right)).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.right.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.right.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for side in [&self.left, &self.right] {
+            side.editor.update(cx, |editor, cx| {
+                Item::added_to_workspace(editor, workspace, window, cx)
+            });
+        }
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        for side in [&self.left, &self.right] {
+            side.editor
+                .update(cx, |editor, cx| editor.deactivated(window, cx));
+        }
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.right
+            .editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.right.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.left.buffer.read(cx).is_dirty() || self.right.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.left.editor.read(cx).can_save(cx) || self.right.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let mut saves = Vec::new();
+        for side in [&self.left, &self.right] {
+            if side.editor.read(cx).can_save(cx) {
+                saves.push(side.editor.update(cx, |editor, cx| {
+                    Item::save(editor, format, project.clone(), window, cx)
+                }));
+            }
+        }
+        cx.background_spawn(async move {
+            for save in saves {
+                save.await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use settings::SettingsStore;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_diff_view_hunks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let left = cx.new(|cx| Buffer::local("one\ntwo\nthree\nfour\n", cx));
+        let right = cx.new(|cx| Buffer::local("one\n2\n2.5\nthree\n", cx));
+
+        let (view, cx) = cx.add_window_view(|window, cx| {
+            let left = DiffSide::for_buffer(left.clone(), cx);
+            let right = DiffSide::for_buffer(right.clone(), cx);
+            DiffView::new(left, right, project.clone(), window, cx)
+        });
+        cx.run_until_parked();
+
+        view.update(cx, |view, cx| {
+            let left_snapshot = view.left.buffer.read(cx).text_snapshot();
+            let right_snapshot = view.right.buffer.read(cx).text_snapshot();
+            let rows = view
+                .hunks
+                .iter()
+                .map(|hunk| {
+                    (
+                        row_range(&hunk.left, &left_snapshot),
+                        row_range(&hunk.right, &right_snapshot),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(rows, [(1..2, 1..3), (3..4, 4..4)]);
+            // The left side is padded by a row for the line that was added on the right.
+            assert_eq!(view.left.padding_blocks.len(), 1);
+            assert_eq!(view.right.padding_blocks.len(), 1);
+        });
+
+        // Move the first hunk from the right side to the left.
+        view.update_in(cx, |view, window, cx| {
+            view.right.editor.update(cx, |editor, cx| {
+                editor.change_selections(None, window, cx, |selections| {
+                    selections.select_ranges([Point::new(2, 0)..Point::new(2, 0)])
+                });
+            });
+            view.move_hunk(Column::Right, window, cx);
+        });
+        cx.run_until_parked();
+
+        view.update(cx, |view, cx| {
+            assert_eq!(
+                view.left.buffer.read(cx).text(),
+                "one\n2\n2.5\nthree\nfour\n"
+            );
+            assert_eq!(view.hunks.len(), 1);
+        });
+    }
+}
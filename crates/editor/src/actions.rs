@@ -188,6 +188,14 @@ pub struct DeleteToPreviousWordStart {
 #[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
 pub struct FoldAtLevel(pub u32);
 
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AlignOnDelimiter {
+    /// The regex matching the delimiter to align. Defaults to `=>`, `:=`, `=` and `:`.
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SpawnNearestTask {
@@ -205,6 +213,7 @@ pub enum UuidVersion {
 impl_actions!(
     editor,
     [
+        AlignOnDelimiter,
         ComposeCompletion,
         ConfirmCodeAction,
         ConfirmCompletion,
@@ -244,11 +253,14 @@ gpui::actions!(
         AcceptPartialEditPrediction,
         AddSelectionAbove,
         AddSelectionBelow,
+        AlignMarkdownTable,
+        AlignSelections,
         ApplyAllDiffHunks,
         ApplyDiffHunk,
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        ClearBookmarks,
         ConfirmRename,
         ContextMenuFirst,
         ContextMenuLast,
@@ -299,8 +311,10 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToNextBookmark,
         GoToPrevDiagnostic,
         GoToPrevHunk,
+        GoToPreviousBookmark,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
@@ -311,6 +325,7 @@ gpui::actions!(
         InsertUuidV7,
         JoinLines,
         KillRingCut,
+        LabelBookmark,
         KillRingYank,
         LineDown,
         LineUp,
@@ -396,6 +411,7 @@ gpui::actions!(
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleIndentGuides,
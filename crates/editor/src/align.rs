@@ -0,0 +1,477 @@
+use std::ops::Range;
+
+use collections::{BTreeMap, BTreeSet, HashMap};
+use gpui::{Context, Window};
+use language::{BufferSnapshot, Point};
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToPoint as _};
+use regex::Regex;
+use unicode_width::UnicodeWidthChar as _;
+use util::ResultExt as _;
+
+use crate::{
+    actions::AlignOnDelimiter, scroll::Autoscroll, AlignMarkdownTable, AlignSelections, Editor,
+    Selection,
+};
+
+/// The delimiters that [`AlignOnDelimiter`] lines up when no pattern is given.
+const DEFAULT_DELIMITER_PATTERN: &str = "=>|:=|=|:";
+
+/// Cells in a formatted Markdown table are at least as wide as the shortest delimiter `---`.
+const MIN_TABLE_COLUMN_WIDTH: usize = 3;
+
+impl Editor {
+    /// Inserts spaces before selections so that the n-th selection on each line starts in the
+    /// same column as the n-th selection on every other line.
+    pub fn align_selections(
+        &mut self,
+        _: &AlignSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+
+        let selections = self.selections.all::<Point>(cx);
+        let mut columns_by_row = BTreeMap::<u32, Vec<u32>>::default();
+        for selection in &selections {
+            columns_by_row
+                .entry(selection.start.row)
+                .or_default()
+                .push(selection.start.column);
+        }
+        self.align_columns(columns_by_row, selections, window, cx);
+    }
+
+    /// Inserts spaces before the first match of the delimiter pattern on each selected line, so
+    /// that the delimiters line up.
+    pub fn align_on_delimiter(
+        &mut self,
+        action: &AlignOnDelimiter,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let pattern = action
+            .pattern
+            .as_deref()
+            .unwrap_or(DEFAULT_DELIMITER_PATTERN);
+        let Some(regex) = Regex::new(pattern).log_err() else {
+            return;
+        };
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+        let mut rows = BTreeSet::default();
+        for selection in &selections {
+            let mut end_row = selection.end.row;
+            if end_row > selection.start.row && selection.end.column == 0 {
+                end_row -= 1;
+            }
+            rows.extend(selection.start.row..=end_row);
+        }
+
+        let mut columns_by_row = BTreeMap::default();
+        for row in rows {
+            let line = line_text(&buffer, row);
+            if let Some(delimiter) = regex.find(&line) {
+                columns_by_row.insert(row, vec![delimiter.start() as u32]);
+            }
+        }
+        self.align_columns(columns_by_row, selections, window, cx);
+    }
+
+    /// Reformats the Markdown table containing the newest selection so that its columns line up.
+    pub fn align_markdown_table(
+        &mut self,
+        _: &AlignMarkdownTable,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest::<usize>(cx).head();
+        let Some(mut excerpt) = buffer.excerpt_containing(cursor..cursor) else {
+            return;
+        };
+        let buffer_cursor = excerpt.map_offset_to_buffer(cursor);
+        let Some(table_range) = markdown_table_range(excerpt.buffer(), buffer_cursor) else {
+            return;
+        };
+        let table_range = excerpt.map_range_from_buffer(table_range);
+        let start = table_range.start.to_point(&buffer);
+        let mut end = table_range.end.to_point(&buffer);
+        if end.column == 0 && end.row > start.row {
+            end.row -= 1;
+        }
+
+        let rows = start.row..end.row + 1;
+        let lines = rows
+            .clone()
+            .map(|row| line_text(&buffer, row))
+            .collect::<Vec<_>>();
+        let formatted = format_markdown_table(&lines);
+        let edits = rows
+            .zip(lines.iter().zip(formatted))
+            .filter(|(_, (line, formatted))| *line != formatted)
+            .map(|(row, (_, formatted))| {
+                let line =
+                    Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row)));
+                (line, formatted)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(window, cx, |this, window, cx| {
+            this.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            this.request_autoscroll(Autoscroll::fit(), cx);
+        });
+    }
+
+    /// Pads every row so that its n-th column is displayed in the same column as the n-th column
+    /// of the other rows, then moves the selections along with the text they were in.
+    fn align_columns(
+        &mut self,
+        columns_by_row: BTreeMap<u32, Vec<u32>>,
+        selections: Vec<Selection<Point>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if columns_by_row.len() < 2 {
+            return;
+        }
+
+        let multi_buffer = self.buffer.read(cx);
+        let buffer = multi_buffer.snapshot(cx);
+        let rows = columns_by_row.keys().copied().collect::<Vec<_>>();
+        let lines = columns_by_row
+            .into_iter()
+            .map(|(row, mut columns)| {
+                columns.sort_unstable();
+                columns.dedup();
+                AlignedLine {
+                    text: line_text(&buffer, row),
+                    offsets: columns.into_iter().map(|column| column as usize).collect(),
+                    tab_size: multi_buffer
+                        .settings_at(Point::new(row, 0), cx)
+                        .tab_size
+                        .get() as usize,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut padding_by_row = HashMap::default();
+        let mut edits = Vec::new();
+        for (row, padding) in rows.into_iter().zip(alignment_padding(&lines)) {
+            for &(offset, len) in &padding {
+                let position = Point::new(row, offset as u32);
+                edits.push((position..position, " ".repeat(len)));
+            }
+            padding_by_row.insert(row, padding);
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        // Padding inserted where a selection starts or where a cursor is goes before it, but
+        // padding inserted where a selection ends belongs to the next selection on the line.
+        let shift = |point: Point, include_padding_at_point: bool| {
+            let Some(padding) = padding_by_row.get(&point.row) else {
+                return point;
+            };
+            let column = point.column as usize;
+            let shift = padding
+                .iter()
+                .filter(|(offset, _)| {
+                    *offset < column || (include_padding_at_point && *offset == column)
+                })
+                .map(|(_, len)| *len as u32)
+                .sum::<u32>();
+            Point::new(point.row, point.column + shift)
+        };
+        let new_selections = selections
+            .into_iter()
+            .map(|mut selection| {
+                let is_empty = selection.is_empty();
+                selection.start = shift(selection.start, true);
+                selection.end = shift(selection.end, is_empty);
+                selection
+            })
+            .collect::<Vec<_>>();
+
+        self.transact(window, cx, |this, window, cx| {
+            this.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            this.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select(new_selections)
+            });
+        });
+    }
+}
+
+fn line_text(buffer: &MultiBufferSnapshot, row: u32) -> String {
+    buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))))
+        .collect()
+}
+
+/// A line of text with positions that should line up with the positions on other lines.
+struct AlignedLine {
+    text: String,
+    /// Byte offsets into `text`, in ascending order.
+    offsets: Vec<usize>,
+    tab_size: usize,
+}
+
+/// Returns, for every line, the spaces to insert as `(offset, len)` pairs so that the n-th offset
+/// of every line ends up in the same column. Columns are counted from the start of the line
+/// rather than of its display row, so soft wraps don't affect the result; tabs extend to the next
+/// tab stop and wide characters take up two columns.
+fn alignment_padding(lines: &[AlignedLine]) -> Vec<Vec<(usize, usize)>> {
+    let mut texts = lines
+        .iter()
+        .map(|line| line.text.clone())
+        .collect::<Vec<_>>();
+    // Where each line's offsets are in the padded text.
+    let mut offsets = lines
+        .iter()
+        .map(|line| line.offsets.clone())
+        .collect::<Vec<_>>();
+    let mut padding = vec![Vec::new(); lines.len()];
+
+    let column_count = offsets.iter().map(Vec::len).max().unwrap_or(0);
+    for column in 0..column_count {
+        let widths = texts
+            .iter()
+            .zip(&offsets)
+            .zip(lines)
+            .map(|((text, offsets), line)| {
+                let offset = *offsets.get(column)?;
+                Some(display_width(&text[..offset], line.tab_size))
+            })
+            .collect::<Vec<_>>();
+        if widths.iter().flatten().count() < 2 {
+            break;
+        }
+        let Some(target) = widths.iter().flatten().copied().max() else {
+            break;
+        };
+
+        for (ix, width) in widths.into_iter().enumerate() {
+            let Some(width) = width.filter(|width| *width < target) else {
+                continue;
+            };
+            let len = target - width;
+            let offset = offsets[ix][column];
+            texts[ix].insert_str(offset, &" ".repeat(len));
+            for later_offset in &mut offsets[ix][column..] {
+                *later_offset += len;
+            }
+            // Report padding in terms of the original text.
+            let original_offset = lines[ix].offsets[column];
+            padding[ix].push((original_offset, len));
+        }
+    }
+    padding
+}
+
+/// The number of columns `text` takes up when displayed from the start of a line.
+fn display_width(text: &str, tab_size: usize) -> usize {
+    text.chars().fold(0, |width, c| {
+        if c == '\t' {
+            width + tab_size - width % tab_size
+        } else {
+            width + c.width().unwrap_or(0)
+        }
+    })
+}
+
+/// Finds the byte range of the `pipe_table` node of the Markdown grammar containing `offset`.
+fn markdown_table_range(buffer: &BufferSnapshot, offset: usize) -> Option<Range<usize>> {
+    buffer.syntax_layers().find_map(|layer| {
+        let mut node = layer.node().descendant_for_byte_range(offset, offset)?;
+        loop {
+            if node.kind() == "pipe_table" {
+                return Some(node.byte_range());
+            }
+            node = node.parent()?;
+        }
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TableAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Pads the cells of a Markdown table so that its pipes line up, following the alignment given
+/// by its delimiter row. The indentation of the first line is kept for all of them.
+fn format_markdown_table(lines: &[String]) -> Vec<String> {
+    let indent = lines
+        .first()
+        .map_or("", |line| &line[..line.len() - line.trim_start().len()]);
+    let rows = lines
+        .iter()
+        .map(|line| split_table_row(line.trim()))
+        .collect::<Vec<_>>();
+    let delimiter_row = rows
+        .iter()
+        .position(|cells| !cells.is_empty() && cells.iter().all(|cell| is_delimiter_cell(cell)));
+    let alignments = delimiter_row.map_or(Vec::new(), |ix| {
+        rows[ix]
+            .iter()
+            .map(|cell| match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => TableAlignment::Center,
+                (true, false) => TableAlignment::Left,
+                (false, true) => TableAlignment::Right,
+                (false, false) => TableAlignment::None,
+            })
+            .collect()
+    });
+
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![MIN_TABLE_COLUMN_WIDTH; column_count];
+    for (ix, cells) in rows.iter().enumerate() {
+        if Some(ix) == delimiter_row {
+            continue;
+        }
+        for (cell, width) in cells.iter().zip(&mut widths) {
+            *width = (*width).max(display_width(cell, 1));
+        }
+    }
+
+    rows.iter()
+        .enumerate()
+        .map(|(ix, cells)| {
+            let mut line = format!("{indent}|");
+            for (column, width) in widths.iter().copied().enumerate() {
+                let cell = cells.get(column).map_or("", String::as_str);
+                let alignment = alignments
+                    .get(column)
+                    .copied()
+                    .unwrap_or(TableAlignment::None);
+                line.push(' ');
+                if Some(ix) == delimiter_row {
+                    let dashes = width
+                        - matches!(alignment, TableAlignment::Left | TableAlignment::Center)
+                            as usize
+                        - matches!(alignment, TableAlignment::Right | TableAlignment::Center)
+                            as usize;
+                    if matches!(alignment, TableAlignment::Left | TableAlignment::Center) {
+                        line.push(':');
+                    }
+                    line.push_str(&"-".repeat(dashes));
+                    if matches!(alignment, TableAlignment::Right | TableAlignment::Center) {
+                        line.push(':');
+                    }
+                } else {
+                    let padding = width - display_width(cell, 1);
+                    let left_padding = match alignment {
+                        TableAlignment::Right => padding,
+                        TableAlignment::Center => padding / 2,
+                        TableAlignment::None | TableAlignment::Left => 0,
+                    };
+                    line.push_str(&" ".repeat(left_padding));
+                    line.push_str(cell);
+                    line.push_str(&" ".repeat(padding - left_padding));
+                }
+                line.push_str(" |");
+            }
+            line
+        })
+        .collect()
+}
+
+/// Splits a table row into its trimmed cells, ignoring escaped pipes and the optional pipes at the
+/// start and end of the row.
+fn split_table_row(row: &str) -> Vec<String> {
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = row.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                cell.extend(chars.next());
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    if !cell.trim().is_empty() {
+        cells.push(cell);
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+fn is_delimiter_cell(cell: &str) -> bool {
+    let dashes = cell.strip_prefix(':').unwrap_or(cell);
+    let dashes = dashes.strip_suffix(':').unwrap_or(dashes);
+    !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alignment_padding() {
+        let line = |text: &str, offsets: &[usize]| AlignedLine {
+            text: text.to_string(),
+            offsets: offsets.to_vec(),
+            tab_size: 4,
+        };
+
+        assert_eq!(
+            alignment_padding(&[line("a = 1", &[2]), line("abc = 2", &[4])]),
+            vec![vec![(2, 2)], vec![]]
+        );
+        // Tabs extend to the next tab stop and wide characters take up two columns.
+        assert_eq!(
+            alignment_padding(&[line("\tx = 1", &[3]), line("日本 = 2", &[7])]),
+            vec![vec![], vec![(7, 1)]]
+        );
+        // Later columns take the padding of earlier ones into account.
+        assert_eq!(
+            alignment_padding(&[line("a,b,c", &[1, 3]), line("aaa,b,c", &[3, 5])]),
+            vec![vec![(1, 2)], vec![]]
+        );
+        // Lines with fewer positions are left alone once they run out.
+        assert_eq!(
+            alignment_padding(&[line("a b", &[1, 2]), line("aa", &[2])]),
+            vec![vec![(1, 1)], vec![]]
+        );
+    }
+
+    #[test]
+    fn test_format_markdown_table() {
+        let lines = [
+            "  | Name | Qty |Note|",
+            "  |:-|--:|:-:|",
+            "  | apple | 10 | red |",
+            "  | 日本 \\| x | 2 |",
+        ]
+        .map(String::from);
+        assert_eq!(
+            format_markdown_table(&lines),
+            [
+                "  | Name      | Qty | Note |",
+                "  | :-------- | --: | :--: |",
+                "  | apple     |  10 | red  |",
+                "  | 日本 \\| x |   2 |      |",
+            ]
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::{path::PathBuf, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, SharedString, Subscription, Task,
+    WeakEntity, Window,
+};
+use language::{Buffer, BufferEvent, BufferSnapshot, Point};
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToPoint as _};
+use text::{Bias, ToPoint as _};
+use ui::{prelude::*, IconButtonShape, Tooltip};
+use util::ResultExt as _;
+use workspace::WorkspaceId;
+
+use crate::{
+    persistence::DB, scroll::Autoscroll, ClearBookmarks, Direction, DisplayRow, Editor,
+    GoToNextBookmark, GoToPreviousBookmark, ToggleBookmark,
+};
+
+const SERIALIZATION_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A line marked by the user, which follows the line as the buffer is edited.
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub anchor: text::Anchor,
+    pub label: Option<SharedString>,
+}
+
+/// A bookmark in a workspace, whether its file is open or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BookmarkEntry {
+    pub abs_path: PathBuf,
+    pub row: u32,
+    pub label: Option<SharedString>,
+}
+
+struct BufferBookmarks {
+    buffer: WeakEntity<Buffer>,
+    workspace_id: Option<WorkspaceId>,
+    abs_path: Option<PathBuf>,
+    /// Sorted by position in the buffer.
+    bookmarks: Vec<Bookmark>,
+    /// The rows last saved in the workspace, or `None` if it's unknown what's saved for the path.
+    saved_rows: Option<Vec<(u32, Option<String>)>>,
+    serialization_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl BufferBookmarks {
+    fn rows(&self, snapshot: &BufferSnapshot) -> Vec<(u32, Option<String>)> {
+        self.bookmarks
+            .iter()
+            .map(|bookmark| {
+                (
+                    bookmark.anchor.to_point(snapshot).row,
+                    bookmark.label.as_ref().map(ToString::to_string),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The bookmarks of all open buffers, shared by the editors displaying them.
+///
+/// Bookmarks of files are saved per workspace, and restored when the file is opened again.
+#[derive(Default)]
+pub struct BookmarkStore {
+    buffers: HashMap<EntityId, BufferBookmarks>,
+}
+
+struct GlobalBookmarkStore(Entity<BookmarkStore>);
+
+impl Global for GlobalBookmarkStore {}
+
+pub(super) fn init(cx: &mut App) {
+    let store = cx.new(|_| BookmarkStore::default());
+    cx.set_global(GlobalBookmarkStore(store));
+}
+
+impl BookmarkStore {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalBookmarkStore>()
+            .map(|store| store.0.clone())
+    }
+
+    /// Starts tracking the bookmarks of the buffer, restoring the ones saved in the workspace.
+    fn register_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(buffer_bookmarks) = self.buffers.get_mut(&buffer.entity_id()) {
+            if buffer_bookmarks.workspace_id.is_some() || workspace_id.is_none() {
+                return;
+            }
+            // The buffer was bookmarked before being opened in a workspace, so the bookmarks
+            // saved in the workspace would be outdated.
+            buffer_bookmarks.workspace_id = workspace_id;
+            buffer_bookmarks.saved_rows = None;
+            self.serialize(buffer, cx);
+            return;
+        }
+
+        let abs_path = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)));
+        let snapshot = buffer.read(cx).snapshot();
+        let saved_rows = workspace_id
+            .zip(abs_path.clone())
+            .and_then(|(workspace_id, abs_path)| {
+                DB.get_bookmarks_for_path(workspace_id, abs_path).log_err()
+            })
+            .unwrap_or_default();
+        let bookmarks = saved_rows
+            .iter()
+            .map(|(row, label)| {
+                let position = snapshot.clip_point(Point::new(*row, 0), Bias::Left);
+                Bookmark {
+                    anchor: snapshot.anchor_before(position),
+                    label: label.clone().map(SharedString::from),
+                }
+            })
+            .collect();
+
+        let entity_id = buffer.entity_id();
+        let subscriptions = vec![
+            cx.subscribe(buffer, |store, buffer, event, cx| match event {
+                BufferEvent::Edited => store.serialize(&buffer, cx),
+                BufferEvent::FileHandleChanged => {
+                    let abs_path = buffer
+                        .read(cx)
+                        .file()
+                        .and_then(|file| Some(file.as_local()?.abs_path(cx)));
+                    if let Some(buffer_bookmarks) = store.buffers.get_mut(&buffer.entity_id()) {
+                        if buffer_bookmarks.abs_path != abs_path {
+                            if let Some((workspace_id, old_path)) = buffer_bookmarks
+                                .workspace_id
+                                .zip(buffer_bookmarks.abs_path.take())
+                            {
+                                cx.background_spawn(DB.save_bookmarks(
+                                    workspace_id,
+                                    old_path,
+                                    Vec::new(),
+                                ))
+                                .detach_and_log_err(cx);
+                            }
+                            buffer_bookmarks.abs_path = abs_path;
+                            buffer_bookmarks.saved_rows = None;
+                            store.serialize(&buffer, cx);
+                        }
+                    }
+                }
+                _ => {}
+            }),
+            cx.observe_release(buffer, move |store, _, _| {
+                // The last edit scheduled saving the final position of the bookmarks, which may
+                // still be pending.
+                if let Some(buffer_bookmarks) = store.buffers.remove(&entity_id) {
+                    buffer_bookmarks.serialization_task.detach();
+                }
+            }),
+        ];
+
+        self.buffers.insert(
+            entity_id,
+            BufferBookmarks {
+                buffer: buffer.downgrade(),
+                workspace_id,
+                abs_path,
+                bookmarks,
+                saved_rows: Some(saved_rows),
+                serialization_task: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        cx.notify();
+    }
+
+    fn serialize(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(buffer_bookmarks) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+        let Some((workspace_id, abs_path)) = buffer_bookmarks
+            .workspace_id
+            .zip(buffer_bookmarks.abs_path.clone())
+        else {
+            return;
+        };
+
+        // Most edits don't move any bookmark to another row, or are to buffers without any.
+        let rows = buffer_bookmarks.rows(&buffer.read(cx).snapshot());
+        if buffer_bookmarks.saved_rows.as_ref() == Some(&rows) {
+            return;
+        }
+        buffer_bookmarks.saved_rows = Some(rows.clone());
+
+        let background_executor = cx.background_executor().clone();
+        buffer_bookmarks.serialization_task = cx.background_spawn(async move {
+            background_executor.timer(SERIALIZATION_DEBOUNCE).await;
+            DB.save_bookmarks(workspace_id, abs_path, rows)
+                .await
+                .log_err();
+        });
+    }
+
+    /// Returns the bookmarks of the buffer, sorted by position.
+    pub fn bookmarks_for_buffer(&self, buffer: &Entity<Buffer>) -> &[Bookmark] {
+        self.buffers
+            .get(&buffer.entity_id())
+            .map_or(&[], |buffer_bookmarks| &buffer_bookmarks.bookmarks)
+    }
+
+    /// Adds a bookmark to the given row of the buffer, or removes the one that is already there.
+    pub fn toggle_bookmark(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut Context<Self>,
+    ) {
+        self.register_buffer(buffer, workspace_id, cx);
+        let Some(buffer_bookmarks) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = &mut buffer_bookmarks.bookmarks;
+        if let Some(ix) = bookmarks
+            .iter()
+            .position(|bookmark| bookmark.anchor.to_point(&snapshot).row == row)
+        {
+            bookmarks.remove(ix);
+        } else {
+            let anchor = snapshot.anchor_before(Point::new(row, 0));
+            let ix = bookmarks
+                .partition_point(|bookmark| bookmark.anchor.cmp(&anchor, &snapshot).is_lt());
+            bookmarks.insert(
+                ix,
+                Bookmark {
+                    anchor,
+                    label: None,
+                },
+            );
+        }
+
+        self.serialize(buffer, cx);
+        cx.notify();
+    }
+
+    /// Labels the bookmark on the given row of the buffer, adding one if there is none.
+    pub fn set_bookmark_label(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        label: Option<SharedString>,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut Context<Self>,
+    ) {
+        self.register_buffer(buffer, workspace_id, cx);
+        let Some(buffer_bookmarks) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = &mut buffer_bookmarks.bookmarks;
+        if let Some(bookmark) = bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.anchor.to_point(&snapshot).row == row)
+        {
+            bookmark.label = label;
+        } else {
+            let anchor = snapshot.anchor_before(Point::new(row, 0));
+            let ix = bookmarks
+                .partition_point(|bookmark| bookmark.anchor.cmp(&anchor, &snapshot).is_lt());
+            bookmarks.insert(ix, Bookmark { anchor, label });
+        }
+
+        self.serialize(buffer, cx);
+        cx.notify();
+    }
+
+    pub fn clear_bookmarks(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(buffer_bookmarks) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+        if buffer_bookmarks.bookmarks.is_empty() {
+            return;
+        }
+        buffer_bookmarks.bookmarks.clear();
+        self.serialize(buffer, cx);
+        cx.notify();
+    }
+
+    /// Returns the bookmarks of all files in the workspace, sorted by path and row.
+    pub fn workspace_bookmarks(&self, workspace_id: WorkspaceId, cx: &App) -> Vec<BookmarkEntry> {
+        let mut entries = Vec::new();
+        let mut open_paths = HashSet::default();
+        for buffer_bookmarks in self.buffers.values() {
+            let (Some(buffer), Some(abs_path)) = (
+                buffer_bookmarks.buffer.upgrade(),
+                buffer_bookmarks.abs_path.as_ref(),
+            ) else {
+                continue;
+            };
+            if buffer_bookmarks.workspace_id != Some(workspace_id) {
+                continue;
+            }
+            open_paths.insert(abs_path.clone());
+            let snapshot = buffer.read(cx).snapshot();
+            entries.extend(
+                buffer_bookmarks
+                    .bookmarks
+                    .iter()
+                    .map(|bookmark| BookmarkEntry {
+                        abs_path: abs_path.clone(),
+                        row: bookmark.anchor.to_point(&snapshot).row,
+                        label: bookmark.label.clone(),
+                    }),
+            );
+        }
+
+        // The saved bookmarks of open files may be outdated.
+        let saved_bookmarks = DB.get_bookmarks(workspace_id).log_err().unwrap_or_default();
+        entries.extend(
+            saved_bookmarks
+                .into_iter()
+                .filter(|(abs_path, _, _)| !open_paths.contains(abs_path))
+                .map(|(abs_path, row, label)| BookmarkEntry {
+                    abs_path,
+                    row,
+                    label: label.map(SharedString::from),
+                }),
+        );
+        entries.sort_by(|a, b| a.abs_path.cmp(&b.abs_path).then(a.row.cmp(&b.row)));
+        entries
+    }
+}
+
+/// Restores the bookmarks of the editor's buffers that are saved in its workspace.
+pub(super) fn register_buffers(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let Some(store) = BookmarkStore::global(cx) else {
+        return;
+    };
+    let Some(workspace_id) = editor.workspace_id() else {
+        return;
+    };
+    let buffers = editor.buffer.read(cx).all_buffers();
+    store.update(cx, |store, cx| {
+        for buffer in buffers {
+            store.register_buffer(&buffer, Some(workspace_id), cx);
+        }
+    });
+}
+
+impl Editor {
+    fn workspace_id(&self) -> Option<WorkspaceId> {
+        self.workspace.as_ref().and_then(|workspace| workspace.1)
+    }
+
+    /// Returns the bookmarks in the editor's excerpts, sorted by position.
+    pub fn bookmarks(
+        &self,
+        snapshot: &MultiBufferSnapshot,
+        cx: &App,
+    ) -> Vec<(Anchor, Option<SharedString>)> {
+        let Some(store) = BookmarkStore::global(cx) else {
+            return Vec::new();
+        };
+        let store = store.read(cx);
+        let multi_buffer = self.buffer.read(cx);
+
+        let mut bookmarks = Vec::new();
+        for (excerpt_id, buffer_snapshot, range) in snapshot.excerpts() {
+            let Some(buffer) = multi_buffer.buffer(buffer_snapshot.remote_id()) else {
+                continue;
+            };
+            for bookmark in store.bookmarks_for_buffer(&buffer) {
+                if bookmark
+                    .anchor
+                    .cmp(&range.context.start, buffer_snapshot)
+                    .is_lt()
+                    || bookmark
+                        .anchor
+                        .cmp(&range.context.end, buffer_snapshot)
+                        .is_gt()
+                {
+                    continue;
+                }
+                if let Some(anchor) = snapshot.anchor_in_excerpt(excerpt_id, bookmark.anchor) {
+                    bookmarks.push((anchor, bookmark.label.clone()));
+                }
+            }
+        }
+        bookmarks
+    }
+
+    /// Returns the bookmark on the line of the newest cursor, if there is one.
+    pub fn bookmark_at_cursor(&self, cx: &mut App) -> Option<(Anchor, Option<SharedString>)> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let row = self.selections.newest::<Point>(cx).head().row;
+        self.bookmarks(&snapshot, cx)
+            .into_iter()
+            .find(|(anchor, _)| anchor.to_point(&snapshot).row == row)
+    }
+
+    /// Labels the bookmark on the line of the newest cursor, adding one if there is none.
+    pub fn set_bookmark_label(&mut self, label: Option<SharedString>, cx: &mut Context<Self>) {
+        let Some(store) = BookmarkStore::global(cx) else {
+            return;
+        };
+        let head = self.selections.newest::<Point>(cx).head();
+        let Some((buffer, point, _)) = self.buffer.read(cx).point_to_buffer_point(head, cx) else {
+            return;
+        };
+        let workspace_id = self.workspace_id();
+        store.update(cx, |store, cx| {
+            store.set_bookmark_label(&buffer, point.row, label, workspace_id, cx)
+        });
+    }
+
+    pub fn toggle_bookmark(
+        &mut self,
+        _: &ToggleBookmark,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let heads = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| selection.head())
+            .collect();
+        self.toggle_bookmarks_at(heads, cx);
+    }
+
+    fn toggle_bookmarks_at(&mut self, positions: Vec<Point>, cx: &mut Context<Self>) {
+        let Some(store) = BookmarkStore::global(cx) else {
+            return;
+        };
+        let multi_buffer = self.buffer.read(cx);
+        let mut rows = Vec::<(Entity<Buffer>, u32)>::new();
+        for position in positions {
+            let Some((buffer, point, _)) = multi_buffer.point_to_buffer_point(position, cx) else {
+                continue;
+            };
+            // Toggle each line once, even if it has multiple cursors.
+            if !rows
+                .iter()
+                .any(|(b, row)| *b == buffer && *row == point.row)
+            {
+                rows.push((buffer, point.row));
+            }
+        }
+
+        let workspace_id = self.workspace_id();
+        store.update(cx, |store, cx| {
+            for (buffer, row) in rows {
+                store.toggle_bookmark(&buffer, row, workspace_id, cx);
+            }
+        });
+    }
+
+    pub fn clear_bookmarks(
+        &mut self,
+        _: &ClearBookmarks,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(store) = BookmarkStore::global(cx) else {
+            return;
+        };
+        let buffers = self.buffer.read(cx).all_buffers();
+        store.update(cx, |store, cx| {
+            for buffer in buffers {
+                store.clear_bookmarks(&buffer, cx);
+            }
+        });
+    }
+
+    pub fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToNextBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Next, window, cx);
+    }
+
+    pub fn go_to_previous_bookmark(
+        &mut self,
+        _: &GoToPreviousBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Prev, window, cx);
+    }
+
+    fn go_to_bookmark(
+        &mut self,
+        direction: Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let rows = self
+            .bookmarks(&snapshot, cx)
+            .into_iter()
+            .map(|(anchor, _)| anchor.to_point(&snapshot).row)
+            .collect::<Vec<_>>();
+        let row = self.selections.newest::<Point>(cx).head().row;
+
+        // Wrap around at the start and end of the editor.
+        let target_row = match direction {
+            Direction::Next => rows
+                .iter()
+                .find(|bookmark_row| **bookmark_row > row)
+                .or(rows.first()),
+            Direction::Prev => rows
+                .iter()
+                .rev()
+                .find(|bookmark_row| **bookmark_row < row)
+                .or(rows.last()),
+        };
+        if let Some(target_row) = target_row {
+            let target = Point::new(*target_row, 0);
+            self.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                selections.select_ranges([target..target])
+            });
+        }
+    }
+
+    pub(crate) fn render_bookmark_indicator(
+        &self,
+        row: DisplayRow,
+        position: Point,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        IconButton::new(("bookmark_indicator", row.0 as usize), IconName::Book)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::XSmall)
+            .icon_color(Color::Accent)
+            .when_some(label, |button, label| button.tooltip(Tooltip::text(label)))
+            .on_click(cx.listener(move |editor, _, _, cx| {
+                editor.toggle_bookmarks_at(vec![position], cx);
+            }))
+    }
+}
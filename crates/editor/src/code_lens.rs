@@ -0,0 +1,449 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use futures::future::join_all;
+use gpui::{Context, Entity, Task, WeakEntity, Window};
+use language::{Buffer, BufferId, Point, ToOffset as _, ToPoint as _};
+use lsp::LanguageServerId;
+use multi_buffer::ExcerptId;
+use project::{CodeLens, CodeLensClientCommand};
+use settings::Settings as _;
+use ui::{prelude::*, ButtonLike};
+use util::ResultExt as _;
+use workspace::notifications::NotifyTaskExt as _;
+
+use crate::{
+    actions::{FindAllReferences, ToggleCodeActions},
+    display_map::{
+        BlockContext, BlockPlacement, BlockProperties, BlockStyle, RenderBlock, ToDisplayPoint as _,
+    },
+    CustomBlockId, Editor, EditorMode, EditorSettings,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Code lenses currently displayed in an editor, rendered as blocks above the lines they
+/// belong to.
+#[derive(Default)]
+pub(super) struct CodeLensState {
+    buffers: HashMap<BufferId, BufferCodeLenses>,
+    blocks: Vec<CodeLensBlock>,
+    refresh_task: Option<Task<()>>,
+}
+
+/// The lenses last fetched for a buffer.
+///
+/// Servers may leave out the command of a lens until it is resolved, which can be expensive
+/// (e.g. counting references), so lenses are only resolved once they become visible.
+struct BufferCodeLenses {
+    buffer: Entity<Buffer>,
+    version: clock::Global,
+    lenses: Vec<CodeLens>,
+    /// Lenses resolved at `version`, by server and range.
+    resolved: HashMap<(LanguageServerId, lsp::Range), CodeLens>,
+    resolving: HashSet<(LanguageServerId, lsp::Range)>,
+}
+
+struct CodeLensBlock {
+    id: CustomBlockId,
+    excerpt_id: ExcerptId,
+    buffer_id: BufferId,
+    position: text::Anchor,
+    lenses: Vec<lsp::CodeLens>,
+}
+
+pub(super) fn refresh_code_lens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    if !EditorSettings::get_global(cx).code_lens {
+        clear_code_lens(editor, cx);
+        return;
+    }
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.code_lens.refresh_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let Ok(lens_tasks) = project.update(&mut cx, |project, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let version = buffer.read(cx).version();
+                    let lenses = project.code_lens(&buffer, cx);
+                    async move {
+                        let lenses = lenses.await.log_err().unwrap_or_default();
+                        (buffer, version, lenses)
+                    }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let lenses = join_all(lens_tasks).await;
+
+        editor
+            .update_in(&mut cx, |editor, window, cx| {
+                let mut buffers = std::mem::take(&mut editor.code_lens.buffers);
+                for (buffer, version, lenses) in lenses {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    let mut buffer_lenses = buffers
+                        .remove(&buffer_id)
+                        .filter(|buffer_lenses| buffer_lenses.version == version)
+                        .unwrap_or_else(|| BufferCodeLenses {
+                            buffer,
+                            version,
+                            lenses: Vec::new(),
+                            resolved: HashMap::default(),
+                            resolving: HashSet::default(),
+                        });
+                    buffer_lenses.lenses = lenses;
+                    editor.code_lens.buffers.insert(buffer_id, buffer_lenses);
+                }
+                resolve_visible_code_lens(editor, window, cx);
+                display_code_lens(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Resolves the lenses in the visible part of the editor that don't have a command yet.
+pub(super) fn resolve_visible_code_lens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.code_lens.buffers.is_empty() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let mut resolve_tasks = Vec::new();
+    for (_, (buffer, version, visible_range)) in editor.excerpts_for_inlay_hints_query(None, cx) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(buffer_lenses) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+            continue;
+        };
+        if buffer_lenses.version != version {
+            continue;
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        for lens in &buffer_lenses.lenses {
+            let key = (lens.server_id, lens.lsp_lens.range);
+            if lens.is_resolved()
+                || buffer_lenses.resolved.contains_key(&key)
+                || buffer_lenses.resolving.contains(&key)
+                || !visible_range.contains(&lens.range.start.to_offset(&snapshot))
+            {
+                continue;
+            }
+            buffer_lenses.resolving.insert(key);
+            let resolve = project.update(cx, |project, cx| {
+                project.resolve_code_lens(buffer.clone(), lens.clone(), cx)
+            });
+            let lens_version = version.clone();
+            resolve_tasks
+                .push(async move { (buffer_id, lens_version, key, resolve.await.log_err()) });
+        }
+    }
+    if resolve_tasks.is_empty() {
+        return;
+    }
+
+    cx.spawn_in(window, |editor, mut cx| async move {
+        let resolved_lenses = join_all(resolve_tasks).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, key, lens) in resolved_lenses {
+                    let Some(buffer_lenses) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+                        continue;
+                    };
+                    if buffer_lenses.version != version {
+                        continue;
+                    }
+                    buffer_lenses.resolving.remove(&key);
+                    if let Some(lens) = lens {
+                        buffer_lenses.resolved.insert(key, lens);
+                    }
+                }
+                display_code_lens(editor, cx);
+            })
+            .ok();
+    })
+    .detach();
+}
+
+fn clear_code_lens(editor: &mut Editor, cx: &mut Context<Editor>) {
+    editor.code_lens.refresh_task = None;
+    editor.code_lens.buffers.clear();
+    let block_ids = std::mem::take(&mut editor.code_lens.blocks)
+        .into_iter()
+        .map(|block| block.id)
+        .collect::<HashSet<_>>();
+    if !block_ids.is_empty() {
+        editor.remove_blocks(block_ids, None, cx);
+    }
+}
+
+/// Updates the displayed blocks to match the resolved lenses, only touching the blocks whose
+/// lenses changed.
+fn display_code_lens(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let editor_handle = cx.entity().downgrade();
+    let multi_buffer = editor.buffer.read(cx);
+    let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+
+    // Existing blocks move with edits, key them by the row they are at now.
+    let mut old_blocks = HashMap::default();
+    let mut removed_block_ids = HashSet::default();
+    for block in std::mem::take(&mut editor.code_lens.blocks) {
+        match multi_buffer.buffer(block.buffer_id) {
+            Some(buffer) => {
+                let row = block.position.to_point(&buffer.read(cx).snapshot()).row;
+                if let Some(duplicate) = old_blocks.insert((block.excerpt_id, row), block) {
+                    removed_block_ids.insert(duplicate.id);
+                }
+            }
+            None => {
+                removed_block_ids.insert(block.id);
+            }
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut new_blocks = Vec::new();
+    let mut replaced_renderers = HashMap::default();
+    for buffer_lenses in editor.code_lens.buffers.values() {
+        let buffer = &buffer_lenses.buffer;
+        let buffer_snapshot = buffer.read(cx).snapshot();
+
+        // Servers report one lens per action, show all lenses of a line in a single block.
+        // Lines whose lenses are still being resolved keep their current block until then.
+        let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::new();
+        let mut unresolved_rows = HashSet::default();
+        for lens in &buffer_lenses.lenses {
+            let row = lens.range.start.to_point(&buffer_snapshot).row;
+            let lens = if lens.is_resolved() {
+                lens
+            } else {
+                match buffer_lenses
+                    .resolved
+                    .get(&(lens.server_id, lens.lsp_lens.range))
+                {
+                    Some(resolved) if resolved.is_resolved() => resolved,
+                    Some(_) => continue,
+                    None => {
+                        unresolved_rows.insert(row);
+                        continue;
+                    }
+                }
+            };
+            lenses_by_row.entry(row).or_default().push(lens.clone());
+        }
+
+        for (excerpt_id, excerpt_range) in
+            multi_buffer.excerpts_for_buffer(buffer_snapshot.remote_id(), cx)
+        {
+            for row in &unresolved_rows {
+                if !lenses_by_row.contains_key(row) {
+                    blocks.extend(old_blocks.remove(&(excerpt_id, *row)));
+                }
+            }
+
+            let context = excerpt_range.context;
+            for (row, lenses) in &lenses_by_row {
+                let indent = buffer_snapshot.indent_size_for_line(*row);
+                let position = buffer_snapshot.anchor_before(Point::new(*row, indent.len));
+                if context.start.cmp(&position, &buffer_snapshot).is_gt()
+                    || context.end.cmp(&position, &buffer_snapshot).is_lt()
+                {
+                    continue;
+                }
+                let lsp_lenses = lenses
+                    .iter()
+                    .map(|lens| lens.lsp_lens.clone())
+                    .collect::<Vec<_>>();
+                let render = || {
+                    render_code_lens(
+                        editor_handle.clone(),
+                        buffer.clone(),
+                        excerpt_id,
+                        lenses.clone(),
+                    )
+                };
+
+                if let Some(mut block) = old_blocks.remove(&(excerpt_id, *row)) {
+                    if block.lenses != lsp_lenses {
+                        replaced_renderers.insert(block.id, render());
+                        block.lenses = lsp_lenses;
+                    }
+                    blocks.push(block);
+                    continue;
+                }
+
+                let Some(anchor) = multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, position)
+                else {
+                    continue;
+                };
+                new_blocks.push((
+                    BlockProperties {
+                        placement: BlockPlacement::Above(anchor),
+                        height: 1,
+                        style: BlockStyle::Flex,
+                        render: render(),
+                        priority: 0,
+                    },
+                    (
+                        excerpt_id,
+                        buffer_snapshot.remote_id(),
+                        position,
+                        lsp_lenses,
+                    ),
+                ));
+            }
+        }
+    }
+    removed_block_ids.extend(old_blocks.into_values().map(|block| block.id));
+
+    if !removed_block_ids.is_empty() {
+        editor.remove_blocks(removed_block_ids, None, cx);
+    }
+    if !replaced_renderers.is_empty() {
+        editor.replace_blocks(replaced_renderers, None, cx);
+    }
+    if !new_blocks.is_empty() {
+        let (properties, new_blocks): (Vec<_>, Vec<_>) = new_blocks.into_iter().unzip();
+        let block_ids = editor.insert_blocks(properties, None, cx);
+        blocks.extend(new_blocks.into_iter().zip(block_ids).map(
+            |((excerpt_id, buffer_id, position, lenses), id)| CodeLensBlock {
+                id,
+                excerpt_id,
+                buffer_id,
+                position,
+                lenses,
+            },
+        ));
+    }
+    editor.code_lens.blocks = blocks;
+}
+
+fn render_code_lens(
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    excerpt_id: ExcerptId,
+    lenses: Vec<CodeLens>,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut children = Vec::with_capacity(lenses.len() * 2);
+        for (ix, lens) in lenses.iter().enumerate() {
+            if ix > 0 {
+                children.push(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Disabled)
+                        .into_any_element(),
+                );
+            }
+            let editor = editor.clone();
+            let buffer = buffer.clone();
+            let lens = lens.clone();
+            children.push(
+                ButtonLike::new(("code-lens", ix))
+                    .child(
+                        Label::new(lens.title().unwrap_or_default().to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .on_click(move |_, window, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                apply_code_lens(
+                                    editor,
+                                    buffer.clone(),
+                                    excerpt_id,
+                                    lens.clone(),
+                                    window,
+                                    cx,
+                                );
+                            })
+                            .ok();
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(children)
+            .into_any_element()
+    })
+}
+
+fn apply_code_lens(
+    editor: &mut Editor,
+    buffer: Entity<Buffer>,
+    excerpt_id: ExcerptId,
+    lens: CodeLens,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    // Client-side commands are handled like their editor counterparts, at the lens position.
+    if let Some(client_command) = lens.client_command() {
+        let snapshot = editor.snapshot(window, cx);
+        let Some(position) = snapshot
+            .buffer_snapshot
+            .anchor_in_excerpt(excerpt_id, lens.range.start)
+        else {
+            return;
+        };
+        window.focus(&editor.focus_handle);
+        match client_command {
+            CodeLensClientCommand::Run => {
+                let row = position.to_display_point(&snapshot).row();
+                editor.toggle_code_actions(
+                    &ToggleCodeActions {
+                        deployed_from_indicator: Some(row),
+                    },
+                    window,
+                    cx,
+                );
+            }
+            CodeLensClientCommand::ShowReferences => {
+                editor.change_selections(None, window, cx, |selections| {
+                    selections.select_anchor_ranges([position..position]);
+                });
+                if let Some(task) = editor.find_all_references(&FindAllReferences, window, cx) {
+                    task.detach_and_log_err(cx);
+                }
+            }
+        }
+        return;
+    }
+
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let workspace = workspace.downgrade();
+    let title = lens.title().unwrap_or_default().to_string();
+    let apply_code_lens =
+        project.update(cx, |project, cx| project.apply_code_lens(buffer, lens, cx));
+    cx.spawn_in(window, |editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    })
+    .detach_and_notify_err(window, cx);
+}
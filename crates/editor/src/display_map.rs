@@ -82,6 +82,17 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+/// Styled ranges of semantic tokens, ordered by their start.
+pub(crate) type SemanticTokenHighlights = Arc<[(Range<Anchor>, HighlightStyle)]>;
+
+/// Where the foldable ranges of a buffer come from, when they aren't derived from indentation.
+#[derive(Clone, Debug)]
+pub(crate) enum BufferFoldRanges {
+    /// Bracket pairs from the buffer's tree-sitter grammar.
+    TreeSitter,
+    /// Line ranges reported by a language server, ordered by their start.
+    LanguageServer(Arc<[Range<text::Anchor>]>),
+}
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -105,6 +116,10 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Regions of text highlighted by semantic tokens from language servers.
+    semantic_token_highlights: SemanticTokenHighlights,
+    /// Sources of foldable ranges for buffers that aren't folded by indentation.
+    fold_ranges: TreeMap<BufferId, BufferFoldRanges>,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -157,6 +172,8 @@ impl DisplayMap {
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_token_highlights: Default::default(),
+            fold_ranges: Default::default(),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -184,6 +201,8 @@ impl DisplayMap {
             crease_snapshot: self.crease_map.snapshot(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
+            fold_ranges: self.fold_ranges.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -482,6 +501,22 @@ impl DisplayMap {
         }
     }
 
+    pub(crate) fn set_semantic_token_highlights(&mut self, highlights: SemanticTokenHighlights) {
+        self.semantic_token_highlights = highlights;
+    }
+
+    pub(crate) fn set_buffer_fold_ranges(
+        &mut self,
+        buffer_id: BufferId,
+        fold_ranges: Option<BufferFoldRanges>,
+    ) {
+        if let Some(fold_ranges) = fold_ranges {
+            self.fold_ranges.insert(buffer_id, fold_ranges);
+        } else {
+            self.fold_ranges.remove(&buffer_id);
+        }
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&type_id)?;
         Some((highlights.0, &highlights.1))
@@ -558,6 +593,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
     pub styles: HighlightStyles,
 }
 
@@ -692,6 +728,8 @@ pub struct DisplaySnapshot {
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_token_highlights: SemanticTokenHighlights,
+    fold_ranges: TreeMap<BufferId, BufferFoldRanges>,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -874,6 +912,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_token_highlights: Some(&self.semantic_token_highlights),
                 styles: highlight_styles,
             },
         )
@@ -1184,6 +1223,69 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Returns whether a fold can be started on the given row, without considering creases.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        match self.fold_range_from_source(buffer_row) {
+            Some(range) => range.is_some(),
+            None => self.starts_indent(buffer_row),
+        }
+    }
+
+    /// Returns the range folded from the given row by the source configured for its buffer, or
+    /// `None` if the buffer is folded by indentation.
+    fn fold_range_from_source(&self, buffer_row: MultiBufferRow) -> Option<Option<Range<Point>>> {
+        let row_start = MultiBufferPoint::new(buffer_row.0, 0);
+        let mut excerpt = self
+            .buffer_snapshot
+            .excerpt_containing(row_start..row_start)?;
+        let fold_ranges = self.fold_ranges.get(&excerpt.buffer_id())?;
+        let buffer = excerpt.buffer();
+        let buffer_row = buffer
+            .offset_to_point(
+                excerpt.map_offset_to_buffer(row_start.to_offset(&self.buffer_snapshot)),
+            )
+            .row;
+        let row_end = Point::new(buffer_row, buffer.line_len(buffer_row));
+
+        let range = match fold_ranges {
+            BufferFoldRanges::TreeSitter => buffer
+                .bracket_ranges(Point::new(buffer_row, 0)..row_end)
+                .filter(|pair| {
+                    buffer.offset_to_point(pair.open_range.start).row == buffer_row
+                        && buffer.offset_to_point(pair.close_range.start).row > buffer_row
+                })
+                .min_by_key(|pair| pair.open_range.start)
+                .map(|pair| pair.open_range.end..pair.close_range.start),
+            BufferFoldRanges::LanguageServer(ranges) => {
+                let start_ix = ranges.partition_point(|range| {
+                    text::ToPoint::to_point(&range.start, buffer).row < buffer_row
+                });
+                ranges[start_ix..]
+                    .iter()
+                    .take_while(|range| {
+                        text::ToPoint::to_point(&range.start, buffer).row == buffer_row
+                    })
+                    .map(|range| text::ToPoint::to_point(&range.end, buffer))
+                    .filter(|end| end.row > buffer_row)
+                    .max()
+                    .map(|end| buffer.point_to_offset(row_end)..buffer.point_to_offset(end))
+            }
+        };
+        let Some(mut range) = range else {
+            return Some(None);
+        };
+
+        // Don't let folds extend past the excerpt they start in.
+        range.end = range.end.min(excerpt.buffer_range().end);
+        if range.start >= range.end {
+            return Some(None);
+        }
+        let range = excerpt.map_range_from_buffer(range);
+        Some(Some(
+            range.start.to_point(&self.buffer_snapshot)..range.end.to_point(&self.buffer_snapshot),
+        ))
+    }
+
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
         let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
@@ -1220,6 +1322,15 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
+        } else if let Some(range) = self.fold_range_from_source(buffer_row) {
+            let range = range.filter(|_| !self.is_line_folded(buffer_row))?;
+            Some(Crease::Inline {
+                range,
+                placeholder: self.fold_placeholder.clone(),
+                render_toggle: None,
+                render_trailer: None,
+                metadata: None,
+            })
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
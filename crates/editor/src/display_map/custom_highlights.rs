@@ -12,6 +12,8 @@ use std::{
 };
 use sum_tree::TreeMap;
 
+use super::SemanticTokenHighlights;
+
 pub struct CustomHighlightsChunks<'a> {
     buffer_chunks: MultiBufferChunks<'a>,
     buffer_chunk: Option<Chunk<'a>>,
@@ -21,6 +23,9 @@ pub struct CustomHighlightsChunks<'a> {
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
     active_highlights: BTreeMap<TypeId, HighlightStyle>,
     text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+
+    semantic_highlights: Peekable<vec::IntoIter<(Range<usize>, HighlightStyle)>>,
+    semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -36,8 +41,12 @@ impl<'a> CustomHighlightsChunks<'a> {
         range: Range<usize>,
         language_aware: bool,
         text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+        semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
         multibuffer_snapshot: &'a MultiBufferSnapshot,
     ) -> Self {
+        // Semantic tokens refine the syntax highlighting, so they don't apply to chunks that
+        // aren't language aware either.
+        let semantic_token_highlights = semantic_token_highlights.filter(|_| language_aware);
         Self {
             buffer_chunks: multibuffer_snapshot.chunks(range.clone(), language_aware),
             buffer_chunk: None,
@@ -50,6 +59,12 @@ impl<'a> CustomHighlightsChunks<'a> {
                 multibuffer_snapshot,
             ),
             active_highlights: Default::default(),
+            semantic_token_highlights,
+            semantic_highlights: create_semantic_highlights(
+                &range,
+                semantic_token_highlights,
+                multibuffer_snapshot,
+            ),
             multibuffer_snapshot,
         }
     }
@@ -57,6 +72,11 @@ impl<'a> CustomHighlightsChunks<'a> {
     pub fn seek(&mut self, new_range: Range<usize>) {
         self.highlight_endpoints =
             create_highlight_endpoints(&new_range, self.text_highlights, self.multibuffer_snapshot);
+        self.semantic_highlights = create_semantic_highlights(
+            &new_range,
+            self.semantic_token_highlights,
+            self.multibuffer_snapshot,
+        );
         self.offset = new_range.start;
         self.buffer_chunks.seek(new_range);
         self.buffer_chunk.take();
@@ -112,6 +132,38 @@ fn create_highlight_endpoints(
     highlight_endpoints.into_iter().peekable()
 }
 
+fn create_semantic_highlights(
+    range: &Range<usize>,
+    semantic_token_highlights: Option<&SemanticTokenHighlights>,
+    buffer: &MultiBufferSnapshot,
+) -> Peekable<vec::IntoIter<(Range<usize>, HighlightStyle)>> {
+    let mut semantic_highlights = Vec::new();
+    if let Some(semantic_token_highlights) = semantic_token_highlights {
+        let start = buffer.anchor_after(range.start);
+        let end = buffer.anchor_after(range.end);
+        let start_ix = match semantic_token_highlights.binary_search_by(|(probe, _)| {
+            if probe.end.cmp(&start, buffer).is_gt() {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Less
+            }
+        }) {
+            Ok(i) | Err(i) => i,
+        };
+
+        for (token_range, style) in &semantic_token_highlights[start_ix..] {
+            if token_range.start.cmp(&end, buffer).is_ge() {
+                break;
+            }
+            semantic_highlights.push((
+                token_range.start.to_offset(buffer)..token_range.end.to_offset(buffer),
+                *style,
+            ));
+        }
+    }
+    semantic_highlights.into_iter().peekable()
+}
+
 impl<'a> Iterator for CustomHighlightsChunks<'a> {
     type Item = Chunk<'a>;
 
@@ -131,6 +183,21 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             }
         }
 
+        while self
+            .semantic_highlights
+            .next_if(|(range, _)| range.end <= self.offset)
+            .is_some()
+        {}
+        let mut semantic_style = None;
+        if let Some((range, style)) = self.semantic_highlights.peek() {
+            if range.start <= self.offset {
+                semantic_style = Some(*style);
+                next_highlight_endpoint = next_highlight_endpoint.min(range.end);
+            } else {
+                next_highlight_endpoint = next_highlight_endpoint.min(range.start);
+            }
+        }
+
         let chunk = self
             .buffer_chunk
             .get_or_insert_with(|| self.buffer_chunks.next().unwrap());
@@ -148,8 +215,8 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             text: prefix,
             ..chunk.clone()
         };
-        if !self.active_highlights.is_empty() {
-            let mut highlight_style = HighlightStyle::default();
+        if semantic_style.is_some() || !self.active_highlights.is_empty() {
+            let mut highlight_style = semantic_style.unwrap_or_default();
             for active_highlight in self.active_highlights.values() {
                 highlight_style.highlight(*active_highlight);
             }
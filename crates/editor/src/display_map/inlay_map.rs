@@ -1,5 +1,6 @@
 use crate::{HighlightStyles, InlayId};
 use collections::BTreeSet;
+use gpui::Hsla;
 use language::{Chunk, Edit, Point, TextSummary};
 use multi_buffer::{
     Anchor, MultiBufferRow, MultiBufferRows, MultiBufferSnapshot, RowInfo, ToOffset,
@@ -39,6 +40,8 @@ pub struct Inlay {
     pub(crate) id: InlayId,
     pub position: Anchor,
     pub text: text::Rope,
+    /// The color the inlay's text is displayed in, overriding its highlight style.
+    pub color: Option<Hsla>,
 }
 
 impl Inlay {
@@ -54,6 +57,7 @@ impl Inlay {
             id: InlayId::Hint(id),
             position,
             text: text.into(),
+            color: None,
         }
     }
 
@@ -62,6 +66,27 @@ impl Inlay {
             id: InlayId::InlineCompletion(id),
             position,
             text: text.into(),
+            color: None,
+        }
+    }
+
+    /// The text that a search match would be replaced with, displayed after the match.
+    pub fn replacement_preview<T: Into<Rope>>(id: usize, position: Anchor, text: T) -> Self {
+        Self {
+            id: InlayId::ReplacementPreview(id),
+            position,
+            text: text.into(),
+            color: None,
+        }
+    }
+
+    /// A swatch of the given color, displayed before a color literal.
+    pub fn color(id: usize, position: Anchor, color: Hsla) -> Self {
+        Self {
+            id: InlayId::Color(id),
+            position,
+            text: "■ ".into(),
+            color: Some(color),
         }
     }
 }
@@ -287,7 +312,14 @@ impl<'a> Iterator for InlayChunks<'a> {
                         })
                     }
                     InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    InlayId::Color(_) => None,
+                    InlayId::ReplacementPreview(_) => {
+                        self.highlight_styles.inline_completion.map(|s| s.insertion)
+                    }
                 };
+                if let Some(color) = inlay.color {
+                    highlight_style.get_or_insert_with(Default::default).color = Some(color);
+                }
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
                 if let Some((style, highlight)) = inlay_style_and_highlight {
@@ -640,6 +672,7 @@ impl InlayMap {
                     id: inlay_id,
                     position: snapshot.buffer.anchor_at(position, bias),
                     text: text.into(),
+                    color: None,
                 });
             } else {
                 to_remove.push(
@@ -998,6 +1031,7 @@ impl InlaySnapshot {
             buffer_range,
             language_aware,
             highlights.text_highlights,
+            highlights.semantic_token_highlights,
             &self.buffer,
         );
 
@@ -1176,6 +1210,7 @@ mod tests {
                 id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                 position: buffer.read(cx).snapshot(cx).anchor_after(3),
                 text: "|123|".into(),
+                color: None,
             }],
         );
         assert_eq!(inlay_snapshot.text(), "abc|123|defghi");
@@ -1253,11 +1288,13 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(3),
                     text: "|123|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::InlineCompletion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_after(3),
                     text: "|456|".into(),
+                    color: None,
                 },
             ],
         );
@@ -1468,16 +1505,19 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(0),
                     text: "|123|\n".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(4),
                     text: "|456|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::InlineCompletion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(7),
                     text: "\n|567|\n".into(),
+                    color: None,
                 },
             ],
         );
@@ -0,0 +1,357 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{
+    hsla, AnyElement, App, Context, Entity, Focusable as _, Hsla, Pixels, Point, Task, WeakEntity,
+    Window,
+};
+use language::{language_settings::language_settings, Buffer};
+use project::{ColorPresentation, DocumentColor};
+use text::Bias;
+use ui::{prelude::*, ContextMenu};
+use util::{post_inc, ResultExt as _};
+
+use crate::{
+    display_map::Inlay, element::PointForPosition, mouse_context_menu::MouseContextMenu, Editor,
+    EditorMode, EditorSnapshot, InlayId,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The hues of the color picker's palette, one column each.
+const PALETTE_HUES: usize = 12;
+/// The lightnesses of the color picker's palette, one row each, followed by a row of grays.
+const PALETTE_LIGHTNESSES: [f32; 3] = [0.75, 0.5, 0.25];
+
+/// Color swatches currently displayed in an editor, rendered as inlays before the color literals
+/// reported by language servers.
+#[derive(Default)]
+pub(super) struct DocumentColorsState {
+    swatches: HashMap<InlayId, (Entity<Buffer>, DocumentColor)>,
+    refresh_task: Option<Task<()>>,
+}
+
+pub(super) fn refresh_document_colors(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(
+                buffer.language().map(|language| language.name()),
+                buffer.file(),
+                cx,
+            )
+            .document_colors
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        clear_document_colors(editor, cx);
+        return;
+    }
+
+    editor.document_colors.refresh_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let Ok(color_tasks) = project.update(&mut cx, |project, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let colors = project.document_colors(&buffer, cx);
+                    async move { (buffer, colors.await.log_err().unwrap_or_default()) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let colors = join_all(color_tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                display_document_colors(editor, colors, cx);
+            })
+            .ok();
+    }));
+}
+
+fn clear_document_colors(editor: &mut Editor, cx: &mut Context<Editor>) {
+    editor.document_colors.refresh_task = None;
+    let inlay_ids = std::mem::take(&mut editor.document_colors.swatches)
+        .into_keys()
+        .collect::<Vec<_>>();
+    if !inlay_ids.is_empty() {
+        editor.splice_inlays(&inlay_ids, Vec::new(), cx);
+    }
+}
+
+fn display_document_colors(
+    editor: &mut Editor,
+    colors: Vec<(Entity<Buffer>, Vec<DocumentColor>)>,
+    cx: &mut Context<Editor>,
+) {
+    let multi_buffer = editor.buffer.read(cx);
+    let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+    let mut swatches = HashMap::default();
+    let mut inlays = Vec::new();
+    for (buffer, colors) in colors {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        for (excerpt_id, excerpt_range) in
+            multi_buffer.excerpts_for_buffer(buffer_snapshot.remote_id(), cx)
+        {
+            let context = excerpt_range.context;
+            for color in &colors {
+                let position = color.range.start;
+                if context.start.cmp(&position, &buffer_snapshot).is_gt()
+                    || context.end.cmp(&position, &buffer_snapshot).is_lt()
+                {
+                    continue;
+                }
+                let Some(anchor) = multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, position)
+                else {
+                    continue;
+                };
+                let inlay = Inlay::color(post_inc(&mut editor.next_inlay_id), anchor, color.hsla());
+                swatches.insert(inlay.id, (buffer.clone(), color.clone()));
+                inlays.push(inlay);
+            }
+        }
+    }
+
+    let old_inlay_ids = std::mem::replace(&mut editor.document_colors.swatches, swatches)
+        .into_keys()
+        .collect::<Vec<_>>();
+    editor.splice_inlays(&old_inlay_ids, inlays, cx);
+}
+
+/// Opens a color picker for the color swatch at the given position, if there is one. Returns
+/// whether a swatch was clicked.
+pub(super) fn deploy_color_picker(
+    editor: &mut Editor,
+    snapshot: &EditorSnapshot,
+    point_for_position: &PointForPosition,
+    position: Point<Pixels>,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> bool {
+    // Inlays occupy no space in the buffer, so clicking one lands between two valid positions.
+    if editor.document_colors.swatches.is_empty()
+        || point_for_position.previous_valid == point_for_position.next_valid
+        || point_for_position.column_overshoot_after_line_end > 0
+    {
+        return false;
+    }
+    let Some(project) = editor.project.clone() else {
+        return false;
+    };
+
+    let buffer_snapshot = &snapshot.buffer_snapshot;
+    let previous_valid_anchor = buffer_snapshot.anchor_at(
+        point_for_position.previous_valid.to_point(snapshot),
+        Bias::Left,
+    );
+    let next_valid_anchor = buffer_snapshot.anchor_at(
+        point_for_position.next_valid.to_point(snapshot),
+        Bias::Right,
+    );
+    let Some((anchor, buffer, color)) = editor
+        .display_map
+        .read(cx)
+        .current_inlays()
+        .filter(|inlay| {
+            inlay
+                .position
+                .cmp(&previous_valid_anchor, buffer_snapshot)
+                .is_ge()
+                && inlay
+                    .position
+                    .cmp(&next_valid_anchor, buffer_snapshot)
+                    .is_le()
+        })
+        .find_map(|inlay| {
+            let (buffer, color) = editor.document_colors.swatches.get(&inlay.id)?;
+            Some((inlay.position, buffer.clone(), color.clone()))
+        })
+    else {
+        return false;
+    };
+
+    // Offer the other ways of writing the current color next to the palette.
+    let presentations = project.update(cx, |project, cx| {
+        project.color_presentations(&buffer, &color, color.color, cx)
+    });
+    cx.spawn_in(window, |editor, mut cx| async move {
+        let presentations = presentations.await.log_err().unwrap_or_default();
+        editor.update_in(&mut cx, |editor, window, cx| {
+            let context_menu = build_color_picker(
+                cx.entity().downgrade(),
+                buffer,
+                color,
+                presentations,
+                window,
+                cx,
+            );
+            editor.mouse_context_menu = MouseContextMenu::pinned_to_editor(
+                editor,
+                anchor,
+                position,
+                context_menu,
+                window,
+                cx,
+            );
+            cx.notify();
+        })
+    })
+    .detach_and_log_err(cx);
+    true
+}
+
+fn build_color_picker(
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    color: DocumentColor,
+    presentations: Vec<ColorPresentation>,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Entity<ContextMenu> {
+    ContextMenu::build(window, cx, |menu, _, _| {
+        let mut menu = menu.custom_row({
+            let editor = editor.clone();
+            let buffer = buffer.clone();
+            let color = color.clone();
+            move |_, cx| render_palette(editor.clone(), buffer.clone(), color.clone(), cx)
+        });
+        if !presentations.is_empty() {
+            menu = menu.separator();
+        }
+        for presentation in presentations {
+            let editor = editor.clone();
+            let buffer = buffer.clone();
+            menu = menu.entry(presentation.label.clone(), None, move |window, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        apply_color_presentation(
+                            editor,
+                            buffer.clone(),
+                            presentation.clone(),
+                            window,
+                            cx,
+                        );
+                    })
+                    .ok();
+            });
+        }
+        menu
+    })
+}
+
+fn render_palette(
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    color: DocumentColor,
+    cx: &mut App,
+) -> AnyElement {
+    let alpha = color.color.alpha;
+    let border_color = cx.theme().colors().border;
+    let rows = PALETTE_LIGHTNESSES
+        .iter()
+        .map(|lightness| {
+            (0..PALETTE_HUES)
+                .map(|column| hsla(column as f32 / PALETTE_HUES as f32, 0.8, *lightness, alpha))
+                .collect::<Vec<_>>()
+        })
+        .chain([(0..PALETTE_HUES)
+            .map(|column| hsla(0., 0., column as f32 / (PALETTE_HUES - 1) as f32, alpha))
+            .collect()]);
+
+    let mut ix = 0;
+    v_flex()
+        .gap_1()
+        .px_1()
+        .children(rows.map(|row| {
+            h_flex().gap_1().children(row.into_iter().map(|swatch| {
+                let editor = editor.clone();
+                let buffer = buffer.clone();
+                let color = color.clone();
+                div()
+                    .id(("color-swatch", post_inc(&mut ix)))
+                    .size_4()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(border_color)
+                    .bg(swatch)
+                    .cursor_pointer()
+                    .on_click(move |_, window, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                editor.mouse_context_menu.take();
+                                window.focus(&editor.focus_handle(cx));
+                                pick_color(editor, buffer.clone(), &color, swatch, window, cx);
+                            })
+                            .ok();
+                    })
+            }))
+        }))
+        .into_any_element()
+}
+
+/// Replaces the color literal with the language server's preferred way of writing `new_color`.
+fn pick_color(
+    editor: &mut Editor,
+    buffer: Entity<Buffer>,
+    color: &DocumentColor,
+    new_color: Hsla,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let rgba = new_color.to_rgb();
+    let new_color = lsp::Color {
+        red: rgba.r,
+        green: rgba.g,
+        blue: rgba.b,
+        alpha: rgba.a,
+    };
+    let presentations = project.update(cx, |project, cx| {
+        project.color_presentations(&buffer, color, new_color, cx)
+    });
+    cx.spawn_in(window, |editor, mut cx| async move {
+        let presentation = presentations
+            .await?
+            .into_iter()
+            .next()
+            .context("language server proposed no color presentation")?;
+        editor.update_in(&mut cx, |editor, window, cx| {
+            apply_color_presentation(editor, buffer, presentation, window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn apply_color_presentation(
+    editor: &mut Editor,
+    buffer: Entity<Buffer>,
+    presentation: ColorPresentation,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    editor.transact(window, cx, |_, _, cx| {
+        buffer.update(cx, |buffer, cx| buffer.edit(presentation.edits, None, cx));
+    });
+}
@@ -13,14 +13,19 @@
 //!
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
+mod align;
 mod blink_manager;
+pub mod bookmarks;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod commit_tooltip;
 pub mod display_map;
+mod document_colors;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -37,6 +42,8 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
+mod snippet_variables;
 pub mod tasks;
 
 #[cfg(test)]
@@ -52,6 +59,7 @@ pub use actions::{AcceptEditPrediction, OpenExcerpts, OpenExcerptsSplit};
 use aho_corasick::AhoCorasick;
 use anyhow::{anyhow, Context as _, Result};
 use blink_manager::BlinkManager;
+use bookmarks::BookmarkStore;
 use buffer_diff::DiffHunkSecondaryStatus;
 use client::{Collaborator, ParticipantIndex};
 use clock::ReplicaId;
@@ -60,10 +68,13 @@ use convert_case::{Case, Casing};
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
 pub use editor_settings::{
-    CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine, SearchSettings, ShowScrollbar,
+    CurrentLineHighlight, EditorSettings, MinimapThumb, ScrollBeyondLastLine, SearchSettings,
+    ShowMinimap, ShowScrollbar,
 };
 pub use editor_settings_controls::*;
-use element::{layout_line, AcceptEditPredictionBinding, LineWithInvisibles, PositionMap};
+use element::{
+    layout_line, AcceptEditPredictionBinding, LineWithInvisibles, MinimapWords, PositionMap,
+};
 pub use element::{
     CursorLayout, EditorElement, HighlightedRange, HighlightedRangeLine, PointForPosition,
 };
@@ -101,8 +112,8 @@ use language::{
     language_settings::{
         self, all_language_settings, language_settings, InlayHintSettings, RewrapBehavior,
     },
-    point_from_lsp, text_diff_with_options, AutoindentMode, BracketMatch, BracketPair, Buffer,
-    Capability, CharKind, CodeLabel, CursorShape, Diagnostic, DiffOptions, DiskState,
+    range_from_lsp_encoded, text_diff_with_options, AutoindentMode, BracketMatch, BracketPair,
+    Buffer, Capability, CharKind, CodeLabel, CursorShape, Diagnostic, DiffOptions, DiskState,
     EditPredictionsMode, EditPreview, HighlightedText, IndentKind, IndentSize, Language,
     OffsetRangeExt, Point, Selection, SelectionGoal, TextObject, TransactionId, TreeSitterOptions,
 };
@@ -137,6 +148,7 @@ use multi_buffer::{
 use project::{
     lsp_store::{CompletionDocumentation, FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
+    search::SearchQuery,
     CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
     PrepareRenameResponse, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
 };
@@ -221,6 +233,8 @@ const COLUMNAR_SELECTION_MODIFIERS: Modifiers = Modifiers {
 pub enum InlayId {
     InlineCompletion(usize),
     Hint(usize),
+    Color(usize),
+    ReplacementPreview(usize),
 }
 
 impl InlayId {
@@ -228,6 +242,8 @@ impl InlayId {
         match self {
             Self::InlineCompletion(id) => *id,
             Self::Hint(id) => *id,
+            Self::Color(id) => *id,
+            Self::ReplacementPreview(id) => *id,
         }
     }
 }
@@ -259,6 +275,7 @@ pub fn init_settings(cx: &mut App) {
 
 pub fn init(cx: &mut App) {
     init_settings(cx);
+    bookmarks::init(cx);
 
     workspace::register_project_item::<Editor>(cx);
     workspace::FollowableViewRegistry::register::<Editor>(cx);
@@ -649,6 +666,11 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    code_lens: code_lens::CodeLensState,
+    semantic_tokens_task: Option<Task<()>>,
+    folding_ranges_task: Option<Task<()>>,
+    document_colors: document_colors::DocumentColorsState,
+    replacement_previews: Vec<InlayId>,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -712,6 +734,7 @@ pub struct Editor {
     >,
     last_bounds: Option<Bounds<Pixels>>,
     last_position_map: Option<Rc<PositionMap>>,
+    minimap_words: Option<Rc<MinimapWords>>,
     expect_bounds_change: Option<Bounds<Pixels>>,
     tasks: BTreeMap<(BufferId, BufferRow), RunnableTasks>,
     tasks_update_task: Option<Task<()>>,
@@ -944,6 +967,14 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    transformations: Vec<SnippetTransformation>,
+}
+
+/// Text in a snippet that's kept in sync with a tabstop by applying a transform to its text.
+struct SnippetTransformation {
+    source: Range<Anchor>,
+    target: Range<Anchor>,
+    transform: snippet::Transform,
 }
 
 #[doc(hidden)]
@@ -1205,7 +1236,13 @@ impl Editor {
 
         let blink_manager = cx.new(|cx| BlinkManager::new(CURSOR_BLINK_INTERVAL, cx));
 
-        let soft_wrap_mode_override = matches!(mode, EditorMode::SingleLine { .. })
+        // Wrapping every line of a large file would stall scrolling.
+        let is_large_file = buffer
+            .read(cx)
+            .as_singleton()
+            .is_some_and(|buffer| buffer.read(cx).is_large_file());
+        let soft_wrap_mode_override = (matches!(mode, EditorMode::SingleLine { .. })
+            || is_large_file)
             .then(|| language_settings::SoftWrap::None);
 
         let mut project_subscriptions = Vec::new();
@@ -1223,6 +1260,13 @@ impl Editor {
                         if let project::Event::RefreshInlayHints = event {
                             editor
                                 .refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                        } else if let project::Event::RefreshCodeLens = event {
+                            code_lens::refresh_code_lens(editor, window, cx);
+                        } else if let project::Event::RefreshSemanticTokens = event {
+                            semantic_tokens::refresh_semantic_tokens(editor, window, cx);
+                        } else if let project::Event::LanguageServerAdded(..) = event {
+                            folding_ranges::refresh_folding_ranges(editor, window, cx);
+                            document_colors::refresh_document_colors(editor, window, cx);
                         } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                             if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                                 let focus_handle = editor.focus_handle(cx);
@@ -1359,6 +1403,11 @@ impl Editor {
             selection_highlight_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            code_lens: Default::default(),
+            semantic_tokens_task: Default::default(),
+            folding_ranges_task: Default::default(),
+            document_colors: Default::default(),
+            replacement_previews: Vec::new(),
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
@@ -1390,6 +1439,7 @@ impl Editor {
             pixel_position_of_newest_cursor: None,
             last_bounds: None,
             last_position_map: None,
+            minimap_words: None,
             expect_bounds_change: None,
             gutter_dimensions: GutterDimensions::default(),
             style: None,
@@ -1398,7 +1448,7 @@ impl Editor {
             next_editor_action_id: EditorActionId::default(),
             editor_actions: Rc::default(),
             inline_completions_hidden_for_vim_mode: false,
-            show_inline_completions_override: None,
+            show_inline_completions_override: is_large_file.then_some(false),
             menu_inline_completions_policy: MenuInlineCompletionsPolicy::ByProvider,
             edit_prediction_settings: EditPredictionSettings::Disabled,
             edit_prediction_indent_conflict: false,
@@ -1451,12 +1501,21 @@ impl Editor {
             load_diff_task: load_uncommitted_diff,
         };
         this.tasks_update_task = Some(this.refresh_runnables(window, cx));
+        code_lens::refresh_code_lens(&mut this, window, cx);
+        semantic_tokens::refresh_semantic_tokens(&mut this, window, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, window, cx);
+        document_colors::refresh_document_colors(&mut this, window, cx);
         this._subscriptions.extend(project_subscriptions);
 
         this.end_selection(window, cx);
         this.scroll_manager.show_scrollbar(window, cx);
 
         if mode == EditorMode::Full {
+            if let Some(bookmark_store) = BookmarkStore::global(cx) {
+                this._subscriptions
+                    .push(cx.observe(&bookmark_store, |_, _, cx| cx.notify()));
+            }
+
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
             cx.set_global(ScrollbarAutoHide(should_auto_hide_scrollbars));
 
@@ -3640,8 +3699,19 @@ impl Editor {
         self.inlay_hint_cache.enabled
     }
 
+    /// Whether the editor shows a single buffer that was opened in large-file mode.
+    fn is_large_file(&self, cx: &App) -> bool {
+        self.buffer
+            .read(cx)
+            .as_singleton()
+            .is_some_and(|buffer| buffer.read(cx).is_large_file())
+    }
+
     fn refresh_inlay_hints(&mut self, reason: InlayHintRefreshReason, cx: &mut Context<Self>) {
-        if self.semantics_provider.is_none() || self.mode != EditorMode::Full {
+        if self.semantics_provider.is_none()
+            || self.mode != EditorMode::Full
+            || self.is_large_file(cx)
+        {
             return;
         }
 
@@ -3808,6 +3878,40 @@ impl Editor {
         cx.notify();
     }
 
+    /// Shows what each of the search matches would be replaced with by the query after it,
+    /// in place of any previews shown before.
+    pub fn show_replacement_previews(
+        &mut self,
+        matches: &[Range<Anchor>],
+        query: &SearchQuery,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let to_insert = matches
+            .iter()
+            .filter_map(|range| {
+                let replacement = items::replacement_for_match(&snapshot, range, query)?;
+                Some(Inlay::replacement_preview(
+                    post_inc(&mut self.next_inlay_id),
+                    range.end,
+                    format!(" → {}", replacement.replace('\n', "⏎")),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let to_remove = mem::replace(
+            &mut self.replacement_previews,
+            to_insert.iter().map(|inlay| inlay.id).collect(),
+        );
+        self.splice_inlays(&to_remove, to_insert, cx);
+    }
+
+    pub fn clear_replacement_previews(&mut self, cx: &mut Context<Self>) {
+        let to_remove = mem::take(&mut self.replacement_previews);
+        if !to_remove.is_empty() {
+            self.splice_inlays(&to_remove, Vec::new(), cx);
+        }
+    }
+
     fn trigger_on_type_formatting(
         &self,
         input: String,
@@ -4050,7 +4154,14 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let buffer = self.buffer.read(cx).snapshot(cx);
+            let app: &App = cx;
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, &|name| {
+                    self.resolve_snippet_variable(name, &buffer, app)
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -4712,7 +4823,7 @@ impl Editor {
         cx: &mut Context<Editor>,
     ) {
         self.selection_highlight_task.take();
-        if !EditorSettings::get_global(cx).selection_highlight {
+        if !EditorSettings::get_global(cx).selection_highlight || self.is_large_file(cx) {
             self.clear_background_highlights::<SelectedTextHighlight>(cx);
             return;
         }
@@ -6768,7 +6879,7 @@ impl Editor {
             choices: Option<Vec<String>>,
         }
 
-        let tabstops = self.buffer.update(cx, |buffer, cx| {
+        let (tabstops, transformations) = self.buffer.update(cx, |buffer, cx| {
             let snippet_text: Arc<str> = snippet.text.clone().into();
             buffer.edit(
                 insertion_ranges
@@ -6781,7 +6892,7 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
-            snippet
+            let tabstops = snippet
                 .tabstops
                 .iter()
                 .map(|tabstop| {
@@ -6814,7 +6925,38 @@ impl Editor {
                         choices: tabstop.choices.clone(),
                     }
                 })
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+
+            let mut delta = 0_isize;
+            let transformations = insertion_ranges
+                .iter()
+                .flat_map(|insertion_range| {
+                    let insertion_start = insertion_range.start as isize + delta;
+                    delta += snippet.text.len() as isize - insertion_range.len() as isize;
+
+                    let offset = move |offset: isize| {
+                        ((insertion_start + offset) as usize).min(snapshot.len())
+                    };
+                    snippet
+                        .transformed_tabstops
+                        .iter()
+                        .filter_map(move |transformed| {
+                            let source =
+                                snippet.tabstops.get(transformed.tabstop)?.ranges.first()?;
+                            Some(SnippetTransformation {
+                                source: snapshot.anchor_before(offset(source.start))
+                                    ..snapshot.anchor_after(offset(source.end)),
+                                // Text typed at the edges of the target belongs to the tabstops
+                                // next to it, not to the transformed text.
+                                target: snapshot.anchor_after(offset(transformed.range.start))
+                                    ..snapshot.anchor_before(offset(transformed.range.end)),
+                                transform: transformed.transform.clone(),
+                            })
+                        })
+                })
+                .collect::<Vec<_>>();
+
+            (tabstops, transformations)
         });
         if let Some(tabstop) = tabstops.first() {
             self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
@@ -6844,6 +6986,7 @@ impl Editor {
                     active_index: 0,
                     ranges,
                     choices,
+                    transformations,
                 });
             }
 
@@ -11735,11 +11878,17 @@ impl Editor {
             let location = match location_task {
                 Some(task) => Some({
                     let target_buffer_handle = task.await.context("open local buffer")?;
+                    let encoding = project.update(&mut cx, |project, cx| {
+                        project.lsp_store().read(cx).position_encoding(server_id)
+                    })?;
                     let range = target_buffer_handle.update(&mut cx, |target_buffer, _| {
-                        let target_start = target_buffer
-                            .clip_point_utf16(point_from_lsp(lsp_location.range.start), Bias::Left);
-                        let target_end = target_buffer
-                            .clip_point_utf16(point_from_lsp(lsp_location.range.end), Bias::Left);
+                        let range = range_from_lsp_encoded(
+                            lsp_location.range,
+                            target_buffer.as_rope(),
+                            encoding,
+                        );
+                        let target_start = target_buffer.clip_point_utf16(range.start, Bias::Left);
+                        let target_end = target_buffer.clip_point_utf16(range.end, Bias::Left);
                         target_buffer.anchor_after(target_start)
                             ..target_buffer.anchor_before(target_end)
                     })?;
@@ -12634,9 +12783,76 @@ impl Editor {
     ) -> Option<TransactionId> {
         self.start_transaction_at(Instant::now(), window, cx);
         update(self, window, cx);
+        self.refresh_snippet_transformations(cx);
         self.end_transaction_at(Instant::now(), cx)
     }
 
+    /// Updates the text that snippets derive from their tabstops with transforms, so that it
+    /// follows the edits made to the tabstops.
+    fn refresh_snippet_transformations(&mut self, cx: &mut Context<Self>) {
+        if self
+            .snippet_stack
+            .iter()
+            .all(|snippet| snippet.transformations.is_empty())
+        {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut edits = Vec::new();
+        for (snippet_ix, snippet) in self.snippet_stack.iter().enumerate() {
+            for (transformation_ix, transformation) in snippet.transformations.iter().enumerate() {
+                let source_text = snapshot
+                    .text_for_range(transformation.source.clone())
+                    .collect::<String>();
+                let new_text = transformation.transform.apply(&source_text);
+                let target_start = transformation.target.start.to_offset(&snapshot);
+                let target_end = transformation
+                    .target
+                    .end
+                    .to_offset(&snapshot)
+                    .max(target_start);
+                let old_text = snapshot
+                    .text_for_range(target_start..target_end)
+                    .collect::<String>();
+                if old_text != new_text {
+                    edits.push((
+                        snippet_ix,
+                        transformation_ix,
+                        target_start..target_end,
+                        new_text,
+                    ));
+                }
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        edits.sort_by_key(|(_, _, range, _)| range.start);
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                edits
+                    .iter()
+                    .map(|(_, _, range, new_text)| (range.clone(), new_text.clone())),
+                None,
+                cx,
+            );
+        });
+
+        // Anchors in the replaced text don't say where the new text is, so find it from the
+        // lengths of the edits before it.
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut delta = 0_isize;
+        for (snippet_ix, transformation_ix, range, new_text) in edits {
+            let start = (range.start as isize + delta) as usize;
+            delta += new_text.len() as isize - range.len() as isize;
+            let end = start + new_text.len();
+            self.snippet_stack[snippet_ix].transformations[transformation_ix].target =
+                snapshot.anchor_after(start)..snapshot.anchor_before(end);
+        }
+    }
+
     pub fn start_transaction_at(
         &mut self,
         now: Instant,
@@ -15066,6 +15282,10 @@ impl Editor {
                     (telemetry, is_via_ssh)
                 };
                 refresh_linked_ranges(self, window, cx);
+                code_lens::refresh_code_lens(self, window, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
+                document_colors::refresh_document_colors(self, window, cx);
                 telemetry.log_edit_event("editor", is_via_ssh);
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -15074,6 +15294,11 @@ impl Editor {
                 excerpts,
             } => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                code_lens::refresh_code_lens(self, window, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
+                document_colors::refresh_document_colors(self, window, cx);
+                bookmarks::register_buffers(self, cx);
                 let buffer_id = buffer.read(cx).remote_id();
                 if self.buffer.read(cx).diff_for(buffer_id).is_none() {
                     if let Some(project) = &self.project {
@@ -15117,6 +15342,9 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, window, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
+                document_colors::refresh_document_colors(self, window, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -15152,6 +15380,10 @@ impl Editor {
 
     fn settings_changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+        code_lens::refresh_code_lens(self, window, cx);
+        semantic_tokens::refresh_semantic_tokens(self, window, cx);
+        folding_ranges::refresh_folding_ranges(self, window, cx);
+        document_colors::refresh_document_colors(self, window, cx);
         self.refresh_inline_completion(true, false, window, cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
@@ -17048,7 +17280,7 @@ impl EditorSnapshot {
             }
         }
 
-        is_foldable |= self.starts_indent(buffer_row);
+        is_foldable |= self.starts_fold(buffer_row);
 
         if folded || (is_foldable && (row_contains_cursor || self.gutter_hovered)) {
             Some(
@@ -16,6 +16,8 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
+    pub sticky_scroll: StickyScroll,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -36,6 +38,7 @@ pub struct EditorSettings {
     pub search: SearchSettings,
     pub auto_signature_help: bool,
     pub show_signature_help_after_edits: bool,
+    pub code_lens: bool,
     pub jupyter: Jupyter,
 }
 
@@ -112,6 +115,19 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: ShowMinimap,
+    pub width: f32,
+    pub thumb: MinimapThumb,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -137,6 +153,32 @@ pub enum ShowScrollbar {
     Never,
 }
 
+/// When to show the minimap in the editor.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowMinimap {
+    /// Always show the minimap, next to the text.
+    Always,
+    /// Show the minimap over the text when hovering the right edge of the editor.
+    Hover,
+    /// Never show the minimap.
+    Never,
+}
+
+/// How to draw the part of the minimap that is visible in the editor.
+///
+/// Default: solid
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimapThumb {
+    /// Fill the visible part of the minimap.
+    Solid,
+    /// Only draw a border around the visible part of the minimap.
+    Border,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -300,6 +342,10 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -378,6 +424,11 @@ pub struct EditorSettingsContent {
     /// Default: false
     pub show_signature_help_after_edits: Option<bool>,
 
+    /// Whether to show code lenses provided by language servers above the items they refer to.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
+
     /// Jupyter REPL settings.
     pub jupyter: Option<JupyterContent>,
 }
@@ -449,6 +500,37 @@ pub struct ScrollbarAxesContent {
     vertical: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// When to show the minimap in the editor.
+    ///
+    /// Default: never
+    pub show: Option<ShowMinimap>,
+    /// The width of the minimap in pixels.
+    ///
+    /// Default: 120
+    pub width: Option<f32>,
+    /// How to draw the part of the minimap that is visible in the editor.
+    ///
+    /// Default: solid
+    pub thumb: Option<MinimapThumb>,
+}
+
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the lines that open the scopes enclosing the top of the viewport, such as
+    /// functions, classes and impls, at the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of enclosing scopes to pin.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
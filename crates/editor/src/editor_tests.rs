@@ -3779,6 +3779,75 @@ async fn test_manipulate_lines_with_multi_selection(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_align_selections(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        let a ˇ= 1;
+        let bcd ˇ= 2;
+    "});
+    cx.update_editor(|e, window, cx| e.align_selections(&AlignSelections, window, cx));
+    cx.assert_editor_state(indoc! {"
+        let a   ˇ= 1;
+        let bcd ˇ= 2;
+    "});
+
+    // The n-th selection on each line is aligned with the n-th selection on the others.
+    cx.set_state(indoc! {"
+        «aˇ» «bbˇ»
+        «cccˇ» «dˇ»
+    "});
+    cx.update_editor(|e, window, cx| e.align_selections(&AlignSelections, window, cx));
+    cx.assert_editor_state(indoc! {"
+        «aˇ»   «bbˇ»
+        «cccˇ» «dˇ»
+    "});
+
+    // A single line has nothing to align with.
+    cx.set_state("a ˇb ˇc");
+    cx.update_editor(|e, window, cx| e.align_selections(&AlignSelections, window, cx));
+    cx.assert_editor_state("a ˇb ˇc");
+}
+
+#[gpui::test]
+async fn test_align_on_delimiter(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        «a = 1
+        bcd := 2
+        ef => 3ˇ»
+        g = 4
+    "});
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(&AlignOnDelimiter::default(), window, cx)
+    });
+    cx.assert_editor_state(indoc! {"
+        «a   = 1
+        bcd := 2
+        ef  => 3ˇ»
+        g = 4
+    "});
+
+    // Tabs extend to the next tab stop and wide characters take up two columns.
+    cx.set_state("«\tx, 1\n日本, 2ˇ»");
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(
+            &AlignOnDelimiter {
+                pattern: Some(",".into()),
+            },
+            window,
+            cx,
+        )
+    });
+    cx.assert_editor_state("«\tx, 1\n日本 , 2ˇ»");
+}
+
 #[gpui::test]
 async fn test_manipulate_text(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -7220,6 +7289,64 @@ async fn test_snippets(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_transformed_tabstops(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges("ˇ\nˇ", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| build_editor(buffer, window, cx));
+
+    editor.update_in(cx, |editor, window, cx| {
+        let snippet = Snippet::parse("let ${1:name} = ${1/(.*)/${1:/upcase}/};$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, window, cx)
+            .unwrap();
+
+        fn assert(editor: &mut Editor, cx: &mut Context<Editor>, marked_text: &str) {
+            let (expected_text, selection_ranges) = marked_text_ranges(marked_text, false);
+            assert_eq!(editor.text(cx), expected_text);
+            assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+        }
+
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let «name» = NAME;
+                let «name» = NAME;"},
+        );
+
+        // The transformed text follows the tabstop as it's typed.
+        editor.handle_input("foo", window, cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let fooˇ = FOO;
+                let fooˇ = FOO;"},
+        );
+        editor.backspace(&Backspace, window, cx);
+        editor.handle_input("x_bar", window, cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let fox_barˇ = FOX_BAR;
+                let fox_barˇ = FOX_BAR;"},
+        );
+
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let fox_bar = FOX_BAR;ˇ
+                let fox_bar = FOX_BAR;ˇ"},
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -11185,6 +11312,64 @@ fn test_split_words() {
     assert_eq!(split(":do_the_thing"), &[":", "do_", "the_", "thing"]);
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("ˇone\ntwo\nthree\nfour\n");
+    let bookmarked_rows = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .bookmarks(&snapshot, cx)
+                .into_iter()
+                .map(|(anchor, _)| anchor.to_point(&snapshot).row)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(2, 1)..Point::new(2, 1)])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), vec![0, 2]);
+
+    // Bookmarks follow their lines when editing.
+    cx.set_selections_state("ˇone\ntwo\nthree\nfour\n");
+    cx.update_editor(|editor, window, cx| {
+        editor.newline_above(&NewlineAbove, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), vec![1, 3]);
+
+    cx.set_selections_state("\none\ntwo\nthreˇe\nfour\n");
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+    });
+    cx.assert_editor_state("\nˇone\ntwo\nthree\nfour\n");
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_previous_bookmark(&GoToPreviousBookmark, window, cx);
+    });
+    cx.assert_editor_state("\none\ntwo\nˇthree\nfour\n");
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+    });
+    cx.assert_editor_state("\nˇone\ntwo\nthree\nfour\n");
+
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), vec![3]);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.clear_bookmarks(&ClearBookmarks, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), Vec::<u32>::new());
+}
+
 #[gpui::test]
 async fn test_move_to_enclosing_bracket(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
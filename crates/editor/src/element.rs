@@ -4,9 +4,10 @@ use crate::{
     display_map::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
+    document_colors,
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ScrollbarDiagnostics, ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, MinimapThumb, MultiCursorModifier,
+        ScrollBeyondLastLine, ScrollbarDiagnostics, ShowMinimap, ShowScrollbar,
     },
     git::blame::GitBlame,
     hover_popover::{
@@ -14,7 +15,7 @@ use crate::{
     },
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
-    scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    scroll::{axis_pair, scroll_amount::ScrollAmount, Autoscroll, AxisPair},
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, EditDisplayMode, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GoToHunk,
@@ -67,7 +68,7 @@ use std::{
 };
 use sum_tree::Bias;
 use text::BufferId;
-use theme::{ActiveTheme, Appearance, BufferLineHeight, PlayerColor};
+use theme::{ActiveTheme, Appearance, BufferLineHeight, PlayerColor, SyntaxTheme};
 use ui::{
     h_flex, prelude::*, ButtonLike, ButtonStyle, ContextMenu, IconButtonShape, KeyBinding, Tooltip,
     POPOVER_Y_PADDING,
@@ -78,6 +79,8 @@ use workspace::{item::Item, notifications::NotifyTaskExt};
 
 const INLINE_BLAME_PADDING_EM_WIDTHS: f32 = 7.;
 const MIN_SCROLL_THUMB_SIZE: f32 = 25.;
+/// The size of the minimap's text relative to the editor's text.
+const MINIMAP_SCALE: f32 = 0.15;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum DisplayDiffHunk {
@@ -216,6 +219,9 @@ impl EditorElement {
         register_action(editor, window, Editor::sort_lines_case_insensitive);
         register_action(editor, window, Editor::reverse_lines);
         register_action(editor, window, Editor::shuffle_lines);
+        register_action(editor, window, Editor::align_selections);
+        register_action(editor, window, Editor::align_on_delimiter);
+        register_action(editor, window, Editor::align_markdown_table);
         register_action(editor, window, Editor::convert_to_upper_case);
         register_action(editor, window, Editor::convert_to_lower_case);
         register_action(editor, window, Editor::convert_to_title_case);
@@ -394,6 +400,10 @@ impl EditorElement {
         register_action(editor, window, Editor::unfold_at);
         register_action(editor, window, Editor::fold_selected_ranges);
         register_action(editor, window, Editor::set_mark);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::clear_bookmarks);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_previous_bookmark);
         register_action(editor, window, Editor::swap_selection_ends);
         register_action(editor, window, Editor::show_completions);
         register_action(editor, window, Editor::toggle_code_actions);
@@ -600,6 +610,21 @@ impl EditorElement {
         }
 
         let point_for_position = position_map.point_for_position(event.position);
+        if click_count == 1
+            && !modifiers.modified()
+            && document_colors::deploy_color_picker(
+                editor,
+                &position_map.snapshot,
+                &point_for_position,
+                event.position,
+                window,
+                cx,
+            )
+        {
+            cx.stop_propagation();
+            return;
+        }
+
         let position = point_for_position.previous_valid;
         if modifiers == COLUMNAR_SELECTION_MODIFIERS {
             editor.select(
@@ -1463,6 +1488,281 @@ impl EditorElement {
         axis_pair(horizontal_scrollbar, vertical_scrollbar)
     }
 
+    /// The width taken from the text by the minimap, which only happens when it's always shown.
+    fn reserved_minimap_width(&self, snapshot: &EditorSnapshot, cx: &App) -> Pixels {
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+        if snapshot.mode == EditorMode::Full && minimap_settings.show == ShowMinimap::Always {
+            px(minimap_settings.width)
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        height_in_lines: f32,
+        max_scroll_top: f32,
+        line_height: Pixels,
+        em_advance: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<MinimapLayout> {
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+        if snapshot.mode != EditorMode::Full || minimap_settings.show == ShowMinimap::Never {
+            return None;
+        }
+
+        let right = bounds.right() - self.style.scrollbar_width;
+        let minimap_bounds = Bounds::from_corners(
+            point(right - px(minimap_settings.width), bounds.top()),
+            point(right, bounds.bottom()),
+        );
+        let hitbox = window.insert_hitbox(minimap_bounds, false);
+        let visible = match minimap_settings.show {
+            ShowMinimap::Always => true,
+            ShowMinimap::Hover => {
+                hitbox.is_hovered(window)
+                    || self.editor.read(cx).scroll_manager.is_dragging_minimap()
+            }
+            ShowMinimap::Never => false,
+        };
+
+        let minimap_line_height = line_height * MINIMAP_SCALE;
+        let column_width = em_advance * MINIMAP_SCALE;
+        let row_count = snapshot.max_point().row().next_row().as_f32();
+        let scroll = MinimapScroll::new(
+            scroll_position.y,
+            height_in_lines,
+            max_scroll_top,
+            row_count,
+            minimap_line_height,
+            minimap_bounds.size.height,
+        );
+        let scroll_top = scroll.scroll_top;
+        let minimap_rows = minimap_bounds.size.height / minimap_line_height;
+
+        let y_for_row = |row: f32| minimap_bounds.top() + scroll.y_for_row(row);
+        let thumb_bounds = Bounds::new(
+            point(minimap_bounds.left(), y_for_row(scroll_position.y)),
+            size(
+                minimap_bounds.size.width,
+                height_in_lines * minimap_line_height,
+            ),
+        );
+
+        let mut quads = Vec::new();
+        if visible {
+            let start_row = DisplayRow(scroll_top.floor() as u32);
+            let end_row = DisplayRow(
+                ((scroll_top + minimap_rows).ceil() as u32)
+                    .min(snapshot.max_point().row().next_row().0),
+            );
+            let start_point = DisplayPoint::new(start_row, 0).to_point(snapshot);
+            let end_point = DisplayPoint::new(end_row, 0).to_point(snapshot);
+            let text_left = minimap_bounds.left() + MinimapLayout::GUTTER_WIDTH;
+            let row_bounds = |start: DisplayPoint, end: DisplayPoint| {
+                let (left, right) = if start.row() == end.row() {
+                    (
+                        text_left + column_width * start.column() as f32,
+                        text_left + column_width * end.column().max(start.column() + 1) as f32,
+                    )
+                } else {
+                    (text_left, minimap_bounds.right())
+                };
+                Bounds::from_corners(
+                    point(left, y_for_row(start.row().as_f32())),
+                    point(
+                        right.min(minimap_bounds.right()),
+                        y_for_row(end.row().as_f32() + 1.),
+                    ),
+                )
+            };
+
+            let search_highlights = self
+                .editor
+                .read(cx)
+                .background_highlight_row_ranges::<BufferSearchHighlights>(
+                    snapshot.buffer_snapshot.anchor_before(start_point)
+                        ..snapshot.buffer_snapshot.anchor_after(end_point),
+                    &snapshot.display_snapshot,
+                    usize::MAX,
+                );
+            for range in search_highlights {
+                let bounds = Bounds::from_corners(
+                    point(text_left, y_for_row(range.start().row().as_f32())),
+                    point(
+                        minimap_bounds.right(),
+                        y_for_row(range.end().row().as_f32() + 1.),
+                    ),
+                );
+                quads.push(fill(bounds, cx.theme().colors().search_match_background));
+            }
+
+            let words = self.layout_minimap_words(
+                snapshot,
+                start_row..end_row,
+                minimap_bounds.size.width - MinimapLayout::GUTTER_WIDTH,
+                bounds.size.width,
+                column_width,
+                minimap_line_height,
+                cx,
+            );
+            let words_origin = point(text_left, y_for_row(start_row.as_f32()));
+            quads.extend(
+                words
+                    .quads
+                    .iter()
+                    .map(|(bounds, color)| fill(*bounds + words_origin, *color)),
+            );
+
+            let theme = cx.theme();
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<Point>(start_point..end_point)
+                .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let mut color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => theme.status().error,
+                    DiagnosticSeverity::WARNING => theme.status().warning,
+                    DiagnosticSeverity::INFORMATION => theme.status().info,
+                    _ => theme.status().hint,
+                };
+                color.fade_out(0.5);
+                quads.push(fill(
+                    row_bounds(
+                        diagnostic.range.start.to_display_point(snapshot),
+                        diagnostic.range.end.to_display_point(snapshot),
+                    ),
+                    color,
+                ));
+            }
+
+            for hunk in snapshot
+                .buffer_snapshot
+                .diff_hunks_in_range(start_point..end_point)
+            {
+                let start_row = MultiBufferPoint::new(hunk.row_range.start.0, 0)
+                    .to_display_point(snapshot)
+                    .row();
+                let end_row = MultiBufferPoint::new(hunk.row_range.end.0, 0)
+                    .to_display_point(snapshot)
+                    .row()
+                    .max(start_row.next_row());
+                let color = match hunk.status().kind {
+                    DiffHunkStatusKind::Added => theme.status().created,
+                    DiffHunkStatusKind::Modified => theme.status().modified,
+                    DiffHunkStatusKind::Deleted => theme.status().deleted,
+                };
+                quads.push(fill(
+                    Bounds::from_corners(
+                        point(minimap_bounds.left(), y_for_row(start_row.as_f32())),
+                        point(
+                            minimap_bounds.left() + MinimapLayout::GUTTER_WIDTH / 2.,
+                            y_for_row(end_row.as_f32()),
+                        ),
+                    ),
+                    color,
+                ));
+            }
+        }
+
+        Some(MinimapLayout {
+            hitbox,
+            visible,
+            scroll,
+            thumb_bounds,
+            quads,
+        })
+    }
+
+    /// Lays out a quad for each word in the given rows of the minimap, relative to the start of
+    /// the first row. Going through the highlighted chunks is too slow to do on every frame, so
+    /// the layout is kept on the editor until the rows or the buffer change.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap_words(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        text_width: Pixels,
+        editor_width: Pixels,
+        column_width: Pixels,
+        line_height: Pixels,
+        cx: &mut App,
+    ) -> Rc<MinimapWords> {
+        let key = MinimapWordsKey {
+            edit_count: snapshot.buffer_snapshot.edit_count(),
+            non_text_state_update_count: snapshot.buffer_snapshot.non_text_state_update_count(),
+            fold_version: snapshot.fold_snapshot.version,
+            max_point: snapshot.max_point(),
+            rows: rows.clone(),
+            text_width,
+            editor_width,
+            column_width,
+            line_height,
+            text_color: self.style.text.color,
+        };
+        if let Some(words) = &self.editor.read(cx).minimap_words {
+            if words.key == key && Arc::ptr_eq(&words.syntax, &self.style.syntax) {
+                return words.clone();
+            }
+        }
+
+        let mut quads = Vec::new();
+        let mut row: f32 = 0.;
+        let mut column = 0;
+        for chunk in snapshot.highlighted_chunks(rows, true, &self.style) {
+            let mut color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            color.fade_out(0.25);
+            for (ix, line) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    row += 1.;
+                    column = 0;
+                }
+                let y = line_height * row;
+                let mut word_start = None;
+                for (char_ix, c) in line.chars().chain(iter::once(' ')).enumerate() {
+                    match (word_start, c.is_whitespace()) {
+                        (None, false) => word_start = Some(column + char_ix),
+                        (Some(start), true) => {
+                            let end = column + char_ix;
+                            let left = column_width * start as f32;
+                            if left < text_width {
+                                let right = (column_width * end as f32).min(text_width);
+                                quads.push((
+                                    Bounds::from_corners(
+                                        point(left, y),
+                                        point(right, y + line_height * 0.75),
+                                    ),
+                                    color,
+                                ));
+                            }
+                            word_start = None;
+                        }
+                        _ => {}
+                    }
+                }
+                column += line.chars().count();
+            }
+        }
+
+        let words = Rc::new(MinimapWords {
+            key,
+            syntax: self.style.syntax.clone(),
+            quads,
+        });
+        self.editor.update(cx, |editor, _| {
+            editor.minimap_words = Some(words.clone());
+        });
+        words
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -1614,6 +1914,82 @@ impl EditorElement {
         display_hunks
     }
 
+    /// Highlights the words that changed within expanded diff hunks, on top of the row
+    /// backgrounds of their added and deleted lines.
+    fn layout_word_diff_highlights(
+        &self,
+        visible_range: Range<Anchor>,
+        start_row: DisplayRow,
+        row_infos: &[RowInfo],
+        snapshot: &EditorSnapshot,
+        cx: &App,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let is_expanded_row = |range: &Range<DisplayPoint>, kind: DiffHunkStatusKind| {
+            let row = range.start.row().max(start_row);
+            row <= range.end.row()
+                && row_infos
+                    .get((row - start_row).0 as usize)
+                    .and_then(|info| info.diff_status)
+                    .is_some_and(|status| status.kind == kind)
+        };
+
+        let mut highlights = Vec::new();
+        for hunk in snapshot.buffer_snapshot.diff_hunks_in_range(visible_range) {
+            if hunk.buffer_word_diffs.is_empty() && hunk.base_word_diffs.is_empty() {
+                continue;
+            }
+            let Some(base_text) = snapshot
+                .buffer_snapshot
+                .diff_for_buffer_id(hunk.buffer_id)
+                .and_then(|diff| diff.base_text())
+            else {
+                continue;
+            };
+
+            let opacity = if hunk.secondary_status == DiffHunkSecondaryStatus::None {
+                0.25
+            } else {
+                0.12
+            };
+            let added_color = cx.theme().colors().version_control_added.opacity(opacity);
+            let deleted_color = cx.theme().colors().version_control_deleted.opacity(opacity);
+
+            for range in hunk.base_word_diffs.iter() {
+                let anchor_at = |offset: usize, bias: Bias| Anchor {
+                    buffer_id: Some(hunk.buffer_id),
+                    excerpt_id: hunk.excerpt_id,
+                    text_anchor: hunk.buffer_range.start,
+                    diff_base_anchor: Some(base_text.anchor_at(offset, bias)),
+                };
+                let range = anchor_at(range.start, Bias::Left).to_display_point(snapshot)
+                    ..anchor_at(range.end, Bias::Right).to_display_point(snapshot);
+                if is_expanded_row(&range, DiffHunkStatusKind::Deleted) {
+                    highlights.push((range, deleted_color));
+                }
+            }
+
+            for range in hunk.buffer_word_diffs.iter() {
+                let Some(start) = snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(hunk.excerpt_id, range.start)
+                else {
+                    continue;
+                };
+                let Some(end) = snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(hunk.excerpt_id, range.end)
+                else {
+                    continue;
+                };
+                let range = start.to_display_point(snapshot)..end.to_display_point(snapshot);
+                if is_expanded_row(&range, DiffHunkStatusKind::Added) {
+                    highlights.push((range, added_color));
+                }
+            }
+        }
+        highlights
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_inline_diagnostics(
         &self,
@@ -2126,6 +2502,51 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        occupied_rows: &HashSet<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        snapshot: &EditorSnapshot,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            editor
+                .bookmarks(&snapshot.buffer_snapshot, cx)
+                .into_iter()
+                .filter_map(|(anchor, label)| {
+                    let position = anchor.to_point(&snapshot.buffer_snapshot);
+                    if snapshot.is_line_folded(MultiBufferRow(position.row)) {
+                        return None;
+                    }
+                    let display_row = position.to_display_point(snapshot).row();
+                    if !range.contains(&display_row) || occupied_rows.contains(&display_row) {
+                        return None;
+                    }
+
+                    let button = editor.render_bookmark_indicator(display_row, position, label, cx);
+                    Some(prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        window,
+                        cx,
+                    ))
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -3234,6 +3655,80 @@ impl EditorElement {
         header
     }
 
+    /// Lays out the lines that open the scopes enclosing the top of the viewport, which stay
+    /// pinned at the top of the editor while scrolling through them.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_scopes(
+        &self,
+        snapshot: &EditorSnapshot,
+        start_row: DisplayRow,
+        text_hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        editor_width: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<StickyScopesLayout> {
+        let sticky_scroll = EditorSettings::get_global(cx).sticky_scroll;
+        // Multibuffers already pin the header of the excerpt at the top of the viewport.
+        if !sticky_scroll.enabled
+            || sticky_scroll.max_depth == 0
+            || snapshot.mode != EditorMode::Full
+            || snapshot.buffer_snapshot.show_headers()
+        {
+            return None;
+        }
+
+        // Each pinned line hides a row of the viewport, so pin the scopes enclosing the first row
+        // that is still visible below them.
+        let mut scopes = Vec::new();
+        for _ in 0..sticky_scroll.max_depth {
+            let row = DisplayRow(start_row.0 + scopes.len() as u32);
+            let enclosing_scopes = enclosing_scopes(snapshot, row, sticky_scroll.max_depth);
+            let done = enclosing_scopes.len() <= scopes.len();
+            scopes = enclosing_scopes;
+            if done {
+                break;
+            }
+        }
+        if scopes.is_empty() {
+            return None;
+        }
+
+        let lines = scopes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(ix, (row, anchor))| {
+                let line = Self::layout_lines(
+                    row..row.next_row(),
+                    snapshot,
+                    &self.style,
+                    editor_width,
+                    |_| false,
+                    window,
+                    cx,
+                )
+                .pop()?;
+                Some(StickyScopeLine {
+                    line,
+                    origin: point(
+                        content_origin.x - scroll_pixel_position.x,
+                        text_hitbox.top() + line_height * ix as f32,
+                    ),
+                    anchor,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let bounds = Bounds::new(
+            text_hitbox.origin,
+            size(text_hitbox.size.width, line_height * lines.len() as f32),
+        );
+        let hitbox = window.insert_hitbox(bounds, true);
+        Some(StickyScopesLayout { hitbox, lines })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_cursor_popovers(
         &self,
@@ -4487,6 +4982,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(window, cx);
+            }
+
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(window, cx);
             }
@@ -4717,6 +5216,210 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_scopes(
+        &mut self,
+        layout: &mut EditorLayout,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(sticky_scopes) = layout.sticky_scopes.take() else {
+            return;
+        };
+        let hitbox = sticky_scopes.hitbox;
+        let line_height = layout.position_map.line_height;
+
+        window.paint_layer(hitbox.bounds, |window| {
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: hitbox.bounds,
+                }),
+                |window| {
+                    window.paint_quad(quad(
+                        hitbox.bounds,
+                        Corners::default(),
+                        cx.theme().colors().editor_background,
+                        Edges {
+                            bottom: px(1.),
+                            ..Default::default()
+                        },
+                        cx.theme().colors().border_variant,
+                    ));
+                    for sticky_line in &sticky_scopes.lines {
+                        let mut fragment_origin = sticky_line.origin;
+                        for fragment in &sticky_line.line.fragments {
+                            match fragment {
+                                LineFragment::Text(line) => {
+                                    line.paint(fragment_origin, line_height, window, cx)
+                                        .log_err();
+                                    fragment_origin.x += line.width;
+                                }
+                                LineFragment::Element { size, .. } => {
+                                    fragment_origin.x += size.width;
+                                }
+                            }
+                        }
+                    }
+                },
+            );
+        });
+        window.set_cursor_style(CursorStyle::PointingHand, &hitbox);
+
+        let anchors = sticky_scopes
+            .lines
+            .iter()
+            .map(|sticky_line| sticky_line.anchor)
+            .collect::<Vec<_>>();
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture
+                    || event.button != MouseButton::Left
+                    || !hitbox.is_hovered(window)
+                {
+                    return;
+                }
+
+                let ix = ((event.position.y - hitbox.top()) / line_height) as usize;
+                let Some(anchor) = anchors.get(ix).copied() else {
+                    return;
+                };
+                editor.update(cx, |editor, cx| {
+                    window.focus(&editor.focus_handle(cx));
+                    // Scroll the clicked line to where it is pinned, so that it doesn't move.
+                    editor.change_selections(
+                        Some(Autoscroll::top_relative(ix)),
+                        window,
+                        cx,
+                        |selections| selections.select_ranges([anchor..anchor]),
+                    );
+                });
+                cx.stop_propagation();
+            }
+        });
+    }
+
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+        let hitbox = minimap.hitbox.clone();
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+
+        if minimap.visible {
+            window.paint_layer(hitbox.bounds, |window| {
+                window.with_content_mask(
+                    Some(ContentMask {
+                        bounds: hitbox.bounds,
+                    }),
+                    |window| {
+                        window.paint_quad(quad(
+                            hitbox.bounds,
+                            Corners::default(),
+                            cx.theme().colors().editor_background,
+                            Edges {
+                                top: Pixels::ZERO,
+                                right: Pixels::ZERO,
+                                bottom: Pixels::ZERO,
+                                left: ScrollbarLayout::BORDER_WIDTH,
+                            },
+                            cx.theme().colors().scrollbar_track_border,
+                        ));
+                        for quad in &minimap.quads {
+                            window.paint_quad(quad.clone());
+                        }
+                        let thumb_background = match minimap_settings.thumb {
+                            MinimapThumb::Solid => cx.theme().colors().scrollbar_thumb_background,
+                            MinimapThumb::Border => transparent_black(),
+                        };
+                        window.paint_quad(quad(
+                            minimap.thumb_bounds,
+                            Corners::default(),
+                            thumb_background,
+                            ScrollbarLayout::BORDER_WIDTH,
+                            cx.theme().colors().scrollbar_thumb_border,
+                        ));
+                    },
+                );
+            });
+            window.set_cursor_style(CursorStyle::Arrow, &hitbox);
+        }
+
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = hitbox.clone();
+            let show_on_hover = minimap_settings.show == ShowMinimap::Hover;
+            let visible = minimap.visible;
+            let rows_per_pixel = minimap.scroll.rows_per_pixel;
+            let max_scroll_top = minimap.scroll.max_scroll_top;
+            let mut mouse_position = window.mouse_position();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if editor.scroll_manager.is_dragging_minimap() {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            let mut position = editor.scroll_position(cx);
+                            position.y += (event.position.y - mouse_position.y).0 * rows_per_pixel;
+                            position.y = position.y.clamp(0., max_scroll_top);
+                            editor.set_scroll_position(position, window, cx);
+                        } else {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                    } else if show_on_hover && hitbox.is_hovered(window) != visible {
+                        cx.notify();
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else if minimap.visible {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the thumb centers the clicked row in the editor.
+                        let y = event.position.y;
+                        if y < minimap.thumb_bounds.top() || minimap.thumb_bounds.bottom() < y {
+                            let mut position = editor.scroll_position(cx);
+                            position.y = minimap
+                                .scroll
+                                .scroll_position_for_y(y - minimap.hitbox.top());
+                            editor.set_scroll_position(position, window, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn paint_scrollbars(&mut self, layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
         let (scrollbar_x, scrollbar_y) = layout.scrollbars_layout.as_xy();
 
@@ -6551,8 +7254,11 @@ impl Element for EditorElement {
                         .unwrap_or_default();
                     let text_width = bounds.size.width - gutter_dimensions.width;
 
-                    let editor_width =
-                        text_width - gutter_dimensions.margin - em_width - style.scrollbar_width;
+                    let editor_width = text_width
+                        - gutter_dimensions.margin
+                        - em_width
+                        - style.scrollbar_width
+                        - self.reserved_minimap_width(&snapshot, cx);
 
                     snapshot = self.editor.update(cx, |editor, cx| {
                         editor.last_bounds = Some(bounds);
@@ -6715,11 +7421,18 @@ impl Element for EditorElement {
                             .or_insert(background_color.into());
                     }
 
-                    let highlighted_ranges = self.editor.read(cx).background_highlights_in_range(
+                    let mut highlighted_ranges = self.layout_word_diff_highlights(
+                        start_anchor..end_anchor,
+                        start_row,
+                        &row_infos,
+                        &snapshot,
+                        cx,
+                    );
+                    highlighted_ranges.extend(self.editor.read(cx).background_highlights_in_range(
                         start_anchor..end_anchor,
                         &snapshot.display_snapshot,
                         cx.theme().colors(),
-                    );
+                    ));
                     let highlighted_gutter_ranges =
                         self.editor.read(cx).gutter_highlights_in_range(
                             start_anchor..end_anchor,
@@ -6976,6 +7689,18 @@ impl Element for EditorElement {
                         scroll_position.y * line_height,
                     );
 
+                    let sticky_scopes = self.layout_sticky_scopes(
+                        &snapshot,
+                        start_row,
+                        &text_hitbox,
+                        content_origin,
+                        scroll_pixel_position,
+                        line_height,
+                        editor_width,
+                        window,
+                        cx,
+                    );
+
                     let indent_guides = self.layout_indent_guides(
                         content_origin,
                         text_hitbox.origin,
@@ -7151,6 +7876,18 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = self.layout_minimap(
+                        &snapshot,
+                        bounds,
+                        scroll_position,
+                        height_in_lines,
+                        max_scroll_top,
+                        line_height,
+                        em_advance,
+                        window,
+                        cx,
+                    );
+
                     let scrollbars_layout = self.layout_scrollbars(
                         &snapshot,
                         scrollbar_range_data,
@@ -7269,6 +8006,34 @@ impl Element for EditorElement {
                         Vec::new()
                     };
 
+                    // Bookmarks share the gutter with the run and code actions indicators, which
+                    // take precedence.
+                    let mut occupied_rows = HashSet::default();
+                    if gutter_settings.runnables {
+                        occupied_rows.extend(
+                            self.editor
+                                .read(cx)
+                                .tasks
+                                .values()
+                                .map(|tasks| tasks.offset.to_display_point(&snapshot).row()),
+                        );
+                    }
+                    if code_actions_indicator.is_some() {
+                        occupied_rows.extend(newest_selection_head.map(|head| head.row()));
+                    }
+                    let bookmark_indicators = self.layout_bookmark_indicators(
+                        line_height,
+                        start_row..end_row,
+                        &occupied_rows,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &snapshot,
+                        window,
+                        cx,
+                    );
+
                     self.layout_signature_help(
                         &hitbox,
                         content_origin,
@@ -7396,6 +8161,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -7414,12 +8180,14 @@ impl Element for EditorElement {
                         diff_hunk_controls,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scopes,
                     }
                 })
             })
@@ -7480,12 +8248,15 @@ impl Element for EditorElement {
                         });
                     }
 
+                    self.paint_sticky_scopes(layout, window, cx);
+
                     window.with_element_namespace("blocks", |window| {
                         if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
                             sticky_header.paint(window, cx)
                         }
                     });
 
+                    self.paint_minimap(layout, window, cx);
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
@@ -7495,6 +8266,32 @@ impl Element for EditorElement {
     }
 }
 
+/// Returns the first row and the start of the scopes enclosing the given row, from the outermost
+/// to the innermost, as long as they start above it.
+fn enclosing_scopes(
+    snapshot: &EditorSnapshot,
+    row: DisplayRow,
+    max_depth: usize,
+) -> Vec<(DisplayRow, Anchor)> {
+    if row > snapshot.max_point().row() {
+        return Vec::new();
+    }
+    let position = DisplayPoint::new(row, 0).to_point(snapshot);
+    let Some((_, items)) = snapshot.buffer_snapshot.symbols_containing(position, None) else {
+        return Vec::new();
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let start_row = item.range.start.to_display_point(snapshot).row();
+            let end_row = item.range.end.to_display_point(snapshot).row();
+            (start_row < row && row <= end_row).then_some((start_row, item.range.start))
+        })
+        .dedup_by(|(a, _), (b, _)| a == b)
+        .take(max_depth)
+        .collect()
+}
+
 pub(super) fn gutter_bounds(
     editor_bounds: Bounds<Pixels>,
     gutter_dimensions: GutterDimensions,
@@ -7572,6 +8369,7 @@ pub struct EditorLayout {
     gutter_hitbox: Hitbox,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -7593,6 +8391,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     diff_hunk_controls: Vec<AnyElement>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -7601,6 +8400,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scopes: Option<StickyScopesLayout>,
 }
 
 impl EditorLayout {
@@ -7621,6 +8421,122 @@ struct ColoredRange<T> {
     color: Hsla,
 }
 
+struct StickyScopesLayout {
+    hitbox: Hitbox,
+    lines: Vec<StickyScopeLine>,
+}
+
+struct StickyScopeLine {
+    line: LineWithInvisibles,
+    origin: gpui::Point<Pixels>,
+    /// The start of the scope, which is revealed when clicking the line.
+    anchor: Anchor,
+}
+
+struct MinimapLayout {
+    hitbox: Hitbox,
+    visible: bool,
+    scroll: MinimapScroll,
+    /// The part of the minimap that is visible in the editor.
+    thumb_bounds: Bounds<Pixels>,
+    quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    /// The space left of the text, where diff hunks are shown.
+    const GUTTER_WIDTH: Pixels = px(4.0);
+}
+
+/// How the rows of the minimap line up with the rows of the editor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MinimapScroll {
+    /// The display row at the top of the minimap.
+    scroll_top: f32,
+    line_height: Pixels,
+    page_rows: f32,
+    max_scroll_top: f32,
+    /// How many rows the editor scrolls per pixel the thumb is dragged.
+    rows_per_pixel: f32,
+}
+
+impl MinimapScroll {
+    fn new(
+        editor_scroll_top: f32,
+        page_rows: f32,
+        max_scroll_top: f32,
+        row_count: f32,
+        line_height: Pixels,
+        height: Pixels,
+    ) -> Self {
+        // The minimap shows the whole buffer when it fits, and otherwise scrolls along with the
+        // editor, so that its top and bottom line up with the top and bottom of the buffer.
+        let minimap_rows = height / line_height;
+        let overflowing_rows = (row_count - minimap_rows).max(0.);
+        let scroll_top = if max_scroll_top > 0. {
+            (editor_scroll_top / max_scroll_top).clamp(0., 1.) * overflowing_rows
+        } else {
+            0.
+        };
+
+        // Dragging the thumb moves it along with the mouse, as long as the minimap scrolls slower
+        // than the thumb.
+        let thumb_speed = if max_scroll_top > 0. {
+            1. - overflowing_rows / max_scroll_top
+        } else {
+            1.
+        };
+        let rows_per_pixel = if thumb_speed > 0.05 {
+            1. / (line_height.0 * thumb_speed)
+        } else {
+            max_scroll_top / height.0
+        };
+
+        Self {
+            scroll_top,
+            line_height,
+            page_rows,
+            max_scroll_top,
+            rows_per_pixel,
+        }
+    }
+
+    /// The offset of the given display row from the top of the minimap.
+    fn y_for_row(&self, row: f32) -> Pixels {
+        (row - self.scroll_top) * self.line_height
+    }
+
+    /// The editor scroll position that centers the row at the given offset from the top of the
+    /// minimap.
+    fn scroll_position_for_y(&self, y: Pixels) -> f32 {
+        let row = self.scroll_top + y / self.line_height;
+        (row - self.page_rows / 2.).clamp(0., self.max_scroll_top.max(0.))
+    }
+}
+
+/// The words in some rows of the minimap, see [`EditorElement::layout_minimap_words`].
+pub(crate) struct MinimapWords {
+    key: MinimapWordsKey,
+    syntax: Arc<SyntaxTheme>,
+    quads: Vec<(Bounds<Pixels>, Hsla)>,
+}
+
+#[derive(PartialEq)]
+struct MinimapWordsKey {
+    edit_count: usize,
+    non_text_state_update_count: usize,
+    /// Changes along with folds and inlays.
+    fold_version: usize,
+    /// Soft wraps and blocks aren't versioned, but they change the number of rows or depend on
+    /// the editor's width.
+    max_point: DisplayPoint,
+    editor_width: Pixels,
+    rows: Range<DisplayRow>,
+    text_width: Pixels,
+    column_width: Pixels,
+    line_height: Pixels,
+    text_color: Hsla,
+}
+
 #[derive(Clone)]
 struct ScrollbarLayout {
     hitbox: Hitbox,
@@ -8212,12 +9128,14 @@ fn compute_auto_height_layout(
 mod tests {
     use super::*;
     use crate::{
+        actions::ExpandAllDiffHunks,
         display_map::{BlockPlacement, BlockProperties},
         editor_tests::{init_test, update_test_language_settings},
+        test::editor_test_context::EditorTestContext,
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, Language, LanguageConfig};
     use log::info;
     use std::num::NonZeroU32;
     use util::test::sample_text;
@@ -8523,6 +9441,125 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_word_diff_highlights(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorTestContext::new(cx).await;
+        let diff_base = "let x = 1;\nfn main() {}\n";
+        cx.set_diff_base(diff_base);
+        cx.set_index_text(diff_base);
+        cx.set_state("ˇlet y = 12;\nfn main() {}\n");
+
+        let style = cx.editor(|editor, _, _| editor.style().unwrap().clone());
+        let element = EditorElement::new(&cx.editor, style);
+        let mut word_diff_highlights = |cx: &mut EditorTestContext| {
+            cx.update_editor(|editor, window, cx| {
+                let snapshot = editor.snapshot(window, cx);
+                let row_infos = snapshot
+                    .row_infos(DisplayRow(0))
+                    .take(3)
+                    .collect::<Vec<_>>();
+                element.layout_word_diff_highlights(
+                    Anchor::min()..Anchor::max(),
+                    DisplayRow(0),
+                    &row_infos,
+                    &snapshot,
+                    cx,
+                )
+            })
+        };
+
+        // Collapsed hunks show no word highlights.
+        assert!(word_diff_highlights(&mut cx).is_empty());
+
+        cx.update_editor(|editor, window, cx| {
+            editor.expand_all_diff_hunks(&ExpandAllDiffHunks, window, cx);
+        });
+        cx.run_until_parked();
+
+        // The deleted line is shown above the added one, and only the changed words of each
+        // are highlighted.
+        let (added_color, deleted_color) = cx.update(|_, cx| {
+            let colors = cx.theme().colors();
+            (
+                colors.version_control_added.opacity(0.25),
+                colors.version_control_deleted.opacity(0.25),
+            )
+        });
+        assert_eq!(
+            word_diff_highlights(&mut cx),
+            [
+                (
+                    DisplayPoint::new(DisplayRow(0), 4)..DisplayPoint::new(DisplayRow(0), 5),
+                    deleted_color
+                ),
+                (
+                    DisplayPoint::new(DisplayRow(0), 8)..DisplayPoint::new(DisplayRow(0), 9),
+                    deleted_color
+                ),
+                (
+                    DisplayPoint::new(DisplayRow(1), 4)..DisplayPoint::new(DisplayRow(1), 5),
+                    added_color
+                ),
+                (
+                    DisplayPoint::new(DisplayRow(1), 8)..DisplayPoint::new(DisplayRow(1), 10),
+                    added_color
+                ),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_enclosing_scopes(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig::default(),
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                (mod_item
+                    "mod" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = "mod outer {\n    fn inner() {\n        let a = 1;\n    }\n}\nfn after() {}\n";
+        let buffer = cx.new(|cx| language::Buffer::local(text, cx).with_language(language, cx));
+        let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) = cx.add_window_view(|window, cx| {
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        editor
+            .condition::<crate::EditorEvent>(cx, |editor, cx| {
+                !editor.buffer.read(cx).is_parsing(cx)
+            })
+            .await;
+
+        editor.update_in(cx, |editor, window, cx| {
+            let snapshot = editor.snapshot(window, cx);
+            let scope_rows = |row: u32, max_depth: usize| {
+                enclosing_scopes(&snapshot, DisplayRow(row), max_depth)
+                    .into_iter()
+                    .map(|(row, _)| row.0)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(scope_rows(0, 5), Vec::<u32>::new());
+            assert_eq!(scope_rows(1, 5), vec![0]);
+            assert_eq!(scope_rows(2, 5), vec![0, 1]);
+            assert_eq!(scope_rows(2, 1), vec![0]);
+            assert_eq!(scope_rows(4, 5), vec![0]);
+            assert_eq!(scope_rows(5, 5), Vec::<u32>::new());
+        });
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -8695,6 +9732,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_minimap_row_mapping() {
+        // When the buffer fits in the minimap, the minimap doesn't scroll.
+        let scroll = MinimapScroll::new(5., 10., 40., 50., px(2.), px(100.));
+        assert_eq!(scroll.scroll_top, 0.);
+        assert_eq!(scroll.y_for_row(10.), px(20.));
+        assert_eq!(scroll.rows_per_pixel, 0.5);
+        // Clicking a row centers it in the editor, without scrolling past the top.
+        assert_eq!(scroll.scroll_position_for_y(px(40.)), 15.);
+        assert_eq!(scroll.scroll_position_for_y(px(2.)), 0.);
+
+        // Otherwise, the top and bottom of the minimap line up with the top and bottom of the
+        // buffer.
+        let scroll_at =
+            |scroll_top| MinimapScroll::new(scroll_top, 20., 980., 1000., px(2.), px(100.));
+        assert_eq!(scroll_at(0.).y_for_row(0.), px(0.));
+        assert_eq!(scroll_at(980.).y_for_row(1000.), px(100.));
+        let scroll = scroll_at(490.);
+        assert_eq!(scroll.scroll_top, 475.);
+        assert_eq!(scroll.y_for_row(490.), px(30.));
+        assert_eq!(scroll.scroll_position_for_y(px(50.)), 490.);
+        // The minimap scrolls almost as fast as the editor, so instead of following the mouse,
+        // dragging the thumb across the minimap scrolls through the whole buffer.
+        assert_eq!(scroll.rows_per_pixel, 9.8);
+
+        // When the minimap scrolls slower than the editor, dragging the thumb by some pixels moves
+        // it by as many pixels.
+        let scroll_at =
+            |scroll_top| MinimapScroll::new(scroll_top, 20., 80., 100., px(2.), px(100.));
+        let thumb_top = |scroll_top| scroll_at(scroll_top).y_for_row(scroll_top);
+        let dragged_scroll_top = 30. + 10. * scroll_at(30.).rows_per_pixel;
+        assert!(((thumb_top(dragged_scroll_top) - thumb_top(30.)).0 - 10.).abs() < 0.001);
+    }
+
     fn collect_invisibles_from_new_editor(
         cx: &mut TestAppContext,
         editor_mode: EditorMode,
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use gpui::{Context, Entity, Window};
+use language::{
+    language_settings::{language_settings, FoldSource},
+    Buffer,
+};
+use project::FoldingRange;
+use util::ResultExt as _;
+
+use crate::{display_map::BufferFoldRanges, Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Updates the source of foldable ranges for every buffer in the editor, according to the
+/// `fold_source` setting of its language. Buffers without a usable source are folded by
+/// indentation.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let mut language_server_buffers = Vec::new();
+    for buffer_handle in editor.buffer.read(cx).all_buffers() {
+        let buffer = buffer_handle.read(cx);
+        let fold_source = language_settings(
+            buffer.language().map(|language| language.name()),
+            buffer.file(),
+            cx,
+        )
+        .fold_source;
+        let fold_ranges = match fold_source {
+            FoldSource::Indentation => None,
+            FoldSource::TreeSitter => buffer
+                .language()
+                .and_then(|language| language.grammar())
+                .map(|_| BufferFoldRanges::TreeSitter),
+            FoldSource::LanguageServer => {
+                // Keep the current ranges until the language server responds.
+                language_server_buffers.push(buffer_handle.clone());
+                continue;
+            }
+        };
+        let buffer_id = buffer.remote_id();
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.set_buffer_fold_ranges(buffer_id, fold_ranges)
+        });
+    }
+    cx.notify();
+
+    let Some(project) = editor.project.clone() else {
+        editor.folding_ranges_task = None;
+        return;
+    };
+    if language_server_buffers.is_empty() {
+        editor.folding_ranges_task = None;
+        return;
+    }
+
+    editor.folding_ranges_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let Ok(range_tasks) = project.update(&mut cx, |project, cx| {
+            language_server_buffers
+                .into_iter()
+                .map(|buffer| {
+                    let ranges = project.folding_ranges(&buffer, cx);
+                    async move { (buffer, ranges.await.log_err().unwrap_or_default()) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let ranges = join_all(range_tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                display_folding_ranges(editor, ranges, cx);
+            })
+            .ok();
+    }));
+}
+
+fn display_folding_ranges(
+    editor: &mut Editor,
+    ranges: Vec<(Entity<Buffer>, Vec<FoldingRange>)>,
+    cx: &mut Context<Editor>,
+) {
+    editor.display_map.update(cx, |display_map, cx| {
+        for (buffer, ranges) in ranges {
+            // Fall back to indentation while the language server has nothing to offer, e.g.
+            // when it's still starting up.
+            let fold_ranges = (!ranges.is_empty()).then(|| {
+                BufferFoldRanges::LanguageServer(
+                    ranges
+                        .into_iter()
+                        .map(|folding_range| folding_range.range)
+                        .collect(),
+                )
+            });
+            display_map.set_buffer_fold_ranges(buffer.read(cx).remote_id(), fold_ranges);
+        }
+    });
+    cx.notify();
+}
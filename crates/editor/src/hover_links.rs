@@ -540,6 +540,16 @@ pub fn show_link_definition(
                             (range, vec![HoverLink::Url(url)])
                         })
                         .ok()
+                    } else if let Some((link_range, link)) =
+                        find_document_link(&buffer, project.clone(), buffer_position, &mut cx).await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+
+                        Some((range, vec![link]))
                     } else if let Some((filename_range, filename)) =
                         find_file(&buffer, project.clone(), buffer_position, &mut cx).await
                     {
@@ -769,6 +779,49 @@ pub(crate) fn find_url_from_range(
     None
 }
 
+/// Finds the language server provided document link at the given position, resolving its
+/// target into either a file to open in the workspace or a URL to open externally.
+pub(crate) async fn find_document_link(
+    buffer: &Entity<language::Buffer>,
+    project: Option<Entity<Project>>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let project = project?;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let links = project
+        .update(cx, |project, cx| project.document_links(buffer, cx))
+        .ok()?
+        .await
+        .log_err()?;
+    let link = links.into_iter().find(|link| {
+        link.range.start.cmp(&position, &snapshot).is_le()
+            && link.range.end.cmp(&position, &snapshot).is_ge()
+    })?;
+    let link = project
+        .update(cx, |project, cx| {
+            project.resolve_document_link(buffer.clone(), link, cx)
+        })
+        .ok()?
+        .await
+        .log_err()?;
+
+    let target = link.target()?;
+    let hover_link = if target.scheme() == "file" {
+        let path = target.to_file_path().ok()?;
+        let resolved_path = project
+            .update(cx, |project, cx| {
+                project.resolve_abs_path(&path.to_string_lossy(), cx)
+            })
+            .ok()?
+            .await?;
+        HoverLink::File(resolved_path)
+    } else {
+        HoverLink::Url(target.to_string())
+    };
+    Some((link.range, hover_link))
+}
+
 pub(crate) async fn find_file(
     buffer: &Entity<language::Buffer>,
     project: Option<Entity<Project>>,
@@ -1490,6 +1543,63 @@ mod tests {
         assert_eq!(cx.opened_url(), Some("https://zed.dev/releases".into()));
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            ˇextern crate serde;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            extern crate «serde»;
+        "});
+
+        // The server only provides the target of the link once it's resolved.
+        let mut link_requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: None,
+                    tooltip: None,
+                    data: Some("serde".into()),
+                }]))
+            },
+        );
+        let mut resolve_requests =
+            cx.handle_request::<lsp::request::DocumentLinkResolve, _, _>(|link, _, _| async move {
+                assert_eq!(link.data, Some("serde".into()));
+                Ok(lsp::DocumentLink {
+                    target: Some(lsp::Url::parse("https://docs.rs/serde").unwrap()),
+                    ..link
+                })
+            });
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            extern crate seˇrde;
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        link_requests.next().await;
+        resolve_requests.next().await;
+        cx.background_executor.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            extern crate «serdeˇ»;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
     #[gpui::test]
     async fn test_surrounding_filename(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
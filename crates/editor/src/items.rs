@@ -1,9 +1,11 @@
 use crate::{
+    bookmarks,
     editor_settings::SeedQuerySetting,
     persistence::{SerializedEditor, DB},
     scroll::ScrollAnchor,
     Anchor, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, FormatTarget,
-    MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
+    MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToOffset as _,
+    ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -945,9 +947,10 @@ impl Item for Editor {
         &mut self,
         workspace: &mut Workspace,
         _window: &mut Window,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
         self.workspace = Some((workspace.weak_handle(), workspace.database_id()));
+        bookmarks::register_buffers(self, cx);
     }
 
     fn to_item_events(event: &EditorEvent, mut f: impl FnMut(ItemEvent)) {
@@ -1429,17 +1432,8 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&snapshot, identifier, query) {
             self.transact(window, cx, |this, _, cx| {
                 this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
             });
@@ -1452,19 +1446,10 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
+            if let Some(replacement) = replacement_for_match(&snapshot, m, query) {
                 edits.push((m.clone(), Arc::from(&*replacement)));
             }
         }
@@ -1608,6 +1593,32 @@ impl SearchableItem for Editor {
     }
 }
 
+/// Returns the text that a search match would be replaced with, if the query has a replacement.
+pub(crate) fn replacement_for_match(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let range = range.to_offset(snapshot);
+    if query.is_structural() {
+        // Structural replacements depend on the syntax tree of the match's buffer.
+        let mut excerpt = snapshot.excerpt_containing(range.clone())?;
+        let buffer_range = excerpt.map_range_to_buffer(range);
+        return query.structural_replacement_for(excerpt.buffer(), buffer_range);
+    }
+
+    let text = snapshot.text_for_range(range).collect::<Vec<_>>();
+    let text: Cow<_> = if text.len() == 1 {
+        text.first().cloned().unwrap().into()
+    } else {
+        let joined_chunks = text.join("");
+        joined_chunks.into()
+    };
+    query
+        .replacement_for(&text)
+        .map(|replacement| replacement.into_owned())
+}
+
 pub fn active_match_index(
     ranges: &[Range<Anchor>],
     cursor: &Anchor,
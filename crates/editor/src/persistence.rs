@@ -88,6 +88,13 @@ define_connection!(
     //   mtime_seconds: Option<i64>,
     //   mtime_nanos: Option<i32>,
     // )
+    //
+    // bookmarks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   row: u32,
+    //   label: Option<String>,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> = &[
         sql! (
             CREATE TABLE editors(
@@ -147,6 +154,18 @@ define_connection!(
                 ON DELETE CASCADE
             ) STRICT;
         ),
+        sql! (
+            CREATE TABLE bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                label TEXT,
+                PRIMARY KEY(workspace_id, path, row),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
     ];
 );
 
@@ -268,6 +287,62 @@ VALUES {placeholders};
         Ok(())
     }
 
+    query! {
+        pub fn get_bookmarks(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, Option<String>)>> {
+            SELECT path, row, label
+            FROM bookmarks
+            WHERE workspace_id = ?
+            ORDER BY path, row
+        }
+    }
+
+    query! {
+        pub fn get_bookmarks_for_path(workspace_id: WorkspaceId, path: PathBuf) -> Result<Vec<(u32, Option<String>)>> {
+            SELECT row, label
+            FROM bookmarks
+            WHERE workspace_id = ?1 AND path = ?2
+            ORDER BY row
+        }
+    }
+
+    /// Replaces the bookmarks of the file at the given path.
+    pub async fn save_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        bookmarks: Vec<(u32, Option<String>)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM bookmarks WHERE workspace_id = ? AND path = ?
+                ))?((workspace_id, path.as_path()))?;
+
+                for chunk in bookmarks.chunks(MAX_QUERY_PLACEHOLDERS / 4) {
+                    let placeholders = std::iter::repeat("(?1, ?2, ?, ?)")
+                        .take(chunk.len())
+                        .join(", ");
+                    let query = format!(
+                        r#"
+INSERT OR IGNORE INTO bookmarks (workspace_id, path, row, label)
+VALUES {placeholders};
+"#
+                    );
+                    let mut statement = Statement::prepare(conn, query)?;
+                    statement.bind(&workspace_id, 1)?;
+                    let mut next_index = statement.bind(&path, 2)?;
+                    for (row, label) in chunk {
+                        next_index = statement.bind(row, next_index)?;
+                        next_index = statement.bind(label, next_index)?;
+                    }
+                    statement.exec()?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -374,4 +449,48 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_bookmarks() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let path = PathBuf::from("/root/bookmarks.rs");
+
+        DB.save_bookmarks(
+            workspace_id,
+            path.clone(),
+            vec![(3, None), (1, Some("start".to_owned()))],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            DB.get_bookmarks_for_path(workspace_id, path.clone())
+                .unwrap(),
+            vec![(1, Some("start".to_owned())), (3, None)]
+        );
+        assert_eq!(
+            DB.get_bookmarks(workspace_id).unwrap(),
+            vec![
+                (path.clone(), 1, Some("start".to_owned())),
+                (path.clone(), 3, None)
+            ]
+        );
+
+        DB.save_bookmarks(workspace_id, path.clone(), Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(DB.get_bookmarks(workspace_id).unwrap(), Vec::new());
+
+        // More bookmarks than fit in a single query are saved in chunks.
+        let bookmarks = (0..MAX_QUERY_PLACEHOLDERS as u32)
+            .map(|row| (row, None))
+            .collect::<Vec<_>>();
+        DB.save_bookmarks(workspace_id, path.clone(), bookmarks.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_bookmarks_for_path(workspace_id, path.clone())
+                .unwrap(),
+            bookmarks
+        );
+    }
 }
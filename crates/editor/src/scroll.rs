@@ -182,6 +182,7 @@ pub struct ScrollManager {
     show_scrollbars: bool,
     hide_scrollbar_task: Option<Task<()>>,
     dragging_scrollbar: AxisPair<bool>,
+    dragging_minimap: bool,
     visible_line_count: Option<f32>,
     forbid_vertical_scroll: bool,
 }
@@ -196,6 +197,7 @@ impl ScrollManager {
             show_scrollbars: true,
             hide_scrollbar_task: None,
             dragging_scrollbar: axis_pair(false, false),
+            dragging_minimap: false,
             last_autoscroll: None,
             visible_line_count: None,
             forbid_vertical_scroll: false,
@@ -382,6 +384,15 @@ impl ScrollManager {
         cx.notify();
     }
 
+    pub fn is_dragging_minimap(&self) -> bool {
+        self.dragging_minimap
+    }
+
+    pub fn set_is_dragging_minimap(&mut self, dragging: bool, cx: &mut Context<Editor>) {
+        self.dragging_minimap = dragging;
+        cx.notify();
+    }
+
     pub fn clamp_scroll_left(&mut self, max: f32) -> bool {
         if max < self.anchor.offset.x {
             self.anchor.offset.x = max;
@@ -516,6 +527,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        crate::code_lens::resolve_visible_code_lens(self, window, cx);
     }
 
     pub fn scroll_position(&self, cx: &mut Context<Self>) -> gpui::Point<f32> {
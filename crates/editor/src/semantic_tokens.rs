@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use gpui::{Context, Entity, Window};
+use language::{language_settings::language_settings, Buffer};
+use project::SemanticTokens;
+use theme::ActiveTheme as _;
+use util::ResultExt as _;
+
+use crate::{Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(
+                buffer.language().map(|language| language.name()),
+                buffer.file(),
+                cx,
+            )
+            .semantic_tokens
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        editor.semantic_tokens_task = None;
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.set_semantic_token_highlights(Default::default())
+        });
+        cx.notify();
+        return;
+    }
+
+    editor.semantic_tokens_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let Ok(token_tasks) = project.update(&mut cx, |project, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let tokens = project.semantic_tokens(&buffer, cx);
+                    async move { (buffer, tokens.await.log_err().unwrap_or_default()) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let tokens = join_all(token_tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                display_semantic_tokens(editor, tokens, cx);
+            })
+            .ok();
+    }));
+}
+
+fn display_semantic_tokens(
+    editor: &mut Editor,
+    tokens: Vec<(Entity<Buffer>, SemanticTokens)>,
+    cx: &mut Context<Editor>,
+) {
+    let syntax_theme = cx.theme().syntax().clone();
+    let multi_buffer = editor.buffer.read(cx);
+    let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+    let mut highlights = Vec::new();
+    for (buffer, tokens) in tokens {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let styled_tokens = tokens
+            .tokens
+            .iter()
+            .filter_map(|token| {
+                let style = syntax_theme.semantic_token_style(
+                    tokens.token_type(token)?,
+                    tokens.token_modifiers(token),
+                )?;
+                Some((token.range.clone(), style))
+            })
+            .collect::<Vec<_>>();
+
+        for (excerpt_id, excerpt_range) in
+            multi_buffer.excerpts_for_buffer(buffer_snapshot.remote_id(), cx)
+        {
+            let context = excerpt_range.context;
+            let start_ix = styled_tokens.partition_point(|(range, _)| {
+                range.end.cmp(&context.start, &buffer_snapshot).is_le()
+            });
+            for (range, style) in &styled_tokens[start_ix..] {
+                if range.start.cmp(&context.end, &buffer_snapshot).is_ge() {
+                    break;
+                }
+                let (Some(start), Some(end)) = (
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, range.start),
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, range.end),
+                ) else {
+                    continue;
+                };
+                highlights.push((start..end, *style));
+            }
+        }
+    }
+    highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &multi_buffer_snapshot));
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_semantic_token_highlights(highlights.into())
+    });
+    cx.notify();
+}
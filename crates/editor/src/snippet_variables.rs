@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use chrono::Local;
+use gpui::App;
+use language::Point;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToPoint as _};
+use rand::Rng as _;
+
+use crate::Editor;
+
+impl Editor {
+    /// Returns the value of a snippet variable such as `$TM_FILENAME` or `$CURRENT_YEAR` at the
+    /// newest selection, or `None` if it isn't a known variable or has no value here.
+    pub(crate) fn resolve_snippet_variable(
+        &self,
+        name: &str,
+        buffer: &MultiBufferSnapshot,
+        cx: &App,
+    ) -> Option<String> {
+        let selection = self.selections.newest_anchor();
+        let head = selection.head();
+        let row = head.to_point(buffer).row;
+        let file = || {
+            let (excerpt_buffer, _) = buffer.point_to_buffer_offset(head)?;
+            excerpt_buffer.file().cloned()
+        };
+        let abs_path = || {
+            let file = file()?;
+            Some(
+                file.as_local()
+                    .map_or_else(|| file.full_path(cx), |file| file.abs_path(cx)),
+            )
+        };
+        let now = Local::now();
+
+        let value = match name {
+            "TM_SELECTED_TEXT" => buffer
+                .text_for_range(selection.start..selection.end)
+                .collect(),
+            "TM_CURRENT_LINE" => buffer
+                .text_for_range(
+                    Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))),
+                )
+                .collect(),
+            "TM_CURRENT_WORD" => {
+                let (range, _) = buffer.surrounding_word(head, false);
+                buffer.text_for_range(range).collect()
+            }
+            "TM_LINE_INDEX" => row.to_string(),
+            "TM_LINE_NUMBER" => (row + 1).to_string(),
+            "TM_FILENAME" => file()?.file_name(cx).to_string_lossy().into_owned(),
+            "TM_FILENAME_BASE" => Path::new(file()?.file_name(cx))
+                .file_stem()?
+                .to_string_lossy()
+                .into_owned(),
+            "TM_DIRECTORY" => abs_path()?.parent()?.to_string_lossy().into_owned(),
+            "TM_FILEPATH" => abs_path()?.to_string_lossy().into_owned(),
+            "RELATIVE_FILEPATH" => file()?.path().to_string_lossy().into_owned(),
+            "WORKSPACE_NAME" => file()?
+                .full_path(cx)
+                .components()
+                .next()?
+                .as_os_str()
+                .to_string_lossy()
+                .into_owned(),
+            "WORKSPACE_FOLDER" => {
+                let file = file()?;
+                let abs_path = file.as_local()?.abs_path(cx);
+                abs_path
+                    .ancestors()
+                    .nth(file.path().components().count())?
+                    .to_string_lossy()
+                    .into_owned()
+            }
+            "CLIPBOARD" => cx.read_from_clipboard()?.text()?,
+            "CURRENT_YEAR" => now.format("%Y").to_string(),
+            "CURRENT_YEAR_SHORT" => now.format("%y").to_string(),
+            "CURRENT_MONTH" => now.format("%m").to_string(),
+            "CURRENT_MONTH_NAME" => now.format("%B").to_string(),
+            "CURRENT_MONTH_NAME_SHORT" => now.format("%b").to_string(),
+            "CURRENT_DATE" => now.format("%d").to_string(),
+            "CURRENT_DAY_NAME" => now.format("%A").to_string(),
+            "CURRENT_DAY_NAME_SHORT" => now.format("%a").to_string(),
+            "CURRENT_HOUR" => now.format("%H").to_string(),
+            "CURRENT_MINUTE" => now.format("%M").to_string(),
+            "CURRENT_SECOND" => now.format("%S").to_string(),
+            "CURRENT_SECONDS_UNIX" => now.timestamp().to_string(),
+            "CURRENT_TIMEZONE_OFFSET" => now.format("%:z").to_string(),
+            "RANDOM" => format!("{:06}", rand::thread_rng().gen_range(0..1_000_000)),
+            "RANDOM_HEX" => format!("{:06x}", rand::thread_rng().gen_range(0..0x1000000)),
+            "UUID" => uuid::Uuid::new_v4().to_string(),
+            "BLOCK_COMMENT_START" => buffer
+                .language_scope_at(head)?
+                .block_comment_delimiters()?
+                .0
+                .to_string(),
+            "BLOCK_COMMENT_END" => buffer
+                .language_scope_at(head)?
+                .block_comment_delimiters()?
+                .1
+                .to_string(),
+            "LINE_COMMENT" => buffer
+                .language_scope_at(head)?
+                .line_comment_prefixes()
+                .first()?
+                .trim_end()
+                .to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
@@ -299,6 +299,19 @@ impl EditorTestContext {
     }
 
     #[track_caller]
+    pub fn set_index_text(&mut self, index_text: &str) {
+        self.cx.run_until_parked();
+        let fs = self.update_editor(|editor, _, cx| {
+            editor.project.as_ref().unwrap().read(cx).fs().as_fake()
+        });
+        let path = self.update_buffer(|buffer, _| buffer.file().unwrap().path().clone());
+        fs.set_index_for_repo(
+            &Self::root_path().join(".git"),
+            &[(path.into(), index_text.to_string())],
+        );
+        self.cx.run_until_parked();
+    }
+
     pub fn assert_index_text(&mut self, expected: Option<&str>) {
         let fs = self.update_editor(|editor, _, cx| {
             editor.project.as_ref().unwrap().read(cx).fs().as_fake()
@@ -0,0 +1,153 @@
+use editor::Editor;
+use gpui::{
+    div, Context, Corner, Entity, EntityId, IntoElement, ParentElement, Render, Subscription,
+    WeakEntity, Window,
+};
+use language::Encoding;
+use ui::{
+    Button, ButtonCommon, Color, ContextMenu, FluentBuilder, IconName, IconPosition, IconSize,
+    LabelSize, PopoverMenu, Tooltip,
+};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+use crate::{EncodingSelector, Mode, ReopenWithEncoding, SaveWithEncoding};
+
+pub struct ActiveBufferEncoding {
+    active_encoding: Option<Encoding>,
+    /// Whether the active buffer's file wasn't valid in its encoding.
+    is_lossy: bool,
+    workspace: WeakEntity<Workspace>,
+    _observe_active_editor: Option<Subscription>,
+    observe_active_buffer: Option<(EntityId, Subscription)>,
+}
+
+impl ActiveBufferEncoding {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            active_encoding: None,
+            is_lossy: false,
+            workspace: workspace.weak_handle(),
+            _observe_active_editor: None,
+            observe_active_buffer: None,
+        }
+    }
+
+    fn update_encoding(&mut self, editor: Entity<Editor>, _: &mut Window, cx: &mut Context<Self>) {
+        let Some((_, buffer, _)) = editor.read(cx).active_excerpt(cx) else {
+            self.active_encoding = None;
+            self.observe_active_buffer = None;
+            cx.notify();
+            return;
+        };
+
+        // The encoding changes without the editor noticing when the buffer is reopened or saved.
+        let buffer_id = buffer.entity_id();
+        if self
+            .observe_active_buffer
+            .as_ref()
+            .map_or(true, |(observed_id, _)| *observed_id != buffer_id)
+        {
+            let subscription = cx.observe(&buffer, |this, buffer, cx| {
+                this.active_encoding = Some(buffer.read(cx).encoding());
+                this.is_lossy = buffer.read(cx).is_lossy();
+                cx.notify();
+            });
+            self.observe_active_buffer = Some((buffer_id, subscription));
+        }
+        self.active_encoding = Some(buffer.read(cx).encoding());
+        self.is_lossy = buffer.read(cx).is_lossy();
+
+        cx.notify();
+    }
+}
+
+impl Render for ActiveBufferEncoding {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        let is_lossy = self.is_lossy;
+        div().when_some(self.active_encoding, |el, active_encoding| {
+            let workspace = self.workspace.clone();
+            // A file that wasn't valid in its encoding was probably written in another one.
+            let tooltip = if is_lossy {
+                format!("Contains bytes that aren't valid {active_encoding}. Select Encoding")
+            } else {
+                "Select Encoding".to_string()
+            };
+            el.child(
+                PopoverMenu::new("encoding-menu")
+                    .trigger(
+                        Button::new("change-encoding", active_encoding.to_string())
+                            .label_size(LabelSize::Small)
+                            .when(is_lossy, |button| {
+                                button
+                                    .icon(IconName::Warning)
+                                    .icon_size(IconSize::Small)
+                                    .icon_color(Color::Warning)
+                                    .icon_position(IconPosition::Start)
+                            })
+                            .tooltip(Tooltip::text(tooltip)),
+                    )
+                    .anchor(Corner::BottomRight)
+                    .menu(move |window, cx| {
+                        let reopen_workspace = workspace.clone();
+                        let save_workspace = workspace.clone();
+                        Some(ContextMenu::build(window, cx, |menu, _, _| {
+                            menu.entry(
+                                "Reopen with Encoding",
+                                Some(Box::new(ReopenWithEncoding)),
+                                move |window, cx| {
+                                    reopen_workspace
+                                        .update(cx, |workspace, cx| {
+                                            EncodingSelector::toggle(
+                                                workspace,
+                                                Mode::Reopen,
+                                                window,
+                                                cx,
+                                            );
+                                        })
+                                        .ok();
+                                },
+                            )
+                            .entry(
+                                "Save with Encoding",
+                                Some(Box::new(SaveWithEncoding)),
+                                move |window, cx| {
+                                    save_workspace
+                                        .update(cx, |workspace, cx| {
+                                            EncodingSelector::toggle(
+                                                workspace,
+                                                Mode::Save,
+                                                window,
+                                                cx,
+                                            );
+                                        })
+                                        .ok();
+                                },
+                            )
+                        }))
+                    }),
+            )
+        })
+    }
+}
+
+impl StatusItemView for ActiveBufferEncoding {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.downcast::<Editor>()) {
+            self._observe_active_editor =
+                Some(cx.observe_in(&editor, window, Self::update_encoding));
+            self.update_encoding(editor, window, cx);
+        } else {
+            self.active_encoding = None;
+            self.is_lossy = false;
+            self._observe_active_editor = None;
+            self.observe_active_buffer = None;
+        }
+
+        cx.notify();
+    }
+}
@@ -0,0 +1,326 @@
+mod active_buffer_encoding;
+
+pub use active_buffer_encoding::ActiveBufferEncoding;
+use collections::HashSet;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    ParentElement, PromptLevel, Render, Styled, Task, WeakEntity, Window,
+};
+use language::{Buffer, Encoding};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+actions!(encoding_selector, [ReopenWithEncoding, SaveWithEncoding]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(EncodingSelector::register).detach();
+}
+
+/// What happens to the active buffer once an encoding is picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Decode the file on disk again with the encoding.
+    Reopen,
+    /// Write the buffer to disk with the encoding.
+    Save,
+}
+
+pub struct EncodingSelector {
+    picker: Entity<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(move |workspace, _: &ReopenWithEncoding, window, cx| {
+            Self::toggle(workspace, Mode::Reopen, window, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithEncoding, window, cx| {
+            Self::toggle(workspace, Mode::Save, window, cx);
+        });
+    }
+
+    pub fn toggle(
+        workspace: &mut Workspace,
+        mode: Mode,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        // Only files on disk can be decoded again.
+        if mode == Mode::Reopen && buffer.read(cx).file()?.disk_state().mtime().is_none() {
+            return None;
+        }
+        let project = workspace.project().clone();
+
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            EncodingSelector::new(buffer, project, mode, window, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        mode: Mode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            EncodingSelectorDelegate::new(cx.entity().downgrade(), buffer, project, mode, cx);
+
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(24.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for EncodingSelector {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+pub struct EncodingSelectorDelegate {
+    encoding_selector: WeakEntity<EncodingSelector>,
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    mode: Mode,
+    current_encoding: Encoding,
+    encodings: Vec<Encoding>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakEntity<EncodingSelector>,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        mode: Mode,
+        cx: &App,
+    ) -> Self {
+        let encodings = Encoding::all();
+        let candidates = encodings
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, encoding)| {
+                StringMatchCandidate::new(candidate_id, &encoding.to_string())
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            encoding_selector,
+            current_encoding: buffer.read(cx).encoding(),
+            buffer,
+            project,
+            mode,
+            encodings,
+            candidates,
+            matches: vec![],
+            selected_index: 0,
+        }
+    }
+
+    fn reopen(&self, encoding: Encoding, window: &mut Window, cx: &mut App) {
+        let buffer = self.buffer.clone();
+        let project = self.project.clone();
+        // Reopening replaces the buffer's text with the file's, discarding unsaved changes.
+        let confirmation = buffer.read(cx).is_dirty().then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!("Discard unsaved changes and reopen with {encoding}?"),
+                None,
+                &["Discard and Reopen", "Cancel"],
+                cx,
+            )
+        });
+        window
+            .spawn(cx, |mut cx| async move {
+                if let Some(confirmation) = confirmation {
+                    if confirmation.await != Ok(0) {
+                        return Ok(());
+                    }
+                }
+                let buffers = HashSet::from_iter([buffer]);
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.reload_buffers_with_encoding(buffers, encoding, true, cx)
+                    })?
+                    .await?;
+                anyhow::Ok(())
+            })
+            .detach_and_prompt_err("Failed to reopen file", window, cx, |_, _, _| None);
+    }
+
+    fn save(&self, encoding: Encoding, window: &mut Window, cx: &mut App) {
+        let buffer = self.buffer.clone();
+        let previous_encoding = buffer.read(cx).encoding();
+        buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+        // Untitled buffers are written with the encoding once they're saved.
+        if buffer.read(cx).file().is_none() {
+            return;
+        }
+
+        let save = self
+            .project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx));
+        cx.spawn(|mut cx| async move {
+            let result = save.await;
+            if result.is_err() {
+                buffer.update(&mut cx, |buffer, cx| {
+                    buffer.set_encoding(previous_encoding, cx)
+                })?;
+            }
+            result
+        })
+        .detach_and_prompt_err("Failed to save file", window, cx, |_, _, _| None);
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.mode {
+            Mode::Reopen => "Reopen with encoding…".into(),
+            Mode::Save => "Save with encoding…".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let encoding = self.encodings[mat.candidate_id];
+            match self.mode {
+                Mode::Reopen => self.reopen(encoding, window, cx),
+                Mode::Save => self.save(encoding, window, cx),
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                // Start out on the buffer's current encoding.
+                delegate.selected_index = if query.is_empty() {
+                    delegate
+                        .matches
+                        .iter()
+                        .position(|mat| {
+                            delegate.encodings[mat.candidate_id] == delegate.current_encoding
+                        })
+                        .unwrap_or(0)
+                } else {
+                    0
+                };
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let is_current = self.encodings[mat.candidate_id] == self.current_encoding;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .when(is_current, |item| {
+                    item.end_slot(
+                        Label::new("current")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                }),
+        )
+    }
+}
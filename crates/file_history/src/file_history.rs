@@ -0,0 +1,446 @@
+mod file_history_settings;
+mod file_history_view;
+pub mod persistence;
+
+use std::sync::Arc;
+
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, Render, Task, WeakEntity, Window,
+};
+use language::{Buffer, BufferEvent};
+use picker::{Picker, PickerDelegate};
+use project::{buffer_store::BufferStoreEvent, Project};
+use settings::Settings;
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+pub use file_history_settings::FileHistorySettings;
+pub use file_history_view::FileHistoryView;
+use persistence::{SnapshotId, FILE_HISTORY};
+
+actions!(file_history, [Toggle]);
+
+pub fn init(cx: &mut App) {
+    FileHistorySettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        workspace.register_action(FileHistoryPicker::toggle);
+        record_saved_buffers(workspace.project().clone(), cx);
+    })
+    .detach();
+}
+
+/// Records a snapshot of each of the project's files whenever it's saved.
+fn record_saved_buffers(project: Entity<Project>, cx: &mut Context<Workspace>) {
+    // Other collaborators' files are theirs to keep history for.
+    if project.read(cx).is_via_collab() {
+        return;
+    }
+
+    let buffer_store = project.read(cx).buffer_store().clone();
+    for buffer in buffer_store.read(cx).buffers().collect::<Vec<_>>() {
+        cx.subscribe(&buffer, on_buffer_event).detach();
+    }
+    cx.subscribe(&buffer_store, |_, _, event, cx| {
+        if let BufferStoreEvent::BufferAdded(buffer) = event {
+            cx.subscribe(buffer, on_buffer_event).detach();
+        }
+    })
+    .detach();
+}
+
+fn on_buffer_event(
+    _: &mut Workspace,
+    buffer: Entity<Buffer>,
+    event: &BufferEvent,
+    cx: &mut Context<Workspace>,
+) {
+    if let BufferEvent::Saved = event {
+        record_snapshot(&buffer, cx);
+    }
+}
+
+fn record_snapshot(buffer: &Entity<Buffer>, cx: &mut App) {
+    let settings = FileHistorySettings::get_global(cx);
+    if !settings.enabled {
+        return;
+    }
+    let buffer = buffer.read(cx);
+    if buffer.len() as u64 > settings.max_file_size_kb * 1024 {
+        return;
+    }
+    let Some(file) = project::File::from_dyn(buffer.file()) else {
+        return;
+    };
+
+    let worktree_path = file.worktree.read(cx).abs_path().to_path_buf();
+    let path = file.path.to_path_buf();
+    let max_worktree_size = settings.max_worktree_size_mb * 1024 * 1024;
+    let text = buffer.as_rope().clone();
+    cx.background_spawn(async move {
+        FILE_HISTORY
+            .save_snapshot(
+                worktree_path,
+                path,
+                OffsetDateTime::now_utc().unix_timestamp(),
+                text.to_string(),
+                max_worktree_size,
+            )
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Lists the snapshots of the active file, opening the selected one in a [`FileHistoryView`].
+pub struct FileHistoryPicker {
+    picker: Entity<Picker<FileHistoryPickerDelegate>>,
+}
+
+impl ModalView for FileHistoryPicker {}
+
+impl EventEmitter<DismissEvent> for FileHistoryPicker {}
+
+impl Focusable for FileHistoryPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for FileHistoryPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(24.)).child(self.picker.clone())
+    }
+}
+
+impl FileHistoryPicker {
+    fn toggle(
+        workspace: &mut Workspace,
+        _: &Toggle,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        let Some(file) = project::File::from_dyn(buffer.read(cx).file()) else {
+            return;
+        };
+        let worktree_path = file.worktree.read(cx).abs_path().to_path_buf();
+        let path = file.path.to_path_buf();
+        let load_snapshots =
+            cx.background_spawn(async move { FILE_HISTORY.snapshots(worktree_path, path) });
+
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let snapshots = load_snapshots.await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let workspace_handle = cx.entity().downgrade();
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    FileHistoryPicker::new(workspace_handle, buffer, snapshots, window, cx)
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        buffer: Entity<Buffer>,
+        snapshots: Vec<(SnapshotId, i64)>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let now = OffsetDateTime::now_utc();
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let timezone = UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC);
+        let snapshots = snapshots
+            .into_iter()
+            .filter_map(|(snapshot_id, timestamp)| {
+                let timestamp = OffsetDateTime::from_unix_timestamp(timestamp).log_err()?;
+                Some(SnapshotEntry {
+                    snapshot_id,
+                    label: time_format::format_localized_timestamp(
+                        timestamp,
+                        now,
+                        timezone,
+                        TimestampFormat::EnhancedAbsolute,
+                    )
+                    .into(),
+                    relative_label: time_format::format_localized_timestamp(
+                        timestamp,
+                        now,
+                        timezone,
+                        TimestampFormat::Relative,
+                    )
+                    .into(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let candidates = snapshots
+            .iter()
+            .enumerate()
+            .map(|(id, snapshot)| StringMatchCandidate::new(id, &snapshot.label))
+            .collect();
+
+        let delegate = FileHistoryPickerDelegate {
+            picker: cx.entity().downgrade(),
+            workspace,
+            buffer,
+            snapshots,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+struct SnapshotEntry {
+    snapshot_id: SnapshotId,
+    label: SharedString,
+    relative_label: SharedString,
+}
+
+struct FileHistoryPickerDelegate {
+    picker: WeakEntity<FileHistoryPicker>,
+    workspace: WeakEntity<Workspace>,
+    buffer: Entity<Buffer>,
+    snapshots: Vec<SnapshotEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for FileHistoryPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search file history...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        "No snapshots of this file have been saved yet".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let snapshot = &self.snapshots[mat.candidate_id];
+        let snapshot_id = snapshot.snapshot_id;
+        let load_content =
+            cx.background_spawn(async move { FILE_HISTORY.snapshot_content(snapshot_id) });
+
+        let workspace = self.workspace.clone();
+        let file_history_picker = self.picker.clone();
+        let buffer = self.buffer.clone();
+        let snapshot_label = snapshot.label.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            let Some(snapshot_text) = load_content.await? else {
+                return Ok(());
+            };
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let project = workspace.project().clone();
+                let view = cx.new(|cx| {
+                    FileHistoryView::new(buffer, snapshot_text, snapshot_label, project, window, cx)
+                });
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })?;
+            file_history_picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let snapshot = &self.snapshots[mat.candidate_id];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(snapshot.relative_label.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+    use workspace::AppState;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_and_restore_snapshots(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "a.txt": "one\n" }))
+            .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let buffer = editor.update(cx, |editor, cx| {
+            editor.buffer().read(cx).as_singleton().unwrap()
+        });
+
+        // Each save records a snapshot of the saved text.
+        for text in ["two\n", "three\n"] {
+            editor.update_in(cx, |editor, window, cx| editor.set_text(text, window, cx));
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+                .await
+                .unwrap();
+            cx.run_until_parked();
+        }
+        let snapshots = FILE_HISTORY
+            .snapshots(PathBuf::from(path!("/dir")), PathBuf::from("a.txt"))
+            .unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|(snapshot_id, _)| FILE_HISTORY.snapshot_content(*snapshot_id).unwrap())
+                .collect::<Vec<_>>(),
+            [Some("three\n".to_string()), Some("two\n".to_string())]
+        );
+
+        // Restoring the older snapshot brings back its text.
+        cx.dispatch_action(Toggle);
+        cx.run_until_parked();
+        let picker = workspace.update(cx, |workspace, cx| {
+            let file_history_picker = workspace.active_modal::<FileHistoryPicker>(cx).unwrap();
+            file_history_picker.read(cx).picker.clone()
+        });
+        picker.update_in(cx, |picker, window, cx| {
+            assert_eq!(picker.delegate.match_count(), 2);
+            picker.delegate.set_selected_index(1, window, cx);
+        });
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+
+        let file_history_view = workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<FileHistoryPicker>(cx).is_none());
+            workspace.active_item_as::<FileHistoryView>(cx).unwrap()
+        });
+        file_history_view.update_in(cx, |view, window, cx| view.restore_file(window, cx));
+        cx.run_until_parked();
+        assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "two\n");
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
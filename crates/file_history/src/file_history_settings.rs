@@ -0,0 +1,43 @@
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The settings for the local history of files.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FileHistorySettings {
+    /// Whether to record a snapshot of a file each time it is saved.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// The maximum size of a file, in kilobytes, for it to be recorded.
+    ///
+    /// Default: 1024
+    pub max_file_size_kb: u64,
+    /// The maximum combined size, in megabytes, of the snapshots kept for each worktree. The
+    /// oldest snapshots are discarded first.
+    ///
+    /// Default: 50
+    pub max_worktree_size_mb: u64,
+}
+
+impl Default for FileHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_file_size_kb: 1024,
+            max_worktree_size_mb: 50,
+        }
+    }
+}
+
+impl Settings for FileHistorySettings {
+    const KEY: Option<&'static str> = Some("file_history");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,241 @@
+use std::{any::TypeId, time::Duration};
+
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyView, App, Context, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription,
+    Task, Window,
+};
+use language::{Buffer, BufferEvent};
+use multi_buffer::MultiBuffer;
+use project::Project;
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use workspace::{searchable::SearchableItemHandle, Item, ItemNavHistory, Workspace};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Shows the changes between a snapshot from a file's local history and its current content.
+///
+/// The snapshot is the base text of the diff, so restoring a hunk brings back the snapshot's
+/// version of it.
+pub struct FileHistoryView {
+    editor: Entity<Editor>,
+    buffer: Entity<Buffer>,
+    snapshot_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    title: SharedString,
+    snapshot_label: SharedString,
+    update_diff_task: Task<()>,
+    _subscription: Subscription,
+}
+
+impl FileHistoryView {
+    pub fn new(
+        buffer: Entity<Buffer>,
+        snapshot_text: String,
+        snapshot_label: SharedString,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot_buffer = cx.new(|cx| {
+            let mut snapshot_buffer = Buffer::local(snapshot_text, cx);
+            if let Some(language_registry) = buffer.read(cx).language_registry() {
+                snapshot_buffer.set_language_registry(language_registry);
+            }
+            snapshot_buffer.set_language(buffer.read(cx).language().cloned(), cx);
+            snapshot_buffer
+        });
+        let diff = cx.new(|cx| {
+            let buffer_snapshot = buffer.read(cx).text_snapshot();
+            let mut diff = BufferDiff::new(&buffer_snapshot);
+            let _ = diff.set_base_text(snapshot_buffer.clone(), buffer_snapshot, cx);
+            diff
+        });
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+
+        Self {
+            _subscription: cx.subscribe(&buffer, Self::on_buffer_event),
+            editor,
+            buffer,
+            snapshot_buffer,
+            diff,
+            title: format!("{file_name} @ {snapshot_label}").into(),
+            snapshot_label,
+            update_diff_task: Task::ready(()),
+        }
+    }
+
+    fn on_buffer_event(&mut self, _: Entity<Buffer>, event: &BufferEvent, cx: &mut Context<Self>) {
+        if let BufferEvent::Edited = event {
+            self.update_diff_task = cx.spawn(|this, mut cx| async move {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+                this.update(&mut cx, |this, cx| {
+                    let buffer_snapshot = this.buffer.read(cx).text_snapshot();
+                    let snapshot_buffer = this.snapshot_buffer.clone();
+                    this.diff.update(cx, |diff, cx| {
+                        let _ = diff.set_base_text(snapshot_buffer, buffer_snapshot, cx);
+                    });
+                })
+                .ok();
+            });
+        }
+    }
+
+    /// Replaces the file's content with the snapshot's, by restoring every hunk.
+    pub(crate) fn restore_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            editor.restore_file(&git::RestoreFile, window, cx);
+        });
+    }
+}
+
+impl Render for FileHistoryView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .key_context("FileHistoryView")
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!(
+                            "Comparing with the snapshot from {}",
+                            self.snapshot_label
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("restore-file", "Restore File")
+                            .label_size(LabelSize::Small)
+                            .tooltip(Tooltip::text(
+                                "Replace the file's content with the snapshot",
+                            ))
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.restore_file(window, cx)),
+                            ),
+                    ),
+            )
+            .child(div().flex_1().child(self.editor.clone()))
+    }
+}
+
+impl Focusable for FileHistoryView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for FileHistoryView {}
+
+impl Item for FileHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let path = self.buffer.read(cx).file()?.full_path(cx);
+        Some(format!("{} @ {}", path.display(), self.snapshot_label).into())
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+}
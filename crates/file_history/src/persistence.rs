@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+use sha2::{Digest, Sha256};
+
+pub type SnapshotId = i64;
+
+define_connection!(pub static ref FILE_HISTORY: FileHistoryDb<()> =
+    &[sql!(
+        CREATE TABLE file_history_snapshots(
+            snapshot_id INTEGER PRIMARY KEY AUTOINCREMENT,
+            worktree_path BLOB NOT NULL,
+            path BLOB NOT NULL,
+            timestamp INTEGER NOT NULL,
+            content_hash TEXT NOT NULL,
+            content TEXT NOT NULL
+        ) STRICT;
+        CREATE INDEX file_history_snapshots_by_path
+        ON file_history_snapshots(worktree_path, path);
+    )];
+);
+
+impl FileHistoryDb {
+    // Returns the id and timestamp (in seconds since the Unix epoch) of each snapshot of the
+    // given file, newest first.
+    query! {
+        pub fn snapshots(worktree_path: PathBuf, path: PathBuf) -> Result<Vec<(SnapshotId, i64)>> {
+            SELECT snapshot_id, timestamp
+            FROM file_history_snapshots
+            WHERE worktree_path = ? AND path = ?
+            ORDER BY timestamp DESC, snapshot_id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_content(snapshot_id: SnapshotId) -> Result<Option<String>> {
+            SELECT content
+            FROM file_history_snapshots
+            WHERE snapshot_id = ?
+        }
+    }
+
+    query! {
+        pub async fn delete_snapshots(worktree_path: PathBuf, path: PathBuf) -> Result<()> {
+            DELETE FROM file_history_snapshots
+            WHERE worktree_path = ? AND path = ?
+        }
+    }
+
+    /// Records a snapshot of a file, unless its content is the same as the latest snapshot's.
+    /// Afterwards the oldest snapshots of the worktree are evicted until their combined size
+    /// fits within `max_worktree_size` bytes. Returns whether a snapshot was recorded.
+    pub async fn save_snapshot(
+        &self,
+        worktree_path: PathBuf,
+        path: PathBuf,
+        timestamp: i64,
+        content: String,
+        max_worktree_size: u64,
+    ) -> Result<bool> {
+        let content_hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+        self.write(move |conn| {
+            conn.with_savepoint("save_file_history_snapshot", || {
+                let latest_hash = conn.select_row_bound::<_, String>(sql!(
+                    SELECT content_hash
+                    FROM file_history_snapshots
+                    WHERE worktree_path = ? AND path = ?
+                    ORDER BY timestamp DESC, snapshot_id DESC
+                    LIMIT 1
+                ))?((worktree_path.as_path(), path.as_path()))?;
+                if latest_hash.as_ref() == Some(&content_hash) {
+                    return Ok(false);
+                }
+
+                conn.exec_bound(sql!(
+                    INSERT INTO file_history_snapshots(
+                        worktree_path,
+                        path,
+                        timestamp,
+                        content_hash,
+                        content
+                    )
+                    VALUES (?, ?, ?, ?, ?)
+                ))?((
+                    worktree_path.as_path(),
+                    path.as_path(),
+                    timestamp,
+                    content_hash.as_str(),
+                    content.as_str(),
+                ))?;
+
+                // Evict every snapshot that doesn't fit, once the newer ones are accounted for.
+                conn.exec_bound(sql!(
+                    DELETE FROM file_history_snapshots
+                    WHERE snapshot_id IN (
+                        SELECT snapshot_id FROM (
+                            SELECT
+                                snapshot_id,
+                                SUM(length(CAST(content AS BLOB))) OVER (
+                                    ORDER BY timestamp DESC, snapshot_id DESC
+                                ) AS total_size
+                            FROM file_history_snapshots
+                            WHERE worktree_path = ?
+                        )
+                        WHERE total_size > ?
+                    )
+                ))?((worktree_path.as_path(), max_worktree_size as i64))?;
+
+                Ok(true)
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_snapshots() {
+        let db = FileHistoryDb(db::open_test_db("test_save_snapshots").await);
+        let worktree = PathBuf::from("/project");
+        let path = PathBuf::from("src/main.rs");
+
+        assert!(db
+            .save_snapshot(worktree.clone(), path.clone(), 1, "one".into(), 1024)
+            .await
+            .unwrap());
+        // Saving the same content again is deduplicated.
+        assert!(!db
+            .save_snapshot(worktree.clone(), path.clone(), 2, "one".into(), 1024)
+            .await
+            .unwrap());
+        assert!(db
+            .save_snapshot(worktree.clone(), path.clone(), 3, "two".into(), 1024)
+            .await
+            .unwrap());
+
+        let snapshots = db.snapshots(worktree.clone(), path.clone()).unwrap();
+        assert_eq!(
+            snapshots.iter().map(|(_, time)| *time).collect::<Vec<_>>(),
+            [3, 1]
+        );
+        assert_eq!(
+            db.snapshot_content(snapshots[0].0).unwrap().as_deref(),
+            Some("two")
+        );
+
+        // Snapshots of other files in the same worktree count against the same budget.
+        assert!(db
+            .save_snapshot(worktree.clone(), "README.md".into(), 4, "three".into(), 8)
+            .await
+            .unwrap());
+        assert_eq!(
+            db.snapshots(worktree.clone(), path.clone())
+                .unwrap()
+                .iter()
+                .map(|(_, time)| *time)
+                .collect::<Vec<_>>(),
+            [3]
+        );
+
+        db.delete_snapshots(worktree.clone(), path.clone())
+            .await
+            .unwrap();
+        assert!(db.snapshots(worktree, path).unwrap().is_empty());
+    }
+}
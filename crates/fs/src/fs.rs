@@ -30,14 +30,15 @@ use rope::Rope;
 use serde::{Deserialize, Serialize};
 use smol::io::AsyncWriteExt;
 use std::{
-    io::{self, Write},
+    io::{self, Read as _, Seek as _, Write},
+    ops::Range,
     path::{Component, Path, PathBuf},
     pin::Pin,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tempfile::{NamedTempFile, TempDir};
-use text::LineEnding;
+use text::{Encoding, LineEnding};
 use util::ResultExt;
 
 #[cfg(any(test, feature = "test-support"))]
@@ -106,8 +107,28 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads the bytes of a file in the given range, or fewer when the file ends sooner.
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>>;
+    /// Loads a file of any encoding, returning its text along with the encoding it was decoded
+    /// from, and whether malformed sequences in it were replaced with U+FFFD.
+    async fn load_with_encoding(&self, path: &Path) -> Result<(String, Encoding, bool)> {
+        let bytes = self.load_bytes(path).await?;
+        let encoding = Encoding::detect(&bytes);
+        let (text, lossy) = encoding.decode(bytes);
+        Ok((text, encoding, lossy))
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::default())
+            .await
+    }
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -518,6 +539,19 @@ impl Fs for RealFs {
         Ok(bytes)
     }
 
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        let path = path.to_path_buf();
+        smol::unblock(move || {
+            let mut file = std::fs::File::open(path)?;
+            file.seek(io::SeekFrom::Start(range.start))?;
+            let mut bytes = Vec::new();
+            file.take(range.end.saturating_sub(range.start))
+                .read_to_end(&mut bytes)?;
+            Ok(bytes)
+        })
+        .await
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         smol::unblock(move || {
             let mut tmp_file = if cfg!(any(target_os = "linux", target_os = "freebsd")) {
@@ -547,15 +581,35 @@ impl Fs for RealFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
+        // Encode before creating the file, so that text which can't be represented in the
+        // encoding doesn't leave the file truncated.
+        let encoded = if encoding.is_utf8() {
+            None
+        } else {
+            let content = chunks(text, line_ending).collect::<String>();
+            Some(encoding.encode(&content)?.into_owned())
+        };
+
         let buffer_size = text.summary().len.min(10 * 1024);
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
+        if let Some(encoded) = encoded {
+            writer.write_all(&encoded).await?;
+        } else {
+            writer.write_all(encoding.bom()).await?;
+            for chunk in chunks(text, line_ending) {
+                writer.write_all(chunk.as_bytes()).await?;
+            }
         }
         writer.flush().await?;
         Ok(())
@@ -1292,6 +1346,17 @@ impl FakeFs {
         });
     }
 
+    pub fn set_conflict_texts_for_repo(
+        &self,
+        dot_git: &Path,
+        conflicts: Vec<(RepoPath, git::repository::ConflictTexts)>,
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.conflict_texts.clear();
+            state.conflict_texts.extend(conflicts);
+        });
+    }
+
     pub fn set_blame_for_repo(&self, dot_git: &Path, blames: Vec<(RepoPath, git::blame::Blame)>) {
         self.with_git_state(dot_git, true, |state| {
             state.blames.clear();
@@ -1783,6 +1848,13 @@ impl Fs for FakeFs {
         self.load_internal(path).await
     }
 
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        let bytes = self.load_internal(path).await?;
+        let end = (range.end as usize).min(bytes.len());
+        let start = (range.start as usize).min(end);
+        Ok(bytes[start..end].to_vec())
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
@@ -1790,14 +1862,21 @@ impl Fs for FakeFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
         let content = chunks(text, line_ending).collect::<String>();
+        let content = encoding.encode(&content)?.into_owned();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, content.into_bytes())?;
+        self.write_file_internal(path, content)?;
         Ok(())
     }
 
@@ -0,0 +1,318 @@
+use std::ops::Range;
+
+/// The length of the markers git writes around conflicting hunks, e.g. `<<<<<<<`.
+const MARKER_LEN: usize = 7;
+
+/// A region of a file containing the conflict markers git writes when a merge, rebase or
+/// cherry-pick cannot combine two versions of a hunk.
+///
+/// All ranges are byte offsets into the parsed text. The content ranges include the trailing
+/// newline of their last line, so replacing [`ConflictRegion::range`] with the text of a
+/// content range leaves the surrounding lines intact.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// The whole region, from the start of the `<<<<<<<` line to the end of the `>>>>>>>` line.
+    pub range: Range<usize>,
+    /// The lines between `<<<<<<<` and `|||||||` (or `=======`).
+    pub ours: Range<usize>,
+    /// The lines between `|||||||` and `=======`, present with the `diff3` and `zdiff3`
+    /// conflict styles.
+    pub base: Option<Range<usize>>,
+    /// The lines between `=======` and `>>>>>>>`.
+    pub theirs: Range<usize>,
+    /// The label git wrote after `<<<<<<<`, usually `HEAD`.
+    pub ours_label: String,
+    /// The label git wrote after `>>>>>>>`, usually the name of the branch or commit being merged.
+    pub theirs_label: String,
+}
+
+enum State {
+    Outside,
+    Ours {
+        start: usize,
+        ours_start: usize,
+        ours_label: String,
+    },
+    Base {
+        start: usize,
+        ours: Range<usize>,
+        base_start: usize,
+        ours_label: String,
+    },
+    Theirs {
+        start: usize,
+        ours: Range<usize>,
+        base: Option<Range<usize>>,
+        theirs_start: usize,
+        ours_label: String,
+    },
+}
+
+/// Finds the well-formed conflict regions in the given text.
+///
+/// Unterminated or out-of-order markers are ignored, so that a half-resolved region doesn't
+/// swallow the rest of the file.
+pub fn parse_conflicts(text: &str) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    let mut state = State::Outside;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(label) = marker_label(content, b'<') {
+            // A new region starts here, even if the previous one was never closed.
+            state = State::Ours {
+                start: line_start,
+                ours_start: line_end,
+                ours_label: label.to_string(),
+            };
+        } else {
+            state = match state {
+                State::Outside => State::Outside,
+                State::Ours {
+                    start,
+                    ours_start,
+                    ours_label,
+                } => {
+                    if marker_label(content, b'|').is_some() {
+                        State::Base {
+                            start,
+                            ours: ours_start..line_start,
+                            base_start: line_end,
+                            ours_label,
+                        }
+                    } else if is_separator(content) {
+                        State::Theirs {
+                            start,
+                            ours: ours_start..line_start,
+                            base: None,
+                            theirs_start: line_end,
+                            ours_label,
+                        }
+                    } else if marker_label(content, b'>').is_some() {
+                        State::Outside
+                    } else {
+                        State::Ours {
+                            start,
+                            ours_start,
+                            ours_label,
+                        }
+                    }
+                }
+                State::Base {
+                    start,
+                    ours,
+                    base_start,
+                    ours_label,
+                } => {
+                    if is_separator(content) {
+                        State::Theirs {
+                            start,
+                            ours,
+                            base: Some(base_start..line_start),
+                            theirs_start: line_end,
+                            ours_label,
+                        }
+                    } else if marker_label(content, b'>').is_some() {
+                        State::Outside
+                    } else {
+                        State::Base {
+                            start,
+                            ours,
+                            base_start,
+                            ours_label,
+                        }
+                    }
+                }
+                State::Theirs {
+                    start,
+                    ours,
+                    base,
+                    theirs_start,
+                    ours_label,
+                } => {
+                    if let Some(theirs_label) = marker_label(content, b'>') {
+                        regions.push(ConflictRegion {
+                            range: start..line_end,
+                            ours,
+                            base,
+                            theirs: theirs_start..line_start,
+                            ours_label,
+                            theirs_label: theirs_label.to_string(),
+                        });
+                        State::Outside
+                    } else {
+                        State::Theirs {
+                            start,
+                            ours,
+                            base,
+                            theirs_start,
+                            ours_label,
+                        }
+                    }
+                }
+            };
+        }
+
+        line_start = line_end;
+    }
+    regions
+}
+
+/// Whether any line of the text, given in chunks, starts with a `<<<<<<<` marker. This is a
+/// cheap check to run before [`parse_conflicts`], which needs the text in one piece.
+pub fn may_contain_conflicts<'a>(chunks: impl IntoIterator<Item = &'a str>) -> bool {
+    // How much of the marker the current line starts with, or `None` once it can't start with it.
+    let mut matched_len = Some(0);
+    for chunk in chunks {
+        for byte in chunk.bytes() {
+            if byte == b'\n' {
+                matched_len = Some(0);
+            } else if let Some(len) = matched_len {
+                if byte != b'<' {
+                    matched_len = None;
+                } else if len + 1 == MARKER_LEN {
+                    return true;
+                } else {
+                    matched_len = Some(len + 1);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Returns the label following a marker made of `MARKER_LEN` repetitions of `marker`, or `None`
+/// if the line isn't such a marker.
+fn marker_label(line: &str, marker: u8) -> Option<&str> {
+    let bytes = line.as_bytes();
+    if bytes.len() < MARKER_LEN || bytes[..MARKER_LEN].iter().any(|byte| *byte != marker) {
+        return None;
+    }
+    let rest = &line[MARKER_LEN..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+fn is_separator(line: &str) -> bool {
+    marker_label(line, b'=').is_some_and(str::is_empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use unindent::Unindent as _;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_merge_style_conflicts() {
+        let text = "
+            fn main() {
+            <<<<<<< HEAD
+                println!(\"ours\");
+            =======
+                println!(\"theirs\");
+                println!(\"more\");
+            >>>>>>> feature
+            }
+            "
+        .unindent();
+
+        let regions = parse_conflicts(&text);
+        assert_eq!(regions.len(), 1);
+        let region = &regions[0];
+        assert_eq!(
+            &text[region.range.clone()],
+            "<<<<<<< HEAD\n    println!(\"ours\");\n=======\n    println!(\"theirs\");\n    println!(\"more\");\n>>>>>>> feature\n"
+        );
+        assert_eq!(&text[region.ours.clone()], "    println!(\"ours\");\n");
+        assert_eq!(
+            &text[region.theirs.clone()],
+            "    println!(\"theirs\");\n    println!(\"more\");\n"
+        );
+        assert_eq!(region.base, None);
+        assert_eq!(region.ours_label, "HEAD");
+        assert_eq!(region.theirs_label, "feature");
+    }
+
+    #[test]
+    fn test_parse_diff3_style_conflicts() {
+        let text = "
+            a
+            <<<<<<< HEAD
+            ours
+            ||||||| base
+            original
+            =======
+            >>>>>>> 1234567 (Some commit)
+            b
+            <<<<<<< HEAD
+            =======
+            theirs
+            >>>>>>> other
+            "
+        .unindent();
+
+        let regions = parse_conflicts(&text);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(&text[regions[0].ours.clone()], "ours\n");
+        assert_eq!(
+            regions[0].base.clone().map(|base| &text[base]),
+            Some("original\n")
+        );
+        assert_eq!(&text[regions[0].theirs.clone()], "");
+        assert_eq!(regions[0].theirs_label, "1234567 (Some commit)");
+        assert_eq!(&text[regions[1].ours.clone()], "");
+        assert_eq!(&text[regions[1].theirs.clone()], "theirs\n");
+    }
+
+    #[test]
+    fn test_parse_malformed_conflicts() {
+        // Unterminated regions, stray markers and marker-like lines are ignored.
+        let text = "
+            <<<<<<< HEAD
+            ours
+            >>>>>>> no separator
+            <<<<<<<<< not a marker
+            =======
+            <<<<<<< HEAD
+            ours
+            =======
+            theirs
+            >>>>>>> other
+            <<<<<<< HEAD
+            unterminated
+            =======
+            "
+        .unindent();
+
+        let regions = parse_conflicts(&text);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(&text[regions[0].ours.clone()], "ours\n");
+        assert_eq!(&text[regions[0].theirs.clone()], "theirs\n");
+    }
+
+    #[test]
+    fn test_may_contain_conflicts() {
+        assert!(may_contain_conflicts(["a\n<<<<", "<<< HEAD\n"]));
+        assert!(may_contain_conflicts(["<<<<<<<"]));
+        assert!(!may_contain_conflicts(["a <<<<<<< b\n", "<<<<<<"]));
+        assert!(!may_contain_conflicts(["=======\n>>>>>>> other\n"]));
+    }
+
+    #[test]
+    fn test_parse_conflicts_with_crlf_line_endings() {
+        let text = "<<<<<<< HEAD\r\nours\r\n=======\r\ntheirs\r\n>>>>>>> other\r\n";
+        let regions = parse_conflicts(text);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].range, 0..text.len());
+        assert_eq!(&text[regions[0].ours.clone()], "ours\r\n");
+        assert_eq!(&text[regions[0].theirs.clone()], "theirs\r\n");
+        assert_eq!(regions[0].theirs_label, "other");
+    }
+}
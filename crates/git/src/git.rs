@@ -1,5 +1,6 @@
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 mod hosting_provider;
 mod remote;
 pub mod repository;
@@ -56,6 +57,12 @@ actions!(
         Pull,
         Fetch,
         Commit,
+        // stash
+        StashChanges,
+        ViewStash,
+        ApplyStash,
+        PopStash,
+        DropStash,
     ]
 );
 action_with_deprecated_aliases!(git, RestoreFile, ["editor::RevertFile"]);
@@ -1,4 +1,4 @@
-use crate::status::FileStatus;
+use crate::status::{FileStatus, StatusCode};
 use crate::GitHostingProviderRegistry;
 use crate::{blame::Blame, status::GitStatus};
 use anyhow::{anyhow, Context, Result};
@@ -97,6 +97,42 @@ pub struct CommitDetails {
     pub committer_name: SharedString,
 }
 
+/// The versions of a conflicted file recorded in the index's merge stages.
+///
+/// Each version is `None` when that side of the merge has no entry for the file, e.g. when one
+/// side deleted it.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ConflictTexts {
+    /// The common ancestor's version (stage 1).
+    pub base: Option<String>,
+    /// The version on the branch being merged into (stage 2).
+    pub ours: Option<String>,
+    /// The version on the branch being merged (stage 3).
+    pub theirs: Option<String>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The entry's position in the stash, where 0 is the most recent entry (`stash@{0}`).
+    pub index: usize,
+    pub sha: SharedString,
+    /// The entry's description, e.g. `WIP on main: 1234567 Subject` or `On main: <message>`.
+    pub message: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+}
+
+/// The versions of a file before and after the changes recorded in a stash entry.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StashFileDiff {
+    pub path: RepoPath,
+    /// The file's text in the commit the changes were stashed on top of, or `None` if the
+    /// file was added.
+    pub old_text: Option<String>,
+    /// The file's stashed text, or `None` if the file was deleted.
+    pub new_text: Option<String>,
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Remote {
     pub name: SharedString,
@@ -128,6 +164,9 @@ pub trait GitRepository: Send + Sync {
 
     fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> anyhow::Result<()>;
 
+    /// Returns the base, ours and theirs versions of a file with unresolved merge conflicts.
+    fn load_conflict_texts(&self, path: &RepoPath) -> Result<ConflictTexts>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
 
@@ -183,6 +222,23 @@ pub trait GitRepository: Send + Sync {
     fn pull(&self, branch_name: &str, upstream_name: &str) -> Result<()>;
     fn get_remotes(&self, branch_name: Option<&str>) -> Result<Vec<Remote>>;
     fn fetch(&self) -> Result<()>;
+
+    /// Stashes the changes in the index and working tree, along with untracked files if
+    /// `include_untracked` is set.
+    fn stash(&self, message: Option<&str>, include_untracked: bool) -> Result<()>;
+    /// Lists the stash entries, most recent first.
+    fn stash_entries(&self) -> Result<Vec<StashEntry>>;
+    /// Applies the changes in the stash entry at `index`, keeping the entry.
+    ///
+    /// Apply, pop and drop fail if the entry at `index` isn't the one with the given `sha`, since
+    /// the stash may have changed since it was listed.
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()>;
+    /// Applies the changes in the stash entry at `index` and removes the entry.
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()>;
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()>;
+    /// Loads the files changed by the stash entry at `index`, including any untracked files
+    /// it recorded.
+    fn stash_diff(&self, index: usize) -> Result<Vec<StashFileDiff>>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -223,6 +279,40 @@ impl RealGitRepository {
             .context("failed to read git work directory")
             .map(Path::to_path_buf)
     }
+
+    /// Returns the `stash@{n}` reference for the entry at `index`, checking that it's still the
+    /// entry with the given `sha`.
+    fn stash_entry_ref(&self, index: usize, sha: &str) -> Result<String> {
+        let entry = format!("stash@{{{index}}}");
+        let oid = self
+            .repository
+            .lock()
+            .revparse_single(&entry)
+            .with_context(|| format!("{entry} no longer exists"))?
+            .id();
+        anyhow::ensure!(
+            oid.to_string() == sha,
+            "{entry} has changed since the stash was listed"
+        );
+        Ok(entry)
+    }
+
+    fn run_stash_command(&self, description: &str, args: &[&str]) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to {description}:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
@@ -382,6 +472,31 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
+    fn load_conflict_texts(&self, path: &RepoPath) -> Result<ConflictTexts> {
+        const STAGE_BASE: i32 = 1;
+        const STAGE_OURS: i32 = 2;
+        const STAGE_THEIRS: i32 = 3;
+
+        let repo = self.repository.lock();
+        let index = repo.index()?;
+        check_path_to_repo_path_errors(path)?;
+
+        let load_stage = |stage| -> Result<Option<String>> {
+            let oid = match index.get_path(path, stage) {
+                Some(entry) if entry.mode != GIT_MODE_SYMLINK => entry.id,
+                _ => return Ok(None),
+            };
+            let content = repo.find_blob(oid)?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        };
+
+        Ok(ConflictTexts {
+            base: load_stage(STAGE_BASE)?,
+            ours: load_stage(STAGE_OURS)?,
+            theirs: load_stage(STAGE_THEIRS)?,
+        })
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -705,6 +820,99 @@ impl GitRepository for RealGitRepository {
             ));
         }
     }
+
+    fn stash(&self, message: Option<&str>, include_untracked: bool) -> Result<()> {
+        let mut args = vec!["stash", "push", "--quiet"];
+        if include_untracked {
+            args.push("--include-untracked");
+        }
+        if let Some(message) = message {
+            args.extend(["--message", message]);
+        }
+        self.run_stash_command("stash", &args)
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", "list", "--format=%H%x00%ct%x00%gs"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to list stash entries:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()> {
+        let entry = self.stash_entry_ref(index, sha)?;
+        self.run_stash_command("apply stash", &["stash", "apply", "--quiet", &entry])
+    }
+
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()> {
+        let entry = self.stash_entry_ref(index, sha)?;
+        self.run_stash_command("pop stash", &["stash", "pop", "--quiet", &entry])
+    }
+
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()> {
+        let entry = self.stash_entry_ref(index, sha)?;
+        self.run_stash_command("drop stash", &["stash", "drop", "--quiet", &entry])
+    }
+
+    fn stash_diff(&self, index: usize) -> Result<Vec<StashFileDiff>> {
+        let repo = self.repository.lock();
+        let stash = repo
+            .revparse_single(&format!("stash@{{{index}}}"))?
+            .peel_to_commit()?;
+        let base_tree = stash.parent(0)?.tree()?;
+        let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash.tree()?), None)?;
+        let mut files = file_diffs_for_diff(&repo, &diff)?;
+
+        // Untracked files are recorded in a third parent with no history of its own.
+        if let Ok(untracked) = stash.parent(2) {
+            let diff = repo.diff_tree_to_tree(None, Some(&untracked.tree()?), None)?;
+            files.extend(file_diffs_for_diff(&repo, &diff)?);
+        }
+
+        files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+}
+
+/// Reads both versions of each text file changed in the given diff, skipping binary files.
+fn file_diffs_for_diff(repo: &git2::Repository, diff: &git2::Diff) -> Result<Vec<StashFileDiff>> {
+    // Yields `None` for binary files and `Some(None)` for files missing from that side.
+    let read_text = |file: git2::DiffFile| -> Result<Option<Option<String>>> {
+        if file.id().is_zero() {
+            return Ok(Some(None));
+        }
+        let blob = repo.find_blob(file.id())?;
+        Ok(String::from_utf8(blob.content().to_vec()).ok().map(Some))
+    };
+
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let (Some(old_text), Some(new_text)) =
+            (read_text(delta.old_file())?, read_text(delta.new_file())?)
+        else {
+            continue;
+        };
+        files.push(StashFileDiff {
+            path: RepoPath::from(path),
+            old_text,
+            new_text,
+        });
+    }
+    Ok(files)
 }
 
 #[derive(Debug, Clone)]
@@ -718,10 +926,15 @@ pub struct FakeGitRepositoryState {
     pub event_emitter: smol::channel::Sender<PathBuf>,
     pub head_contents: HashMap<RepoPath, String>,
     pub index_contents: HashMap<RepoPath, String>,
+    pub conflict_texts: HashMap<RepoPath, ConflictTexts>,
     pub blames: HashMap<RepoPath, Blame>,
     pub statuses: HashMap<RepoPath, FileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    pub stash_entries: Vec<StashEntry>,
+    /// The files changed by each stash entry, by the entry's sha.
+    pub stash_diffs: HashMap<SharedString, Vec<StashFileDiff>>,
+    stashes_created: usize,
 }
 
 impl FakeGitRepository {
@@ -737,11 +950,51 @@ impl FakeGitRepositoryState {
             event_emitter,
             head_contents: Default::default(),
             index_contents: Default::default(),
+            conflict_texts: Default::default(),
             blames: Default::default(),
             statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            stash_entries: Default::default(),
+            stash_diffs: Default::default(),
+            stashes_created: 0,
+        }
+    }
+
+    fn check_stash_entry(&self, index: usize, sha: &str) -> Result<()> {
+        let entry = self
+            .stash_entries
+            .get(index)
+            .with_context(|| format!("stash@{{{index}}} no longer exists"))?;
+        anyhow::ensure!(
+            entry.sha == sha,
+            "stash@{{{index}}} has changed since the stash was listed"
+        );
+        Ok(())
+    }
+
+    /// Restores the statuses of the files changed by a stash entry.
+    fn apply_stash_diff(&mut self, sha: &str) {
+        let Some(files) = self.stash_diffs.get(sha) else {
+            return;
+        };
+        for file in files {
+            let status = if file.old_text.is_some() {
+                StatusCode::Modified.worktree()
+            } else {
+                FileStatus::Untracked
+            };
+            self.statuses.insert(file.path.clone(), status);
+        }
+    }
+
+    fn stash_entries_changed(&mut self) {
+        for (index, entry) in self.stash_entries.iter_mut().enumerate() {
+            entry.index = index;
         }
+        self.event_emitter
+            .try_send(self.path.clone())
+            .expect("Dropped repo change event");
     }
 }
 
@@ -772,6 +1025,15 @@ impl GitRepository for FakeGitRepository {
         Ok(())
     }
 
+    fn load_conflict_texts(&self, path: &RepoPath) -> Result<ConflictTexts> {
+        let state = self.state.lock();
+        state
+            .conflict_texts
+            .get(path)
+            .cloned()
+            .with_context(|| format!("no merge conflict for {:?}", path))
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -878,8 +1140,23 @@ impl GitRepository for FakeGitRepository {
             .cloned()
     }
 
-    fn stage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
-        unimplemented!()
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            // Staging a conflicted file marks its conflicts as resolved.
+            state.conflict_texts.remove(path);
+            if let Some(status) = state.statuses.get_mut(path) {
+                *status = match status {
+                    FileStatus::Untracked => StatusCode::Added.index(),
+                    _ => StatusCode::Modified.index(),
+                };
+            }
+        }
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
     }
 
     fn unstage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
@@ -905,6 +1182,96 @@ impl GitRepository for FakeGitRepository {
     fn get_remotes(&self, _branch: Option<&str>) -> Result<Vec<Remote>> {
         unimplemented!()
     }
+
+    fn stash(&self, message: Option<&str>, include_untracked: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        let message = match (message, &state.current_branch_name) {
+            (Some(message), Some(branch)) => format!("On {branch}: {message}"),
+            (Some(message), None) => format!("On (no branch): {message}"),
+            (None, Some(branch)) => format!("WIP on {branch}"),
+            (None, None) => "WIP on (no branch)".to_string(),
+        };
+
+        // The index text stands in for the working copy, which the fake repository can't see.
+        let mut stashed_paths = state
+            .statuses
+            .iter()
+            .filter(|(_, status)| include_untracked || !status.is_untracked())
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        anyhow::ensure!(!stashed_paths.is_empty(), "no local changes to save");
+        stashed_paths.sort_unstable();
+        let files = stashed_paths
+            .into_iter()
+            .map(|path| {
+                state.statuses.remove(&path);
+                StashFileDiff {
+                    old_text: state.head_contents.get(&path).cloned(),
+                    new_text: state.index_contents.get(&path).cloned(),
+                    path,
+                }
+            })
+            .collect();
+
+        state.stashes_created += 1;
+        let sha = SharedString::from(format!("{:040x}", state.stashes_created));
+        state.stash_diffs.insert(sha.clone(), files);
+        state.stash_entries.insert(
+            0,
+            StashEntry {
+                index: 0,
+                sha,
+                message: message.into(),
+                commit_timestamp: 0,
+            },
+        );
+        state.stash_entries_changed();
+        Ok(())
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        Ok(self.state.lock().stash_entries.clone())
+    }
+
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        state.check_stash_entry(index, sha)?;
+        state.apply_stash_diff(sha);
+        state.stash_entries_changed();
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        state.check_stash_entry(index, sha)?;
+        state.apply_stash_diff(sha);
+        state.stash_entries.remove(index);
+        state.stash_diffs.remove(sha);
+        state.stash_entries_changed();
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        state.check_stash_entry(index, sha)?;
+        state.stash_entries.remove(index);
+        state.stash_diffs.remove(sha);
+        state.stash_entries_changed();
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize) -> Result<Vec<StashFileDiff>> {
+        let state = self.state.lock();
+        let entry = state
+            .stash_entries
+            .get(index)
+            .with_context(|| format!("no stash entry {index}"))?;
+        Ok(state
+            .stash_diffs
+            .get(&entry.sha)
+            .cloned()
+            .unwrap_or_default())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -1073,6 +1440,29 @@ fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
     Ok(branches)
 }
 
+/// Parses the output of `git stash list --format=%H%x00%ct%x00%gs`.
+fn parse_stash_list(input: &str) -> Result<Vec<StashEntry>> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.splitn(3, '\0');
+            let (Some(sha), Some(timestamp), Some(message)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(anyhow!("malformed stash entry: {line:?}"));
+            };
+            Ok(StashEntry {
+                index,
+                sha: sha.to_string().into(),
+                message: message.to_string().into(),
+                commit_timestamp: timestamp.parse()?,
+            })
+        })
+        .collect()
+}
+
 fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
     if upstream_track == "" {
         return Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
@@ -1131,3 +1521,29 @@ fn test_branches_parsing() {
         }]
     )
 }
+
+#[test]
+fn test_stash_list_parsing() {
+    // suppress "help: octal escapes are not supported, `\0` is always null"
+    #[allow(clippy::octal_escapes)]
+    let input = "1d1a2e6f1b0b0e9e5a0ea8a0f1c2d3e4f5a6b7c8\01733187470\0On main: tweak settings\n\
+                 8c7b6a5f4e3d2c1f0a8ea0a5e9e0b0b1f6e2a1d1\01733100000\0WIP on main: 060964d generated protobuf\n";
+    assert_eq!(
+        parse_stash_list(input).unwrap(),
+        vec![
+            StashEntry {
+                index: 0,
+                sha: "1d1a2e6f1b0b0e9e5a0ea8a0f1c2d3e4f5a6b7c8".into(),
+                message: "On main: tweak settings".into(),
+                commit_timestamp: 1733187470,
+            },
+            StashEntry {
+                index: 1,
+                sha: "8c7b6a5f4e3d2c1f0a8ea0a5e9e0b0b1f6e2a1d1".into(),
+                message: "WIP on main: 060964d generated protobuf".into(),
+                commit_timestamp: 1733100000,
+            },
+        ]
+    );
+    assert!(parse_stash_list("not a stash entry\n").is_err());
+}
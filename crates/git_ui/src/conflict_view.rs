@@ -0,0 +1,818 @@
+use std::{any::Any, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use editor::{
+    display_map::{
+        BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+    Addon, Editor, EditorMode, MultiBufferSnapshot,
+};
+use git::conflict::{may_contain_conflicts, parse_conflicts, ConflictRegion};
+use gpui::{
+    actions, Action, App, AppContext as _, Context, Entity, Hsla, Subscription, Task, WeakEntity,
+    Window,
+};
+use language::{Anchor, Buffer, BufferEvent, BufferSnapshot, Point};
+use project::{git::GitEvent, Project};
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use workspace::{notifications::DetachAndPromptErr, Workspace};
+
+use crate::merge_view::MergeView;
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+actions!(
+    git,
+    [
+        // per-conflict
+        AcceptOurs,
+        AcceptTheirs,
+        AcceptBoth,
+        AcceptBase,
+        // per-file
+        MarkResolved,
+        OpenMergeView,
+    ]
+);
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|editor: &mut Editor, _, cx| {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let editor_handle = cx.entity().downgrade();
+        let project = editor.project.clone();
+        let resolver = cx.new(|cx| ConflictResolver::new(editor_handle, buffer, project, cx));
+        editor.register_addon(ConflictAddon { resolver });
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(mark_resolved)
+            .register_action(MergeView::deploy);
+    })
+    .detach();
+}
+
+/// Which text replaces a conflict region when it's resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Resolution {
+    Ours,
+    Theirs,
+    /// Our lines followed by their lines.
+    Both,
+    /// The common ancestor's lines, only known with the `diff3` and `zdiff3` conflict styles.
+    Base,
+}
+
+/// A [`ConflictRegion`], anchored in its buffer so that it survives edits around it.
+#[derive(Clone, Debug)]
+struct Conflict {
+    range: Range<Anchor>,
+    ours: Range<Anchor>,
+    base: Option<Range<Anchor>>,
+    theirs: Range<Anchor>,
+    ours_label: SharedString,
+    theirs_label: SharedString,
+}
+
+impl Conflict {
+    fn new(region: ConflictRegion, snapshot: &BufferSnapshot) -> Self {
+        let anchor_range = |range: Range<usize>| {
+            snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+        };
+        Self {
+            range: anchor_range(region.range),
+            ours: anchor_range(region.ours),
+            base: region.base.map(anchor_range),
+            theirs: anchor_range(region.theirs),
+            ours_label: region.ours_label.into(),
+            theirs_label: region.theirs_label.into(),
+        }
+    }
+
+    /// The lines of markers around and between the sides of the conflict.
+    fn marker_ranges(&self) -> Vec<Range<Anchor>> {
+        let mut ranges = vec![self.range.start..self.ours.start];
+        match &self.base {
+            Some(base) => {
+                ranges.push(self.ours.end..base.start);
+                ranges.push(base.end..self.theirs.start);
+            }
+            None => ranges.push(self.ours.end..self.theirs.start),
+        }
+        ranges.push(self.theirs.end..self.range.end);
+        ranges
+    }
+}
+
+struct ConflictAddon {
+    resolver: Entity<ConflictResolver>,
+}
+
+impl Addon for ConflictAddon {
+    fn to_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+enum ConflictMarkers {}
+enum ConflictOurs {}
+enum ConflictBase {}
+enum ConflictTheirs {}
+
+/// Finds the conflict markers in an editor's buffer, highlights the regions between them, and
+/// puts buttons above each region to resolve it with either side, both, or the common ancestor.
+///
+/// Once no markers are left in a file that git still considers conflicted, a banner offers to
+/// mark the file as resolved.
+pub(crate) struct ConflictResolver {
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    project: Option<Entity<Project>>,
+    conflicts: Vec<Conflict>,
+    block_ids: Vec<CustomBlockId>,
+    /// Whether git reported the file as conflicted when the editor was last refreshed.
+    has_git_conflict: bool,
+    /// Whether to offer opening the file in a [`MergeView`], unless the editor is already in one.
+    offer_merge_view: bool,
+    /// The editor's resolution actions, which are only registered while there are conflicts.
+    action_subscriptions: Vec<Subscription>,
+    refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ConflictResolver {
+    fn new(
+        editor: WeakEntity<Editor>,
+        buffer: Entity<Buffer>,
+        project: Option<Entity<Project>>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut subscriptions = vec![cx.subscribe(&buffer, |this, _, event, cx| match event {
+            BufferEvent::Edited => this.schedule_refresh(Some(REFRESH_DEBOUNCE), cx),
+            BufferEvent::Reloaded => this.schedule_refresh(None, cx),
+            _ => {}
+        })];
+        if let Some(project) = &project {
+            let git_store = project.read(cx).git_store().clone();
+            // Whether the file is still conflicted decides whether to offer marking it resolved.
+            subscriptions.push(cx.subscribe(&git_store, |this, _, event, cx| {
+                if let GitEvent::GitStateUpdated = event {
+                    let has_git_conflict = this.read_git_conflict(cx);
+                    if has_git_conflict != this.has_git_conflict {
+                        this.has_git_conflict = has_git_conflict;
+                        this.refresh_editor(cx);
+                    }
+                }
+            }));
+        }
+
+        let mut this = Self {
+            editor,
+            buffer,
+            project,
+            conflicts: Vec::new(),
+            block_ids: Vec::new(),
+            has_git_conflict: false,
+            offer_merge_view: true,
+            action_subscriptions: Vec::new(),
+            refresh_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.has_git_conflict = this.read_git_conflict(cx);
+        this.schedule_refresh(None, cx);
+        this
+    }
+
+    pub(crate) fn conflict_count(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    pub(crate) fn hide_merge_view_button(&mut self, cx: &mut Context<Self>) {
+        self.offer_merge_view = false;
+        self.refresh_editor(cx);
+    }
+
+    fn schedule_refresh(&mut self, delay: Option<Duration>, cx: &mut Context<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot();
+        self.refresh_task = cx.spawn(|this, mut cx| async move {
+            if let Some(delay) = delay {
+                cx.background_executor().timer(delay).await;
+            }
+            // Most files have no conflict markers, so their text isn't copied to be parsed.
+            let regions = cx
+                .background_executor()
+                .spawn({
+                    let snapshot = snapshot.clone();
+                    async move {
+                        if may_contain_conflicts(snapshot.as_rope().chunks()) {
+                            parse_conflicts(&snapshot.text())
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                let nothing_to_show = regions.is_empty() && !this.has_git_conflict;
+                if nothing_to_show && this.conflicts.is_empty() && this.block_ids.is_empty() {
+                    return;
+                }
+                this.conflicts = regions
+                    .into_iter()
+                    .map(|region| Conflict::new(region, &snapshot))
+                    .collect();
+                this.refresh_editor(cx);
+                this.update_actions(cx);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    /// Whether git reports the buffer's file as having unresolved merge conflicts.
+    fn read_git_conflict(&self, cx: &App) -> bool {
+        let Some(project) = &self.project else {
+            return false;
+        };
+        project
+            .read(cx)
+            .repository_and_path_for_buffer_id(self.buffer.read(cx).remote_id(), cx)
+            .is_some_and(|(repository, repo_path)| repository.read(cx).has_conflict(&repo_path))
+    }
+
+    fn refresh_editor(&mut self, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let show_resolved_banner = self.conflicts.is_empty() && self.has_git_conflict;
+        // Leave the editor alone when there's nothing to show, and nothing was shown before.
+        if self.conflicts.is_empty() && !show_resolved_banner && self.block_ids.is_empty() {
+            return;
+        }
+        let resolver = cx.entity().downgrade();
+        let offer_merge_view = self.offer_merge_view && self.has_git_conflict;
+        let colors = cx.theme().colors();
+        let marker_color = colors.version_control_conflict.opacity(0.2);
+        let ours_color = colors.version_control_added.opacity(0.1);
+        let base_color = colors.version_control_modified.opacity(0.1);
+        let theirs_color = cx.theme().status().info.opacity(0.1);
+
+        editor.update(cx, |editor, cx| {
+            editor.clear_row_highlights::<ConflictMarkers>();
+            editor.clear_row_highlights::<ConflictOurs>();
+            editor.clear_row_highlights::<ConflictBase>();
+            editor.clear_row_highlights::<ConflictTheirs>();
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut blocks = Vec::new();
+            for conflict in &self.conflicts {
+                for range in conflict.marker_ranges() {
+                    highlight_rows::<ConflictMarkers>(editor, &snapshot, range, marker_color, cx);
+                }
+                highlight_rows::<ConflictOurs>(
+                    editor,
+                    &snapshot,
+                    conflict.ours.clone(),
+                    ours_color,
+                    cx,
+                );
+                if let Some(base) = &conflict.base {
+                    highlight_rows::<ConflictBase>(editor, &snapshot, base.clone(), base_color, cx);
+                }
+                highlight_rows::<ConflictTheirs>(
+                    editor,
+                    &snapshot,
+                    conflict.theirs.clone(),
+                    theirs_color,
+                    cx,
+                );
+
+                let Some(anchor) = to_editor_anchor(&snapshot, conflict.range.start) else {
+                    continue;
+                };
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(anchor),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render: render_conflict_buttons(
+                        resolver.clone(),
+                        conflict.clone(),
+                        offer_merge_view,
+                    ),
+                    priority: 0,
+                });
+            }
+
+            if show_resolved_banner {
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(snapshot.anchor_before(Point::zero())),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render: render_resolved_banner(resolver.clone()),
+                    priority: 0,
+                });
+            }
+
+            let old_block_ids = std::mem::take(&mut self.block_ids);
+            if !old_block_ids.is_empty() {
+                editor.remove_blocks(old_block_ids.into_iter().collect(), None, cx);
+            }
+            self.block_ids = editor.insert_blocks(blocks, None, cx);
+        });
+    }
+
+    fn update_actions(&mut self, cx: &mut Context<Self>) {
+        if self.conflicts.is_empty() {
+            self.action_subscriptions.clear();
+            return;
+        }
+        if !self.action_subscriptions.is_empty() {
+            return;
+        }
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let resolver = cx.entity().downgrade();
+        editor.update(cx, |editor, cx| {
+            self.action_subscriptions = vec![
+                register_resolution_action::<AcceptOurs>(editor, &resolver, Resolution::Ours),
+                register_resolution_action::<AcceptTheirs>(editor, &resolver, Resolution::Theirs),
+                register_resolution_action::<AcceptBoth>(editor, &resolver, Resolution::Both),
+                register_resolution_action::<AcceptBase>(editor, &resolver, Resolution::Base),
+            ];
+            cx.notify();
+        });
+    }
+
+    fn resolve(&mut self, conflict: &Conflict, resolution: Resolution, cx: &mut Context<Self>) {
+        self.buffer.update(cx, |buffer, cx| {
+            let text_for_range =
+                |range: &Range<Anchor>| buffer.text_for_range(range.clone()).collect::<String>();
+            let text = match resolution {
+                Resolution::Ours => text_for_range(&conflict.ours),
+                Resolution::Theirs => text_for_range(&conflict.theirs),
+                Resolution::Both => {
+                    text_for_range(&conflict.ours) + &text_for_range(&conflict.theirs)
+                }
+                Resolution::Base => match &conflict.base {
+                    Some(base) => text_for_range(base),
+                    None => return,
+                },
+            };
+            buffer.edit([(conflict.range.clone(), text)], None, cx);
+        });
+    }
+
+    /// Resolves the conflict containing the editor's newest cursor.
+    fn resolve_at_cursor(&mut self, resolution: Resolution, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let cursor = editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head()
+            .text_anchor;
+        let snapshot = self.buffer.read(cx).snapshot();
+        let Some(conflict) = self
+            .conflicts
+            .iter()
+            .find(|conflict| {
+                conflict.range.start.cmp(&cursor, &snapshot).is_le()
+                    && conflict.range.end.cmp(&cursor, &snapshot).is_ge()
+            })
+            .cloned()
+        else {
+            return;
+        };
+        self.resolve(&conflict, resolution, cx);
+    }
+}
+
+fn register_resolution_action<A: Action>(
+    editor: &mut Editor,
+    resolver: &WeakEntity<ConflictResolver>,
+    resolution: Resolution,
+) -> Subscription {
+    let resolver = resolver.clone();
+    editor.register_action(move |_: &A, _, cx| {
+        resolver
+            .update(cx, |resolver, cx| {
+                resolver.resolve_at_cursor(resolution, cx)
+            })
+            .ok();
+    })
+}
+
+fn to_editor_anchor(snapshot: &MultiBufferSnapshot, anchor: Anchor) -> Option<editor::Anchor> {
+    let (excerpt_id, _, _) = snapshot.as_singleton()?;
+    snapshot.anchor_in_excerpt(*excerpt_id, anchor)
+}
+
+fn highlight_rows<T: 'static>(
+    editor: &mut Editor,
+    snapshot: &MultiBufferSnapshot,
+    range: Range<Anchor>,
+    color: Hsla,
+    cx: &mut Context<Editor>,
+) {
+    if let Some((start, end)) =
+        to_editor_anchor(snapshot, range.start).zip(to_editor_anchor(snapshot, range.end))
+    {
+        editor.highlight_rows::<T>(start..end, color, false, cx);
+    }
+}
+
+fn render_conflict_buttons(
+    resolver: WeakEntity<ConflictResolver>,
+    conflict: Conflict,
+    offer_merge_view: bool,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut buttons = vec![
+            (
+                "accept-ours",
+                format!("Accept Ours ({})", conflict.ours_label),
+                Resolution::Ours,
+                AcceptOurs.boxed_clone(),
+            ),
+            (
+                "accept-theirs",
+                format!("Accept Theirs ({})", conflict.theirs_label),
+                Resolution::Theirs,
+                AcceptTheirs.boxed_clone(),
+            ),
+            (
+                "accept-both",
+                "Accept Both".to_string(),
+                Resolution::Both,
+                AcceptBoth.boxed_clone(),
+            ),
+        ];
+        if conflict.base.is_some() {
+            buttons.push((
+                "accept-base",
+                "Accept Base".to_string(),
+                Resolution::Base,
+                AcceptBase.boxed_clone(),
+            ));
+        }
+
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(buttons.into_iter().map(|(id, label, resolution, action)| {
+                let resolver = resolver.clone();
+                let conflict = conflict.clone();
+                Button::new(id, label)
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .tooltip(move |window, cx| {
+                        Tooltip::for_action("Resolve Conflict", action.as_ref(), window, cx)
+                    })
+                    .on_click(move |_, _, cx| {
+                        resolver
+                            .update(cx, |resolver, cx| {
+                                resolver.resolve(&conflict, resolution, cx)
+                            })
+                            .ok();
+                    })
+            }))
+            .when(offer_merge_view, |this| {
+                this.child(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Disabled),
+                )
+                .child(
+                    Button::new("open-merge-view", "Open Merge View")
+                        .label_size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .tooltip(Tooltip::for_action_title(
+                            "Compare Base, Ours and Theirs",
+                            &OpenMergeView,
+                        ))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(OpenMergeView.boxed_clone(), cx)
+                        }),
+                )
+            })
+            .into_any_element()
+    })
+}
+
+fn render_resolved_banner(resolver: WeakEntity<ConflictResolver>) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let resolver = resolver.clone();
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_2()
+            .child(
+                Label::new("No conflict markers are left in this file")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Button::new("mark-resolved", "Mark as Resolved")
+                    .label_size(LabelSize::Small)
+                    .tooltip(Tooltip::for_action_title("Stage the File", &MarkResolved))
+                    .on_click(move |_, window, cx| {
+                        let Some((project, buffer)) = resolver
+                            .read_with(cx, |resolver, _| {
+                                Some((resolver.project.clone()?, resolver.buffer.clone()))
+                            })
+                            .ok()
+                            .flatten()
+                        else {
+                            return;
+                        };
+                        mark_buffer_resolved(&project, &buffer, cx).detach_and_prompt_err(
+                            "Failed to mark file as resolved",
+                            window,
+                            cx,
+                            |e, _, _| Some(format!("{e}")),
+                        );
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
+/// Returns how many conflict regions are left in an editor's buffer.
+pub(crate) fn conflict_count(editor: &Entity<Editor>, cx: &App) -> Option<usize> {
+    let addon = editor.read(cx).addon::<ConflictAddon>()?;
+    Some(addon.resolver.read(cx).conflict_count())
+}
+
+/// Returns the conflict resolver of an editor, to be observed for changes to its conflicts.
+pub(crate) fn conflict_resolver(
+    editor: &Entity<Editor>,
+    cx: &App,
+) -> Option<Entity<ConflictResolver>> {
+    let addon = editor.read(cx).addon::<ConflictAddon>()?;
+    Some(addon.resolver.clone())
+}
+
+/// Stages a conflicted file, once no conflict markers are left in it.
+pub(crate) fn mark_buffer_resolved(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let unresolved = parse_conflicts(&buffer.read(cx).text()).len();
+    if unresolved > 0 {
+        return Task::ready(Err(anyhow!(
+            "{unresolved} conflict{} must be resolved first",
+            if unresolved == 1 { "" } else { "s" }
+        )));
+    }
+    let Some((repository, repo_path)) = project
+        .read(cx)
+        .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+    else {
+        return Task::ready(Err(anyhow!("The file isn't in a git repository")));
+    };
+    // Staging saves the file first.
+    repository.update(cx, |repository, cx| {
+        repository.stage_entries(vec![repo_path], cx)
+    })
+}
+
+fn mark_resolved(
+    workspace: &mut Workspace,
+    _: &MarkResolved,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let project = workspace.project().clone();
+    mark_buffer_resolved(&project, &buffer, cx).detach_and_prompt_err(
+        "Failed to mark file as resolved",
+        window,
+        cx,
+        |e, _, _| Some(format!("{e}")),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use git::{
+        repository::ConflictTexts,
+        status::{FileStatus, StatusCode, UnmergedStatus, UnmergedStatusCode},
+    };
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use unindent::Unindent as _;
+    use util::path;
+
+    use super::*;
+
+    const UNMERGED: FileStatus = FileStatus::Unmerged(UnmergedStatus {
+        first_head: UnmergedStatusCode::Updated,
+        second_head: UnmergedStatusCode::Updated,
+    });
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            init(cx);
+        });
+    }
+
+    async fn conflicted_project(text: String, cx: &mut TestAppContext) -> Entity<Project> {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": text,
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.set_status_for_repo_via_git_operation(dot_git, &[(Path::new("a.txt"), UNMERGED)]);
+        fs.set_conflict_texts_for_repo(
+            dot_git,
+            vec![(
+                Path::new("a.txt").into(),
+                ConflictTexts {
+                    base: Some("base\n".into()),
+                    ours: Some("ours\n".into()),
+                    theirs: Some("theirs\n".into()),
+                },
+            )],
+        );
+        Project::test(fs, [path!("/project").as_ref()], cx).await
+    }
+
+    #[gpui::test]
+    async fn test_resolve_conflicts(cx: &mut TestAppContext) {
+        init_test(cx);
+        let text = "
+            fn main() {
+            <<<<<<< HEAD
+                ours();
+            ||||||| base
+                base();
+            =======
+                theirs();
+            >>>>>>> feature
+            }
+            <<<<<<< HEAD
+            a
+            =======
+            b
+            >>>>>>> feature
+            "
+        .unindent();
+        let project = conflicted_project(text, cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let cx = cx.add_empty_window();
+        let editor = cx.new_window_entity(|window, cx| {
+            Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx)
+        });
+        cx.run_until_parked();
+
+        let resolver = cx.update(|_, cx| conflict_resolver(&editor, cx)).unwrap();
+        resolver.read_with(cx, |resolver, _| {
+            assert_eq!(resolver.conflict_count(), 2);
+            assert_eq!(resolver.conflicts[0].ours_label, "HEAD");
+            assert_eq!(resolver.conflicts[0].theirs_label, "feature");
+            assert!(resolver.conflicts[0].base.is_some());
+            assert_eq!(resolver.block_ids.len(), 2);
+        });
+
+        // The versions of the file git recorded for the conflict are loaded from the index.
+        let (repository, repo_path) = project
+            .read_with(cx, |project, cx| {
+                project.repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+            })
+            .unwrap();
+        let conflict_texts = repository
+            .update(cx, |repository, cx| {
+                repository.load_conflict_texts(repo_path, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(conflict_texts.ours.as_deref(), Some("ours\n"));
+        assert_eq!(conflict_texts.theirs.as_deref(), Some("theirs\n"));
+
+        resolver.update(cx, |resolver, cx| {
+            let conflict = resolver.conflicts[0].clone();
+            resolver.resolve(&conflict, Resolution::Base, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        resolver.read_with(cx, |resolver, _| assert_eq!(resolver.conflict_count(), 1));
+
+        resolver.update(cx, |resolver, cx| {
+            let conflict = resolver.conflicts[0].clone();
+            resolver.resolve(&conflict, Resolution::Both, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "fn main() {\n    base();\n}\na\nb\n");
+        });
+        // Only the banner offering to mark the file as resolved is left.
+        resolver.read_with(cx, |resolver, _| {
+            assert_eq!(resolver.conflict_count(), 0);
+            assert_eq!(resolver.block_ids.len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_mark_resolved_stages_file(cx: &mut TestAppContext) {
+        init_test(cx);
+        let text = "
+            <<<<<<< HEAD
+            ours
+            =======
+            theirs
+            >>>>>>> feature
+            "
+        .unindent();
+        let project = conflicted_project(text, cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let cx = cx.add_empty_window();
+        let editor = cx.new_window_entity(|window, cx| {
+            Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx)
+        });
+        cx.run_until_parked();
+
+        let error = cx
+            .update(|_, cx| mark_buffer_resolved(&project, &buffer, cx))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "1 conflict must be resolved first");
+
+        let resolver = cx.update(|_, cx| conflict_resolver(&editor, cx)).unwrap();
+        resolver.update(cx, |resolver, cx| {
+            let conflict = resolver.conflicts[0].clone();
+            resolver.resolve(&conflict, Resolution::Theirs, cx);
+        });
+        cx.update(|_, cx| mark_buffer_resolved(&project, &buffer, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        // The resolved file is saved before it's staged.
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "theirs\n"
+        );
+        buffer.read_with(cx, |buffer, _| assert!(!buffer.is_dirty()));
+
+        let (repository, repo_path) = project
+            .read_with(cx, |project, cx| {
+                project.repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+            })
+            .unwrap();
+        repository.read_with(cx, |repository, _| {
+            let status = repository
+                .status()
+                .find(|entry| entry.repo_path == repo_path)
+                .unwrap()
+                .status;
+            assert_eq!(status, StatusCode::Modified.index());
+        });
+    }
+}
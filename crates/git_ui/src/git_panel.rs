@@ -13,7 +13,9 @@ use editor::{
 };
 use git::repository::{Branch, CommitDetails, PushOptions, Remote, ResetMode, UpstreamTracking};
 use git::{repository::RepoPath, status::FileStatus, Commit, ToggleStaged};
-use git::{Push, RestoreTrackedFiles, StageAll, TrashUntrackedFiles, UnstageAll};
+use git::{
+    Push, RestoreTrackedFiles, StageAll, StashChanges, TrashUntrackedFiles, UnstageAll, ViewStash,
+};
 use gpui::*;
 use itertools::Itertools;
 use language::{Buffer, File};
@@ -2397,6 +2399,9 @@ impl GitPanel {
                 .separator()
                 .action("Discard Tracked Changes", RestoreTrackedFiles.boxed_clone())
                 .action("Trash Untracked Files", TrashUntrackedFiles.boxed_clone())
+                .separator()
+                .action("Stash Changes…", StashChanges.boxed_clone())
+                .action("View Stash", ViewStash.boxed_clone())
         });
         self.set_context_menu(context_menu, position, window, cx);
     }
@@ -7,17 +7,23 @@ use ui::{ActiveTheme, Color, Icon, IconName, IntoElement};
 
 pub mod branch_picker;
 mod commit_modal;
+mod conflict_view;
 pub mod git_panel;
 mod git_panel_settings;
+mod merge_view;
 pub mod picker_prompt;
 pub mod project_diff;
 pub mod repository_selector;
+mod stash_picker;
+mod stash_view;
 
 pub fn init(cx: &mut App) {
     GitPanelSettings::register(cx);
     branch_picker::init(cx);
     cx.observe_new(ProjectDiff::register).detach();
     commit_modal::init(cx);
+    conflict_view::init(cx);
+    stash_picker::init(cx);
 }
 
 // TODO: Add updated status colors to theme
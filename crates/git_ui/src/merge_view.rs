@@ -0,0 +1,389 @@
+use std::any::{Any, TypeId};
+
+use anyhow::anyhow;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use git::repository::ConflictTexts;
+use gpui::{
+    AnyView, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable, Render,
+    Subscription, Task, Window,
+};
+use language::Buffer;
+use multi_buffer::MultiBuffer;
+use project::Project;
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use workspace::{
+    item::ItemEvent, notifications::DetachAndPromptErr, searchable::SearchableItemHandle, Item,
+    ItemNavHistory, Workspace,
+};
+
+use crate::conflict_view::{
+    conflict_count, conflict_resolver, mark_buffer_resolved, MarkResolved, OpenMergeView,
+};
+
+/// One of the versions of a conflicted file shown above the file in a [`MergeView`].
+struct VersionEditor {
+    label: SharedString,
+    editor: Entity<Editor>,
+}
+
+/// Shows the common ancestor of a conflicted file and the versions of it on both sides of the
+/// merge, above an editor for the file itself in which the conflicts are resolved.
+///
+/// Both sides show what they changed relative to the common ancestor.
+pub(crate) struct MergeView {
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    versions: [VersionEditor; 3],
+    result_editor: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeView {
+    /// Opens a merge view for the active file, after loading its merge stages from the index.
+    pub(crate) fn deploy(
+        workspace: &mut Workspace,
+        _: &OpenMergeView,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        let project = workspace.project().clone();
+        let Some((repository, repo_path)) = project
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+        else {
+            workspace.show_error(&anyhow!("The file isn't in a git repository"), cx);
+            return;
+        };
+        if !repository.read(cx).has_conflict(&repo_path) {
+            workspace.show_error(&anyhow!("{repo_path} has no merge conflicts"), cx);
+            return;
+        }
+
+        let texts = repository.update(cx, |repository, cx| {
+            repository.load_conflict_texts(repo_path, cx)
+        });
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let texts = texts.await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let view = cx.new(|cx| MergeView::new(buffer, texts, project, window, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open merge view", window, cx, |e, _, _| {
+            Some(format!("{e}"))
+        });
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        texts: ConflictTexts,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let base_buffer = scratch_buffer(texts.base.clone().unwrap_or_default(), &buffer, cx);
+        let ours = Self::new_version_editor(
+            "Ours",
+            texts.ours,
+            &base_buffer,
+            &buffer,
+            &project,
+            window,
+            cx,
+        );
+        let theirs = Self::new_version_editor(
+            "Theirs",
+            texts.theirs,
+            &base_buffer,
+            &buffer,
+            &project,
+            window,
+            cx,
+        );
+        let base = VersionEditor {
+            label: if texts.base.is_some() {
+                "Base"
+            } else {
+                "Base (none)"
+            }
+            .into(),
+            editor: cx.new(|cx| {
+                let mut editor = Editor::for_buffer(base_buffer, Some(project.clone()), window, cx);
+                editor.set_read_only(true);
+                editor
+            }),
+        };
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx));
+
+        let mut subscriptions = vec![cx.subscribe_in(
+            &result_editor,
+            window,
+            |_, _, event: &EditorEvent, _, cx| match event {
+                EditorEvent::DirtyChanged | EditorEvent::Saved | EditorEvent::TitleChanged => {
+                    cx.emit(event.clone())
+                }
+                _ => {}
+            },
+        )];
+        if let Some(resolver) = conflict_resolver(&result_editor, cx) {
+            resolver.update(cx, |resolver, cx| resolver.hide_merge_view_button(cx));
+            subscriptions.push(cx.observe(&resolver, |_, _, cx| cx.notify()));
+        }
+
+        Self {
+            buffer,
+            project,
+            versions: [ours, base, theirs],
+            result_editor,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// Creates a read-only editor for one side of the merge, showing its changes relative to
+    /// `base_buffer`. A side without a version of the file deleted it.
+    fn new_version_editor(
+        label: &str,
+        text: Option<String>,
+        base_buffer: &Entity<Buffer>,
+        like_buffer: &Entity<Buffer>,
+        project: &Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> VersionEditor {
+        let label = match &text {
+            Some(_) => label.to_string(),
+            None => format!("{label} (deleted)"),
+        };
+        let buffer = scratch_buffer(text.unwrap_or_default(), like_buffer, cx);
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            let diff = cx.new(|cx| {
+                let buffer_snapshot = buffer.read(cx).text_snapshot();
+                let mut diff = BufferDiff::new(&buffer_snapshot);
+                let _ = diff.set_base_text(base_buffer.clone(), buffer_snapshot, cx);
+                diff
+            });
+            multibuffer.add_diff(diff, cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer, Some(project.clone()), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_read_only(true);
+            editor
+        });
+        VersionEditor {
+            label: label.into(),
+            editor,
+        }
+    }
+
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        mark_buffer_resolved(&self.project, &self.buffer, cx).detach_and_prompt_err(
+            "Failed to mark file as resolved",
+            window,
+            cx,
+            |e, _, _| Some(format!("{e}")),
+        );
+    }
+
+    fn file_name(&self, cx: &App) -> SharedString {
+        match self.buffer.read(cx).file() {
+            Some(file) => file.file_name(cx).to_string_lossy().to_string().into(),
+            None => "untitled".into(),
+        }
+    }
+}
+
+/// Creates a buffer for text that isn't in any file, highlighted in the language of `like_buffer`.
+fn scratch_buffer(text: String, like_buffer: &Entity<Buffer>, cx: &mut App) -> Entity<Buffer> {
+    cx.new(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        if let Some(language_registry) = like_buffer.read(cx).language_registry() {
+            buffer.set_language_registry(language_registry);
+        }
+        buffer.set_language(like_buffer.read(cx).language().cloned(), cx);
+        buffer
+    })
+}
+
+impl Render for MergeView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border_variant;
+        let remaining = conflict_count(&self.result_editor, cx).unwrap_or(0);
+        let header = |label: SharedString| {
+            h_flex()
+                .w_full()
+                .px_2()
+                .py_1()
+                .border_b_1()
+                .border_color(border_color)
+                .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+        };
+        let status = match remaining {
+            0 => "All conflicts resolved".to_string(),
+            1 => "1 conflict left".to_string(),
+            remaining => format!("{remaining} conflicts left"),
+        };
+
+        v_flex()
+            .size_full()
+            .key_context("MergeView")
+            .on_action(cx.listener(Self::mark_resolved))
+            .child(
+                h_flex()
+                    .h(relative(0.5))
+                    .w_full()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .children(self.versions.iter().enumerate().map(|(ix, version)| {
+                        v_flex()
+                            .flex_1()
+                            .h_full()
+                            .when(ix > 0, |this| this.border_l_1().border_color(border_color))
+                            .child(header(version.label.clone()))
+                            .child(div().flex_1().w_full().child(version.editor.clone()))
+                    })),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new("Result")
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(Label::new(status).size(LabelSize::Small)),
+                    )
+                    .child(
+                        Button::new("mark-resolved", "Mark as Resolved")
+                            .label_size(LabelSize::Small)
+                            .disabled(remaining > 0)
+                            .tooltip(Tooltip::for_action_title("Stage the File", &MarkResolved))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.mark_resolved(&MarkResolved, window, cx)
+                            })),
+                    ),
+            )
+            .child(div().flex_1().w_full().child(self.result_editor.clone()))
+    }
+}
+
+impl Focusable for MergeView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for MergeView {}
+
+impl Item for MergeView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        Some(format!("Merge {}", self.file_name(cx)).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.result_editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.result_editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.result_editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+}
@@ -0,0 +1,455 @@
+use anyhow::anyhow;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::repository::StashEntry;
+use git::{ApplyStash, DropStash, PopStash, StashChanges, ViewStash};
+use gpui::{
+    rems, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled,
+    Subscription, Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use project::git::Repository;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, Workspace};
+
+use crate::stash_view::StashView;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &StashChanges, window, cx| {
+            open(workspace, false, window, cx)
+        });
+        workspace.register_action(|workspace, _: &ViewStash, window, cx| {
+            open(workspace, true, window, cx)
+        });
+    })
+    .detach();
+}
+
+/// Opens the stash picker for the active repository, selecting the most recent stash entry
+/// rather than the options to stash the current changes if `select_entry` is set.
+fn open(
+    workspace: &mut Workspace,
+    select_entry: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        workspace.show_error(&anyhow!("No active repository"), cx);
+        return;
+    };
+    let entries = repository.read(cx).stash_entries(cx);
+    cx.spawn_in(window, |this, mut cx| async move {
+        let entries = entries.await?;
+        this.update_in(&mut cx, |workspace, window, cx| {
+            let delegate = StashListDelegate {
+                matches: Vec::new(),
+                entries,
+                repository,
+                workspace: this.clone(),
+                selected_index: 0,
+                select_entry,
+                last_query: String::new(),
+            };
+            workspace.toggle_modal(window, cx, |window, cx| {
+                StashList::new(delegate, 34., window, cx)
+            })
+        })?;
+        anyhow::Ok(())
+    })
+    .detach_and_prompt_err("Failed to read the stash", window, cx, |e, _, _| {
+        Some(format!("{e}"))
+    })
+}
+
+pub struct StashList {
+    picker: Entity<Picker<StashListDelegate>>,
+    rem_width: f32,
+    _subscription: Subscription,
+}
+
+impl StashList {
+    fn new(
+        delegate: StashListDelegate,
+        rem_width: f32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            rem_width,
+            _subscription,
+        }
+    }
+
+    fn run_on_selected(
+        &mut self,
+        action: StashAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            let ix = picker.delegate.selected_index;
+            picker.delegate.run(ix, action, window, cx)
+        })
+    }
+}
+
+impl ModalView for StashList {}
+impl EventEmitter<DismissEvent> for StashList {}
+
+impl Focusable for StashList {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("StashList")
+            .w(rems(self.rem_width))
+            .on_action(cx.listener(|this, _: &ApplyStash, window, cx| {
+                this.run_on_selected(StashAction::Apply, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &PopStash, window, cx| {
+                this.run_on_selected(StashAction::Pop, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &DropStash, window, cx| {
+                this.run_on_selected(StashAction::Drop, window, cx)
+            }))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
+                this.picker.update(cx, |this, cx| {
+                    this.cancel(&Default::default(), window, cx);
+                })
+            }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StashAction {
+    Apply,
+    Pop,
+    Drop,
+}
+
+#[derive(Debug, Clone)]
+enum StashListEntry {
+    /// Stashes the current changes, using the query as the message.
+    NewStash {
+        include_untracked: bool,
+    },
+    Entry(StringMatch),
+}
+
+pub struct StashListDelegate {
+    matches: Vec<StashListEntry>,
+    entries: Vec<StashEntry>,
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    selected_index: usize,
+    /// Whether to select the first stash entry the next time the matches are updated.
+    select_entry: bool,
+    last_query: String,
+}
+
+impl StashListDelegate {
+    fn stash_entry(&self, ix: usize) -> Option<&StashEntry> {
+        match self.matches.get(ix)? {
+            StashListEntry::Entry(entry) => self.entries.get(entry.candidate_id),
+            StashListEntry::NewStash { .. } => None,
+        }
+    }
+
+    fn stash_message(&self) -> Option<SharedString> {
+        let message = self.last_query.trim();
+        (!message.is_empty()).then(|| message.to_string().into())
+    }
+
+    /// Applies, pops or drops the stash entry at `ix`, confirming first before dropping it.
+    fn run(
+        &mut self,
+        ix: usize,
+        action: StashAction,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(entry) = self.stash_entry(ix).cloned() else {
+            return;
+        };
+        let repository = self.repository.clone();
+        let confirmation = (action == StashAction::Drop).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!("Drop stash@{{{}}}?", entry.index),
+                Some(entry.message.as_ref()),
+                &["Drop", "Cancel"],
+                cx,
+            )
+        });
+        let error_message = match action {
+            StashAction::Apply => "Failed to apply stash",
+            StashAction::Pop => "Failed to pop stash",
+            StashAction::Drop => "Failed to drop stash",
+        };
+
+        cx.spawn_in(window, |picker, mut cx| async move {
+            if let Some(confirmation) = confirmation {
+                if confirmation.await? != 0 {
+                    return Ok(());
+                }
+            }
+            repository
+                .update(&mut cx, |repository, cx| match action {
+                    StashAction::Apply => repository.stash_apply(entry.index, entry.sha, cx),
+                    StashAction::Pop => repository.stash_pop(entry.index, entry.sha, cx),
+                    StashAction::Drop => {
+                        let result = repository.stash_drop(entry.index, entry.sha);
+                        cx.background_spawn(async move { result.await? })
+                    }
+                })?
+                .await?;
+
+            if action == StashAction::Drop {
+                let entries = repository
+                    .update(&mut cx, |repository, cx| repository.stash_entries(cx))?
+                    .await?;
+                picker.update_in(&mut cx, |picker, window, cx| {
+                    picker.delegate.entries = entries;
+                    picker.refresh(window, cx);
+                })?;
+            } else {
+                picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err(error_message, window, cx, |e, _, _| Some(format!("{e}")));
+    }
+}
+
+impl PickerDelegate for StashListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Stash message or search stash entries...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn separators_after_indices(&self) -> Vec<usize> {
+        let new_stash_count = self
+            .matches
+            .iter()
+            .take_while(|entry| matches!(entry, StashListEntry::NewStash { .. }))
+            .count();
+        if new_stash_count > 0 && new_stash_count < self.matches.len() {
+            vec![new_stash_count - 1]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.message))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, move |picker, mut cx| async move {
+            let entry_matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.,
+                        positions: Vec::new(),
+                        string: candidate.string,
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = vec![
+                        StashListEntry::NewStash {
+                            include_untracked: false,
+                        },
+                        StashListEntry::NewStash {
+                            include_untracked: true,
+                        },
+                    ];
+                    let new_stash_count = delegate.matches.len();
+                    delegate
+                        .matches
+                        .extend(entry_matches.into_iter().map(StashListEntry::Entry));
+                    if delegate.select_entry && delegate.matches.len() > new_stash_count {
+                        delegate.selected_index = new_stash_count;
+                    } else {
+                        delegate.selected_index = delegate
+                            .selected_index
+                            .min(delegate.matches.len().saturating_sub(1));
+                    }
+                    delegate.select_entry = false;
+                    delegate.last_query = query;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+
+        match entry {
+            StashListEntry::NewStash { include_untracked } => {
+                let message = self.stash_message();
+                let stash = self.repository.update(cx, |repository, cx| {
+                    repository.stash(message, *include_untracked, cx)
+                });
+                cx.spawn(|picker, mut cx| async move {
+                    stash.await?;
+                    picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))
+                })
+                .detach_and_prompt_err(
+                    "Failed to stash changes",
+                    window,
+                    cx,
+                    |e, _, _| Some(format!("{e}")),
+                );
+            }
+            StashListEntry::Entry(_) => {
+                let Some(entry) = self.stash_entry(self.selected_index).cloned() else {
+                    return;
+                };
+                let Some(workspace) = self.workspace.upgrade() else {
+                    return;
+                };
+                let repository = self.repository.clone();
+                workspace.update(cx, |workspace, cx| {
+                    StashView::deploy(workspace, repository, entry, window, cx)
+                });
+                cx.emit(DismissEvent);
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(SharedString::from(format!("stash-{ix}")))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+
+        Some(match &self.matches[ix] {
+            StashListEntry::NewStash { include_untracked } => {
+                let label = if *include_untracked {
+                    "Stash changes including untracked files"
+                } else {
+                    "Stash changes"
+                };
+                let label = match self.stash_message() {
+                    Some(message) => format!("{label} as '{message}'"),
+                    None => label.to_string(),
+                };
+                item.start_slot(Icon::new(IconName::Plus).size(IconSize::Small))
+                    .child(Label::new(label))
+            }
+            StashListEntry::Entry(entry_match) => {
+                let index = self.entries.get(entry_match.candidate_id)?.index;
+                let button = |id: &'static str, icon, tooltip, action: StashAction| {
+                    IconButton::new(id, icon)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text(tooltip))
+                        .on_click(cx.listener(move |picker, _, window, cx| {
+                            picker.delegate.run(ix, action, window, cx)
+                        }))
+                };
+                item.child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            entry_match.string.clone(),
+                            entry_match.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(format!("stash@{{{index}}}"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .when(selected, |item| {
+                    item.end_slot(
+                        h_flex()
+                            .gap_1()
+                            .child(button(
+                                "apply-stash",
+                                IconName::Check,
+                                "Apply",
+                                StashAction::Apply,
+                            ))
+                            .child(button(
+                                "pop-stash",
+                                IconName::ArrowUpFromLine,
+                                "Pop",
+                                StashAction::Pop,
+                            ))
+                            .child(button(
+                                "drop-stash",
+                                IconName::Trash,
+                                "Drop",
+                                StashAction::Drop,
+                            )),
+                    )
+                })
+            }
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use std::any::{Any, TypeId};
+
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use git::repository::{StashEntry, StashFileDiff};
+use gpui::{
+    AnyView, App, AppContext as _, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, Render, Task, WeakEntity, Window,
+};
+use language::{Anchor, Buffer, Capability, LanguageRegistry, ToPoint as _};
+use multi_buffer::{MultiBuffer, PathKey};
+use project::{git::Repository, Project};
+use std::sync::Arc;
+use theme::ActiveTheme;
+use ui::prelude::*;
+use workspace::{
+    item::ItemEvent, notifications::DetachAndPromptErr, searchable::SearchableItemHandle, Item,
+    ItemNavHistory, Workspace,
+};
+
+/// Namespace for the paths of the files shown in a [`StashView`].
+const STASH_NAMESPACE: &str = "stash";
+
+/// Shows the changes recorded in a stash entry, with an excerpt for each changed file.
+pub(crate) struct StashView {
+    entry: StashEntry,
+    multibuffer: Entity<MultiBuffer>,
+    editor: Entity<Editor>,
+    _load_files: Task<Result<()>>,
+}
+
+impl StashView {
+    /// Opens a view of the given stash entry, after loading the files it changed.
+    pub(crate) fn deploy(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        entry: StashEntry,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let files = repository.read(cx).stash_diff(entry.index, cx);
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let files = files.await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let view = cx.new(|cx| StashView::new(entry, files, project, window, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open stash", window, cx, |e, _, _| {
+            Some(format!("{e}"))
+        });
+    }
+
+    fn new(
+        entry: StashEntry,
+        files: Vec<StashFileDiff>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(
+                multibuffer.clone(),
+                Some(project.clone()),
+                true,
+                window,
+                cx,
+            );
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_read_only(true);
+            editor
+        });
+        let language_registry = project.read(cx).languages().clone();
+        let load_files = cx.spawn_in(window, |this, cx| {
+            Self::load_files(this, files, language_registry, cx)
+        });
+
+        Self {
+            entry,
+            multibuffer,
+            editor,
+            _load_files: load_files,
+        }
+    }
+
+    /// Adds an excerpt for each file, showing its stashed changes as expanded diff hunks.
+    async fn load_files(
+        this: WeakEntity<Self>,
+        files: Vec<StashFileDiff>,
+        language_registry: Arc<LanguageRegistry>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<()> {
+        for file in files {
+            let language = language_registry
+                .language_for_file_path(&file.path)
+                .await
+                .ok();
+            let new_buffer_for = |text: Option<String>, cx: &mut App| {
+                cx.new(|cx| {
+                    let mut buffer = Buffer::local(text.unwrap_or_default(), cx);
+                    buffer.set_language_registry(language_registry.clone());
+                    buffer.set_language(language.clone(), cx);
+                    buffer
+                })
+            };
+            let (buffer, diff, diff_loaded) = cx.update(|_, cx| {
+                let base_buffer = new_buffer_for(file.old_text, cx);
+                let buffer = new_buffer_for(file.new_text, cx);
+                let buffer_snapshot = buffer.read(cx).text_snapshot();
+                let mut diff_loaded = None;
+                let diff = cx.new(|cx| {
+                    let mut diff = BufferDiff::new(&buffer_snapshot);
+                    diff_loaded = Some(diff.set_base_text(base_buffer, buffer_snapshot, cx));
+                    diff
+                });
+                (buffer, diff, diff_loaded)
+            })?;
+            if let Some(diff_loaded) = diff_loaded {
+                diff_loaded.await.ok();
+            }
+
+            this.update(&mut cx, |this, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                let hunk_ranges = diff
+                    .read(cx)
+                    .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot, cx)
+                    .map(|hunk| hunk.buffer_range.to_point(&snapshot))
+                    .collect::<Vec<_>>();
+                this.multibuffer.update(cx, |multibuffer, cx| {
+                    multibuffer.add_diff(diff, cx);
+                    multibuffer.set_excerpts_for_path(
+                        PathKey::namespaced(STASH_NAMESPACE, file.path.0.clone()),
+                        buffer,
+                        hunk_ranges,
+                        editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                        cx,
+                    );
+                });
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Render for StashView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!("stash@{{{}}}", self.entry.index))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(self.entry.message.clone()).size(LabelSize::Small)),
+            )
+            .child(div().flex_1().w_full().child(self.editor.clone()))
+    }
+}
+
+impl Focusable for StashView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for StashView {}
+
+impl Item for StashView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(format!("stash@{{{}}}", self.entry.index).into())
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.entry.message.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    cmp::Reverse,
+    ffi::OsStr,
+    ops::Range,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use language::{Encoding, File as _};
+use project::{File, Project, ProjectEntryId, ProjectPath, UnsupportedProjectItem};
+use util::ResultExt as _;
+
+use crate::find_byte_pattern;
+
+/// The number of bytes at the start of a file that are inspected to tell whether it's binary.
+const BINARY_DETECTION_LEN: usize = 8192;
+/// The number of bytes of a large file that are read at once.
+const PAGE_LEN: usize = 64 * 1024;
+/// The number of pages of a large file that are kept in memory.
+const MAX_LOADED_PAGES: usize = 64;
+/// The number of bytes of a large file that are searched at once.
+const SEARCH_CHUNK_LEN: usize = 1024 * 1024;
+
+/// Extensions of files that are opened as binary without looking at their content.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "7z", "a", "bin", "class", "db", "dll", "dylib", "exe", "gz", "jar", "lib", "o", "obj", "otf",
+    "pdf", "pyc", "so", "sqlite", "tar", "ttf", "wasm", "woff", "woff2", "xz", "zip", "zst",
+];
+
+pub enum BinaryFileEvent {
+    Edited,
+    Saved,
+    Reloaded,
+}
+
+/// The content of a file that isn't text, opened in the [`HexView`](crate::HexView).
+pub struct BinaryFile {
+    file: Arc<File>,
+    content: Content,
+    is_dirty: bool,
+}
+
+enum Content {
+    Loaded(Vec<u8>),
+    /// Files above the large-file threshold are read a page at a time as they're shown, and
+    /// can't be edited.
+    Paged(Pages),
+}
+
+struct Pages {
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    len: usize,
+    loaded: HashMap<usize, Arc<[u8]>>,
+    loading: HashMap<usize, Task<()>>,
+}
+
+impl Pages {
+    fn new(fs: Arc<dyn Fs>, abs_path: PathBuf, len: usize) -> Self {
+        Self {
+            fs,
+            abs_path,
+            len,
+            loaded: HashMap::default(),
+            loading: HashMap::default(),
+        }
+    }
+
+    /// Drops the pages furthest from the given ones once too many are in memory.
+    fn evict(&mut self, keep: &Range<usize>) {
+        let Some(excess) = self.loaded.len().checked_sub(MAX_LOADED_PAGES) else {
+            return;
+        };
+        let mut pages = self.loaded.keys().copied().collect::<Vec<_>>();
+        pages.sort_by_key(|&page| {
+            Reverse(
+                keep.start
+                    .saturating_sub(page)
+                    .max(page.saturating_sub(keep.end)),
+            )
+        });
+        for page in pages.into_iter().take(excess) {
+            self.loaded.remove(&page);
+        }
+    }
+}
+
+impl EventEmitter<BinaryFileEvent> for BinaryFile {}
+
+impl BinaryFile {
+    pub fn new(file: Arc<File>, bytes: Vec<u8>) -> Self {
+        Self {
+            file,
+            content: Content::Loaded(bytes),
+            is_dirty: false,
+        }
+    }
+
+    fn paged(file: Arc<File>, fs: Arc<dyn Fs>, abs_path: PathBuf, len: usize) -> Self {
+        Self {
+            file,
+            content: Content::Paged(Pages::new(fs, abs_path, len)),
+            is_dirty: false,
+        }
+    }
+
+    pub fn file(&self) -> &Arc<File> {
+        &self.file
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.content {
+            Content::Loaded(bytes) => bytes.len(),
+            Content::Paged(pages) => pages.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    /// Whether the file is too large to be loaded whole, and so can't be edited.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.content, Content::Paged(_))
+    }
+
+    /// The byte at `offset`, or `None` when it's past the end of the file or its page hasn't
+    /// been read yet.
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        match &self.content {
+            Content::Loaded(bytes) => bytes.get(offset).copied(),
+            Content::Paged(pages) => pages
+                .loaded
+                .get(&(offset / PAGE_LEN))?
+                .get(offset % PAGE_LEN)
+                .copied(),
+        }
+    }
+
+    /// Starts reading the pages that the given bytes are on, if the file is paged.
+    pub fn load_range(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        let Content::Paged(pages) = &mut self.content else {
+            return;
+        };
+        let page_range = range.start / PAGE_LEN..range.end.min(pages.len).div_ceil(PAGE_LEN);
+        pages.evict(&page_range);
+        for page in page_range {
+            if pages.loaded.contains_key(&page) || pages.loading.contains_key(&page) {
+                continue;
+            }
+            let fs = pages.fs.clone();
+            let abs_path = pages.abs_path.clone();
+            let start = (page * PAGE_LEN) as u64;
+            let load = cx.spawn(|this, mut cx| async move {
+                let bytes = fs
+                    .load_bytes_range(&abs_path, start..start + PAGE_LEN as u64)
+                    .await;
+                this.update(&mut cx, |this, cx| {
+                    if let Content::Paged(pages) = &mut this.content {
+                        pages.loading.remove(&page);
+                        if let Some(bytes) = bytes.log_err() {
+                            pages.loaded.insert(page, bytes.into());
+                            cx.notify();
+                        }
+                    }
+                })
+                .ok();
+            });
+            pages.loading.insert(page, load);
+        }
+    }
+
+    /// Reads the given bytes, from disk if the file is paged.
+    pub fn read_range(&self, range: Range<usize>, cx: &App) -> Task<Result<Vec<u8>>> {
+        match &self.content {
+            Content::Loaded(bytes) => {
+                Task::ready(Ok(bytes.get(range).unwrap_or_default().to_vec()))
+            }
+            Content::Paged(pages) => {
+                let fs = pages.fs.clone();
+                let abs_path = pages.abs_path.clone();
+                cx.background_spawn(async move {
+                    fs.load_bytes_range(&abs_path, range.start as u64..range.end as u64)
+                        .await
+                })
+            }
+        }
+    }
+
+    /// Finds the non-overlapping occurrences of `pattern` in the file, reading a paged file a
+    /// chunk at a time.
+    pub fn find(&self, pattern: Vec<u8>, cx: &App) -> Task<Vec<Range<usize>>> {
+        match &self.content {
+            Content::Loaded(bytes) => {
+                let bytes = bytes.clone();
+                cx.background_spawn(async move { find_byte_pattern(&bytes, &pattern) })
+            }
+            Content::Paged(pages) => {
+                let fs = pages.fs.clone();
+                let abs_path = pages.abs_path.clone();
+                let len = pages.len;
+                cx.background_spawn(async move {
+                    find_in_chunks(fs.as_ref(), &abs_path, len, &pattern, SEARCH_CHUNK_LEN)
+                        .await
+                        .log_err()
+                        .unwrap_or_default()
+                })
+            }
+        }
+    }
+
+    /// Overwrites the byte at `offset`. The size of the file never changes.
+    pub fn set_byte(&mut self, offset: usize, value: u8, cx: &mut Context<Self>) {
+        let Content::Loaded(bytes) = &mut self.content else {
+            return;
+        };
+        let Some(byte) = bytes.get_mut(offset) else {
+            return;
+        };
+        if *byte != value {
+            *byte = value;
+            self.is_dirty = true;
+            cx.emit(BinaryFileEvent::Edited);
+            cx.notify();
+        }
+    }
+
+    pub fn save(&mut self, fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Content::Loaded(bytes) = &self.content else {
+            return Task::ready(Err(anyhow!("large files are read-only")));
+        };
+        let Some(abs_path) = self.file.as_local().map(|file| file.abs_path(cx)) else {
+            return Task::ready(Err(anyhow!("only local files can be saved")));
+        };
+        let bytes = bytes.clone();
+        cx.spawn(|this, mut cx| async move {
+            let mut content = futures::io::Cursor::new(bytes);
+            fs.create_file_with(&abs_path, Pin::new(&mut content))
+                .await?;
+            this.update(&mut cx, |this, cx| {
+                this.is_dirty = false;
+                cx.emit(BinaryFileEvent::Saved);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Loads the file from disk again, discarding unsaved edits. Paged files drop the pages
+    /// they've read, and read them again as they're shown.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if let Content::Paged(pages) = &self.content {
+            let fs = pages.fs.clone();
+            let abs_path = pages.abs_path.clone();
+            return cx.spawn(|this, mut cx| async move {
+                let metadata = fs
+                    .metadata(&abs_path)
+                    .await?
+                    .ok_or_else(|| anyhow!("{abs_path:?} no longer exists"))?;
+                this.update(&mut cx, |this, cx| {
+                    this.content = Content::Paged(Pages::new(fs, abs_path, metadata.len as usize));
+                    cx.emit(BinaryFileEvent::Reloaded);
+                    cx.notify();
+                })
+            });
+        }
+
+        let Some(local_file) = self.file.as_local() else {
+            return Task::ready(Err(anyhow!("only local files can be reloaded")));
+        };
+        let load = local_file.load_bytes(cx);
+        cx.spawn(|this, mut cx| async move {
+            let bytes = load.await?;
+            this.update(&mut cx, |this, cx| {
+                this.content = Content::Loaded(bytes);
+                this.is_dirty = false;
+                cx.emit(BinaryFileEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl project::ProjectItem for BinaryFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let worktree = project.read(cx).worktree_for_id(path.worktree_id, cx)?;
+        // Remote worktrees can't load binary files yet.
+        if !worktree.read(cx).is_local() {
+            return None;
+        }
+        let entry = worktree.read(cx).entry_for_path(&path.path)?.clone();
+        if !entry.is_file() {
+            return None;
+        }
+        let len = entry.size as usize;
+        let file = File::for_entry(entry, worktree.clone());
+
+        // Files with a binary extension are opened without reading them, and files of a known
+        // language are left to the editor. Only the others are inspected.
+        let has_binary_extension = path
+            .path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| BINARY_EXTENSIONS.contains(&&*extension.to_lowercase()));
+        let language_file: Arc<dyn language::File> = file.clone();
+        if !has_binary_extension
+            && project
+                .read(cx)
+                .languages()
+                .language_for_file(&language_file, None, cx)
+                .is_some()
+        {
+            return None;
+        }
+
+        let abs_path = worktree.read(cx).absolutize(&path.path).ok()?;
+        let fs = project.read(cx).fs().clone();
+        let is_large_file = worktree.read(cx).is_large_file(&path.path, cx);
+        let path = path.path.clone();
+
+        Some(cx.spawn(|mut cx| async move {
+            if !has_binary_extension {
+                let head = fs
+                    .load_bytes_range(&abs_path, 0..BINARY_DETECTION_LEN as u64)
+                    .await;
+                // Text files and files that can't be read are left to the editor.
+                if !head.is_ok_and(|head| is_binary(&head)) {
+                    return Err(UnsupportedProjectItem.into());
+                }
+            }
+
+            if is_large_file.await {
+                return cx.new(|_| BinaryFile::paged(file, fs, abs_path, len));
+            }
+            let loaded = worktree
+                .update(&mut cx, |worktree, cx| worktree.load_binary_file(&path, cx))?
+                .await?;
+            cx.new(|_| BinaryFile::new(loaded.file, loaded.content))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.file.entry_id
+    }
+
+    fn project_path(&self, cx: &App) -> Option<ProjectPath> {
+        Some(ProjectPath {
+            worktree_id: self.file.worktree_id(cx),
+            path: self.file.path.clone(),
+        })
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+}
+
+/// Files with a zero byte near their start are binary, unless they're UTF-16 text, which is full
+/// of zero bytes too.
+pub fn is_binary(head: &[u8]) -> bool {
+    head.contains(&0) && !Encoding::detect(head).name().starts_with("UTF-16")
+}
+
+/// Finds the non-overlapping occurrences of `pattern` in a file, reading `chunk_len` bytes at a
+/// time. Each chunk is read with enough of the next one to find matches that span both.
+pub(crate) async fn find_in_chunks(
+    fs: &dyn Fs,
+    abs_path: &Path,
+    len: usize,
+    pattern: &[u8],
+    chunk_len: usize,
+) -> Result<Vec<Range<usize>>> {
+    let mut matches = Vec::new();
+    if pattern.is_empty() {
+        return Ok(matches);
+    }
+    let mut start = 0;
+    while start + pattern.len() <= len {
+        let chunk_end = (start + chunk_len).min(len);
+        let read_end = (chunk_end + pattern.len() - 1).min(len);
+        let chunk = fs
+            .load_bytes_range(abs_path, start as u64..read_end as u64)
+            .await?;
+        let mut next_start = chunk_end;
+        for range in find_byte_pattern(&chunk, pattern) {
+            // Matches starting in the next chunk are found when it's searched.
+            if start + range.start >= chunk_end {
+                break;
+            }
+            next_start = next_start.max(start + range.end);
+            matches.push(start + range.start..start + range.end);
+        }
+        start = next_start;
+    }
+    Ok(matches)
+}
@@ -0,0 +1,141 @@
+use editor::Editor;
+use gpui::{
+    div, prelude::*, App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render,
+    SharedString, Styled, Subscription,
+};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::HexView;
+
+pub struct GoToOffset {
+    offset_editor: Entity<Editor>,
+    hex_view: Entity<HexView>,
+    current_text: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ModalView for GoToOffset {}
+
+impl Focusable for GoToOffset {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.offset_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for GoToOffset {}
+
+impl GoToOffset {
+    pub fn new(hex_view: Entity<HexView>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let (cursor, len) = {
+            let hex_view = hex_view.read(cx);
+            (hex_view.cursor(), hex_view.binary_file().read(cx).len())
+        };
+
+        let offset_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(format!("{cursor:X}"), cx);
+            editor
+        });
+        let offset_editor_change =
+            cx.subscribe_in(&offset_editor, window, Self::on_offset_editor_event);
+
+        let current_text = format!("Current offset: 0x{cursor:X} of 0x{len:X} bytes");
+
+        Self {
+            offset_editor,
+            hex_view,
+            current_text: current_text.into(),
+            _subscriptions: vec![offset_editor_change],
+        }
+    }
+
+    fn on_offset_editor_event(
+        &mut self,
+        _: &Entity<Editor>,
+        event: &editor::EditorEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            editor::EditorEvent::Blurred => cx.emit(DismissEvent),
+            editor::EditorEvent::BufferEdited { .. } => cx.notify(),
+            _ => {}
+        }
+    }
+
+    fn offset_from_query(&self, cx: &App) -> Option<usize> {
+        parse_offset(&self.offset_editor.read(cx).text(cx))
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(offset) = self.offset_from_query(cx) {
+            self.hex_view.update(cx, |hex_view, cx| {
+                hex_view.go_to_offset(offset, cx);
+                hex_view.focus_handle(cx).focus(window);
+            });
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+/// Offsets are written in hex, with or without a `0x` prefix.
+fn parse_offset(query: &str) -> Option<usize> {
+    let query = query.trim();
+    let digits = query
+        .strip_prefix("0x")
+        .or_else(|| query.strip_prefix("0X"))
+        .unwrap_or(query);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+impl Render for GoToOffset {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let help_text = match self.offset_from_query(cx) {
+            Some(offset) => format!("Go to offset 0x{offset:X} ({offset})").into(),
+            None => self.current_text.clone(),
+        };
+
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("GoToOffset")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.offset_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .child(Label::new(help_text).color(Color::Muted)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("1A0"), Some(0x1a0));
+        assert_eq!(parse_offset(" 0x1a0 "), Some(0x1a0));
+        assert_eq!(parse_offset("0X10"), Some(0x10));
+        assert_eq!(parse_offset("10"), Some(0x10));
+        assert_eq!(parse_offset(""), None);
+        assert_eq!(parse_offset("0x"), None);
+        assert_eq!(parse_offset("xyz"), None);
+    }
+}
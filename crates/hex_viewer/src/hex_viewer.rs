@@ -0,0 +1,902 @@
+mod binary_file;
+mod go_to_offset;
+
+use std::{cmp::Ordering, ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use editor::{
+    actions::{
+        Copy, MoveDown, MoveLeft, MoveRight, MoveToBeginning, MoveToEnd, MoveUp, SelectAll,
+        SelectDown, SelectLeft, SelectRight, SelectUp,
+    },
+    items::entry_git_aware_label_color,
+};
+use file_icons::FileIcons;
+use gpui::{
+    actions, transparent_black, uniform_list, AnyElement, App, ClipboardItem, Context, Entity,
+    EventEmitter, FocusHandle, Focusable, Hsla, InteractiveElement, IntoElement, KeyDownEvent,
+    MouseButton, MouseDownEvent, ParentElement, Render, ScrollStrategy, Styled, Subscription, Task,
+    UniformListScrollHandle, WeakEntity, Window,
+};
+use language::File as _;
+use project::{search::SearchQuery, Project};
+use settings::Settings;
+use theme::{Theme, ThemeSettings};
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, TabContentParams},
+    searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+    ItemSettings, ToolbarItemLocation, Workspace, WorkspaceId,
+};
+
+pub use crate::binary_file::*;
+use crate::go_to_offset::GoToOffset;
+
+actions!(hex_viewer, [ToggleGoToOffset, CopyAsHex, CopyAsCArray]);
+
+/// The number of bytes shown on each row.
+const BYTES_PER_ROW: usize = 16;
+/// The number of bytes on each line of a copied C array.
+const C_ARRAY_BYTES_PER_LINE: usize = 12;
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<HexView>(cx);
+}
+
+/// Shows a [`BinaryFile`] as a hex dump, with an offset, hex and ASCII column for each row.
+pub struct HexView {
+    binary_file: Entity<BinaryFile>,
+    project: Entity<Project>,
+    workspace: Option<WeakEntity<Workspace>>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The offset of the byte under the cursor.
+    cursor: usize,
+    /// The other end of the selection, which includes the bytes at both of its ends.
+    selection_tail: Option<usize>,
+    /// Whether the next hex digit typed replaces the low half of the byte under the cursor.
+    editing_low_nibble: bool,
+    search_matches: Vec<Range<usize>>,
+    _subscription: Subscription,
+}
+
+pub enum HexViewEvent {
+    TitleChanged,
+    Edited,
+}
+
+impl EventEmitter<HexViewEvent> for HexView {}
+impl EventEmitter<SearchEvent> for HexView {}
+
+impl HexView {
+    pub fn new(
+        binary_file: Entity<BinaryFile>,
+        project: Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&binary_file, Self::on_binary_file_event);
+        Self {
+            binary_file,
+            project,
+            workspace: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            cursor: 0,
+            selection_tail: None,
+            editing_low_nibble: false,
+            search_matches: Vec::new(),
+            _subscription: subscription,
+        }
+    }
+
+    pub fn binary_file(&self) -> &Entity<BinaryFile> {
+        &self.binary_file
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The selected bytes, which are the byte under the cursor when nothing is selected.
+    pub fn selection(&self, cx: &App) -> Range<usize> {
+        let len = self.binary_file.read(cx).len();
+        let tail = self.selection_tail.unwrap_or(self.cursor);
+        let end = (self.cursor.max(tail) + 1).min(len);
+        self.cursor.min(tail).min(end)..end
+    }
+
+    pub fn go_to_offset(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.select(offset..offset.saturating_add(1), cx);
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Center);
+    }
+
+    fn select(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        let last_offset = self.binary_file.read(cx).len().saturating_sub(1);
+        let start = range.start.min(last_offset);
+        let end = range.end.saturating_sub(1).clamp(start, last_offset);
+        self.selection_tail = (start != end).then_some(start);
+        self.cursor = end;
+        self.editing_low_nibble = false;
+        cx.emit(SearchEvent::ActiveMatchChanged);
+        cx.notify();
+    }
+
+    fn on_binary_file_event(
+        &mut self,
+        _: Entity<BinaryFile>,
+        event: &BinaryFileEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            BinaryFileEvent::Edited => {
+                cx.emit(HexViewEvent::Edited);
+                cx.emit(SearchEvent::MatchesInvalidated);
+            }
+            BinaryFileEvent::Saved => cx.emit(HexViewEvent::TitleChanged),
+            BinaryFileEvent::Reloaded => {
+                let last_offset = self.binary_file.read(cx).len().saturating_sub(1);
+                self.cursor = self.cursor.min(last_offset);
+                self.selection_tail = None;
+                self.editing_low_nibble = false;
+                cx.emit(HexViewEvent::TitleChanged);
+                cx.emit(SearchEvent::MatchesInvalidated);
+            }
+        }
+        cx.notify();
+    }
+
+    fn move_cursor(&mut self, delta: isize, select: bool, cx: &mut Context<Self>) {
+        let len = self.binary_file.read(cx).len();
+        if len == 0 {
+            return;
+        }
+        if select {
+            self.selection_tail.get_or_insert(self.cursor);
+        } else {
+            self.selection_tail = None;
+        }
+        self.cursor = self.cursor.saturating_add_signed(delta).min(len - 1);
+        self.editing_low_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Top);
+        cx.emit(SearchEvent::ActiveMatchChanged);
+        cx.notify();
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-1, false, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(1, false, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-(BYTES_PER_ROW as isize), false, cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(BYTES_PER_ROW as isize, false, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(isize::MIN, false, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(isize::MAX, false, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-1, true, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(1, true, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-(BYTES_PER_ROW as isize), true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(BYTES_PER_ROW as isize, true, cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        let len = self.binary_file.read(cx).len();
+        self.select(0..len, cx);
+    }
+
+    fn click_byte(
+        &mut self,
+        offset: usize,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_handle.focus(window);
+        if event.modifiers.shift {
+            self.selection_tail.get_or_insert(self.cursor);
+        } else {
+            self.selection_tail = None;
+        }
+        self.cursor = offset;
+        self.editing_low_nibble = false;
+        cx.emit(SearchEvent::ActiveMatchChanged);
+        cx.notify();
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let modifiers = event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(digit) = event
+            .keystroke
+            .key_char
+            .as_deref()
+            .filter(|key| key.len() == 1)
+            .and_then(|key| key.chars().next()?.to_digit(16))
+        else {
+            return;
+        };
+        self.type_hex_digit(digit as u8, cx);
+        cx.stop_propagation();
+    }
+
+    /// Replaces one half of the byte under the cursor, moving on to the next byte once both
+    /// halves have been typed.
+    fn type_hex_digit(&mut self, digit: u8, cx: &mut Context<Self>) {
+        let offset = self.cursor;
+        let binary_file = self.binary_file.read(cx);
+        if binary_file.is_read_only() {
+            return;
+        }
+        let Some(byte) = binary_file.byte(offset) else {
+            return;
+        };
+        let value = if self.editing_low_nibble {
+            byte & 0xf0 | digit
+        } else {
+            digit << 4 | byte & 0x0f
+        };
+        self.binary_file.update(cx, |binary_file, cx| {
+            binary_file.set_byte(offset, value, cx)
+        });
+
+        if self.editing_low_nibble {
+            self.move_cursor(1, false, cx);
+        } else {
+            self.selection_tail = None;
+            self.editing_low_nibble = true;
+            cx.notify();
+        }
+    }
+
+    /// The selected bytes, or `None` when some of them haven't been read yet.
+    fn selected_bytes(&self, cx: &App) -> Option<Vec<u8>> {
+        let binary_file = self.binary_file.read(cx);
+        self.selection(cx)
+            .map(|offset| binary_file.byte(offset))
+            .collect()
+    }
+
+    /// Copies the selected bytes in the given format, reading them first if they aren't in
+    /// memory.
+    fn copy_selection(&mut self, format: fn(&[u8]) -> String, cx: &mut Context<Self>) {
+        let read = self.binary_file.read(cx).read_range(self.selection(cx), cx);
+        cx.spawn(|_, mut cx| async move {
+            let bytes = read.await?;
+            cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string(format(&bytes))))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn copy(&mut self, _: &Copy, window: &mut Window, cx: &mut Context<Self>) {
+        self.copy_as_hex(&CopyAsHex, window, cx);
+    }
+
+    fn copy_as_hex(&mut self, _: &CopyAsHex, _: &mut Window, cx: &mut Context<Self>) {
+        self.copy_selection(format_hex, cx);
+    }
+
+    fn copy_as_c_array(&mut self, _: &CopyAsCArray, _: &mut Window, cx: &mut Context<Self>) {
+        self.copy_selection(format_c_array, cx);
+    }
+
+    fn toggle_go_to_offset(
+        &mut self,
+        _: &ToggleGoToOffset,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.upgrade())
+        else {
+            return;
+        };
+        let hex_view = cx.entity();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                GoToOffset::new(hex_view, window, cx)
+            });
+        });
+    }
+
+    fn is_search_match(&self, offset: usize) -> bool {
+        self.search_matches
+            .binary_search_by(|range| {
+                if range.end <= offset {
+                    Ordering::Less
+                } else if range.start > offset {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    fn byte_background(&self, offset: usize, selection: &Range<usize>, cx: &App) -> Option<Hsla> {
+        if self.selection_tail.is_some() && selection.contains(&offset) {
+            Some(cx.theme().players().local().selection)
+        } else if self.is_search_match(offset) {
+            Some(cx.theme().colors().search_match_background)
+        } else {
+            None
+        }
+    }
+
+    fn render_row(&self, row: usize, window: &Window, cx: &Context<Self>) -> impl IntoElement {
+        let binary_file = self.binary_file.read(cx);
+        let row_start = row * BYTES_PER_ROW;
+        let selection = self.selection(cx);
+        let colors = cx.theme().colors();
+        let cursor_color = cx.theme().players().local().cursor;
+        let show_cursor = self.focus_handle.is_focused(window);
+
+        let hex_cells = (row_start..row_start + BYTES_PER_ROW).map(|offset| {
+            // Bytes of large files are blank until their page has been read.
+            let Some(byte) = binary_file.byte(offset) else {
+                return div()
+                    .px_0p5()
+                    .border_1()
+                    .invisible()
+                    .child("00")
+                    .into_any_element();
+            };
+            let is_cursor = show_cursor && offset == self.cursor;
+            div()
+                .px_0p5()
+                .border_1()
+                .border_color(if is_cursor {
+                    cursor_color
+                } else {
+                    transparent_black()
+                })
+                .when_some(
+                    self.byte_background(offset, &selection, cx),
+                    |cell, color| cell.bg(color),
+                )
+                // Half of the byte has been typed over.
+                .when(is_cursor && self.editing_low_nibble, |cell| {
+                    cell.bg(cursor_color.opacity(0.2))
+                })
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, event, window, cx| {
+                        this.click_byte(offset, event, window, cx)
+                    }),
+                )
+                .child(format!("{byte:02X}"))
+                .into_any_element()
+        });
+
+        let ascii_cells =
+            (row_start..(row_start + BYTES_PER_ROW).min(binary_file.len())).map(|offset| {
+                let byte = binary_file.byte(offset);
+                let is_printable = byte.is_some_and(|byte| byte.is_ascii_graphic() || byte == b' ');
+                div()
+                    .when(byte.is_none(), |cell| cell.invisible())
+                    .border_b_1()
+                    .border_color(if show_cursor && offset == self.cursor {
+                        cursor_color
+                    } else {
+                        transparent_black()
+                    })
+                    .when(!is_printable, |cell| cell.text_color(colors.text_muted))
+                    .when_some(
+                        self.byte_background(offset, &selection, cx),
+                        |cell, color| cell.bg(color),
+                    )
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event, window, cx| {
+                            this.click_byte(offset, event, window, cx)
+                        }),
+                    )
+                    .child(match byte {
+                        Some(byte) if is_printable => (byte as char).to_string(),
+                        _ => ".".to_string(),
+                    })
+            });
+
+        h_flex()
+            .gap_4()
+            .child(
+                div()
+                    .text_color(colors.editor_line_number)
+                    .child(format!("{row_start:08X}")),
+            )
+            .child(h_flex().children(hex_cells))
+            .child(h_flex().children(ascii_cells))
+    }
+}
+
+/// Formats bytes as space-separated hex, e.g. `DE AD BE EF`.
+fn format_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats bytes as a C array declaration.
+fn format_c_array(bytes: &[u8]) -> String {
+    let mut text = format!("unsigned char data[{}] = {{\n", bytes.len());
+    for line in bytes.chunks(C_ARRAY_BYTES_PER_LINE) {
+        let line = line
+            .iter()
+            .map(|byte| format!("0x{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        text.push_str(&format!("    {line},\n"));
+    }
+    text.push_str("};\n");
+    text
+}
+
+/// Turns a search query into the bytes to look for. Queries made of pairs of hex digits,
+/// optionally separated by whitespace, look for those bytes. Other queries, as well as queries
+/// wrapped in double quotes, look for their text.
+fn parse_byte_pattern(query: &str) -> Vec<u8> {
+    if let Some(text) = query
+        .strip_prefix('"')
+        .and_then(|query| query.strip_suffix('"'))
+    {
+        return text.as_bytes().to_vec();
+    }
+
+    let digits = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return query.as_bytes().to_vec();
+    }
+    (0..digits.len())
+        .step_by(2)
+        .filter_map(|ix| u8::from_str_radix(&digits[ix..ix + 2], 16).ok())
+        .collect()
+}
+
+/// Finds the non-overlapping occurrences of `pattern` in `bytes`.
+fn find_byte_pattern(bytes: &[u8], pattern: &[u8]) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    if pattern.is_empty() {
+        return matches;
+    }
+    let mut start = 0;
+    while let Some(ix) = bytes
+        .get(start..)
+        .unwrap_or_default()
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+    {
+        let match_start = start + ix;
+        start = match_start + pattern.len();
+        matches.push(match_start..start);
+    }
+    matches
+}
+
+impl Item for HexView {
+    type Event = HexViewEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexViewEvent::TitleChanged => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::UpdateBreadcrumbs);
+            }
+            HexViewEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.binary_file.entity_id(), self.binary_file.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.binary_file.read(cx).file().as_local()?.abs_path(cx);
+        let file_path = abs_path.compact().to_string_lossy().to_string();
+        Some(file_path.into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let file = self.binary_file.read(cx).file();
+        let label_color = if ItemSettings::get_global(cx).git_status {
+            let project_path = project::ProjectPath {
+                worktree_id: file.worktree_id(cx),
+                path: file.path.clone(),
+            };
+            let git_status = self
+                .project
+                .read(cx)
+                .project_path_git_status(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(file.file_name(cx).to_string_lossy().to_string())
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.binary_file.read(cx).file().path.clone();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(&path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let file = self.binary_file.read(cx).file();
+        let mut text = file.file_name(cx).to_string_lossy().to_string();
+        if self.project.read(cx).visible_worktrees(cx).count() > 1 {
+            text = PathBuf::from(file.worktree.read(cx).root_name())
+                .join(text)
+                .to_string_lossy()
+                .to_string();
+        }
+        Some(vec![BreadcrumbText {
+            text,
+            highlights: None,
+            font: None,
+        }])
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Self>>
+    where
+        Self: Sized,
+    {
+        let workspace = self.workspace.clone();
+        Some(cx.new(|cx| {
+            let mut hex_view = Self::new(self.binary_file.clone(), self.project.clone(), cx);
+            hex_view.workspace = workspace;
+            hex_view
+        }))
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        self.workspace = Some(workspace.weak_handle());
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.binary_file.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        let binary_file = self.binary_file.read(cx);
+        binary_file.file().as_local().is_some() && !binary_file.is_read_only()
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        project: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let fs = project.read(cx).fs().clone();
+        self.binary_file
+            .update(cx, |binary_file, cx| binary_file.save(fs, cx))
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.binary_file
+            .update(cx, |binary_file, cx| binary_file.reload(cx))
+    }
+
+    fn as_searchable(&self, handle: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+}
+
+impl SearchableItem for HexView {
+    type Match = Range<usize>;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: false,
+            word: false,
+            regex: false,
+            replacement: false,
+            selection: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches.clear();
+        cx.notify();
+    }
+
+    fn update_matches(&mut self, matches: &[Self::Match], _: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches = matches.to_vec();
+        cx.notify();
+    }
+
+    fn query_suggestion(&mut self, _: &mut Window, cx: &mut Context<Self>) -> String {
+        if self.selection_tail.is_some() {
+            self.selected_bytes(cx)
+                .map(|bytes| format_hex(&bytes))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        }
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(range) = matches.get(index) {
+            self.select(range.clone(), cx);
+            self.scroll_handle
+                .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Center);
+        }
+    }
+
+    fn select_matches(
+        &mut self,
+        matches: &[Self::Match],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Only a single range of bytes can be selected.
+        self.activate_match(0, matches, window, cx);
+    }
+
+    fn replace(&mut self, _: &Self::Match, _: &SearchQuery, _: &mut Window, _: &mut Context<Self>) {
+        // Replacement isn't supported, as it could change the size of the file.
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let pattern = parse_byte_pattern(query.as_str());
+        self.binary_file.read(cx).find(pattern, cx)
+    }
+
+    fn active_match_index(
+        &mut self,
+        matches: &[Self::Match],
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        // The match under or after the cursor, wrapping around to the first one.
+        Some(
+            matches
+                .iter()
+                .position(|range| range.end > self.cursor)
+                .unwrap_or(0),
+        )
+    }
+}
+
+impl Focusable for HexView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let row_count = self.binary_file.read(cx).len().div_ceil(BYTES_PER_ROW);
+        let settings = ThemeSettings::get_global(cx);
+
+        v_flex()
+            .key_context("HexView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::copy))
+            .on_action(cx.listener(Self::copy_as_hex))
+            .on_action(cx.listener(Self::copy_as_c_array))
+            .on_action(cx.listener(Self::toggle_go_to_offset))
+            .on_key_down(cx.listener(Self::key_down))
+            .size_full()
+            .p_2()
+            .bg(cx.theme().colors().editor_background)
+            .font_family(settings.buffer_font.family.clone())
+            .text_size(settings.buffer_font_size(cx))
+            .child(
+                uniform_list(
+                    cx.entity(),
+                    "hex-view-rows",
+                    row_count,
+                    |this, range, window, cx| {
+                        this.binary_file.update(cx, |binary_file, cx| {
+                            binary_file.load_range(
+                                range.start * BYTES_PER_ROW..range.end * BYTES_PER_ROW,
+                                cx,
+                            )
+                        });
+                        range.map(|row| this.render_row(row, window, cx)).collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+    }
+}
+
+impl ProjectItem for HexView {
+    type Item = BinaryFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        item: Entity<Self::Item>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use std::path::Path;
+
+    use super::*;
+    use crate::binary_file::find_in_chunks;
+
+    #[test]
+    fn test_byte_patterns() {
+        assert_eq!(
+            parse_byte_pattern("DE AD be ef"),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(parse_byte_pattern("cafe"), vec![0xca, 0xfe]);
+        assert_eq!(parse_byte_pattern("\"cafe\""), b"cafe".to_vec());
+        assert_eq!(parse_byte_pattern("abc"), b"abc".to_vec());
+        assert_eq!(parse_byte_pattern("PNG"), b"PNG".to_vec());
+
+        let bytes = b"\x00abab\x00aba";
+        assert_eq!(find_byte_pattern(bytes, b"ab"), vec![1..3, 3..5, 6..8]);
+        assert_eq!(find_byte_pattern(bytes, b"aba"), vec![1..4, 6..9]);
+        assert_eq!(find_byte_pattern(bytes, b"abc"), vec![]);
+        assert_eq!(find_byte_pattern(bytes, b""), vec![]);
+    }
+
+    #[test]
+    fn test_copy_formats() {
+        assert_eq!(format_hex(&[0xde, 0xad, 0x00, 0x0f]), "DE AD 00 0F");
+        assert_eq!(format_hex(&[]), "");
+        assert_eq!(
+            format_c_array(&(0..14).collect::<Vec<u8>>()),
+            concat!(
+                "unsigned char data[14] = {\n",
+                "    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B,\n",
+                "    0x0C, 0x0D,\n",
+                "};\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"));
+        assert!(is_binary(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00"));
+        assert!(!is_binary(b"plain text"));
+        assert!(!is_binary(b"caf\xe9"));
+        assert!(!is_binary(b"h\x00e\x00l\x00l\x00o\x00"));
+    }
+
+    #[gpui::test]
+    async fn test_find_in_chunks(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let bytes = b"\x00abab\x00abababa\x00ab".to_vec();
+        let path = Path::new("/file.bin");
+        fs.insert_file(path, bytes.clone()).await;
+
+        // Matches that span chunks, or overlap the end of the previous match, are found the same
+        // way as when the whole file is searched at once.
+        for pattern in [&b"ab"[..], b"aba", b"\x00ab", b"abc", b""] {
+            for chunk_len in [1, 2, 3, 5, 64] {
+                assert_eq!(
+                    find_in_chunks(fs.as_ref(), path, bytes.len(), pattern, chunk_len)
+                        .await
+                        .unwrap(),
+                    find_byte_pattern(&bytes, pattern),
+                    "pattern {pattern:?}, chunk length {chunk_len}",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,573 @@
+use std::{any::TypeId, ops::Range};
+
+use anyhow::Result;
+use collections::HashMap;
+use editor::Editor;
+use gpui::{
+    actions, uniform_list, AnyView, App, ClickEvent, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, KeyContext, ScrollStrategy, SharedString, Task, UniformListScrollHandle, WeakEntity,
+    Window,
+};
+use language::{Anchor, Buffer, ToPoint as _};
+use project::Project;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::ResultExt as _;
+use workspace::{item::Item, SplitDirection, Workspace};
+
+actions!(
+    hierarchy_view,
+    [ToggleDirection, ExpandSelectedEntry, CollapseSelectedEntry]
+);
+
+/// What a [`HierarchyView`] shows for a node and opens when the node is confirmed.
+pub struct HierarchySymbol {
+    pub name: SharedString,
+    pub detail: Option<SharedString>,
+    pub buffer: Entity<Buffer>,
+    pub selection_range: Range<Anchor>,
+}
+
+/// Provides the nodes of a [`HierarchyView`] and anything shown next to its tree.
+pub trait HierarchyDelegate: Sized + 'static {
+    type Node: Clone + 'static;
+
+    /// The label of the current direction, e.g. "Incoming Calls".
+    fn direction_label(&self) -> &'static str;
+
+    /// Switches to the opposite direction. The tree is reloaded from its roots afterwards.
+    fn toggle_direction(&mut self);
+
+    /// Loads the children of the given node in the current direction.
+    fn load_children(&self, node: &Self::Node, cx: &mut App) -> Task<Result<Vec<Self::Node>>>;
+
+    fn symbol(&self, node: &Self::Node, cx: &App) -> HierarchySymbol;
+
+    /// The key context added to the view, alongside "HierarchyView".
+    fn key_context(&self) -> &'static str;
+
+    fn telemetry_event_text(&self) -> &'static str;
+
+    fn clone_on_split(&self, window: &mut Window, cx: &mut App) -> Self;
+
+    /// Called when another node is selected, e.g. to update the details editor.
+    fn selected_node_changed(&mut self, _node: &Self::Node, _window: &mut Window, _cx: &mut App) {}
+
+    /// An editor shown to the right of the tree, if any.
+    fn details_editor(&self) -> Option<&Entity<Editor>> {
+        None
+    }
+}
+
+struct Node<T> {
+    item: T,
+    depth: usize,
+    expanded: bool,
+    children: Children,
+}
+
+enum Children {
+    NotLoaded,
+    Loading,
+    Loaded(Vec<usize>),
+}
+
+/// A tree of symbols related to the symbols it was opened for, such as callers or supertypes.
+/// Nodes are loaded lazily by the delegate when expanded.
+pub struct HierarchyView<D: HierarchyDelegate> {
+    delegate: D,
+    workspace: WeakEntity<Workspace>,
+    roots: Vec<D::Node>,
+    nodes: Vec<Node<D::Node>>,
+    root_nodes: Vec<usize>,
+    visible_nodes: Vec<usize>,
+    selected_node: Option<usize>,
+    load_tasks: HashMap<usize, Task<()>>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl<D: HierarchyDelegate> HierarchyView<D> {
+    pub fn new(
+        workspace: WeakEntity<Workspace>,
+        delegate: D,
+        roots: Vec<D::Node>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            delegate,
+            workspace,
+            roots: Vec::new(),
+            nodes: Vec::new(),
+            root_nodes: Vec::new(),
+            visible_nodes: Vec::new(),
+            selected_node: None,
+            load_tasks: HashMap::default(),
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        };
+        this.set_roots(roots, window, cx);
+        this
+    }
+
+    fn set_roots(&mut self, roots: Vec<D::Node>, window: &mut Window, cx: &mut Context<Self>) {
+        self.nodes.clear();
+        self.root_nodes.clear();
+        self.load_tasks.clear();
+        for item in &roots {
+            self.root_nodes.push(self.nodes.len());
+            self.nodes.push(Node {
+                item: item.clone(),
+                depth: 0,
+                expanded: false,
+                children: Children::NotLoaded,
+            });
+        }
+        self.roots = roots;
+        self.selected_node = None;
+
+        if let Some(&first_root) = self.root_nodes.first() {
+            self.expand_node(first_root, cx);
+            self.select_node(first_root, window, cx);
+        } else {
+            self.update_visible_nodes();
+        }
+    }
+
+    fn update_visible_nodes(&mut self) {
+        self.visible_nodes.clear();
+        let mut stack = self.root_nodes.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(node_ix) = stack.pop() {
+            self.visible_nodes.push(node_ix);
+            let node = &self.nodes[node_ix];
+            if let (true, Children::Loaded(children)) = (node.expanded, &node.children) {
+                stack.extend(children.iter().rev().copied());
+            }
+        }
+    }
+
+    fn expand_node(&mut self, node_ix: usize, cx: &mut Context<Self>) {
+        let node = &mut self.nodes[node_ix];
+        node.expanded = true;
+        if let Children::NotLoaded = node.children {
+            let depth = node.depth + 1;
+            let children = self.delegate.load_children(&node.item, cx);
+            node.children = Children::Loading;
+            let load_task = cx.spawn(|this, mut cx| async move {
+                let children = children.await.log_err().unwrap_or_default();
+                this.update(&mut cx, |this, cx| {
+                    let mut child_nodes = Vec::with_capacity(children.len());
+                    for item in children {
+                        child_nodes.push(this.nodes.len());
+                        this.nodes.push(Node {
+                            item,
+                            depth,
+                            expanded: false,
+                            children: Children::NotLoaded,
+                        });
+                    }
+                    this.nodes[node_ix].children = Children::Loaded(child_nodes);
+                    this.load_tasks.remove(&node_ix);
+                    this.update_visible_nodes();
+                    cx.notify();
+                })
+                .ok();
+            });
+            self.load_tasks.insert(node_ix, load_task);
+        }
+        self.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn collapse_node(&mut self, node_ix: usize, cx: &mut Context<Self>) {
+        self.nodes[node_ix].expanded = false;
+        self.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn toggle_node(&mut self, node_ix: usize, cx: &mut Context<Self>) {
+        if self.nodes[node_ix].expanded {
+            self.collapse_node(node_ix, cx);
+        } else {
+            self.expand_node(node_ix, cx);
+        }
+    }
+
+    fn select_node(&mut self, node_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_node == Some(node_ix) {
+            return;
+        }
+        self.selected_node = Some(node_ix);
+        if let Some(position) = self.visible_nodes.iter().position(|&ix| ix == node_ix) {
+            self.scroll_handle
+                .scroll_to_item(position, ScrollStrategy::Top);
+        }
+        self.delegate
+            .selected_node_changed(&self.nodes[node_ix].item, window, cx);
+        cx.notify();
+    }
+
+    /// Opens the definition of the given node's symbol.
+    fn open_node(&mut self, node_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let symbol = self.delegate.symbol(&self.nodes[node_ix].item, cx);
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let range = symbol.selection_range.to_point(symbol.buffer.read(cx));
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.adjacent_pane(window, cx);
+            let editor =
+                workspace.open_project_item::<Editor>(pane, symbol.buffer, true, true, window, cx);
+            editor.update(cx, |editor, cx| {
+                editor.go_to_singleton_buffer_range(range, window, cx);
+            });
+        });
+    }
+
+    fn selected_position(&self) -> Option<usize> {
+        let selected_node = self.selected_node?;
+        self.visible_nodes
+            .iter()
+            .position(|&node_ix| node_ix == selected_node)
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        let position = self
+            .selected_position()
+            .map_or(0, |position| position + 1)
+            .min(self.visible_nodes.len().saturating_sub(1));
+        if let Some(&node_ix) = self.visible_nodes.get(position) {
+            self.select_node(node_ix, window, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, window: &mut Window, cx: &mut Context<Self>) {
+        let position = self
+            .selected_position()
+            .map_or(0, |position| position.saturating_sub(1));
+        if let Some(&node_ix) = self.visible_nodes.get(position) {
+            self.select_node(node_ix, window, cx);
+        }
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(&node_ix) = self.visible_nodes.first() {
+            self.select_node(node_ix, window, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(&node_ix) = self.visible_nodes.last() {
+            self.select_node(node_ix, window, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(node_ix) = self.selected_node {
+            self.open_node(node_ix, window, cx);
+        }
+    }
+
+    fn expand_selected_entry(
+        &mut self,
+        _: &ExpandSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(node_ix) = self.selected_node else {
+            return;
+        };
+        if !self.nodes[node_ix].expanded {
+            self.expand_node(node_ix, cx);
+        } else if let Children::Loaded(children) = &self.nodes[node_ix].children {
+            if let Some(&first_child) = children.first() {
+                self.select_node(first_child, window, cx);
+            }
+        }
+    }
+
+    fn collapse_selected_entry(
+        &mut self,
+        _: &CollapseSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(node_ix) = self.selected_node else {
+            return;
+        };
+        if self.nodes[node_ix].expanded {
+            self.collapse_node(node_ix, cx);
+            return;
+        }
+
+        // Move the selection to the parent, which is the closest preceding visible node with a
+        // smaller depth.
+        let depth = self.nodes[node_ix].depth;
+        let Some(position) = self.selected_position() else {
+            return;
+        };
+        if let Some(&parent_ix) = self.visible_nodes[..position]
+            .iter()
+            .rev()
+            .find(|&&ix| self.nodes[ix].depth < depth)
+        {
+            self.select_node(parent_ix, window, cx);
+        }
+    }
+
+    fn toggle_direction(
+        &mut self,
+        _: &ToggleDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.delegate.toggle_direction();
+        let roots = std::mem::take(&mut self.roots);
+        self.set_roots(roots, window, cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyView");
+        dispatch_context.add(self.delegate.key_context());
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_node(&self, node_ix: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let node = &self.nodes[node_ix];
+        let symbol = self.delegate.symbol(&node.item, cx);
+        let toggle = match &node.children {
+            Children::Loaded(children) if children.is_empty() => None,
+            _ => Some(node.expanded),
+        };
+
+        let buffer = symbol.buffer.read(cx);
+        let location = buffer.file().map(|file| {
+            let row = symbol.selection_range.start.to_point(buffer).row + 1;
+            format!("{}:{row}", file.file_name(cx).to_string_lossy())
+        });
+
+        ListItem::new(node_ix)
+            .indent_level(node.depth)
+            .indent_step_size(px(12.))
+            .toggle(toggle)
+            .toggle_state(self.selected_node == Some(node_ix))
+            .on_toggle(cx.listener(move |this, _, _, cx| this.toggle_node(node_ix, cx)))
+            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                this.select_node(node_ix, window, cx);
+                if event.down.click_count > 1 {
+                    this.open_node(node_ix, window, cx);
+                }
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(symbol.name))
+                    .when_some(symbol.detail, |this, detail| {
+                        this.child(
+                            Label::new(detail)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    })
+                    .when_some(location, |this, location| {
+                        this.child(
+                            Label::new(location)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+    }
+}
+
+/// Opens a [`HierarchyView`] to the right of the given editor for the roots returned by
+/// `prepare` at the editor's newest cursor. Nothing is opened if there are no roots.
+pub fn deploy<D: HierarchyDelegate>(
+    editor: Entity<Editor>,
+    prepare: impl FnOnce(
+        &mut Project,
+        &Entity<Buffer>,
+        Anchor,
+        &mut Context<Project>,
+    ) -> Task<Result<Vec<D::Node>>>,
+    new_delegate: impl FnOnce(Entity<Project>, &mut Window, &mut App) -> D + 'static,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let Some((buffer, position)) = ({
+        let editor = editor.read(cx);
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+
+    workspace.update(cx, |workspace, cx| {
+        let project = workspace.project().clone();
+        let prepare = project.update(cx, |project, cx| prepare(project, &buffer, position, cx));
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let roots = prepare.await?;
+            if roots.is_empty() {
+                return anyhow::Ok(());
+            }
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let workspace_handle = cx.entity().downgrade();
+                let delegate = new_delegate(project, window, cx);
+                let view =
+                    cx.new(|cx| HierarchyView::new(workspace_handle, delegate, roots, window, cx));
+                workspace.split_item(SplitDirection::Right, Box::new(view), window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    });
+}
+
+impl<D: HierarchyDelegate> Render for HierarchyView<D> {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let details_editor = self.delegate.details_editor().cloned();
+        h_flex()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                v_flex()
+                    .h_full()
+                    .map(|this| {
+                        if details_editor.is_some() {
+                            this.w_1_3()
+                                .border_r_1()
+                                .border_color(cx.theme().colors().border)
+                        } else {
+                            this.w_full()
+                        }
+                    })
+                    .child(
+                        h_flex()
+                            .px_2()
+                            .py_1()
+                            .justify_between()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(
+                                Label::new(self.delegate.direction_label())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                IconButton::new("toggle-direction", IconName::ChevronUpDown)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(Tooltip::for_action_title(
+                                        "Toggle Direction",
+                                        &ToggleDirection,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.toggle_direction(&ToggleDirection, window, cx)
+                                    })),
+                            ),
+                    )
+                    .child(
+                        uniform_list(
+                            cx.entity().clone(),
+                            "hierarchy-nodes",
+                            self.visible_nodes.len(),
+                            |this, range, _, cx| {
+                                range
+                                    .map(|position| {
+                                        this.render_node(this.visible_nodes[position], cx)
+                                    })
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    ),
+            )
+            .when_some(details_editor, |this, editor| {
+                this.child(div().flex_1().h_full().child(editor))
+            })
+    }
+}
+
+impl<D: HierarchyDelegate> EventEmitter<()> for HierarchyView<D> {}
+
+impl<D: HierarchyDelegate> Focusable for HierarchyView<D> {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<D: HierarchyDelegate> Item for HierarchyView<D> {
+    type Event = ();
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        let name = self
+            .roots
+            .first()
+            .map(|root| self.delegate.symbol(root, cx).name)
+            .unwrap_or_default();
+        Some(format!("{} of `{name}`", self.delegate.direction_label()).into())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTree))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some(self.delegate.telemetry_event_text())
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            self.delegate.details_editor().map(|editor| editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Self>>
+    where
+        Self: Sized,
+    {
+        let delegate = self.delegate.clone_on_split(window, cx);
+        Some(cx.new(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                delegate,
+                self.roots.clone(),
+                window,
+                cx,
+            )
+        }))
+    }
+}
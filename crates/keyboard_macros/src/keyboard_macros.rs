@@ -0,0 +1,571 @@
+mod keyboard_macros_settings;
+mod recording_indicator;
+mod save_macro;
+
+use std::collections::{BTreeSet, VecDeque};
+
+use anyhow::{anyhow, Result};
+use editor::{actions::HandleInput, Anchor, Editor, EditorEvent, EditorMode};
+use gpui::{actions, impl_actions, Action, App, Context, Global, KeyContext, WeakEntity, Window};
+use language::Point;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use settings::Settings;
+use workspace::Workspace;
+
+pub use keyboard_macros_settings::{KeyboardMacroSettings, MacroStep};
+pub use recording_indicator::RecordingIndicator;
+use save_macro::SaveMacro;
+
+/// Replays longer than this are assumed to be a macro that plays itself, and are stopped.
+const MAX_REPLAYED_STEPS: usize = 100_000;
+
+actions!(
+    keyboard_macros,
+    [StartRecording, StopRecording, SaveLastMacro]
+);
+
+/// Plays the last recorded macro, or a macro saved in the settings.
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Play {
+    /// The name of the saved macro to play. Defaults to the last recorded macro.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// How many times to play the macro in a row. Defaults to 1.
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+/// Plays a macro once for every line touched by the selections, with a single cursor at the start
+/// of the line.
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PlayOnEachLine {
+    /// The name of the saved macro to play. Defaults to the last recorded macro.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl_actions!(keyboard_macros, [Play, PlayOnEachLine]);
+
+pub fn init(cx: &mut App) {
+    KeyboardMacroSettings::register(cx);
+    cx.set_global(KeyboardMacros::default());
+
+    cx.observe_keystrokes(|event, window, cx| {
+        let Some(action) = &event.action else {
+            return;
+        };
+        if action.name().starts_with("keyboard_macros::")
+            || !records_actions_in(&window.context_stack())
+        {
+            return;
+        }
+        if let Some(index) = record_step(Step::Action(action.boxed_clone()), cx) {
+            // Text inserted by the action, like an accepted completion, is reported while its
+            // effects are flushed, before this deferred callback runs.
+            cx.global_mut::<KeyboardMacros>().action_step = Some(index);
+            cx.defer(|cx| cx.global_mut::<KeyboardMacros>().action_step = None);
+        }
+    })
+    .detach();
+
+    cx.observe_new(|editor: &mut Editor, _, cx| {
+        // Text typed into pickers and other single-line editors belongs to them, and would end up
+        // in the wrong place when replayed.
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let editor = cx.entity();
+        cx.subscribe(&editor, |_, _, event, cx| {
+            if let EditorEvent::InputHandled { text, .. } = event {
+                record_text(text, cx);
+            }
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(start_recording)
+            .register_action(stop_recording)
+            .register_action(play)
+            .register_action(play_on_each_line)
+            .register_action(save_last_macro);
+    })
+    .detach();
+}
+
+/// A step of a macro: either an action dispatched by a keystroke, or text typed into an editor.
+#[derive(Debug)]
+enum Step {
+    Action(Box<dyn Action>),
+    Text(String),
+}
+
+impl Clone for Step {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Text(text) => Self::Text(text.clone()),
+        }
+    }
+}
+
+enum ReplayStep {
+    Step(Step),
+    /// Puts a single cursor at the start of a line before the macro is played on it.
+    MoveToLine(WeakEntity<Editor>, Anchor),
+}
+
+#[derive(Default)]
+struct KeyboardMacros {
+    recording: Option<Vec<Step>>,
+    /// The index of the step recorded for the action being dispatched, until its effects have been
+    /// flushed.
+    action_step: Option<usize>,
+    last_macro: Option<Vec<Step>>,
+    replay_queue: VecDeque<ReplayStep>,
+    /// The number of steps replayed so far, while a replay is running.
+    replayed_steps: Option<usize>,
+}
+
+impl Global for KeyboardMacros {}
+
+pub fn is_recording(cx: &App) -> bool {
+    cx.global::<KeyboardMacros>().recording.is_some()
+}
+
+fn is_replaying(cx: &App) -> bool {
+    cx.global::<KeyboardMacros>().replayed_steps.is_some()
+}
+
+/// Returns the index of the recorded step, if a macro is being recorded.
+fn record_step(step: Step, cx: &mut App) -> Option<usize> {
+    // Steps replayed by a macro are part of that macro, not of the one being recorded.
+    if is_replaying(cx) {
+        return None;
+    }
+    let recording = cx.global_mut::<KeyboardMacros>().recording.as_mut()?;
+    recording.push(step);
+    Some(recording.len() - 1)
+}
+
+/// Records text inserted into an editor. Text inserted by an action replaces that action, as
+/// replaying the action wouldn't insert the same text without the menu or prediction it came from.
+fn record_text(text: &str, cx: &mut App) {
+    let macros = cx.global_mut::<KeyboardMacros>();
+    if let Some(index) = macros.action_step.take() {
+        if let Some(step) = macros
+            .recording
+            .as_mut()
+            .and_then(|recording| recording.get_mut(index))
+        {
+            *step = Step::Text(text.to_string());
+            return;
+        }
+    }
+    record_step(Step::Text(text.to_string()), cx);
+}
+
+/// Whether actions dispatched in the given contexts act on the edited text, rather than on a
+/// picker or other single-line editor that won't be open when the macro is replayed.
+fn records_actions_in(context_stack: &[KeyContext]) -> bool {
+    context_stack.iter().all(|context| {
+        !context.contains("Picker")
+            && (!context.contains("Editor")
+                || context
+                    .get("mode")
+                    .map_or(true, |mode| mode.as_ref() == "full"))
+    })
+}
+
+fn start_recording(
+    _: &mut Workspace,
+    _: &StartRecording,
+    _: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !is_replaying(cx) {
+        cx.global_mut::<KeyboardMacros>().recording = Some(Vec::new());
+    }
+}
+
+fn stop_recording(
+    _: &mut Workspace,
+    _: &StopRecording,
+    _: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    finish_recording(cx);
+}
+
+fn finish_recording(cx: &mut App) {
+    if !is_recording(cx) {
+        return;
+    }
+    let macros = cx.global_mut::<KeyboardMacros>();
+    if let Some(steps) = macros.recording.take() {
+        if !steps.is_empty() {
+            macros.last_macro = Some(steps);
+        }
+    }
+}
+
+fn play(
+    workspace: &mut Workspace,
+    action: &Play,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    // Playing a macro while recording one would record only part of what it does.
+    if is_recording(cx) {
+        return;
+    }
+    let steps = match macro_steps(action.name.as_deref(), cx) {
+        Ok(steps) => steps,
+        Err(error) => {
+            workspace.show_error(&error, cx);
+            return;
+        }
+    };
+
+    let times = action.times.unwrap_or(1);
+    let steps = (0..times)
+        .flat_map(|_| steps.iter().cloned().map(ReplayStep::Step))
+        .collect();
+    replay(steps, window, cx);
+}
+
+fn play_on_each_line(
+    workspace: &mut Workspace,
+    action: &PlayOnEachLine,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if is_recording(cx) {
+        return;
+    }
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let steps = match macro_steps(action.name.as_deref(), cx) {
+        Ok(steps) => steps,
+        Err(error) => {
+            workspace.show_error(&error, cx);
+            return;
+        }
+    };
+
+    let line_starts = editor.update(cx, |editor, cx| {
+        let buffer = editor.buffer().read(cx).snapshot(cx);
+        let mut rows = BTreeSet::new();
+        for selection in editor.selections.all::<Point>(cx) {
+            let mut end_row = selection.end.row;
+            if end_row > selection.start.row && selection.end.column == 0 {
+                end_row -= 1;
+            }
+            rows.extend(selection.start.row..=end_row);
+        }
+        // Anchors keep track of the lines as the macro edits the ones before them.
+        rows.into_iter()
+            .map(|row| buffer.anchor_before(Point::new(row, 0)))
+            .collect::<Vec<_>>()
+    });
+
+    let editor = editor.downgrade();
+    let steps = line_starts
+        .into_iter()
+        .flat_map(|line_start| {
+            Some(ReplayStep::MoveToLine(editor.clone(), line_start))
+                .into_iter()
+                .chain(steps.iter().cloned().map(ReplayStep::Step))
+        })
+        .collect();
+    replay(steps, window, cx);
+}
+
+fn save_last_macro(
+    workspace: &mut Workspace,
+    _: &SaveLastMacro,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    finish_recording(cx);
+    let steps = cx
+        .global::<KeyboardMacros>()
+        .last_macro
+        .as_deref()
+        .ok_or_else(|| anyhow!("No macro has been recorded"))
+        .and_then(|steps| saved_steps(steps, cx));
+    let steps = match steps {
+        Ok(steps) => steps,
+        Err(error) => {
+            workspace.show_error(&error, cx);
+            return;
+        }
+    };
+
+    let fs = workspace.app_state().fs.clone();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        SaveMacro::new(steps, fs, window, cx)
+    });
+}
+
+/// Returns the steps of the saved macro with the given name, or of the last recorded macro.
+fn macro_steps(name: Option<&str>, cx: &App) -> Result<Vec<Step>> {
+    let Some(name) = name else {
+        return cx
+            .global::<KeyboardMacros>()
+            .last_macro
+            .clone()
+            .ok_or_else(|| anyhow!("No macro has been recorded"));
+    };
+
+    let steps = KeyboardMacroSettings::get_global(cx)
+        .macros
+        .get(name)
+        .ok_or_else(|| anyhow!("No macro named \"{name}\" is saved"))?;
+    steps
+        .iter()
+        .map(|step| {
+            Ok(match step {
+                MacroStep::Text { text } => Step::Text(text.clone()),
+                MacroStep::Action(name) => Step::Action(cx.build_action(name, None)?),
+                MacroStep::ActionWithArguments(name, arguments) => {
+                    Step::Action(cx.build_action(name, Some(arguments.clone()))?)
+                }
+            })
+        })
+        .collect()
+}
+
+/// Converts recorded steps to the form they're saved in.
+fn saved_steps(steps: &[Step], cx: &App) -> Result<Vec<MacroStep>> {
+    steps
+        .iter()
+        .map(|step| match step {
+            Step::Text(text) => Ok(MacroStep::Text { text: text.clone() }),
+            Step::Action(action) => {
+                // Actions can't be serialized, so only those that are the same as the action built
+                // from their name alone can be saved.
+                let name = action.name();
+                cx.build_action(name, None)
+                    .ok()
+                    .filter(|built| built.partial_eq(&**action))
+                    .map(|_| MacroStep::Action(name.to_string()))
+                    .ok_or_else(|| {
+                        anyhow!("The macro can't be saved because it uses {name} with arguments")
+                    })
+            }
+        })
+        .collect()
+}
+
+/// Plays the steps before any that remain from a running replay, so that macros can play other
+/// macros.
+fn replay(steps: Vec<ReplayStep>, window: &mut Window, cx: &mut App) {
+    let macros = cx.global_mut::<KeyboardMacros>();
+    for step in steps.into_iter().rev() {
+        macros.replay_queue.push_front(step);
+    }
+    if macros.replayed_steps.is_none() {
+        macros.replayed_steps = Some(0);
+        window.defer(cx, replay_next_step);
+    }
+}
+
+/// Replays a single step, giving its effects a chance to settle before the next one runs.
+fn replay_next_step(window: &mut Window, cx: &mut App) {
+    let macros = cx.global_mut::<KeyboardMacros>();
+    let replayed_steps = macros.replayed_steps.get_or_insert(0);
+    let step = if *replayed_steps < MAX_REPLAYED_STEPS {
+        macros.replay_queue.pop_front()
+    } else {
+        log::error!("Aborting macro replay after {MAX_REPLAYED_STEPS} steps");
+        None
+    };
+    *replayed_steps += 1;
+    let Some(step) = step else {
+        macros.replay_queue.clear();
+        macros.replayed_steps = None;
+        return;
+    };
+
+    match step {
+        ReplayStep::Step(Step::Action(action)) => window.dispatch_action(action, cx),
+        ReplayStep::Step(Step::Text(text)) => {
+            window.dispatch_action(Box::new(HandleInput(text)), cx)
+        }
+        ReplayStep::MoveToLine(editor, line_start) => {
+            editor
+                .update(cx, |editor, cx| {
+                    editor.change_selections(None, window, cx, |selections| {
+                        selections.select_anchor_ranges([line_start..line_start])
+                    })
+                })
+                .ok();
+        }
+    }
+    window.defer(cx, replay_next_step);
+}
+
+#[cfg(test)]
+mod tests {
+    use editor::{actions::MoveDown, test::editor_test_context::EditorTestContext};
+    use gpui::{EntityInputHandler as _, KeyBinding, TestAppContext};
+    use project::Project;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    actions!(test, [InsertCompletion]);
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            init(cx);
+            cx.bind_keys([
+                KeyBinding::new("down", MoveDown, Some("Editor")),
+                KeyBinding::new("ctrl-space", InsertCompletion, Some("Editor")),
+            ]);
+        });
+    }
+
+    fn record(cx: &mut EditorTestContext, keystrokes: &str) -> Vec<MacroStep> {
+        cx.update(|_, cx| cx.global_mut::<KeyboardMacros>().recording = Some(Vec::new()));
+        cx.simulate_keystrokes(keystrokes);
+        cx.update(|_, cx| {
+            finish_recording(cx);
+            let steps = cx.global::<KeyboardMacros>().last_macro.as_deref().unwrap();
+            saved_steps(steps, cx).unwrap()
+        })
+    }
+
+    fn play_last_macro(cx: &mut EditorTestContext) {
+        cx.update(|window, cx| {
+            let steps = macro_steps(None, cx).unwrap();
+            replay(
+                steps.into_iter().map(ReplayStep::Step).collect(),
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇone\ntwo\nthree\n");
+
+        let steps = record(&mut cx, "x y down");
+        assert_eq!(
+            steps,
+            [
+                MacroStep::Text { text: "x".into() },
+                MacroStep::Text { text: "y".into() },
+                MacroStep::Action("editor::MoveDown".into()),
+            ]
+        );
+        cx.assert_editor_state("xyone\ntwˇo\nthree\n");
+
+        play_last_macro(&mut cx);
+        cx.assert_editor_state("xyone\ntwxyo\nthreˇe\n");
+    }
+
+    #[gpui::test]
+    async fn test_record_action_that_inserts_text(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        // Stands in for accepting a completion, which inserts text through the input handler.
+        cx.update_editor(|editor, _, cx| {
+            let editor_handle = cx.entity().downgrade();
+            editor
+                .register_action(move |_: &InsertCompletion, window, cx| {
+                    editor_handle
+                        .update(cx, |editor, cx| {
+                            editor.replace_text_in_range(None, "completion", window, cx)
+                        })
+                        .ok();
+                })
+                .detach();
+        });
+        cx.set_state("ˇ\n\n");
+
+        // The inserted text is recorded in place of the action, so it's only inserted once when
+        // replayed.
+        let steps = record(&mut cx, "ctrl-space down");
+        assert_eq!(
+            steps,
+            [
+                MacroStep::Text {
+                    text: "completion".into()
+                },
+                MacroStep::Action("editor::MoveDown".into()),
+            ]
+        );
+        cx.assert_editor_state("completion\nˇ\n");
+
+        play_last_macro(&mut cx);
+        cx.assert_editor_state("completion\ncompletionˇ");
+    }
+
+    #[test]
+    fn test_records_actions_in() {
+        let contexts = |sources: &[&str]| {
+            sources
+                .iter()
+                .map(|source| KeyContext::parse(source).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert!(records_actions_in(&contexts(&[
+            "Workspace",
+            "Pane",
+            "Editor mode = full"
+        ])));
+        assert!(!records_actions_in(&contexts(&[
+            "Workspace",
+            "Picker",
+            "Editor mode = single_line"
+        ])));
+        assert!(!records_actions_in(&contexts(&[
+            "Workspace",
+            "Editor mode = auto_height"
+        ])));
+    }
+
+    #[test]
+    fn test_deserialize_macro_steps() {
+        let steps: Vec<MacroStep> = serde_json::from_str(
+            r#"[
+                "editor::MoveDown",
+                ["editor::SelectNext", { "replace_newest": true }],
+                { "text": "hello" }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            steps,
+            [
+                MacroStep::Action("editor::MoveDown".into()),
+                MacroStep::ActionWithArguments(
+                    "editor::SelectNext".into(),
+                    serde_json::json!({ "replace_newest": true })
+                ),
+                MacroStep::Text {
+                    text: "hello".into()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The settings for keyboard macros.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct KeyboardMacroSettings {
+    /// Macros saved by name, which can be bound to keys with `keyboard_macros::Play`.
+    ///
+    /// Default: {}
+    pub macros: BTreeMap<String, Vec<MacroStep>>,
+}
+
+/// A step of a saved macro, written the same way as actions in keymaps.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum MacroStep {
+    /// Text typed into the focused editor, such as `{ "text": "hello" }`.
+    Text { text: String },
+    /// An action without arguments, such as `"editor::MoveDown"`.
+    Action(String),
+    /// An action with arguments, such as `["editor::SelectNext", { "replace_newest": true }]`.
+    ActionWithArguments(String, serde_json::Value),
+}
+
+impl Settings for KeyboardMacroSettings {
+    const KEY: Option<&'static str> = Some("keyboard_macros");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
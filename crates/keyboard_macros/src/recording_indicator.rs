@@ -0,0 +1,52 @@
+use gpui::{Context, IntoElement, Render, Window};
+use ui::{prelude::*, ButtonLike, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{is_recording, KeyboardMacros, StopRecording};
+
+/// Shows in the status bar that a macro is being recorded, and stops the recording when clicked.
+pub struct RecordingIndicator;
+
+impl RecordingIndicator {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<KeyboardMacros>(|_, cx| cx.notify())
+            .detach();
+        Self
+    }
+}
+
+impl Render for RecordingIndicator {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !is_recording(cx) {
+            return div().into_any_element();
+        }
+
+        ButtonLike::new("macro-recording")
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Circle)
+                            .size(IconSize::Small)
+                            .color(Color::Error),
+                    )
+                    .child(Label::new("Recording Macro").size(LabelSize::Small)),
+            )
+            .tooltip(Tooltip::for_action_title(
+                "Stop Recording Macro",
+                &StopRecording,
+            ))
+            .on_click(|_, window, cx| window.dispatch_action(Box::new(StopRecording), cx))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for RecordingIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        _: Option<&dyn ItemHandle>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fs::Fs;
+use gpui::{
+    div, prelude::*, App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render,
+    Styled, Subscription,
+};
+use settings::{update_settings_file, Settings as _};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::{KeyboardMacroSettings, MacroStep};
+
+/// Prompts for the name to save the last recorded macro under in the settings.
+pub struct SaveMacro {
+    name_editor: Entity<Editor>,
+    steps: Vec<MacroStep>,
+    fs: Arc<dyn Fs>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ModalView for SaveMacro {}
+
+impl Focusable for SaveMacro {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.name_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SaveMacro {}
+
+impl SaveMacro {
+    pub fn new(
+        steps: Vec<MacroStep>,
+        fs: Arc<dyn Fs>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let name_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Macro name", cx);
+            editor
+        });
+        let name_editor_change = cx.subscribe_in(&name_editor, window, Self::on_name_editor_event);
+
+        Self {
+            name_editor,
+            steps,
+            fs,
+            _subscriptions: vec![name_editor_change],
+        }
+    }
+
+    fn on_name_editor_event(
+        &mut self,
+        _: &Entity<Editor>,
+        event: &editor::EditorEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            editor::EditorEvent::Blurred => cx.emit(DismissEvent),
+            editor::EditorEvent::BufferEdited { .. } => cx.notify(),
+            _ => {}
+        }
+    }
+
+    fn name(&self, cx: &App) -> String {
+        self.name_editor.read(cx).text(cx).trim().to_string()
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let name = self.name(cx);
+        if name.is_empty() {
+            return;
+        }
+        let steps = std::mem::take(&mut self.steps);
+        update_settings_file::<KeyboardMacroSettings>(self.fs.clone(), cx, move |settings, _| {
+            settings.macros.insert(name, steps);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for SaveMacro {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let name = self.name(cx);
+        let help_text = if name.is_empty() {
+            format!("Save the last macro ({} steps)", self.steps.len())
+        } else if KeyboardMacroSettings::get_global(cx)
+            .macros
+            .contains_key(&name)
+        {
+            format!("Replace the saved macro \"{name}\"")
+        } else {
+            format!("Save the last macro as \"{name}\"")
+        };
+
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("SaveMacro")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.name_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .child(Label::new(help_text).color(Color::Muted)),
+            )
+    }
+}
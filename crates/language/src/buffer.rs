@@ -121,6 +121,12 @@ pub struct Buffer {
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
+    /// Whether the file is too large for syntax highlighting, language servers and other
+    /// features that process the whole buffer.
+    large_file: bool,
+    /// Whether the file wasn't valid in its encoding when it was last read, so that malformed
+    /// sequences in it were replaced with U+FFFD.
+    lossy: bool,
     has_conflict: bool,
     /// Memoize calls to has_changes_since(saved_version).
     /// The contents of a cell are (self.version, has_changes) at the time of a last call.
@@ -855,6 +861,11 @@ impl Buffer {
             rpc::proto::LineEnding::from_i32(message.line_ending)
                 .ok_or_else(|| anyhow!("missing line_ending"))?,
         ));
+        if let Some(encoding) = message.encoding.and_then(proto::deserialize_encoding) {
+            this.text.set_encoding(encoding);
+        }
+        this.large_file = message.large_file;
+        this.lossy = message.lossy;
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
         Ok(this)
@@ -867,6 +878,9 @@ impl Buffer {
             file: self.file.as_ref().map(|f| f.to_proto(cx)),
             base_text: self.base_text().to_string(),
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
+            encoding: Some(proto::serialize_encoding(self.encoding())),
+            large_file: self.large_file,
+            lossy: self.lossy,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
         }
@@ -938,6 +952,29 @@ impl Buffer {
         self.capability == Capability::ReadOnly
     }
 
+    /// Whether this buffer was opened in large-file mode, with syntax highlighting, language
+    /// servers and inlay hints disabled.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
+    /// Marks the buffer as opened in large-file mode.
+    pub fn set_large_file(&mut self, large_file: bool) {
+        self.large_file = large_file;
+    }
+
+    /// Whether the buffer's file wasn't valid in its encoding when it was read, so that malformed
+    /// sequences in it were replaced with U+FFFD. Saving such a buffer over its file is refused
+    /// until it's reopened with an encoding that decodes the file cleanly.
+    pub fn is_lossy(&self) -> bool {
+        self.lossy
+    }
+
+    /// Marks the buffer's text as having been decoded lossily from its file.
+    pub fn set_lossy(&mut self, lossy: bool) {
+        self.lossy = lossy;
+    }
+
     /// Builds a [`Buffer`] with the given underlying [`TextBuffer`], diff base, [`File`] and [`Capability`].
     pub fn build(buffer: TextBuffer, file: Option<Arc<dyn File>>, capability: Capability) -> Self {
         let saved_mtime = file.as_ref().and_then(|file| file.disk_state().mtime());
@@ -955,6 +992,8 @@ impl Buffer {
             branch_state: None,
             file,
             capability,
+            large_file: false,
+            lossy: false,
             syntax_map,
             parsing_in_background: false,
             non_text_state_update_count: 0,
@@ -1267,6 +1306,12 @@ impl Buffer {
         cx.emit(BufferEvent::CapabilityChanged)
     }
 
+    /// Sets the encoding that the buffer's file is written with when it is next saved.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut Context<Self>) {
+        self.text.set_encoding(encoding);
+        cx.notify();
+    }
+
     /// This method is called to signal that the buffer has been saved.
     pub fn did_save(
         &mut self,
@@ -1278,6 +1323,7 @@ impl Buffer {
         self.has_unsaved_edits
             .set((self.saved_version().clone(), false));
         self.has_conflict = false;
+        self.lossy = false;
         self.saved_mtime = mtime;
         cx.emit(BufferEvent::Saved);
         cx.notify();
@@ -1291,18 +1337,33 @@ impl Buffer {
 
     /// Reloads the contents of the buffer from disk.
     pub fn reload(&mut self, cx: &Context<Self>) -> oneshot::Receiver<Option<Transaction>> {
+        self.reload_with_encoding(None, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given encoding.
+    /// Without one, the file's byte order mark or else the buffer's current encoding is used.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Option<Encoding>,
+        cx: &Context<Self>,
+    ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
+        let prev_encoding = self.encoding();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
-            let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
+            let Some((new_mtime, new_bytes)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.disk_state().mtime(), file.load(cx)))
+                Some((file.disk_state().mtime(), file.load_bytes(cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let new_bytes = new_bytes.await?;
+            let encoding = encoding
+                .or_else(|| Encoding::from_bom(&new_bytes))
+                .unwrap_or(prev_encoding);
+            let (new_text, lossy) = encoding.decode(new_bytes);
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -1312,6 +1373,8 @@ impl Buffer {
                     this.apply_diff(diff, cx);
                     tx.send(this.finalize_last_transaction().cloned()).ok();
                     this.has_conflict = false;
+                    this.lossy = lossy;
+                    this.text.set_encoding(encoding);
                     this.did_reload(this.version(), this.line_ending(), new_mtime, cx);
                 } else {
                     if !diff.edits.is_empty()
@@ -1365,7 +1428,13 @@ impl Buffer {
             if old_state != new_state {
                 file_changed = true;
                 if !was_dirty && matches!(new_state, DiskState::Present { .. }) {
-                    cx.emit(BufferEvent::ReloadNeeded)
+                    // Reloading a large file would read and diff all of it again, so it's marked
+                    // as conflicting with the file on disk instead, until it's reopened.
+                    if self.large_file {
+                        self.has_conflict = true;
+                    } else {
+                        cx.emit(BufferEvent::ReloadNeeded)
+                    }
                 }
             }
         } else {
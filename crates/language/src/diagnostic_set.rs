@@ -1,7 +1,7 @@
-use crate::{range_to_lsp, Diagnostic};
+use crate::{range_to_lsp_encoded, Diagnostic};
 use anyhow::Result;
 use collections::HashMap;
-use lsp::LanguageServerId;
+use lsp::{LanguageServerId, PositionEncoding};
 use serde::Serialize;
 use std::{
     cmp::{Ordering, Reverse},
@@ -9,7 +9,7 @@ use std::{
     ops::Range,
 };
 use sum_tree::{self, Bias, SumTree};
-use text::{Anchor, FromAnchor, PointUtf16, ToOffset};
+use text::{Anchor, FromAnchor, PointUtf16, Rope, ToOffset};
 
 /// A set of diagnostics associated with a given buffer, provided
 /// by a single language server.
@@ -70,8 +70,12 @@ pub struct Summary {
 impl DiagnosticEntry<PointUtf16> {
     /// Returns a raw LSP diagnostic used to provide diagnostic context to LSP
     /// codeAction request
-    pub fn to_lsp_diagnostic_stub(&self) -> Result<lsp::Diagnostic> {
-        let range = range_to_lsp(self.range.clone())?;
+    pub fn to_lsp_diagnostic_stub(
+        &self,
+        text: &Rope,
+        encoding: PositionEncoding,
+    ) -> Result<lsp::Diagnostic> {
+        let range = range_to_lsp_encoded(self.range.clone(), text, encoding)?;
 
         Ok(lsp::Diagnostic {
             range,
@@ -32,7 +32,10 @@ use gpui::{App, AsyncApp, Entity, SharedString, Task};
 pub use highlight_map::HighlightMap;
 use http_client::HttpClient;
 pub use language_registry::{LanguageName, LoadedLanguage};
-use lsp::{CodeActionKind, InitializeParams, LanguageServerBinary, LanguageServerBinaryOptions};
+use lsp::{
+    CodeActionKind, InitializeParams, LanguageServerBinary, LanguageServerBinaryOptions,
+    PositionEncoding,
+};
 use parking_lot::Mutex;
 use regex::Regex;
 use schemars::{
@@ -63,7 +66,9 @@ use std::{num::NonZeroU32, sync::OnceLock};
 use syntax_map::{QueryCursorHandle, SyntaxSnapshot};
 use task::RunnableTag;
 pub use task_context::{ContextProvider, RunnableRange};
-pub use text_diff::{line_diff, text_diff, text_diff_with_options, unified_diff, DiffOptions};
+pub use text_diff::{
+    line_diff, text_diff, text_diff_with_options, unified_diff, word_diff_ranges, DiffOptions,
+};
 use theme::SyntaxTheme;
 pub use toolchain::{LanguageToolchainStore, Toolchain, ToolchainList, ToolchainLister};
 use tree_sitter::{self, wasmtime, Query, QueryCursor, WasmStore};
@@ -79,7 +84,7 @@ pub use language_registry::{
 pub use lsp::{LanguageServerId, LanguageServerName};
 pub use outline::*;
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer, ToTreeSitterPoint, TreeSitterOptions};
-pub use text::{AnchorRangeExt, LineEnding};
+pub use text::{AnchorRangeExt, Encoding, LineEnding};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
 
 /// Initializes the `language` crate.
@@ -2036,6 +2041,75 @@ pub fn range_from_lsp(range: lsp::Range) -> Range<Unclipped<PointUtf16>> {
     start..end
 }
 
+/// Converts a point of the given text to an LSP position, whose column is measured in the
+/// position encoding negotiated with the language server.
+pub fn point_to_lsp_encoded(
+    point: PointUtf16,
+    text: &Rope,
+    encoding: PositionEncoding,
+) -> lsp::Position {
+    match encoding {
+        PositionEncoding::Utf16 => point_to_lsp(point),
+        PositionEncoding::Utf8 => {
+            let point = text.unclipped_point_utf16_to_point(Unclipped(point));
+            lsp::Position::new(point.row, point.column)
+        }
+        PositionEncoding::Utf32 => {
+            let point = text.unclipped_point_utf16_to_point(Unclipped(point));
+            lsp::Position::new(point.row, text.point_to_char_column(point))
+        }
+    }
+}
+
+/// Converts an LSP position, whose column is measured in the position encoding negotiated with
+/// the language server, to a point of the given text.
+pub fn point_from_lsp_encoded(
+    point: lsp::Position,
+    text: &Rope,
+    encoding: PositionEncoding,
+) -> Unclipped<PointUtf16> {
+    match encoding {
+        PositionEncoding::Utf16 => point_from_lsp(point),
+        PositionEncoding::Utf8 => {
+            let point = text.clip_point(Point::new(point.line, point.character), Bias::Left);
+            Unclipped(text.point_to_point_utf16(point))
+        }
+        PositionEncoding::Utf32 => {
+            let point = text.char_column_to_point(point.line, point.character);
+            Unclipped(text.point_to_point_utf16(point))
+        }
+    }
+}
+
+pub fn range_to_lsp_encoded(
+    range: Range<PointUtf16>,
+    text: &Rope,
+    encoding: PositionEncoding,
+) -> Result<lsp::Range> {
+    if range.start > range.end {
+        // Report the inverted range.
+        return range_to_lsp(range);
+    }
+    Ok(lsp::Range {
+        start: point_to_lsp_encoded(range.start, text, encoding),
+        end: point_to_lsp_encoded(range.end, text, encoding),
+    })
+}
+
+pub fn range_from_lsp_encoded(
+    range: lsp::Range,
+    text: &Rope,
+    encoding: PositionEncoding,
+) -> Range<Unclipped<PointUtf16>> {
+    let mut start = point_from_lsp_encoded(range.start, text, encoding);
+    let mut end = point_from_lsp_encoded(range.end, text, encoding);
+    if start > end {
+        log::warn!("range_from_lsp_encoded called with inverted range {start:?}-{end:?}");
+        mem::swap(&mut start, &mut end);
+    }
+    start..end
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2105,4 +2179,33 @@ mod tests {
         // Loading an unknown language returns an error.
         assert!(languages.language_for_name("Unknown").await.is_err());
     }
+
+    #[test]
+    fn test_lsp_position_encodings() {
+        let text = Rope::from("a🧘b\n𠀀c");
+        // The point after "b" and after "c", in each encoding.
+        let after_b = PointUtf16::new(0, 4);
+        let after_c = PointUtf16::new(1, 3);
+        for (encoding, lsp_after_b, lsp_after_c) in [
+            (PositionEncoding::Utf8, 6, 5),
+            (PositionEncoding::Utf16, 4, 3),
+            (PositionEncoding::Utf32, 3, 2),
+        ] {
+            assert_eq!(
+                point_to_lsp_encoded(after_b, &text, encoding),
+                lsp::Position::new(0, lsp_after_b)
+            );
+            assert_eq!(
+                point_from_lsp_encoded(lsp::Position::new(0, lsp_after_b), &text, encoding),
+                Unclipped(after_b)
+            );
+            assert_eq!(
+                range_to_lsp_encoded(after_b..after_c, &text, encoding).unwrap(),
+                lsp::Range::new(
+                    lsp::Position::new(0, lsp_after_b),
+                    lsp::Position::new(1, lsp_after_c)
+                )
+            );
+        }
+    }
 }
@@ -149,6 +149,14 @@ pub struct LanguageSettings {
     /// Whether to display inline and alongside documentation for items in the
     /// completions menu.
     pub show_completion_documentation: bool,
+    /// Whether to highlight the buffer using semantic tokens provided by
+    /// language servers, on top of the tree-sitter highlights.
+    pub semantic_tokens: bool,
+    /// Where to get the foldable ranges of a buffer from.
+    pub fold_source: FoldSource,
+    /// Whether to show a color swatch before the color literals reported by
+    /// language servers.
+    pub document_colors: bool,
 }
 
 impl LanguageSettings {
@@ -448,6 +456,34 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub show_completion_documentation: Option<bool>,
+    /// Whether to highlight the buffer using semantic tokens provided by
+    /// language servers, on top of the tree-sitter highlights.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Where to get the foldable ranges of a buffer from.
+    ///
+    /// Default: indentation
+    pub fold_source: Option<FoldSource>,
+    /// Whether to show a color swatch before the color literals reported by
+    /// language servers.
+    ///
+    /// Default: true
+    pub document_colors: Option<bool>,
+}
+
+/// Where the editor gets the foldable ranges of a buffer from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FoldSource {
+    /// Use the folding ranges provided by language servers, falling back to
+    /// indentation when no server provides them.
+    LanguageServer,
+    /// Fold the contents of multi-line bracket pairs, as found by tree-sitter.
+    TreeSitter,
+    /// Fold regions of increased indentation.
+    #[default]
+    Indentation,
 }
 
 /// The behavior of `editor::Rewrap`.
@@ -1327,6 +1363,9 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.show_completion_documentation,
         src.show_completion_documentation,
     );
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.fold_source, src.fold_source);
+    merge(&mut settings.document_colors, src.document_colors);
 }
 
 /// Allows to enable/disable formatting with Prettier
@@ -27,6 +27,20 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`text::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(message: proto::Encoding) -> Option<text::Encoding> {
+    let encoding = text::Encoding::for_label(&message.name)?;
+    Some(encoding.with_bom(message.has_bom))
+}
+
+/// Serializes a [`text::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: text::Encoding) -> proto::Encoding {
+    proto::Encoding {
+        name: encoding.name().to_string(),
+        has_bom: encoding.has_bom(),
+    }
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
@@ -120,6 +120,44 @@ pub fn text_diff_with_options(
     edits
 }
 
+/// Computes the word-level changes between two small texts, such as the two sides of a
+/// modified diff hunk.
+///
+/// Returns the changed byte ranges within the old and new text respectively, or `None` if
+/// either text is empty or exceeds the size limits in `options`.
+pub fn word_diff_ranges(
+    old_text: &str,
+    new_text: &str,
+    options: DiffOptions,
+) -> Option<(Vec<Range<usize>>, Vec<Range<usize>>)> {
+    let old_row_count = old_text.matches('\n').count() as u32;
+    let new_row_count = new_text.matches('\n').count() as u32;
+    if !should_perform_word_diff_within_hunk(
+        &(0..old_row_count),
+        &(0..old_text.len()),
+        &(0..new_row_count),
+        &(0..new_text.len()),
+        &options,
+    ) {
+        return None;
+    }
+
+    let mut old_ranges = Vec::new();
+    let mut new_ranges = Vec::new();
+    let mut input = InternedInput::default();
+    input.update_before(tokenize(old_text, options.language_scope.clone()));
+    input.update_after(tokenize(new_text, options.language_scope));
+    diff_internal(&input, |old_byte_range, new_byte_range, _, _| {
+        if !old_byte_range.is_empty() {
+            old_ranges.push(old_byte_range);
+        }
+        if !new_byte_range.is_empty() {
+            new_ranges.push(new_byte_range);
+        }
+    });
+    Some((old_ranges, new_ranges))
+}
+
 fn should_perform_word_diff_within_hunk(
     old_row_range: &Range<u32>,
     old_byte_range: &Range<usize>,
@@ -271,4 +309,27 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_word_diff_ranges() {
+        let old_text = "let x = 1;\n";
+        let new_text = "let y = 12;\n";
+        assert_eq!(
+            word_diff_ranges(old_text, new_text, DiffOptions::default()),
+            Some((vec![4..5, 8..9], vec![4..5, 8..10]))
+        );
+
+        assert_eq!(word_diff_ranges("", "one\n", DiffOptions::default()), None);
+        assert_eq!(
+            word_diff_ranges(
+                "one\ntwo\n",
+                "one\nTWO\n",
+                DiffOptions {
+                    max_word_diff_line_count: 1,
+                    ..Default::default()
+                }
+            ),
+            None
+        );
+    }
 }
@@ -290,6 +290,32 @@ impl<F: Future> LspRequestFuture<F::Output> for LspRequest<F> {
     }
 }
 
+/// The unit in which the columns of positions exchanged with a language server are measured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PositionEncoding {
+    /// Columns count UTF-8 code units, i.e. bytes.
+    Utf8,
+    /// Columns count UTF-16 code units, which servers assume when no encoding was negotiated.
+    #[default]
+    Utf16,
+    /// Columns count Unicode code points.
+    Utf32,
+}
+
+impl PositionEncoding {
+    pub fn from_kind(kind: &PositionEncodingKind) -> Option<Self> {
+        if *kind == PositionEncodingKind::UTF8 {
+            Some(Self::Utf8)
+        } else if *kind == PositionEncodingKind::UTF16 {
+            Some(Self::Utf16)
+        } else if *kind == PositionEncodingKind::UTF32 {
+            Some(Self::Utf32)
+        } else {
+            None
+        }
+    }
+}
+
 /// Combined capabilities of the server and the adapter.
 #[derive(Debug)]
 pub struct AdapterServerCapabilities {
@@ -625,7 +651,13 @@ impl LanguageServer {
             initialization_options: None,
             capabilities: ClientCapabilities {
                 general: Some(GeneralClientCapabilities {
-                    position_encodings: Some(vec![PositionEncodingKind::UTF16]),
+                    // Listed in order of preference: UTF-8 columns are byte offsets, which
+                    // are the cheapest to convert.
+                    position_encodings: Some(vec![
+                        PositionEncodingKind::UTF8,
+                        PositionEncodingKind::UTF16,
+                        PositionEncodingKind::UTF32,
+                    ]),
                     ..Default::default()
                 }),
                 workspace: Some(WorkspaceClientCapabilities {
@@ -645,8 +677,14 @@ impl LanguageServer {
                     inlay_hint: Some(InlayHintWorkspaceClientCapabilities {
                         refresh_support: Some(true),
                     }),
+                    code_lens: Some(CodeLensWorkspaceClientCapabilities {
+                        refresh_support: Some(true),
+                    }),
+                    semantic_tokens: Some(SemanticTokensWorkspaceClientCapabilities {
+                        refresh_support: Some(true),
+                    }),
                     diagnostic: Some(DiagnosticWorkspaceClientCapabilities {
-                        refresh_support: None,
+                        refresh_support: Some(true),
                     }),
                     workspace_edit: Some(WorkspaceEditClientCapabilities {
                         resource_operations: Some(vec![
@@ -746,6 +784,87 @@ impl LanguageServer {
                         }),
                         dynamic_registration: Some(false),
                     }),
+                    call_hierarchy: Some(DynamicRegistrationClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    type_hierarchy: Some(DynamicRegistrationClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    code_lens: Some(CodeLensClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    document_link: Some(DocumentLinkClientCapabilities {
+                        dynamic_registration: Some(false),
+                        tooltip_support: Some(true),
+                    }),
+                    color_provider: Some(DocumentColorClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    semantic_tokens: Some(SemanticTokensClientCapabilities {
+                        dynamic_registration: Some(false),
+                        requests: SemanticTokensClientCapabilitiesRequests {
+                            range: Some(false),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            SemanticTokenType::NAMESPACE,
+                            SemanticTokenType::TYPE,
+                            SemanticTokenType::CLASS,
+                            SemanticTokenType::ENUM,
+                            SemanticTokenType::INTERFACE,
+                            SemanticTokenType::STRUCT,
+                            SemanticTokenType::TYPE_PARAMETER,
+                            SemanticTokenType::PARAMETER,
+                            SemanticTokenType::VARIABLE,
+                            SemanticTokenType::PROPERTY,
+                            SemanticTokenType::ENUM_MEMBER,
+                            SemanticTokenType::EVENT,
+                            SemanticTokenType::FUNCTION,
+                            SemanticTokenType::METHOD,
+                            SemanticTokenType::MACRO,
+                            SemanticTokenType::KEYWORD,
+                            SemanticTokenType::MODIFIER,
+                            SemanticTokenType::COMMENT,
+                            SemanticTokenType::STRING,
+                            SemanticTokenType::NUMBER,
+                            SemanticTokenType::REGEXP,
+                            SemanticTokenType::OPERATOR,
+                            SemanticTokenType::DECORATOR,
+                        ],
+                        token_modifiers: vec![
+                            SemanticTokenModifier::DECLARATION,
+                            SemanticTokenModifier::DEFINITION,
+                            SemanticTokenModifier::READONLY,
+                            SemanticTokenModifier::STATIC,
+                            SemanticTokenModifier::DEPRECATED,
+                            SemanticTokenModifier::ABSTRACT,
+                            SemanticTokenModifier::ASYNC,
+                            SemanticTokenModifier::MODIFICATION,
+                            SemanticTokenModifier::DOCUMENTATION,
+                            SemanticTokenModifier::DEFAULT_LIBRARY,
+                        ],
+                        formats: vec![TokenFormat::RELATIVE],
+                        overlapping_token_support: Some(false),
+                        multiline_token_support: Some(false),
+                        server_cancel_support: Some(false),
+                        augments_syntax_tokens: Some(true),
+                    }),
+                    folding_range: Some(FoldingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                        line_folding_only: Some(true),
+                        folding_range_kind: Some(FoldingRangeKindCapability {
+                            value_set: Some(vec![
+                                FoldingRangeKind::Comment,
+                                FoldingRangeKind::Imports,
+                                FoldingRangeKind::Region,
+                            ]),
+                        }),
+                        ..Default::default()
+                    }),
+                    diagnostic: Some(DiagnosticClientCapabilities {
+                        dynamic_registration: Some(false),
+                        related_document_support: Some(true),
+                    }),
                     publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
                         related_information: Some(true),
                         ..Default::default()
@@ -1043,6 +1162,17 @@ impl LanguageServer {
         self.capabilities.read().clone()
     }
 
+    /// Get the position encoding the running language server picked, defaulting to UTF-16 for
+    /// servers that didn't pick one.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.capabilities
+            .read()
+            .position_encoding
+            .as_ref()
+            .and_then(PositionEncoding::from_kind)
+            .unwrap_or_default()
+    }
+
     /// Get the reported capabilities of the running language server and
     /// what we know on the client/adapter-side of its capabilities.
     pub fn adapter_server_capabilities(&self) -> AdapterServerCapabilities {
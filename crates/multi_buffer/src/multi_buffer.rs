@@ -132,6 +132,10 @@ pub struct MultiBufferDiffHunk {
     /// Whether or not this hunk also appears in the 'secondary diff'.
     pub secondary_status: DiffHunkSecondaryStatus,
     pub secondary_diff_base_byte_range: Option<Range<usize>>,
+    /// The spans within the buffer range that changed, for hunks diffed word by word.
+    pub buffer_word_diffs: Arc<[Range<text::Anchor>]>,
+    /// The spans within the diff base byte range that changed, for hunks diffed word by word.
+    pub base_word_diffs: Arc<[Range<usize>]>,
 }
 
 impl MultiBufferDiffHunk {
@@ -3507,6 +3511,8 @@ impl MultiBufferSnapshot {
                 diff_base_byte_range: hunk.diff_base_byte_range.clone(),
                 secondary_status: hunk.secondary_status,
                 secondary_diff_base_byte_range: hunk.secondary_diff_base_byte_range,
+                buffer_word_diffs: hunk.buffer_word_diffs,
+                base_word_diffs: hunk.base_word_diffs,
             })
         })
     }
@@ -3877,6 +3883,8 @@ impl MultiBufferSnapshot {
                         diff_base_byte_range: hunk.diff_base_byte_range.clone(),
                         secondary_status: hunk.secondary_status,
                         secondary_diff_base_byte_range: hunk.secondary_diff_base_byte_range,
+                        buffer_word_diffs: hunk.buffer_word_diffs,
+                        base_word_diffs: hunk.base_word_diffs,
                     });
                 }
             }
@@ -18,10 +18,11 @@ use gpui::{
 use http_client::Url;
 use language::{
     proto::{
-        deserialize_line_ending, deserialize_version, serialize_line_ending, serialize_version,
-        split_operations,
+        deserialize_encoding, deserialize_line_ending, deserialize_version, serialize_encoding,
+        serialize_line_ending, serialize_version, split_operations,
     },
-    Buffer, BufferEvent, Capability, DiskState, File as _, Language, LanguageRegistry, Operation,
+    Buffer, BufferEvent, Capability, DiskState, Encoding, File as _, Language, LanguageRegistry,
+    Operation,
 };
 use rpc::{
     proto::{self, ToProto},
@@ -40,7 +41,10 @@ use std::{
 };
 use text::BufferId;
 use util::{debug_panic, maybe, ResultExt as _, TryFutureExt};
-use worktree::{File, PathChange, ProjectEntryId, UpdatedGitRepositoriesSet, Worktree, WorktreeId};
+use worktree::{
+    File, LoadedLargeFile, PathChange, ProjectEntryId, UpdatedGitRepositoriesSet, Worktree,
+    WorktreeId,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum DiffKind {
@@ -415,6 +419,7 @@ impl RemoteBufferStore {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id().into();
         let version = buffer.version();
+        let encoding = buffer.encoding();
         let rpc = self.upstream_client.clone();
         let project_id = self.project_id;
         cx.spawn(move |_, mut cx| async move {
@@ -424,6 +429,7 @@ impl RemoteBufferStore {
                     buffer_id,
                     new_path,
                     version: serialize_version(&version),
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .await?;
             let version = deserialize_version(&response.version);
@@ -624,6 +630,7 @@ impl RemoteBufferStore {
     fn reload_buffers(
         &self,
         buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
         push_to_history: bool,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<ProjectTransaction>> {
@@ -633,6 +640,7 @@ impl RemoteBufferStore {
                 .iter()
                 .map(|buffer| buffer.read(cx).remote_id().to_proto())
                 .collect(),
+            encoding: encoding.map(serialize_encoding),
         });
 
         cx.spawn(|this, mut cx| async move {
@@ -696,8 +704,17 @@ impl LocalBufferStore {
 
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = buffer.encoding();
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
+        // Saving a lossily decoded buffer over its file would replace the bytes that couldn't be
+        // decoded with U+FFFD, so it has to be reopened with the right encoding first.
+        if buffer.is_lossy() && !has_changed_file {
+            return Task::ready(Err(anyhow!(
+                "{path:?} contains bytes that aren't valid {}; reopen it with the right encoding before saving",
+                encoding.name()
+            )));
+        }
         if buffer
             .file()
             .is_some_and(|file| file.disk_state() == DiskState::New)
@@ -706,7 +723,7 @@ impl LocalBufferStore {
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            worktree.write_file(path.as_ref(), text, line_ending, encoding, cx)
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -729,6 +746,7 @@ impl LocalBufferStore {
                             buffer_id: buffer_id.to_proto(),
                             version: serialize_version(&version),
                             mtime: mtime.map(|time| time.into()),
+                            encoding: Some(serialize_encoding(encoding)),
                         })
                         .log_err();
                 }
@@ -1113,25 +1131,75 @@ impl LocalBufferStore {
         self.save_local_buffer(buffer, worktree, path.path.clone(), true, cx)
     }
 
-    fn open_buffer(
-        &self,
+    fn load_buffer(
+        worktree: &Worktree,
         path: Arc<Path>,
-        worktree: Entity<Worktree>,
-        cx: &mut Context<BufferStore>,
+        is_large_file: bool,
+        cx: &mut Context<Worktree>,
     ) -> Task<Result<Entity<Buffer>>> {
-        let load_buffer = worktree.update(cx, |worktree, cx| {
-            let load_file = worktree.load_file(path.as_ref(), cx);
+        if is_large_file {
+            let load_file = worktree.load_large_file(path.as_ref(), cx);
             let reservation = cx.reserve_entity();
             let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
-            cx.spawn(move |_, mut cx| async move {
-                let loaded = load_file.await?;
-                let text_buffer = cx
-                    .background_spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
+            return cx.spawn(move |_, mut cx| async move {
+                let LoadedLargeFile {
+                    file,
+                    text,
+                    line_ending,
+                    encoding,
+                    lossy,
+                } = load_file.await?;
+                let mut text_buffer = cx
+                    .background_spawn(async move {
+                        text::Buffer::new_normalized(0, buffer_id, line_ending, text)
+                    })
                     .await;
+                text_buffer.set_encoding(encoding);
                 cx.insert_entity(reservation, |_| {
-                    Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite)
+                    let mut buffer = Buffer::build(text_buffer, Some(file), Capability::ReadOnly);
+                    buffer.set_large_file(true);
+                    buffer.set_lossy(lossy);
+                    buffer
                 })
+            });
+        }
+
+        let load_file = worktree.load_file(path.as_ref(), cx);
+        let reservation = cx.reserve_entity();
+        let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+        cx.spawn(move |_, mut cx| async move {
+            let loaded = load_file.await?;
+            let mut text_buffer = cx
+                .background_spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
+                .await;
+            text_buffer.set_encoding(loaded.encoding);
+            cx.insert_entity(reservation, |_| {
+                let mut buffer =
+                    Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite);
+                buffer.set_lossy(loaded.lossy);
+                buffer
             })
+        })
+    }
+
+    fn open_buffer(
+        &self,
+        path: Arc<Path>,
+        worktree: Entity<Worktree>,
+        cx: &mut Context<BufferStore>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        let is_large_file = worktree.read(cx).is_large_file(&path, cx);
+        let load_buffer = cx.spawn({
+            let worktree = worktree.clone();
+            let path = path.clone();
+            move |_, mut cx| async move {
+                let is_large_file = is_large_file.await;
+                worktree
+                    .update(&mut cx, |worktree, cx| {
+                        Self::load_buffer(worktree, path, is_large_file, cx)
+                    })?
+                    .await
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -1195,6 +1263,7 @@ impl LocalBufferStore {
     fn reload_buffers(
         &self,
         buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
         push_to_history: bool,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<ProjectTransaction>> {
@@ -1202,7 +1271,9 @@ impl LocalBufferStore {
             let mut project_transaction = ProjectTransaction::default();
             for buffer in buffers {
                 let transaction = buffer
-                    .update(&mut cx, |buffer, cx| buffer.reload(cx))?
+                    .update(&mut cx, |buffer, cx| {
+                        buffer.reload_with_encoding(encoding, cx)
+                    })?
                     .await?;
                 buffer.update(&mut cx, |buffer, cx| {
                     if let Some(transaction) = transaction {
@@ -1884,7 +1955,7 @@ impl BufferStore {
             let buffer = handle.read(cx);
             if let Some(entry_id) = buffer.entry_id(cx) {
                 open_buffers.insert(entry_id);
-            } else {
+            } else if query.scope().contains_unnamed(buffer) {
                 limit = limit.saturating_sub(1);
                 unnamed_buffers.push(handle)
             };
@@ -1970,6 +2041,7 @@ impl BufferStore {
                         version: serialize_version(&buffer.version()),
                         mtime: buffer.saved_mtime().map(|t| t.into()),
                         line_ending: serialize_line_ending(buffer.line_ending()) as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
             }
@@ -2096,6 +2168,7 @@ impl BufferStore {
                         mtime: buffer.saved_mtime().map(|time| time.into()),
                         line_ending: language::proto::serialize_line_ending(buffer.line_ending())
                             as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
 
@@ -2209,7 +2282,12 @@ impl BufferStore {
                 buffer.wait_for_version(deserialize_version(&envelope.payload.version))
             })?
             .await?;
-        let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+        let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+            if let Some(encoding) = envelope.payload.encoding.and_then(deserialize_encoding) {
+                buffer.set_encoding(encoding, cx);
+            }
+            buffer.remote_id()
+        })?;
 
         if let Some(new_path) = envelope.payload.new_path {
             let new_path = ProjectPath::from_proto(new_path);
@@ -2227,6 +2305,7 @@ impl BufferStore {
             buffer_id: buffer_id.into(),
             version: serialize_version(buffer.saved_version()),
             mtime: buffer.saved_mtime().map(|time| time.into()),
+            encoding: Some(serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -2262,9 +2341,17 @@ impl BufferStore {
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let version = deserialize_version(&envelope.payload.version);
         let mtime = envelope.payload.mtime.clone().map(|time| time.into());
+        let encoding = envelope
+            .payload
+            .encoding
+            .clone()
+            .and_then(deserialize_encoding);
         this.update(&mut cx, move |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_save(version, mtime, cx);
                 });
             }
@@ -2276,6 +2363,7 @@ impl BufferStore {
                         buffer_id: buffer_id.into(),
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -2294,9 +2382,17 @@ impl BufferStore {
             proto::LineEnding::from_i32(envelope.payload.line_ending)
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
+        let encoding = envelope
+            .payload
+            .encoding
+            .clone()
+            .and_then(deserialize_encoding);
         this.update(&mut cx, |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_reload(version, line_ending, mtime, cx);
                 });
             }
@@ -2309,6 +2405,7 @@ impl BufferStore {
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
                         line_ending: envelope.payload.line_ending,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -2475,13 +2572,29 @@ impl BufferStore {
         buffers: HashSet<Entity<Buffer>>,
         push_to_history: bool,
         cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.reload_buffers_with_encoding(buffers, None, push_to_history, cx)
+    }
+
+    /// Reloads buffers from disk, decoding their files with the given encoding rather than the
+    /// one they were opened with.
+    pub fn reload_buffers_with_encoding(
+        &self,
+        buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
+        push_to_history: bool,
+        cx: &mut Context<Self>,
     ) -> Task<Result<ProjectTransaction>> {
         if buffers.is_empty() {
             return Task::ready(Ok(ProjectTransaction::default()));
         }
         match &self.state {
-            BufferStoreState::Local(this) => this.reload_buffers(buffers, push_to_history, cx),
-            BufferStoreState::Remote(this) => this.reload_buffers(buffers, push_to_history, cx),
+            BufferStoreState::Local(this) => {
+                this.reload_buffers(buffers, encoding, push_to_history, cx)
+            }
+            BufferStoreState::Remote(this) => {
+                this.reload_buffers(buffers, encoding, push_to_history, cx)
+            }
         }
     }
 
@@ -2491,13 +2604,14 @@ impl BufferStore {
         mut cx: AsyncApp,
     ) -> Result<proto::ReloadBuffersResponse> {
         let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let encoding = envelope.payload.encoding.and_then(deserialize_encoding);
         let reload = this.update(&mut cx, |this, cx| {
             let mut buffers = HashSet::default();
             for buffer_id in &envelope.payload.buffer_ids {
                 let buffer_id = BufferId::new(*buffer_id)?;
                 buffers.insert(this.get_existing(buffer_id)?);
             }
-            Ok::<_, anyhow::Error>(this.reload_buffers(buffers, false, cx))
+            Ok::<_, anyhow::Error>(this.reload_buffers_with_encoding(buffers, encoding, false, cx))
         })??;
 
         let project_transaction = reload.await?;
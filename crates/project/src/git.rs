@@ -5,7 +5,9 @@ use anyhow::{Context as _, Result};
 use client::ProjectId;
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt as _;
-use git::repository::{Branch, CommitDetails, PushOptions, Remote, ResetMode};
+use git::repository::{
+    Branch, CommitDetails, ConflictTexts, PushOptions, Remote, ResetMode, StashEntry, StashFileDiff,
+};
 use git::{
     repository::{GitRepository, RepoPath},
     status::{GitSummary, TrackedSummary},
@@ -86,6 +88,14 @@ pub enum Message {
         remote_name: SharedString,
     },
     Fetch(GitRepo),
+    Stash {
+        repo: GitRepo,
+        message: Option<SharedString>,
+        include_untracked: bool,
+    },
+    StashApply(GitRepo, usize, SharedString),
+    StashPop(GitRepo, usize, SharedString),
+    StashDrop(GitRepo, usize, SharedString),
 }
 
 pub enum GitEvent {
@@ -123,11 +133,18 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_push);
         client.add_entity_request_handler(Self::handle_pull);
         client.add_entity_request_handler(Self::handle_fetch);
+        client.add_entity_request_handler(Self::handle_stash);
+        client.add_entity_request_handler(Self::handle_stash_list);
+        client.add_entity_request_handler(Self::handle_stash_apply);
+        client.add_entity_request_handler(Self::handle_stash_pop);
+        client.add_entity_request_handler(Self::handle_stash_drop);
+        client.add_entity_request_handler(Self::handle_load_stash_diff);
         client.add_entity_request_handler(Self::handle_stage);
         client.add_entity_request_handler(Self::handle_unstage);
         client.add_entity_request_handler(Self::handle_commit);
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
+        client.add_entity_request_handler(Self::handle_load_conflict_texts);
         client.add_entity_request_handler(Self::handle_checkout_files);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
         client.add_entity_request_handler(Self::handle_set_index_text);
@@ -501,6 +518,102 @@ impl GitStore {
                 }
                 Ok(())
             }
+            Message::Stash {
+                repo,
+                message,
+                include_untracked,
+            } => {
+                match repo {
+                    GitRepo::Local(repo) => repo.stash(message.as_deref(), include_untracked)?,
+                    GitRepo::Remote {
+                        project_id,
+                        client,
+                        worktree_id,
+                        work_directory_id,
+                    } => {
+                        client
+                            .request(proto::GitStash {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                message: message.map(String::from),
+                                include_untracked,
+                            })
+                            .await
+                            .context("sending stash request")?;
+                    }
+                }
+                Ok(())
+            }
+            Message::StashApply(repo, index, sha) => {
+                match repo {
+                    GitRepo::Local(repo) => repo.stash_apply(index, &sha)?,
+                    GitRepo::Remote {
+                        project_id,
+                        client,
+                        worktree_id,
+                        work_directory_id,
+                    } => {
+                        client
+                            .request(proto::GitStashApply {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                index: index as u64,
+                                sha: sha.into(),
+                            })
+                            .await
+                            .context("sending stash apply request")?;
+                    }
+                }
+                Ok(())
+            }
+            Message::StashPop(repo, index, sha) => {
+                match repo {
+                    GitRepo::Local(repo) => repo.stash_pop(index, &sha)?,
+                    GitRepo::Remote {
+                        project_id,
+                        client,
+                        worktree_id,
+                        work_directory_id,
+                    } => {
+                        client
+                            .request(proto::GitStashPop {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                index: index as u64,
+                                sha: sha.into(),
+                            })
+                            .await
+                            .context("sending stash pop request")?;
+                    }
+                }
+                Ok(())
+            }
+            Message::StashDrop(repo, index, sha) => {
+                match repo {
+                    GitRepo::Local(repo) => repo.stash_drop(index, &sha)?,
+                    GitRepo::Remote {
+                        project_id,
+                        client,
+                        worktree_id,
+                        work_directory_id,
+                    } => {
+                        client
+                            .request(proto::GitStashDrop {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                index: index as u64,
+                                sha: sha.into(),
+                            })
+                            .await
+                            .context("sending stash drop request")?;
+                    }
+                }
+                Ok(())
+            }
             Message::SetIndexText(git_repo, path, text) => match git_repo {
                 GitRepo::Local(repo) => repo.set_index_text(&path, text),
                 GitRepo::Remote {
@@ -586,6 +699,145 @@ impl GitStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_stash(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStash>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let message = envelope.payload.message.map(SharedString::from);
+        let include_untracked = envelope.payload.include_untracked;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash(message, include_untracked, cx)
+            })?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_list(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashEntries> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash_entries(cx)
+            })?
+            .await?;
+
+        Ok(proto::GitStashEntries {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::git_stash_entries::Entry {
+                    index: entry.index as u64,
+                    sha: entry.sha.into(),
+                    message: entry.message.into(),
+                    commit_timestamp: entry.commit_timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let index = envelope.payload.index as usize;
+        let sha = SharedString::from(envelope.payload.sha);
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash_apply(index, sha, cx)
+            })?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_pop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashPop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let index = envelope.payload.index as usize;
+        let sha = SharedString::from(envelope.payload.sha);
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash_pop(index, sha, cx)
+            })?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let index = envelope.payload.index as usize;
+        let sha = SharedString::from(envelope.payload.sha);
+        repository_handle
+            .update(&mut cx, |repository_handle, _cx| {
+                repository_handle.stash_drop(index, sha)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_load_stash_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLoadStashDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashDiff> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let index = envelope.payload.index as usize;
+        let files = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash_diff(index, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitStashDiff {
+            files: files
+                .into_iter()
+                .map(|file| proto::git_stash_diff::File {
+                    path: file.path.as_ref().to_proto(),
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                })
+                .collect(),
+        })
+    }
+
     async fn handle_stage(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::Stage>,
@@ -734,6 +986,29 @@ impl GitStore {
         })
     }
 
+    async fn handle_load_conflict_texts(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLoadConflictTexts>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitConflictTexts> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let path = RepoPath::from_str(&envelope.payload.path);
+        let texts = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.load_conflict_texts(path, cx)
+            })?
+            .await?;
+        Ok(proto::GitConflictTexts {
+            base: texts.base,
+            ours: texts.ours,
+            theirs: texts.theirs,
+        })
+    }
+
     async fn handle_reset(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitReset>,
@@ -1051,6 +1326,40 @@ impl Repository {
         }
     }
 
+    /// Loads the base, ours and theirs versions of a conflicted file from the index.
+    pub fn load_conflict_texts(
+        &self,
+        path: RepoPath,
+        cx: &Context<Self>,
+    ) -> Task<Result<ConflictTexts>> {
+        match self.git_repo.clone() {
+            GitRepo::Local(git_repository) => {
+                cx.background_spawn(async move { git_repository.load_conflict_texts(&path) })
+            }
+            GitRepo::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory_id,
+            } => cx.background_spawn(async move {
+                let response = client
+                    .request(proto::GitLoadConflictTexts {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        path: path.as_ref().to_proto(),
+                    })
+                    .await?;
+
+                Ok(ConflictTexts {
+                    base: response.base,
+                    ours: response.ours,
+                    theirs: response.theirs,
+                })
+            }),
+        }
+    }
+
     fn buffer_store(&self, cx: &App) -> Option<Entity<BufferStore>> {
         Some(self.git_store.upgrade()?.read(cx).buffer_store.clone())
     }
@@ -1209,6 +1518,107 @@ impl Repository {
         })
     }
 
+    /// Stashes the repository's changes, after saving any buffers with unsaved changes to its
+    /// files so that they're stashed too.
+    pub fn stash(
+        &self,
+        message: Option<SharedString>,
+        include_untracked: bool,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        self.send_message_after_saving(
+            Message::Stash {
+                repo: self.git_repo.clone(),
+                message,
+                include_untracked,
+            },
+            cx,
+        )
+    }
+
+    /// Applies the stash entry at `index`, if it's still the entry with the given `sha`.
+    pub fn stash_apply(&self, index: usize, sha: SharedString, cx: &mut App) -> Task<Result<()>> {
+        self.send_message_after_saving(Message::StashApply(self.git_repo.clone(), index, sha), cx)
+    }
+
+    /// Applies and removes the stash entry at `index`, if it's still the entry with the given
+    /// `sha`.
+    pub fn stash_pop(&self, index: usize, sha: SharedString, cx: &mut App) -> Task<Result<()>> {
+        self.send_message_after_saving(Message::StashPop(self.git_repo.clone(), index, sha), cx)
+    }
+
+    /// Removes the stash entry at `index`, if it's still the entry with the given `sha`.
+    pub fn stash_drop(&self, index: usize, sha: SharedString) -> oneshot::Receiver<Result<()>> {
+        self.send_message(Message::StashDrop(self.git_repo.clone(), index, sha))
+    }
+
+    pub fn stash_entries(&self, cx: &App) -> Task<Result<Vec<StashEntry>>> {
+        match self.git_repo.clone() {
+            GitRepo::Local(git_repository) => {
+                cx.background_spawn(async move { git_repository.stash_entries() })
+            }
+            GitRepo::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory_id,
+            } => cx.background_spawn(async move {
+                let response = client
+                    .request(proto::GitStashList {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                    })
+                    .await?;
+
+                Ok(response
+                    .entries
+                    .into_iter()
+                    .map(|entry| StashEntry {
+                        index: entry.index as usize,
+                        sha: entry.sha.into(),
+                        message: entry.message.into(),
+                        commit_timestamp: entry.commit_timestamp,
+                    })
+                    .collect())
+            }),
+        }
+    }
+
+    /// Loads the files changed by the stash entry at `index`.
+    pub fn stash_diff(&self, index: usize, cx: &App) -> Task<Result<Vec<StashFileDiff>>> {
+        match self.git_repo.clone() {
+            GitRepo::Local(git_repository) => {
+                cx.background_spawn(async move { git_repository.stash_diff(index) })
+            }
+            GitRepo::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory_id,
+            } => cx.background_spawn(async move {
+                let response = client
+                    .request(proto::GitLoadStashDiff {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        index: index as u64,
+                    })
+                    .await?;
+
+                Ok(response
+                    .files
+                    .into_iter()
+                    .map(|file| StashFileDiff {
+                        path: RepoPath::from_str(&file.path),
+                        old_text: file.old_text,
+                        new_text: file.new_text,
+                    })
+                    .collect())
+            }),
+        }
+    }
+
     pub fn set_index_text(
         &self,
         path: &RepoPath,
@@ -1254,6 +1664,42 @@ impl Repository {
         }
     }
 
+    /// Sends a message that rewrites the working tree once the buffers with unsaved changes to
+    /// files in the repository have been saved, so that their changes aren't left behind or
+    /// conflict with the files' new contents.
+    fn send_message_after_saving(&self, message: Message, cx: &mut App) -> Task<Result<()>> {
+        let mut save_futures = Vec::new();
+        if let Some(buffer_store) = self.buffer_store(cx) {
+            buffer_store.update(cx, |buffer_store, cx| {
+                let dirty_buffers = buffer_store
+                    .buffers()
+                    .filter(|buffer| {
+                        let buffer = buffer.read(cx);
+                        buffer.is_dirty()
+                            && buffer.file().map_or(false, |file| {
+                                file.worktree_id(cx) == self.worktree_id
+                                    && self.repository_entry.directory_contains(file.path())
+                            })
+                    })
+                    .collect::<Vec<_>>();
+                for buffer in dirty_buffers {
+                    save_futures.push(buffer_store.save_buffer(buffer, cx));
+                }
+            })
+        }
+
+        let update_sender = self.update_sender.clone();
+        cx.spawn(|_| async move {
+            for save_future in save_futures {
+                save_future.await?;
+            }
+            let (result_tx, result_rx) = futures::channel::oneshot::channel();
+            update_sender.unbounded_send((message, result_tx)).ok();
+            result_rx.await.anyhow()??;
+            Ok(())
+        })
+    }
+
     fn send_message(&self, message: Message) -> oneshot::Receiver<anyhow::Result<()>> {
         let (result_tx, result_rx) = futures::channel::oneshot::channel();
         self.update_sender.unbounded_send((message, result_tx)).ok();
@@ -2,9 +2,11 @@ mod signature_help;
 
 use crate::{
     lsp_store::{LocalLspStore, LspStore},
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, PrepareRenameResponse, ProjectTransaction, ResolveState,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, ColorPresentation, CoreCompletion,
+    DocumentColor, DocumentHighlight, DocumentLink, FoldingRange, Hover, HoverBlock,
+    HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip,
+    InlayHintTooltip, Location, LocationLink, MarkupContent, PrepareRenameResponse,
+    ProjectTransaction, ResolveState, TypeHierarchyItem,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -15,20 +17,25 @@ use futures::future;
 use gpui::{App, AsyncApp, Entity};
 use language::{
     language_settings::{language_settings, InlayHintKind, LanguageSettings},
-    point_from_lsp, point_to_lsp,
-    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
-    range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    point_from_lsp, point_from_lsp_encoded, point_to_lsp, point_to_lsp_encoded,
+    proto::{
+        deserialize_anchor, deserialize_anchor_range, deserialize_version, serialize_anchor,
+        serialize_anchor_range, serialize_version,
+    },
+    range_from_lsp_encoded, range_to_lsp_encoded, Anchor, Bias, Buffer, BufferSnapshot,
+    CachedLspAdapter, CharKind, OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16,
+    Transaction, Unclipped,
 };
 use lsp::{
     AdapterServerCapabilities, CodeActionKind, CodeActionOptions, CompletionContext,
     CompletionListItemDefaultsEditRange, CompletionTriggerKind, DocumentHighlightKind,
-    LanguageServer, LanguageServerId, LinkedEditingRangeServerCapabilities, OneOf, RenameOptions,
-    ServerCapabilities,
+    LanguageServer, LanguageServerId, LinkedEditingRangeServerCapabilities, OneOf,
+    PositionEncoding, RenameOptions, ServerCapabilities,
 };
 use signature_help::{lsp_to_proto_signature, proto_to_lsp_signature};
-use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Reverse, iter, ops::Range, path::Path, sync::Arc};
 use text::{BufferId, LineEnding};
+use util::ResultExt as _;
 
 pub use signature_help::SignatureHelp;
 
@@ -61,10 +68,16 @@ pub(crate) fn make_text_document_identifier(path: &Path) -> Result<lsp::TextDocu
 pub(crate) fn make_lsp_text_document_position(
     path: &Path,
     position: PointUtf16,
+    buffer: &Buffer,
+    language_server: &LanguageServer,
 ) -> Result<lsp::TextDocumentPositionParams> {
     Ok(lsp::TextDocumentPositionParams {
         text_document: make_text_document_identifier(path)?,
-        position: point_to_lsp(position),
+        position: point_to_lsp_encoded(
+            position,
+            buffer.as_rope(),
+            language_server.position_encoding(),
+        ),
     })
 }
 
@@ -220,6 +233,24 @@ pub(crate) struct GetCodeActions {
     pub kinds: Option<Vec<lsp::CodeActionKind>>,
 }
 
+#[derive(Debug)]
+pub(crate) struct GetCodeLens;
+
+#[derive(Debug)]
+pub(crate) struct GetFoldingRanges;
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentColors;
+
+#[derive(Debug)]
+pub(crate) struct GetColorPresentations {
+    /// The color literal to replace, along with the color to replace it with.
+    pub color: DocumentColor,
+}
+
 #[derive(Debug)]
 pub(crate) struct OnTypeFormatting {
     pub position: PointUtf16,
@@ -238,6 +269,36 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+#[derive(Debug)]
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetIncomingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetOutgoingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSupertypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSubtypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = PrepareRenameResponse;
@@ -282,25 +343,27 @@ impl LspCommand for PrepareRename {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::TextDocumentPositionParams> {
-        make_lsp_text_document_position(path, self.position)
+        make_lsp_text_document_position(path, self.position, buffer, language_server)
     }
 
     async fn response_from_lsp(
         self,
         message: Option<lsp::PrepareRenameResponse>,
-        _: Entity<LspStore>,
+        lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
-        _: LanguageServerId,
+        server_id: LanguageServerId,
         mut cx: AsyncApp,
     ) -> Result<PrepareRenameResponse> {
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
         buffer.update(&mut cx, |buffer, _| match message {
             Some(lsp::PrepareRenameResponse::Range(range))
             | Some(lsp::PrepareRenameResponse::RangeWithPlaceholder { range, .. }) => {
-                let Range { start, end } = range_from_lsp(range);
+                let Range { start, end } =
+                    range_from_lsp_encoded(range, buffer.as_rope(), encoding);
                 if buffer.clip_point_utf16(start, Bias::Left) == start.0
                     && buffer.clip_point_utf16(end, Bias::Left) == end.0
                 {
@@ -433,12 +496,17 @@ impl LspCommand for PerformRename {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::RenameParams> {
         Ok(lsp::RenameParams {
-            text_document_position: make_lsp_text_document_position(path, self.position)?,
+            text_document_position: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             new_name: self.new_name.clone(),
             work_done_progress_params: Default::default(),
         })
@@ -562,12 +630,17 @@ impl LspCommand for GetDefinition {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::GotoDefinitionParams> {
         Ok(lsp::GotoDefinitionParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
         })
@@ -661,12 +734,17 @@ impl LspCommand for GetDeclaration {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::GotoDeclarationParams> {
         Ok(lsp::GotoDeclarationParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
         })
@@ -753,12 +831,17 @@ impl LspCommand for GetImplementation {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::GotoImplementationParams> {
         Ok(lsp::GotoImplementationParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
         })
@@ -852,12 +935,17 @@ impl LspCommand for GetTypeDefinition {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::GotoTypeDefinitionParams> {
         Ok(lsp::GotoTypeDefinitionParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
         })
@@ -948,6 +1036,18 @@ fn language_server_for_buffer(
         .ok_or_else(|| anyhow!("no language server found for buffer"))
 }
 
+/// The position encoding negotiated with the given language server, used to interpret the
+/// positions in its responses.
+fn position_encoding(
+    lsp_store: &Entity<LspStore>,
+    server_id: LanguageServerId,
+    cx: &AsyncApp,
+) -> PositionEncoding {
+    lsp_store
+        .read_with(cx, |lsp_store, _| lsp_store.position_encoding(server_id))
+        .unwrap_or_default()
+}
+
 async fn location_links_from_proto(
     proto_links: Vec<proto::LocationLink>,
     lsp_store: Entity<LspStore>,
@@ -1047,6 +1147,7 @@ async fn location_links_from_lsp(
 
     let (lsp_adapter, language_server) =
         language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+    let encoding = language_server.position_encoding();
     let mut definitions = Vec::new();
     for (origin_range, target_uri, target_range) in unresolved_links {
         let target_buffer_handle = lsp_store
@@ -1063,10 +1164,14 @@ async fn location_links_from_lsp(
         cx.update(|cx| {
             let origin_location = origin_range.map(|origin_range| {
                 let origin_buffer = buffer.read(cx);
-                let origin_start =
-                    origin_buffer.clip_point_utf16(point_from_lsp(origin_range.start), Bias::Left);
-                let origin_end =
-                    origin_buffer.clip_point_utf16(point_from_lsp(origin_range.end), Bias::Left);
+                let origin_start = origin_buffer.clip_point_utf16(
+                    point_from_lsp_encoded(origin_range.start, origin_buffer.as_rope(), encoding),
+                    Bias::Left,
+                );
+                let origin_end = origin_buffer.clip_point_utf16(
+                    point_from_lsp_encoded(origin_range.end, origin_buffer.as_rope(), encoding),
+                    Bias::Left,
+                );
                 Location {
                     buffer: buffer.clone(),
                     range: origin_buffer.anchor_after(origin_start)
@@ -1075,10 +1180,14 @@ async fn location_links_from_lsp(
             });
 
             let target_buffer = target_buffer_handle.read(cx);
-            let target_start =
-                target_buffer.clip_point_utf16(point_from_lsp(target_range.start), Bias::Left);
-            let target_end =
-                target_buffer.clip_point_utf16(point_from_lsp(target_range.end), Bias::Left);
+            let target_start = target_buffer.clip_point_utf16(
+                point_from_lsp_encoded(target_range.start, target_buffer.as_rope(), encoding),
+                Bias::Left,
+            );
+            let target_end = target_buffer.clip_point_utf16(
+                point_from_lsp_encoded(target_range.end, target_buffer.as_rope(), encoding),
+                Bias::Left,
+            );
             let target_location = Location {
                 buffer: target_buffer_handle,
                 range: target_buffer.anchor_after(target_start)
@@ -1166,12 +1275,17 @@ impl LspCommand for GetReferences {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::ReferenceParams> {
         Ok(lsp::ReferenceParams {
-            text_document_position: make_lsp_text_document_position(path, self.position)?,
+            text_document_position: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
             context: lsp::ReferenceContext {
@@ -1191,6 +1305,7 @@ impl LspCommand for GetReferences {
         let mut references = Vec::new();
         let (lsp_adapter, language_server) =
             language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let encoding = language_server.position_encoding();
 
         if let Some(locations) = locations {
             for lsp_location in locations {
@@ -1208,10 +1323,22 @@ impl LspCommand for GetReferences {
                 target_buffer_handle
                     .clone()
                     .update(&mut cx, |target_buffer, _| {
-                        let target_start = target_buffer
-                            .clip_point_utf16(point_from_lsp(lsp_location.range.start), Bias::Left);
-                        let target_end = target_buffer
-                            .clip_point_utf16(point_from_lsp(lsp_location.range.end), Bias::Left);
+                        let target_start = target_buffer.clip_point_utf16(
+                            point_from_lsp_encoded(
+                                lsp_location.range.start,
+                                target_buffer.as_rope(),
+                                encoding,
+                            ),
+                            Bias::Left,
+                        );
+                        let target_end = target_buffer.clip_point_utf16(
+                            point_from_lsp_encoded(
+                                lsp_location.range.end,
+                                target_buffer.as_rope(),
+                                encoding,
+                            ),
+                            Bias::Left,
+                        );
                         references.push(Location {
                             buffer: target_buffer_handle,
                             range: target_buffer.anchor_after(target_start)
@@ -1341,12 +1468,17 @@ impl LspCommand for GetDocumentHighlights {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::DocumentHighlightParams> {
         Ok(lsp::DocumentHighlightParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
         })
@@ -1355,21 +1487,30 @@ impl LspCommand for GetDocumentHighlights {
     async fn response_from_lsp(
         self,
         lsp_highlights: Option<Vec<lsp::DocumentHighlight>>,
-        _: Entity<LspStore>,
+        lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
-        _: LanguageServerId,
+        server_id: LanguageServerId,
         mut cx: AsyncApp,
     ) -> Result<Vec<DocumentHighlight>> {
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
         buffer.update(&mut cx, |buffer, _| {
             let mut lsp_highlights = lsp_highlights.unwrap_or_default();
             lsp_highlights.sort_unstable_by_key(|h| (h.range.start, Reverse(h.range.end)));
             lsp_highlights
                 .into_iter()
                 .map(|lsp_highlight| {
-                    let start = buffer
-                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.start), Bias::Left);
-                    let end = buffer
-                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.end), Bias::Left);
+                    let start = buffer.clip_point_utf16(
+                        point_from_lsp_encoded(
+                            lsp_highlight.range.start,
+                            buffer.as_rope(),
+                            encoding,
+                        ),
+                        Bias::Left,
+                    );
+                    let end = buffer.clip_point_utf16(
+                        point_from_lsp_encoded(lsp_highlight.range.end, buffer.as_rope(), encoding),
+                        Bias::Left,
+                    );
                     DocumentHighlight {
                         range: buffer.anchor_after(start)..buffer.anchor_before(end),
                         kind: lsp_highlight
@@ -1494,12 +1635,17 @@ impl LspCommand for GetSignatureHelp {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _cx: &App,
     ) -> Result<lsp::SignatureHelpParams> {
         Ok(lsp::SignatureHelpParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             context: None,
             work_done_progress_params: Default::default(),
         })
@@ -1600,12 +1746,17 @@ impl LspCommand for GetHover {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::HoverParams> {
         Ok(lsp::HoverParams {
-            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             work_done_progress_params: Default::default(),
         })
     }
@@ -1613,22 +1764,28 @@ impl LspCommand for GetHover {
     async fn response_from_lsp(
         self,
         message: Option<lsp::Hover>,
-        _: Entity<LspStore>,
+        lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
-        _: LanguageServerId,
+        server_id: LanguageServerId,
         mut cx: AsyncApp,
     ) -> Result<Self::Response> {
         let Some(hover) = message else {
             return Ok(None);
         };
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
 
         let (language, range) = buffer.update(&mut cx, |buffer, _| {
             (
                 buffer.language().cloned(),
                 hover.range.map(|range| {
-                    let token_start =
-                        buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
-                    let token_end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                    let token_start = buffer.clip_point_utf16(
+                        point_from_lsp_encoded(range.start, buffer.as_rope(), encoding),
+                        Bias::Left,
+                    );
+                    let token_end = buffer.clip_point_utf16(
+                        point_from_lsp_encoded(range.end, buffer.as_rope(), encoding),
+                        Bias::Left,
+                    );
                     buffer.anchor_after(token_start)..buffer.anchor_before(token_end)
                 }),
             )
@@ -1822,12 +1979,17 @@ impl LspCommand for GetCompletions {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::CompletionParams> {
         Ok(lsp::CompletionParams {
-            text_document_position: make_lsp_text_document_position(path, self.position)?,
+            text_document_position: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
             context: Some(self.context.clone()),
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
@@ -1923,6 +2085,7 @@ impl LspCommand for GetCompletions {
             }
         }
 
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
         let mut completion_edits = Vec::new();
         buffer.update(&mut cx, |buffer, _cx| {
             let snapshot = buffer.snapshot();
@@ -1934,7 +2097,8 @@ impl LspCommand for GetCompletions {
                     // If the language server provides a range to overwrite, then
                     // check that the range is valid.
                     Some(completion_text_edit) => {
-                        match parse_completion_text_edit(completion_text_edit, &snapshot) {
+                        match parse_completion_text_edit(completion_text_edit, &snapshot, encoding)
+                        {
                             Some(edit) => edit,
                             None => return false,
                         }
@@ -1958,7 +2122,8 @@ impl LspCommand for GetCompletions {
                             });
 
                         let range = if let Some(range) = default_edit_range {
-                            let range = range_from_lsp(*range);
+                            let range =
+                                range_from_lsp_encoded(*range, snapshot.as_rope(), encoding);
                             let start = snapshot.clip_point_utf16(range.start, Bias::Left);
                             let end = snapshot.clip_point_utf16(range.end, Bias::Left);
                             if start != range.start.0 || end != range.end.0 {
@@ -2100,10 +2265,11 @@ impl LspCommand for GetCompletions {
 pub(crate) fn parse_completion_text_edit(
     edit: &lsp::CompletionTextEdit,
     snapshot: &BufferSnapshot,
+    encoding: PositionEncoding,
 ) -> Option<(Range<Anchor>, String)> {
     match edit {
         lsp::CompletionTextEdit::Edit(edit) => {
-            let range = range_from_lsp(edit.range);
+            let range = range_from_lsp_encoded(edit.range, snapshot.as_rope(), encoding);
             let start = snapshot.clip_point_utf16(range.start, Bias::Left);
             let end = snapshot.clip_point_utf16(range.end, Bias::Left);
             if start != range.start.0 || end != range.end.0 {
@@ -2118,7 +2284,7 @@ pub(crate) fn parse_completion_text_edit(
         }
 
         lsp::CompletionTextEdit::InsertAndReplace(edit) => {
-            let range = range_from_lsp(edit.replace);
+            let range = range_from_lsp_encoded(edit.replace, snapshot.as_rope(), encoding);
 
             let start = snapshot.clip_point_utf16(range.start, Bias::Left);
             let end = snapshot.clip_point_utf16(range.end, Bias::Left);
@@ -2174,12 +2340,13 @@ impl LspCommand for GetCodeActions {
         language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<lsp::CodeActionParams> {
+        let encoding = language_server.position_encoding();
         let mut relevant_diagnostics = Vec::new();
         for entry in buffer
             .snapshot()
             .diagnostics_in_range::<_, language::PointUtf16>(self.range.clone(), false)
         {
-            relevant_diagnostics.push(entry.to_lsp_diagnostic_stub()?);
+            relevant_diagnostics.push(entry.to_lsp_diagnostic_stub(buffer.as_rope(), encoding)?);
         }
 
         let supported =
@@ -2204,7 +2371,11 @@ impl LspCommand for GetCodeActions {
 
         Ok(lsp::CodeActionParams {
             text_document: make_text_document_identifier(path)?,
-            range: range_to_lsp(self.range.to_point_utf16(buffer))?,
+            range: range_to_lsp_encoded(
+                self.range.to_point_utf16(buffer),
+                buffer.as_rope(),
+                encoding,
+            )?,
             work_done_progress_params: Default::default(),
             partial_result_params: Default::default(),
             context: lsp::CodeActionContext {
@@ -2356,30 +2527,20 @@ impl GetCodeActions {
 }
 
 #[async_trait(?Send)]
-impl LspCommand for OnTypeFormatting {
-    type Response = Option<Transaction>;
-    type LspRequest = lsp::request::OnTypeFormatting;
-    type ProtoRequest = proto::OnTypeFormatting;
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
 
     fn display_name(&self) -> &str {
-        "Formatting on typing"
+        "Get code lens"
     }
 
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        let Some(on_type_formatting_options) = &capabilities
+        capabilities
             .server_capabilities
-            .document_on_type_formatting_provider
-        else {
-            return false;
-        };
-        on_type_formatting_options
-            .first_trigger_character
-            .contains(&self.trigger)
-            || on_type_formatting_options
-                .more_trigger_character
-                .iter()
-                .flatten()
-                .any(|chars| chars.contains(&self.trigger))
+            .code_lens_provider
+            .is_some()
     }
 
     fn to_lsp(
@@ -2388,790 +2549,2475 @@ impl LspCommand for OnTypeFormatting {
         _: &Buffer,
         _: &Arc<LanguageServer>,
         _: &App,
-    ) -> Result<lsp::DocumentOnTypeFormattingParams> {
-        Ok(lsp::DocumentOnTypeFormattingParams {
-            text_document_position: make_lsp_text_document_position(path, self.position)?,
-            ch: self.trigger.clone(),
-            options: self.options.clone(),
+    ) -> Result<lsp::CodeLensParams> {
+        Ok(lsp::CodeLensParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         })
     }
 
     async fn response_from_lsp(
         self,
-        message: Option<Vec<lsp::TextEdit>>,
+        lenses: Option<Vec<lsp::CodeLens>>,
         lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
         server_id: LanguageServerId,
         mut cx: AsyncApp,
-    ) -> Result<Option<Transaction>> {
-        if let Some(edits) = message {
-            let (lsp_adapter, lsp_server) =
-                language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
-            LocalLspStore::deserialize_text_edits(
-                lsp_store,
-                buffer,
-                edits,
-                self.push_to_history,
-                lsp_adapter,
-                lsp_server,
-                &mut cx,
-            )
-            .await
-        } else {
-            Ok(None)
-        }
+    ) -> Result<Vec<CodeLens>> {
+        let (_, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let capabilities = language_server.capabilities();
+        let can_resolve = Self::can_resolve_lens(&capabilities);
+
+        // Lenses without a command are resolved lazily, once they are visible.
+        let encoding = language_server.position_encoding();
+        buffer.update(&mut cx, |buffer, _| {
+            lenses
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| CodeLens {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_lens.range, encoding),
+                    lsp_lens,
+                })
+                .filter(|lens| {
+                    if lens.is_resolved() {
+                        Self::can_execute_lens(lens, &capabilities)
+                    } else {
+                        can_resolve
+                    }
+                })
+                .collect()
+        })
     }
 
-    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::OnTypeFormatting {
-        proto::OnTypeFormatting {
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
             project_id,
             buffer_id: buffer.remote_id().into(),
-            position: Some(language::proto::serialize_anchor(
-                &buffer.anchor_before(self.position),
-            )),
-            trigger: self.trigger.clone(),
             version: serialize_version(&buffer.version()),
         }
     }
 
     async fn from_proto(
-        message: proto::OnTypeFormatting,
+        message: proto::GetCodeLens,
         _: Entity<LspStore>,
         buffer: Entity<Buffer>,
         mut cx: AsyncApp,
     ) -> Result<Self> {
-        let position = message
-            .position
-            .and_then(deserialize_anchor)
-            .ok_or_else(|| anyhow!("invalid position"))?;
         buffer
             .update(&mut cx, |buffer, _| {
                 buffer.wait_for_version(deserialize_version(&message.version))
             })?
             .await?;
-
-        let options = buffer.update(&mut cx, |buffer, cx| {
-            lsp_formatting_options(
-                language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx).as_ref(),
-            )
-        })?;
-
-        Ok(Self {
-            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
-            trigger: message.trigger.clone(),
-            options,
-            push_to_history: false,
-        })
+        Ok(Self)
     }
 
     fn response_to_proto(
-        response: Option<Transaction>,
+        lenses: Vec<CodeLens>,
         _: &mut LspStore,
         _: PeerId,
-        _: &clock::Global,
+        buffer_version: &clock::Global,
         _: &mut App,
-    ) -> proto::OnTypeFormattingResponse {
-        proto::OnTypeFormattingResponse {
-            transaction: response
-                .map(|transaction| language::proto::serialize_transaction(&transaction)),
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses.iter().map(LspStore::serialize_code_lens).collect(),
+            version: serialize_version(buffer_version),
         }
     }
 
     async fn response_from_proto(
         self,
-        message: proto::OnTypeFormattingResponse,
+        message: proto::GetCodeLensResponse,
         _: Entity<LspStore>,
-        _: Entity<Buffer>,
-        _: AsyncApp,
-    ) -> Result<Option<Transaction>> {
-        let Some(transaction) = message.transaction else {
-            return Ok(None);
-        };
-        Ok(Some(language::proto::deserialize_transaction(transaction)?))
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(LspStore::deserialize_code_lens)
+            .collect()
     }
 
-    fn buffer_id_from_proto(message: &proto::OnTypeFormatting) -> Result<BufferId> {
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
         BufferId::new(message.buffer_id)
     }
 }
 
-impl InlayHints {
-    pub async fn lsp_to_project_hint(
-        lsp_hint: lsp::InlayHint,
-        buffer_handle: &Entity<Buffer>,
-        server_id: LanguageServerId,
-        resolve_state: ResolveState,
-        force_no_type_left_padding: bool,
-        cx: &mut AsyncApp,
-    ) -> anyhow::Result<InlayHint> {
-        let kind = lsp_hint.kind.and_then(|kind| match kind {
-            lsp::InlayHintKind::TYPE => Some(InlayHintKind::Type),
-            lsp::InlayHintKind::PARAMETER => Some(InlayHintKind::Parameter),
-            _ => None,
-        });
+impl GetCodeLens {
+    /// Whether the command of a resolved lens can be run, either by the client or by the
+    /// server. Lenses that can't be run are not shown.
+    pub fn can_execute_lens(lens: &CodeLens, capabilities: &ServerCapabilities) -> bool {
+        let Some(command) = &lens.lsp_lens.command else {
+            return false;
+        };
+        lens.client_command().is_some()
+            || capabilities
+                .execute_command_provider
+                .as_ref()
+                .is_some_and(|options| options.commands.contains(&command.command))
+    }
 
-        let position = buffer_handle.update(cx, |buffer, _| {
-            let position = buffer.clip_point_utf16(point_from_lsp(lsp_hint.position), Bias::Left);
-            if kind == Some(InlayHintKind::Parameter) {
-                buffer.anchor_before(position)
-            } else {
-                buffer.anchor_after(position)
-            }
-        })?;
-        let label = Self::lsp_inlay_label_to_project(lsp_hint.label, server_id)
-            .await
-            .context("lsp to project inlay hint conversion")?;
-        let padding_left = if force_no_type_left_padding && kind == Some(InlayHintKind::Type) {
-            false
-        } else {
-            lsp_hint.padding_left.unwrap_or(false)
-        };
+    pub fn can_resolve_lens(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+}
 
-        Ok(InlayHint {
-            position,
-            padding_left,
-            padding_right: lsp_hint.padding_right.unwrap_or(false),
-            label,
-            kind,
-            tooltip: lsp_hint.tooltip.map(|tooltip| match tooltip {
-                lsp::InlayHintTooltip::String(s) => InlayHintTooltip::String(s),
-                lsp::InlayHintTooltip::MarkupContent(markup_content) => {
-                    InlayHintTooltip::MarkupContent(MarkupContent {
-                        kind: match markup_content.kind {
-                            lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
-                            lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
-                        },
-                        value: markup_content.value,
-                    })
-                }
-            }),
-            resolve_state,
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn display_name(&self) -> &str {
+        "Get folding ranges"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match capabilities.server_capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::FoldingRangeParams> {
+        Ok(lsp::FoldingRangeParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         })
     }
 
-    async fn lsp_inlay_label_to_project(
-        lsp_label: lsp::InlayHintLabel,
-        server_id: LanguageServerId,
-    ) -> anyhow::Result<InlayHintLabel> {
-        let label = match lsp_label {
-            lsp::InlayHintLabel::String(s) => InlayHintLabel::String(s),
-            lsp::InlayHintLabel::LabelParts(lsp_parts) => {
-                let mut parts = Vec::with_capacity(lsp_parts.len());
-                for lsp_part in lsp_parts {
-                    parts.push(InlayHintLabelPart {
-                        value: lsp_part.value,
-                        tooltip: lsp_part.tooltip.map(|tooltip| match tooltip {
-                            lsp::InlayHintLabelPartTooltip::String(s) => {
-                                InlayHintLabelPartTooltip::String(s)
-                            }
-                            lsp::InlayHintLabelPartTooltip::MarkupContent(markup_content) => {
-                                InlayHintLabelPartTooltip::MarkupContent(MarkupContent {
-                                    kind: match markup_content.kind {
-                                        lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
-                                        lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
-                                    },
-                                    value: markup_content.value,
-                                })
-                            }
-                        }),
-                        location: Some(server_id).zip(lsp_part.location),
-                    });
-                }
-                InlayHintLabel::LabelParts(parts)
-            }
-        };
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut lsp_ranges = lsp_ranges.unwrap_or_default();
+            lsp_ranges.sort_unstable_by_key(|range| (range.start_line, Reverse(range.end_line)));
+            lsp_ranges
+                .into_iter()
+                .filter(|range| range.start_line < range.end_line && range.end_line <= max_row)
+                .map(|lsp_range| {
+                    // Folding is line based: the whole range from the end of the start line to
+                    // the end of the end line is folded.
+                    let start =
+                        Point::new(lsp_range.start_line, buffer.line_len(lsp_range.start_line));
+                    let end = Point::new(lsp_range.end_line, buffer.line_len(lsp_range.end_line));
+                    FoldingRange {
+                        range: buffer.anchor_before(start)..buffer.anchor_before(end),
+                        kind: lsp_range.kind,
+                    }
+                })
+                .collect()
+        })
+    }
 
-        Ok(label)
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
     }
 
-    pub fn project_to_proto_hint(response_hint: InlayHint) -> proto::InlayHint {
-        let (state, lsp_resolve_state) = match response_hint.resolve_state {
-            ResolveState::Resolved => (0, None),
-            ResolveState::CanResolve(server_id, resolve_data) => (
-                1,
-                Some(proto::resolve_state::LspResolveState {
-                    server_id: server_id.0 as u64,
-                    value: resolve_data.map(|json_data| {
-                        serde_json::to_string(&json_data)
-                            .expect("failed to serialize resolve json data")
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        ranges: Vec<FoldingRange>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: ranges
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: range.kind.map(|kind| {
+                        match kind {
+                            lsp::FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                            lsp::FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                            lsp::FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                        }
+                        .into()
                     }),
-                }),
-            ),
-            ResolveState::Resolving => (2, None),
-        };
-        let resolve_state = Some(proto::ResolveState {
-            state,
-            lsp_resolve_state,
-        });
-        proto::InlayHint {
-            position: Some(language::proto::serialize_anchor(&response_hint.position)),
-            padding_left: response_hint.padding_left,
-            padding_right: response_hint.padding_right,
-            label: Some(proto::InlayHintLabel {
-                label: Some(match response_hint.label {
-                    InlayHintLabel::String(s) => proto::inlay_hint_label::Label::Value(s),
-                    InlayHintLabel::LabelParts(label_parts) => {
-                        proto::inlay_hint_label::Label::LabelParts(proto::InlayHintLabelParts {
-                            parts: label_parts.into_iter().map(|label_part| {
-                                let location_url = label_part.location.as_ref().map(|(_, location)| location.uri.to_string());
-                                let location_range_start = label_part.location.as_ref().map(|(_, location)| point_from_lsp(location.range.start).0).map(|point| proto::PointUtf16 { row: point.row, column: point.column });
-                                let location_range_end = label_part.location.as_ref().map(|(_, location)| point_from_lsp(location.range.end).0).map(|point| proto::PointUtf16 { row: point.row, column: point.column });
-                                proto::InlayHintLabelPart {
-                                value: label_part.value,
-                                tooltip: label_part.tooltip.map(|tooltip| {
-                                    let proto_tooltip = match tooltip {
-                                        InlayHintLabelPartTooltip::String(s) => proto::inlay_hint_label_part_tooltip::Content::Value(s),
-                                        InlayHintLabelPartTooltip::MarkupContent(markup_content) => proto::inlay_hint_label_part_tooltip::Content::MarkupContent(proto::MarkupContent {
-                                            is_markdown: markup_content.kind == HoverBlockKind::Markdown,
-                                            value: markup_content.value,
-                                        }),
-                                    };
-                                    proto::InlayHintLabelPartTooltip {content: Some(proto_tooltip)}
-                                }),
-                                location_url,
-                                location_range_start,
-                                location_range_end,
-                                language_server_id: label_part.location.as_ref().map(|(server_id, _)| server_id.0 as u64),
-                            }}).collect()
-                        })
-                    }
-                }),
-            }),
-            kind: response_hint.kind.map(|kind| kind.name().to_string()),
-            tooltip: response_hint.tooltip.map(|response_tooltip| {
-                let proto_tooltip = match response_tooltip {
-                    InlayHintTooltip::String(s) => proto::inlay_hint_tooltip::Content::Value(s),
-                    InlayHintTooltip::MarkupContent(markup_content) => {
-                        proto::inlay_hint_tooltip::Content::MarkupContent(proto::MarkupContent {
-                            is_markdown: markup_content.kind == HoverBlockKind::Markdown,
-                            value: markup_content.value,
-                        })
-                    }
-                };
-                proto::InlayHintTooltip {
-                    content: Some(proto_tooltip),
-                }
-            }),
-            resolve_state,
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
         }
     }
 
-    pub fn proto_to_project_hint(message_hint: proto::InlayHint) -> anyhow::Result<InlayHint> {
-        let resolve_state = message_hint.resolve_state.as_ref().unwrap_or_else(|| {
-            panic!("incorrect proto inlay hint message: no resolve state in hint {message_hint:?}",)
-        });
-        let resolve_state_data = resolve_state
-            .lsp_resolve_state.as_ref()
-            .map(|lsp_resolve_state| {
-                let value = lsp_resolve_state.value.as_deref().map(|value| {
-                    serde_json::from_str::<Option<lsp::LSPAny>>(value)
-                        .with_context(|| format!("incorrect proto inlay hint message: non-json resolve state {lsp_resolve_state:?}"))
-                }).transpose()?.flatten();
-                anyhow::Ok((LanguageServerId(lsp_resolve_state.server_id as usize), value))
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .ranges
+            .into_iter()
+            .map(|range| {
+                let start = range
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = range
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                let kind = range
+                    .kind
+                    .and_then(proto::folding_range::Kind::from_i32)
+                    .map(|kind| match kind {
+                        proto::folding_range::Kind::Comment => lsp::FoldingRangeKind::Comment,
+                        proto::folding_range::Kind::Imports => lsp::FoldingRangeKind::Imports,
+                        proto::folding_range::Kind::Region => lsp::FoldingRangeKind::Region,
+                    });
+                Ok(FoldingRange {
+                    range: start..end,
+                    kind,
+                })
             })
-            .transpose()?;
-        let resolve_state = match resolve_state.state {
-            0 => ResolveState::Resolved,
-            1 => {
-                let (server_id, lsp_resolve_state) = resolve_state_data.with_context(|| {
-                    format!(
-                        "No lsp resolve data for the hint that can be resolved: {message_hint:?}"
-                    )
-                })?;
-                ResolveState::CanResolve(server_id, lsp_resolve_state)
-            }
-            2 => ResolveState::Resolving,
-            invalid => {
-                anyhow::bail!("Unexpected resolve state {invalid} for hint {message_hint:?}")
-            }
-        };
-        Ok(InlayHint {
-            position: message_hint
-                .position
-                .and_then(language::proto::deserialize_anchor)
-                .context("invalid position")?,
-            label: match message_hint
-                .label
-                .and_then(|label| label.label)
-                .context("missing label")?
-            {
-                proto::inlay_hint_label::Label::Value(s) => InlayHintLabel::String(s),
-                proto::inlay_hint_label::Label::LabelParts(parts) => {
-                    let mut label_parts = Vec::new();
-                    for part in parts.parts {
-                        label_parts.push(InlayHintLabelPart {
-                            value: part.value,
-                            tooltip: part.tooltip.map(|tooltip| match tooltip.content {
-                                Some(proto::inlay_hint_label_part_tooltip::Content::Value(s)) => {
-                                    InlayHintLabelPartTooltip::String(s)
-                                }
-                                Some(
-                                    proto::inlay_hint_label_part_tooltip::Content::MarkupContent(
-                                        markup_content,
-                                    ),
-                                ) => InlayHintLabelPartTooltip::MarkupContent(MarkupContent {
-                                    kind: if markup_content.is_markdown {
-                                        HoverBlockKind::Markdown
-                                    } else {
-                                        HoverBlockKind::PlainText
-                                    },
-                                    value: markup_content.value,
-                                }),
-                                None => InlayHintLabelPartTooltip::String(String::new()),
-                            }),
-                            location: {
-                                match part
-                                    .location_url
-                                    .zip(
-                                        part.location_range_start.and_then(|start| {
-                                            Some(start..part.location_range_end?)
-                                        }),
-                                    )
-                                    .zip(part.language_server_id)
-                                {
-                                    Some(((uri, range), server_id)) => Some((
-                                        LanguageServerId(server_id as usize),
-                                        lsp::Location {
-                                            uri: lsp::Url::parse(&uri)
-                                                .context("invalid uri in hint part {part:?}")?,
-                                            range: lsp::Range::new(
-                                                point_to_lsp(PointUtf16::new(
-                                                    range.start.row,
-                                                    range.start.column,
-                                                )),
-                                                point_to_lsp(PointUtf16::new(
-                                                    range.end.row,
-                                                    range.end.column,
-                                                )),
-                                            ),
-                                        },
-                                    )),
-                                    None => None,
-                                }
-                            },
-                        });
-                    }
+            .collect()
+    }
 
-                    InlayHintLabel::LabelParts(label_parts)
-                }
-            },
-            padding_left: message_hint.padding_left,
-            padding_right: message_hint.padding_right,
-            kind: message_hint
-                .kind
-                .as_deref()
-                .and_then(InlayHintKind::from_name),
-            tooltip: message_hint.tooltip.and_then(|tooltip| {
-                Some(match tooltip.content? {
-                    proto::inlay_hint_tooltip::Content::Value(s) => InlayHintTooltip::String(s),
-                    proto::inlay_hint_tooltip::Content::MarkupContent(markup_content) => {
-                        InlayHintTooltip::MarkupContent(MarkupContent {
-                            kind: if markup_content.is_markdown {
-                                HoverBlockKind::Markdown
-                            } else {
-                                HoverBlockKind::PlainText
-                            },
-                            value: markup_content.value,
-                        })
-                    }
-                })
-            }),
-            resolve_state,
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn display_name(&self) -> &str {
+        "Get document links"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::DocumentLinkParams> {
+        Ok(lsp::DocumentLinkParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         })
     }
 
-    pub fn project_to_lsp_hint(hint: InlayHint, snapshot: &BufferSnapshot) -> lsp::InlayHint {
-        lsp::InlayHint {
-            position: point_to_lsp(hint.position.to_point_utf16(snapshot)),
-            kind: hint.kind.map(|kind| match kind {
-                InlayHintKind::Type => lsp::InlayHintKind::TYPE,
-                InlayHintKind::Parameter => lsp::InlayHintKind::PARAMETER,
-            }),
-            text_edits: None,
-            tooltip: hint.tooltip.and_then(|tooltip| {
-                Some(match tooltip {
-                    InlayHintTooltip::String(s) => lsp::InlayHintTooltip::String(s),
-                    InlayHintTooltip::MarkupContent(markup_content) => {
-                        lsp::InlayHintTooltip::MarkupContent(lsp::MarkupContent {
-                            kind: match markup_content.kind {
-                                HoverBlockKind::PlainText => lsp::MarkupKind::PlainText,
-                                HoverBlockKind::Markdown => lsp::MarkupKind::Markdown,
-                                HoverBlockKind::Code { .. } => return None,
-                            },
-                            value: markup_content.value,
-                        })
-                    }
+    async fn response_from_lsp(
+        self,
+        links: Option<Vec<lsp::DocumentLink>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
+        buffer.update(&mut cx, |buffer, _| {
+            let mut links = links
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_link| DocumentLink {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_link.range, encoding),
+                    lsp_link,
                 })
-            }),
-            label: match hint.label {
-                InlayHintLabel::String(s) => lsp::InlayHintLabel::String(s),
-                InlayHintLabel::LabelParts(label_parts) => lsp::InlayHintLabel::LabelParts(
-                    label_parts
-                        .into_iter()
-                        .map(|part| lsp::InlayHintLabelPart {
-                            value: part.value,
-                            tooltip: part.tooltip.and_then(|tooltip| {
-                                Some(match tooltip {
-                                    InlayHintLabelPartTooltip::String(s) => {
-                                        lsp::InlayHintLabelPartTooltip::String(s)
-                                    }
-                                    InlayHintLabelPartTooltip::MarkupContent(markup_content) => {
-                                        lsp::InlayHintLabelPartTooltip::MarkupContent(
-                                            lsp::MarkupContent {
-                                                kind: match markup_content.kind {
-                                                    HoverBlockKind::PlainText => {
-                                                        lsp::MarkupKind::PlainText
-                                                    }
-                                                    HoverBlockKind::Markdown => {
-                                                        lsp::MarkupKind::Markdown
-                                                    }
-                                                    HoverBlockKind::Code { .. } => return None,
-                                                },
-                                                value: markup_content.value,
-                                            },
-                                        )
-                                    }
-                                })
-                            }),
-                            location: part.location.map(|(_, location)| location),
-                            command: None,
-                        })
-                        .collect(),
-                ),
-            },
-            padding_left: Some(hint.padding_left),
-            padding_right: Some(hint.padding_right),
-            data: match hint.resolve_state {
-                ResolveState::CanResolve(_, data) => data,
-                ResolveState::Resolving | ResolveState::Resolved => None,
-            },
+                .collect::<Vec<_>>();
+            links.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            links
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
         }
     }
 
-    pub fn can_resolve_inlays(capabilities: &ServerCapabilities) -> bool {
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links
+                .iter()
+                .map(LspStore::serialize_document_link)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(LspStore::deserialize_document_link)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetDocumentLinks {
+    pub fn can_resolve_link(capabilities: &ServerCapabilities) -> bool {
         capabilities
-            .inlay_hint_provider
+            .document_link_provider
             .as_ref()
-            .and_then(|options| match options {
-                OneOf::Left(_is_supported) => None,
-                OneOf::Right(capabilities) => match capabilities {
-                    lsp::InlayHintServerCapabilities::Options(o) => o.resolve_provider,
-                    lsp::InlayHintServerCapabilities::RegistrationOptions(o) => {
-                        o.inlay_hint_options.resolve_provider
-                    }
-                },
-            })
+            .and_then(|options| options.resolve_provider)
             .unwrap_or(false)
     }
 }
 
 #[async_trait(?Send)]
-impl LspCommand for InlayHints {
-    type Response = Vec<InlayHint>;
-    type LspRequest = lsp::InlayHintRequest;
-    type ProtoRequest = proto::InlayHints;
+impl LspCommand for GetDocumentColors {
+    type Response = Vec<DocumentColor>;
+    type LspRequest = lsp::request::DocumentColor;
+    type ProtoRequest = proto::GetDocumentColors;
+
+    fn display_name(&self) -> &str {
+        "Get document colors"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            capabilities.server_capabilities.color_provider,
+            None | Some(lsp::ColorProviderCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::DocumentColorParams> {
+        Ok(lsp::DocumentColorParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        colors: Vec<lsp::ColorInformation>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentColor>> {
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
+        buffer.update(&mut cx, |buffer, _| {
+            let mut colors = colors
+                .into_iter()
+                .map(|color| DocumentColor {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, color.range, encoding),
+                    color: color.color,
+                })
+                .collect::<Vec<_>>();
+            colors.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            colors
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentColors {
+        proto::GetDocumentColors {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentColors,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        colors: Vec<DocumentColor>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetDocumentColorsResponse {
+        proto::GetDocumentColorsResponse {
+            colors: colors
+                .iter()
+                .map(LspStore::serialize_document_color)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentColorsResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .colors
+            .into_iter()
+            .map(LspStore::deserialize_document_color)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentColors) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetColorPresentations {
+    type Response = Vec<ColorPresentation>;
+    type LspRequest = lsp::request::ColorPresentationRequest;
+    type ProtoRequest = proto::GetColorPresentations;
+
+    fn display_name(&self) -> &str {
+        "Get color presentations"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        GetDocumentColors.check_capabilities(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::ColorPresentationParams> {
+        Ok(lsp::ColorPresentationParams {
+            text_document: make_text_document_identifier(path)?,
+            color: self.color.color,
+            range: range_to_lsp_encoded(
+                self.color.range.to_point_utf16(buffer),
+                buffer.as_rope(),
+                language_server.position_encoding(),
+            )?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        presentations: Vec<lsp::ColorPresentation>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<ColorPresentation>> {
+        let encoding = position_encoding(&lsp_store, server_id, &cx);
+        buffer.update(&mut cx, |buffer, _| {
+            presentations
+                .into_iter()
+                .map(|presentation| {
+                    // Without an explicit edit, the label replaces the color literal.
+                    let edit = match presentation.text_edit {
+                        Some(edit) => (
+                            anchor_range_from_lsp(buffer, edit.range, encoding),
+                            edit.new_text,
+                        ),
+                        None => (self.color.range.clone(), presentation.label.clone()),
+                    };
+                    let edits = iter::once(edit)
+                        .chain(
+                            presentation
+                                .additional_text_edits
+                                .into_iter()
+                                .flatten()
+                                .map(|edit| {
+                                    (
+                                        anchor_range_from_lsp(buffer, edit.range, encoding),
+                                        edit.new_text,
+                                    )
+                                }),
+                        )
+                        .collect();
+                    ColorPresentation {
+                        label: presentation.label,
+                        edits,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetColorPresentations {
+        proto::GetColorPresentations {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            color: Some(LspStore::serialize_document_color(&self.color)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetColorPresentations,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let color = message
+            .color
+            .map(LspStore::deserialize_document_color)
+            .ok_or_else(|| anyhow!("missing color"))??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self { color })
+    }
+
+    fn response_to_proto(
+        presentations: Vec<ColorPresentation>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetColorPresentationsResponse {
+        proto::GetColorPresentationsResponse {
+            presentations: presentations
+                .into_iter()
+                .map(|presentation| proto::ColorPresentation {
+                    label: presentation.label,
+                    edits: presentation
+                        .edits
+                        .into_iter()
+                        .map(|(range, new_text)| proto::ColorPresentationEdit {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                            new_text,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetColorPresentationsResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .presentations
+            .into_iter()
+            .map(|presentation| {
+                let edits = presentation
+                    .edits
+                    .into_iter()
+                    .map(|edit| {
+                        let start = edit
+                            .start
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid start"))?;
+                        let end = edit
+                            .end
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid end"))?;
+                        Ok((start..end, edit.new_text))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(ColorPresentation {
+                    label: presentation.label,
+                    edits,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetColorPresentations) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for OnTypeFormatting {
+    type Response = Option<Transaction>;
+    type LspRequest = lsp::request::OnTypeFormatting;
+    type ProtoRequest = proto::OnTypeFormatting;
+
+    fn display_name(&self) -> &str {
+        "Formatting on typing"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        let Some(on_type_formatting_options) = &capabilities
+            .server_capabilities
+            .document_on_type_formatting_provider
+        else {
+            return false;
+        };
+        on_type_formatting_options
+            .first_trigger_character
+            .contains(&self.trigger)
+            || on_type_formatting_options
+                .more_trigger_character
+                .iter()
+                .flatten()
+                .any(|chars| chars.contains(&self.trigger))
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::DocumentOnTypeFormattingParams> {
+        Ok(lsp::DocumentOnTypeFormattingParams {
+            text_document_position: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
+            ch: self.trigger.clone(),
+            options: self.options.clone(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TextEdit>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Option<Transaction>> {
+        if let Some(edits) = message {
+            let (lsp_adapter, lsp_server) =
+                language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+            LocalLspStore::deserialize_text_edits(
+                lsp_store,
+                buffer,
+                edits,
+                self.push_to_history,
+                lsp_adapter,
+                lsp_server,
+                &mut cx,
+            )
+            .await
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::OnTypeFormatting {
+        proto::OnTypeFormatting {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            trigger: self.trigger.clone(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::OnTypeFormatting,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+
+        let options = buffer.update(&mut cx, |buffer, cx| {
+            lsp_formatting_options(
+                language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx).as_ref(),
+            )
+        })?;
+
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            trigger: message.trigger.clone(),
+            options,
+            push_to_history: false,
+        })
+    }
+
+    fn response_to_proto(
+        response: Option<Transaction>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut App,
+    ) -> proto::OnTypeFormattingResponse {
+        proto::OnTypeFormattingResponse {
+            transaction: response
+                .map(|transaction| language::proto::serialize_transaction(&transaction)),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::OnTypeFormattingResponse,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Option<Transaction>> {
+        let Some(transaction) = message.transaction else {
+            return Ok(None);
+        };
+        Ok(Some(language::proto::deserialize_transaction(transaction)?))
+    }
+
+    fn buffer_id_from_proto(message: &proto::OnTypeFormatting) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl InlayHints {
+    pub async fn lsp_to_project_hint(
+        lsp_hint: lsp::InlayHint,
+        buffer_handle: &Entity<Buffer>,
+        server_id: LanguageServerId,
+        encoding: PositionEncoding,
+        resolve_state: ResolveState,
+        force_no_type_left_padding: bool,
+        cx: &mut AsyncApp,
+    ) -> anyhow::Result<InlayHint> {
+        let kind = lsp_hint.kind.and_then(|kind| match kind {
+            lsp::InlayHintKind::TYPE => Some(InlayHintKind::Type),
+            lsp::InlayHintKind::PARAMETER => Some(InlayHintKind::Parameter),
+            _ => None,
+        });
+
+        let position = buffer_handle.update(cx, |buffer, _| {
+            let position = buffer.clip_point_utf16(
+                point_from_lsp_encoded(lsp_hint.position, buffer.as_rope(), encoding),
+                Bias::Left,
+            );
+            if kind == Some(InlayHintKind::Parameter) {
+                buffer.anchor_before(position)
+            } else {
+                buffer.anchor_after(position)
+            }
+        })?;
+        let label = Self::lsp_inlay_label_to_project(lsp_hint.label, server_id)
+            .await
+            .context("lsp to project inlay hint conversion")?;
+        let padding_left = if force_no_type_left_padding && kind == Some(InlayHintKind::Type) {
+            false
+        } else {
+            lsp_hint.padding_left.unwrap_or(false)
+        };
+
+        Ok(InlayHint {
+            position,
+            padding_left,
+            padding_right: lsp_hint.padding_right.unwrap_or(false),
+            label,
+            kind,
+            tooltip: lsp_hint.tooltip.map(|tooltip| match tooltip {
+                lsp::InlayHintTooltip::String(s) => InlayHintTooltip::String(s),
+                lsp::InlayHintTooltip::MarkupContent(markup_content) => {
+                    InlayHintTooltip::MarkupContent(MarkupContent {
+                        kind: match markup_content.kind {
+                            lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
+                            lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
+                        },
+                        value: markup_content.value,
+                    })
+                }
+            }),
+            resolve_state,
+        })
+    }
+
+    async fn lsp_inlay_label_to_project(
+        lsp_label: lsp::InlayHintLabel,
+        server_id: LanguageServerId,
+    ) -> anyhow::Result<InlayHintLabel> {
+        let label = match lsp_label {
+            lsp::InlayHintLabel::String(s) => InlayHintLabel::String(s),
+            lsp::InlayHintLabel::LabelParts(lsp_parts) => {
+                let mut parts = Vec::with_capacity(lsp_parts.len());
+                for lsp_part in lsp_parts {
+                    parts.push(InlayHintLabelPart {
+                        value: lsp_part.value,
+                        tooltip: lsp_part.tooltip.map(|tooltip| match tooltip {
+                            lsp::InlayHintLabelPartTooltip::String(s) => {
+                                InlayHintLabelPartTooltip::String(s)
+                            }
+                            lsp::InlayHintLabelPartTooltip::MarkupContent(markup_content) => {
+                                InlayHintLabelPartTooltip::MarkupContent(MarkupContent {
+                                    kind: match markup_content.kind {
+                                        lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
+                                        lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
+                                    },
+                                    value: markup_content.value,
+                                })
+                            }
+                        }),
+                        location: Some(server_id).zip(lsp_part.location),
+                    });
+                }
+                InlayHintLabel::LabelParts(parts)
+            }
+        };
+
+        Ok(label)
+    }
+
+    pub fn project_to_proto_hint(response_hint: InlayHint) -> proto::InlayHint {
+        let (state, lsp_resolve_state) = match response_hint.resolve_state {
+            ResolveState::Resolved => (0, None),
+            ResolveState::CanResolve(server_id, resolve_data) => (
+                1,
+                Some(proto::resolve_state::LspResolveState {
+                    server_id: server_id.0 as u64,
+                    value: resolve_data.map(|json_data| {
+                        serde_json::to_string(&json_data)
+                            .expect("failed to serialize resolve json data")
+                    }),
+                }),
+            ),
+            ResolveState::Resolving => (2, None),
+        };
+        let resolve_state = Some(proto::ResolveState {
+            state,
+            lsp_resolve_state,
+        });
+        proto::InlayHint {
+            position: Some(language::proto::serialize_anchor(&response_hint.position)),
+            padding_left: response_hint.padding_left,
+            padding_right: response_hint.padding_right,
+            label: Some(proto::InlayHintLabel {
+                label: Some(match response_hint.label {
+                    InlayHintLabel::String(s) => proto::inlay_hint_label::Label::Value(s),
+                    InlayHintLabel::LabelParts(label_parts) => {
+                        proto::inlay_hint_label::Label::LabelParts(proto::InlayHintLabelParts {
+                            parts: label_parts.into_iter().map(|label_part| {
+                                let location_url = label_part.location.as_ref().map(|(_, location)| location.uri.to_string());
+                                let location_range_start = label_part.location.as_ref().map(|(_, location)| point_from_lsp(location.range.start).0).map(|point| proto::PointUtf16 { row: point.row, column: point.column });
+                                let location_range_end = label_part.location.as_ref().map(|(_, location)| point_from_lsp(location.range.end).0).map(|point| proto::PointUtf16 { row: point.row, column: point.column });
+                                proto::InlayHintLabelPart {
+                                value: label_part.value,
+                                tooltip: label_part.tooltip.map(|tooltip| {
+                                    let proto_tooltip = match tooltip {
+                                        InlayHintLabelPartTooltip::String(s) => proto::inlay_hint_label_part_tooltip::Content::Value(s),
+                                        InlayHintLabelPartTooltip::MarkupContent(markup_content) => proto::inlay_hint_label_part_tooltip::Content::MarkupContent(proto::MarkupContent {
+                                            is_markdown: markup_content.kind == HoverBlockKind::Markdown,
+                                            value: markup_content.value,
+                                        }),
+                                    };
+                                    proto::InlayHintLabelPartTooltip {content: Some(proto_tooltip)}
+                                }),
+                                location_url,
+                                location_range_start,
+                                location_range_end,
+                                language_server_id: label_part.location.as_ref().map(|(server_id, _)| server_id.0 as u64),
+                            }}).collect()
+                        })
+                    }
+                }),
+            }),
+            kind: response_hint.kind.map(|kind| kind.name().to_string()),
+            tooltip: response_hint.tooltip.map(|response_tooltip| {
+                let proto_tooltip = match response_tooltip {
+                    InlayHintTooltip::String(s) => proto::inlay_hint_tooltip::Content::Value(s),
+                    InlayHintTooltip::MarkupContent(markup_content) => {
+                        proto::inlay_hint_tooltip::Content::MarkupContent(proto::MarkupContent {
+                            is_markdown: markup_content.kind == HoverBlockKind::Markdown,
+                            value: markup_content.value,
+                        })
+                    }
+                };
+                proto::InlayHintTooltip {
+                    content: Some(proto_tooltip),
+                }
+            }),
+            resolve_state,
+        }
+    }
+
+    pub fn proto_to_project_hint(message_hint: proto::InlayHint) -> anyhow::Result<InlayHint> {
+        let resolve_state = message_hint.resolve_state.as_ref().unwrap_or_else(|| {
+            panic!("incorrect proto inlay hint message: no resolve state in hint {message_hint:?}",)
+        });
+        let resolve_state_data = resolve_state
+            .lsp_resolve_state.as_ref()
+            .map(|lsp_resolve_state| {
+                let value = lsp_resolve_state.value.as_deref().map(|value| {
+                    serde_json::from_str::<Option<lsp::LSPAny>>(value)
+                        .with_context(|| format!("incorrect proto inlay hint message: non-json resolve state {lsp_resolve_state:?}"))
+                }).transpose()?.flatten();
+                anyhow::Ok((LanguageServerId(lsp_resolve_state.server_id as usize), value))
+            })
+            .transpose()?;
+        let resolve_state = match resolve_state.state {
+            0 => ResolveState::Resolved,
+            1 => {
+                let (server_id, lsp_resolve_state) = resolve_state_data.with_context(|| {
+                    format!(
+                        "No lsp resolve data for the hint that can be resolved: {message_hint:?}"
+                    )
+                })?;
+                ResolveState::CanResolve(server_id, lsp_resolve_state)
+            }
+            2 => ResolveState::Resolving,
+            invalid => {
+                anyhow::bail!("Unexpected resolve state {invalid} for hint {message_hint:?}")
+            }
+        };
+        Ok(InlayHint {
+            position: message_hint
+                .position
+                .and_then(language::proto::deserialize_anchor)
+                .context("invalid position")?,
+            label: match message_hint
+                .label
+                .and_then(|label| label.label)
+                .context("missing label")?
+            {
+                proto::inlay_hint_label::Label::Value(s) => InlayHintLabel::String(s),
+                proto::inlay_hint_label::Label::LabelParts(parts) => {
+                    let mut label_parts = Vec::new();
+                    for part in parts.parts {
+                        label_parts.push(InlayHintLabelPart {
+                            value: part.value,
+                            tooltip: part.tooltip.map(|tooltip| match tooltip.content {
+                                Some(proto::inlay_hint_label_part_tooltip::Content::Value(s)) => {
+                                    InlayHintLabelPartTooltip::String(s)
+                                }
+                                Some(
+                                    proto::inlay_hint_label_part_tooltip::Content::MarkupContent(
+                                        markup_content,
+                                    ),
+                                ) => InlayHintLabelPartTooltip::MarkupContent(MarkupContent {
+                                    kind: if markup_content.is_markdown {
+                                        HoverBlockKind::Markdown
+                                    } else {
+                                        HoverBlockKind::PlainText
+                                    },
+                                    value: markup_content.value,
+                                }),
+                                None => InlayHintLabelPartTooltip::String(String::new()),
+                            }),
+                            location: {
+                                match part
+                                    .location_url
+                                    .zip(
+                                        part.location_range_start.and_then(|start| {
+                                            Some(start..part.location_range_end?)
+                                        }),
+                                    )
+                                    .zip(part.language_server_id)
+                                {
+                                    Some(((uri, range), server_id)) => Some((
+                                        LanguageServerId(server_id as usize),
+                                        lsp::Location {
+                                            uri: lsp::Url::parse(&uri)
+                                                .context("invalid uri in hint part {part:?}")?,
+                                            range: lsp::Range::new(
+                                                point_to_lsp(PointUtf16::new(
+                                                    range.start.row,
+                                                    range.start.column,
+                                                )),
+                                                point_to_lsp(PointUtf16::new(
+                                                    range.end.row,
+                                                    range.end.column,
+                                                )),
+                                            ),
+                                        },
+                                    )),
+                                    None => None,
+                                }
+                            },
+                        });
+                    }
+
+                    InlayHintLabel::LabelParts(label_parts)
+                }
+            },
+            padding_left: message_hint.padding_left,
+            padding_right: message_hint.padding_right,
+            kind: message_hint
+                .kind
+                .as_deref()
+                .and_then(InlayHintKind::from_name),
+            tooltip: message_hint.tooltip.and_then(|tooltip| {
+                Some(match tooltip.content? {
+                    proto::inlay_hint_tooltip::Content::Value(s) => InlayHintTooltip::String(s),
+                    proto::inlay_hint_tooltip::Content::MarkupContent(markup_content) => {
+                        InlayHintTooltip::MarkupContent(MarkupContent {
+                            kind: if markup_content.is_markdown {
+                                HoverBlockKind::Markdown
+                            } else {
+                                HoverBlockKind::PlainText
+                            },
+                            value: markup_content.value,
+                        })
+                    }
+                })
+            }),
+            resolve_state,
+        })
+    }
+
+    pub fn project_to_lsp_hint(
+        hint: InlayHint,
+        snapshot: &BufferSnapshot,
+        encoding: PositionEncoding,
+    ) -> lsp::InlayHint {
+        lsp::InlayHint {
+            position: point_to_lsp_encoded(
+                hint.position.to_point_utf16(snapshot),
+                snapshot.as_rope(),
+                encoding,
+            ),
+            kind: hint.kind.map(|kind| match kind {
+                InlayHintKind::Type => lsp::InlayHintKind::TYPE,
+                InlayHintKind::Parameter => lsp::InlayHintKind::PARAMETER,
+            }),
+            text_edits: None,
+            tooltip: hint.tooltip.and_then(|tooltip| {
+                Some(match tooltip {
+                    InlayHintTooltip::String(s) => lsp::InlayHintTooltip::String(s),
+                    InlayHintTooltip::MarkupContent(markup_content) => {
+                        lsp::InlayHintTooltip::MarkupContent(lsp::MarkupContent {
+                            kind: match markup_content.kind {
+                                HoverBlockKind::PlainText => lsp::MarkupKind::PlainText,
+                                HoverBlockKind::Markdown => lsp::MarkupKind::Markdown,
+                                HoverBlockKind::Code { .. } => return None,
+                            },
+                            value: markup_content.value,
+                        })
+                    }
+                })
+            }),
+            label: match hint.label {
+                InlayHintLabel::String(s) => lsp::InlayHintLabel::String(s),
+                InlayHintLabel::LabelParts(label_parts) => lsp::InlayHintLabel::LabelParts(
+                    label_parts
+                        .into_iter()
+                        .map(|part| lsp::InlayHintLabelPart {
+                            value: part.value,
+                            tooltip: part.tooltip.and_then(|tooltip| {
+                                Some(match tooltip {
+                                    InlayHintLabelPartTooltip::String(s) => {
+                                        lsp::InlayHintLabelPartTooltip::String(s)
+                                    }
+                                    InlayHintLabelPartTooltip::MarkupContent(markup_content) => {
+                                        lsp::InlayHintLabelPartTooltip::MarkupContent(
+                                            lsp::MarkupContent {
+                                                kind: match markup_content.kind {
+                                                    HoverBlockKind::PlainText => {
+                                                        lsp::MarkupKind::PlainText
+                                                    }
+                                                    HoverBlockKind::Markdown => {
+                                                        lsp::MarkupKind::Markdown
+                                                    }
+                                                    HoverBlockKind::Code { .. } => return None,
+                                                },
+                                                value: markup_content.value,
+                                            },
+                                        )
+                                    }
+                                })
+                            }),
+                            location: part.location.map(|(_, location)| location),
+                            command: None,
+                        })
+                        .collect(),
+                ),
+            },
+            padding_left: Some(hint.padding_left),
+            padding_right: Some(hint.padding_right),
+            data: match hint.resolve_state {
+                ResolveState::CanResolve(_, data) => data,
+                ResolveState::Resolving | ResolveState::Resolved => None,
+            },
+        }
+    }
+
+    pub fn can_resolve_inlays(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .inlay_hint_provider
+            .as_ref()
+            .and_then(|options| match options {
+                OneOf::Left(_is_supported) => None,
+                OneOf::Right(capabilities) => match capabilities {
+                    lsp::InlayHintServerCapabilities::Options(o) => o.resolve_provider,
+                    lsp::InlayHintServerCapabilities::RegistrationOptions(o) => {
+                        o.inlay_hint_options.resolve_provider
+                    }
+                },
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for InlayHints {
+    type Response = Vec<InlayHint>;
+    type LspRequest = lsp::InlayHintRequest;
+    type ProtoRequest = proto::InlayHints;
+
+    fn display_name(&self) -> &str {
+        "Inlay hints"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        let Some(inlay_hint_provider) = &capabilities.server_capabilities.inlay_hint_provider
+        else {
+            return false;
+        };
+        match inlay_hint_provider {
+            lsp::OneOf::Left(enabled) => *enabled,
+            lsp::OneOf::Right(inlay_hint_capabilities) => match inlay_hint_capabilities {
+                lsp::InlayHintServerCapabilities::Options(_) => true,
+                lsp::InlayHintServerCapabilities::RegistrationOptions(_) => false,
+            },
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::InlayHintParams> {
+        Ok(lsp::InlayHintParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: file_path_to_lsp_url(path)?,
+            },
+            range: range_to_lsp_encoded(
+                self.range.to_point_utf16(buffer),
+                buffer.as_rope(),
+                language_server.position_encoding(),
+            )?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::InlayHint>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> anyhow::Result<Vec<InlayHint>> {
+        let (lsp_adapter, lsp_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        // `typescript-language-server` adds padding to the left for type hints, turning
+        // `const foo: boolean` into `const foo : boolean` which looks odd.
+        // `rust-analyzer` does not have the padding for this case, and we have to accommodate both.
+        //
+        // We could trim the whole string, but being pessimistic on par with the situation above,
+        // there might be a hint with multiple whitespaces at the end(s) which we need to display properly.
+        // Hence let's use a heuristic first to handle the most awkward case and look for more.
+        let force_no_type_left_padding =
+            lsp_adapter.name.0.as_ref() == "typescript-language-server";
+        let encoding = lsp_server.position_encoding();
+
+        let hints = message.unwrap_or_default().into_iter().map(|lsp_hint| {
+            let resolve_state = if InlayHints::can_resolve_inlays(&lsp_server.capabilities()) {
+                ResolveState::CanResolve(lsp_server.server_id(), lsp_hint.data.clone())
+            } else {
+                ResolveState::Resolved
+            };
+
+            let buffer = buffer.clone();
+            cx.spawn(move |mut cx| async move {
+                InlayHints::lsp_to_project_hint(
+                    lsp_hint,
+                    &buffer,
+                    server_id,
+                    encoding,
+                    resolve_state,
+                    force_no_type_left_padding,
+                    &mut cx,
+                )
+                .await
+            })
+        });
+        future::join_all(hints)
+            .await
+            .into_iter()
+            .collect::<anyhow::Result<_>>()
+            .context("lsp to project inlay hints conversion")
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::InlayHints {
+        proto::InlayHints {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            start: Some(language::proto::serialize_anchor(&self.range.start)),
+            end: Some(language::proto::serialize_anchor(&self.range.end)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::InlayHints,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let start = message
+            .start
+            .and_then(language::proto::deserialize_anchor)
+            .context("invalid start")?;
+        let end = message
+            .end
+            .and_then(language::proto::deserialize_anchor)
+            .context("invalid end")?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+
+        Ok(Self { range: start..end })
+    }
+
+    fn response_to_proto(
+        response: Vec<InlayHint>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::InlayHintsResponse {
+        proto::InlayHintsResponse {
+            hints: response
+                .into_iter()
+                .map(InlayHints::project_to_proto_hint)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::InlayHintsResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> anyhow::Result<Vec<InlayHint>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+
+        let mut hints = Vec::new();
+        for message_hint in message.hints {
+            hints.push(InlayHints::proto_to_project_hint(message_hint)?);
+        }
+
+        Ok(hints)
+    }
+
+    fn buffer_id_from_proto(message: &proto::InlayHints) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for LinkedEditingRange {
+    type Response = Vec<Range<Anchor>>;
+    type LspRequest = lsp::request::LinkedEditingRange;
+    type ProtoRequest = proto::LinkedEditingRange;
+
+    fn display_name(&self) -> &str {
+        "Linked editing range"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        let Some(linked_editing_options) = &capabilities
+            .server_capabilities
+            .linked_editing_range_provider
+        else {
+            return false;
+        };
+        if let LinkedEditingRangeServerCapabilities::Simple(false) = linked_editing_options {
+            return false;
+        }
+        true
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::LinkedEditingRangeParams> {
+        let position = self.position.to_point_utf16(&buffer.snapshot());
+        Ok(lsp::LinkedEditingRangeParams {
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                position,
+                buffer,
+                language_server,
+            )?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::LinkedEditingRanges>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncApp,
+    ) -> Result<Vec<Range<Anchor>>> {
+        if let Some(lsp::LinkedEditingRanges { mut ranges, .. }) = message {
+            let encoding = position_encoding(&lsp_store, server_id, &cx);
+            ranges.sort_by_key(|range| range.start);
+
+            buffer.read_with(&cx, |buffer, _| {
+                ranges
+                    .into_iter()
+                    .map(|range| {
+                        let start = buffer.clip_point_utf16(
+                            point_from_lsp_encoded(range.start, buffer.as_rope(), encoding),
+                            Bias::Left,
+                        );
+                        let end = buffer.clip_point_utf16(
+                            point_from_lsp_encoded(range.end, buffer.as_rope(), encoding),
+                            Bias::Left,
+                        );
+                        buffer.anchor_before(start)..buffer.anchor_after(end)
+                    })
+                    .collect()
+            })
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::LinkedEditingRange {
+        proto::LinkedEditingRange {
+            project_id,
+            buffer_id: buffer.remote_id().to_proto(),
+            position: Some(serialize_anchor(&self.position)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::LinkedEditingRange,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let position = deserialize_anchor(position).ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_anchors([position]))?
+            .await?;
+        Ok(Self { position })
+    }
+
+    fn response_to_proto(
+        response: Vec<Range<Anchor>>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::LinkedEditingRangeResponse {
+        proto::LinkedEditingRangeResponse {
+            items: response
+                .into_iter()
+                .map(|range| proto::AnchorRange {
+                    start: Some(serialize_anchor(&range.start)),
+                    end: Some(serialize_anchor(&range.end)),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::LinkedEditingRangeResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<Range<Anchor>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let items: Vec<Range<Anchor>> = message
+            .items
+            .into_iter()
+            .filter_map(|range| {
+                let start = deserialize_anchor(range.start?)?;
+                let end = deserialize_anchor(range.end?)?;
+                Some(start..end)
+            })
+            .collect();
+        for range in &items {
+            buffer
+                .update(&mut cx, |buffer, _| {
+                    buffer.wait_for_anchors([range.start, range.end])
+                })?
+                .await?;
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::LinkedEditingRange) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn anchor_range_from_lsp(
+    buffer: &Buffer,
+    range: lsp::Range,
+    encoding: PositionEncoding,
+) -> Range<Anchor> {
+    let start = buffer.clip_point_utf16(
+        point_from_lsp_encoded(range.start, buffer.as_rope(), encoding),
+        Bias::Left,
+    );
+    let end = buffer.clip_point_utf16(
+        point_from_lsp_encoded(range.end, buffer.as_rope(), encoding),
+        Bias::Left,
+    );
+    buffer.anchor_after(start)..buffer.anchor_before(end)
+}
+
+async fn call_hierarchy_item_from_lsp(
+    lsp_item: lsp::CallHierarchyItem,
+    lsp_store: &Entity<LspStore>,
+    lsp_adapter: &CachedLspAdapter,
+    language_server: &LanguageServer,
+    cx: &mut AsyncApp,
+) -> Result<CallHierarchyItem> {
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.open_local_buffer_via_lsp(
+                lsp_item.uri.clone(),
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+    let encoding = language_server.position_encoding();
+    let (range, selection_range) = buffer.read_with(cx, |buffer, _| {
+        (
+            anchor_range_from_lsp(buffer, lsp_item.range, encoding),
+            anchor_range_from_lsp(buffer, lsp_item.selection_range, encoding),
+        )
+    })?;
+    Ok(CallHierarchyItem {
+        server_id: language_server.server_id(),
+        buffer,
+        range,
+        selection_range,
+        lsp_item,
+    })
+}
+
+fn call_hierarchy_item_to_proto(
+    item: CallHierarchyItem,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> proto::CallHierarchyItem {
+    lsp_store
+        .buffer_store()
+        .update(cx, |buffer_store, cx| {
+            buffer_store.create_buffer_for_peer(&item.buffer, peer_id, cx)
+        })
+        .detach_and_log_err(cx);
+    proto::CallHierarchyItem {
+        server_id: item.server_id.to_proto(),
+        buffer_id: item.buffer.read(cx).remote_id().into(),
+        range: Some(serialize_anchor_range(item.range)),
+        selection_range: Some(serialize_anchor_range(item.selection_range)),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+async fn call_hierarchy_item_from_proto(
+    item: proto::CallHierarchyItem,
+    lsp_store: &Entity<LspStore>,
+    cx: &mut AsyncApp,
+) -> Result<CallHierarchyItem> {
+    let buffer_id = BufferId::new(item.buffer_id)?;
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let range = deserialize_anchor_range(item.range.context("missing item range")?)?;
+    let selection_range = deserialize_anchor_range(
+        item.selection_range
+            .context("missing item selection range")?,
+    )?;
+    buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_anchors([
+                range.start,
+                range.end,
+                selection_range.start,
+                selection_range.end,
+            ])
+        })?
+        .await?;
+    Ok(CallHierarchyItem {
+        server_id: LanguageServerId::from_proto(item.server_id),
+        buffer,
+        range,
+        selection_range,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
+
+fn call_hierarchy_calls_to_proto(
+    calls: Vec<CallHierarchyCall>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> Vec<proto::CallHierarchyCall> {
+    calls
+        .into_iter()
+        .map(|call| {
+            let call_sites = call
+                .call_sites
+                .into_iter()
+                .map(|location| {
+                    lsp_store
+                        .buffer_store()
+                        .update(cx, |buffer_store, cx| {
+                            buffer_store.create_buffer_for_peer(&location.buffer, peer_id, cx)
+                        })
+                        .detach_and_log_err(cx);
+                    proto::Location {
+                        start: Some(serialize_anchor(&location.range.start)),
+                        end: Some(serialize_anchor(&location.range.end)),
+                        buffer_id: location.buffer.read(cx).remote_id().into(),
+                    }
+                })
+                .collect();
+            proto::CallHierarchyCall {
+                item: Some(call_hierarchy_item_to_proto(
+                    call.item, lsp_store, peer_id, cx,
+                )),
+                call_sites,
+            }
+        })
+        .collect()
+}
+
+async fn call_hierarchy_calls_from_proto(
+    calls: Vec<proto::CallHierarchyCall>,
+    lsp_store: Entity<LspStore>,
+    mut cx: AsyncApp,
+) -> Result<Vec<CallHierarchyCall>> {
+    let mut result = Vec::new();
+    for call in calls {
+        let item = call_hierarchy_item_from_proto(
+            call.item.context("missing call hierarchy item")?,
+            &lsp_store,
+            &mut cx,
+        )
+        .await?;
+        let mut call_sites = Vec::new();
+        for location in call.call_sites {
+            let buffer_id = BufferId::new(location.buffer_id)?;
+            let buffer = lsp_store
+                .update(&mut cx, |lsp_store, cx| {
+                    lsp_store.wait_for_remote_buffer(buffer_id, cx)
+                })?
+                .await?;
+            let start = location
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing call site start"))?;
+            let end = location
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing call site end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            call_sites.push(Location {
+                buffer,
+                range: start..end,
+            });
+        }
+        result.push(CallHierarchyCall { item, call_sites });
+    }
+    Ok(result)
+}
+
+fn supports_call_hierarchy(capabilities: &AdapterServerCapabilities) -> bool {
+    match &capabilities.server_capabilities.call_hierarchy_provider {
+        Some(lsp::CallHierarchyServerCapability::Simple(has_support)) => *has_support,
+        Some(lsp::CallHierarchyServerCapability::Options(_)) => true,
+        None => false,
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare call hierarchy"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyPrepareParams> {
+        Ok(lsp::CallHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::CallHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut result = Vec::new();
+        for lsp_item in items.into_iter().flatten() {
+            result.push(
+                call_hierarchy_item_from_lsp(
+                    lsp_item,
+                    &lsp_store,
+                    &lsp_adapter,
+                    &language_server,
+                    &mut cx,
+                )
+                .await?,
+            );
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: response
+                .into_iter()
+                .map(|item| call_hierarchy_item_to_proto(item, lsp_store, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(call_hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn display_name(&self) -> &str {
+        "Find incoming calls"
+    }
+
+    fn status(&self) -> Option<String> {
+        Some("Finding incoming calls...".to_owned())
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyIncomingCallsParams> {
+        Ok(lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        calls: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut result = Vec::new();
+        for call in calls.into_iter().flatten() {
+            let item = call_hierarchy_item_from_lsp(
+                call.from,
+                &lsp_store,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            // Incoming call sites are located in the caller's document.
+            let call_sites: Vec<Location> = item.buffer.read_with(&cx, |caller_buffer, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: item.buffer.clone(),
+                        range: anchor_range_from_lsp(
+                            caller_buffer,
+                            range,
+                            language_server.position_encoding(),
+                        ),
+                    })
+                    .collect()
+            })?;
+            result.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetIncomingCallsResponse {
+        proto::GetIncomingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetIncomingCallsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn display_name(&self) -> &str {
+        "Find outgoing calls"
+    }
+
+    fn status(&self) -> Option<String> {
+        Some("Finding outgoing calls...".to_owned())
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyOutgoingCallsParams> {
+        Ok(lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        calls: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut result = Vec::new();
+        for call in calls.into_iter().flatten() {
+            let item = call_hierarchy_item_from_lsp(
+                call.to,
+                &lsp_store,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            // Outgoing call sites are located in the document of the queried item.
+            let call_sites: Vec<Location> = buffer.read_with(&cx, |callee_buffer, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: buffer.clone(),
+                        range: anchor_range_from_lsp(
+                            callee_buffer,
+                            range,
+                            language_server.position_encoding(),
+                        ),
+                    })
+                    .collect()
+            })?;
+            result.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetOutgoingCallsResponse {
+        proto::GetOutgoingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetOutgoingCallsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        cx: AsyncApp,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+async fn type_hierarchy_items_from_lsp(
+    lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+    lsp_store: Entity<LspStore>,
+    buffer: Entity<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncApp,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+    let encoding = language_server.position_encoding();
+    let mut items = Vec::new();
+    for lsp_item in lsp_items.into_iter().flatten() {
+        let buffer = lsp_store
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.open_local_buffer_via_lsp(
+                    lsp_item.uri.clone(),
+                    language_server.server_id(),
+                    lsp_adapter.name.clone(),
+                    cx,
+                )
+            })?
+            .await?;
+        let (range, selection_range) = buffer.read_with(&cx, |buffer, _| {
+            (
+                anchor_range_from_lsp(buffer, lsp_item.range, encoding),
+                anchor_range_from_lsp(buffer, lsp_item.selection_range, encoding),
+            )
+        })?;
+        items.push(TypeHierarchyItem {
+            server_id: language_server.server_id(),
+            buffer,
+            range,
+            selection_range,
+            lsp_item,
+        });
+    }
+    Ok(items)
+}
+
+fn type_hierarchy_items_to_proto(
+    items: Vec<TypeHierarchyItem>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> Vec<proto::TypeHierarchyItem> {
+    items
+        .into_iter()
+        .map(|item| {
+            lsp_store
+                .buffer_store()
+                .update(cx, |buffer_store, cx| {
+                    buffer_store.create_buffer_for_peer(&item.buffer, peer_id, cx)
+                })
+                .detach_and_log_err(cx);
+            proto::TypeHierarchyItem {
+                server_id: item.server_id.to_proto(),
+                buffer_id: item.buffer.read(cx).remote_id().into(),
+                range: Some(serialize_anchor_range(item.range)),
+                selection_range: Some(serialize_anchor_range(item.selection_range)),
+                lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+            }
+        })
+        .collect()
+}
+
+async fn type_hierarchy_items_from_proto(
+    items: Vec<proto::TypeHierarchyItem>,
+    lsp_store: Entity<LspStore>,
+    mut cx: AsyncApp,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let mut result = Vec::new();
+    for item in items {
+        let buffer_id = BufferId::new(item.buffer_id)?;
+        let buffer = lsp_store
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.wait_for_remote_buffer(buffer_id, cx)
+            })?
+            .await?;
+        let range = deserialize_anchor_range(item.range.context("missing item range")?)?;
+        let selection_range = deserialize_anchor_range(
+            item.selection_range
+                .context("missing item selection range")?,
+        )?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors([
+                    range.start,
+                    range.end,
+                    selection_range.start,
+                    selection_range.end,
+                ])
+            })?
+            .await?;
+        result.push(TypeHierarchyItem {
+            server_id: LanguageServerId::from_proto(item.server_id),
+            buffer,
+            range,
+            selection_range,
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        });
+    }
+    Ok(result)
+}
+
+// `lsp::ServerCapabilities` does not expose `typeHierarchyProvider`, so the type hierarchy
+// commands keep the default `check_capabilities` and let servers without support reject them.
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare type hierarchy"
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::TypeHierarchyPrepareParams> {
+        Ok(lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(
+                path,
+                self.position,
+                buffer,
+                language_server,
+            )?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncApp,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, lsp_store, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        cx: AsyncApp,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
 
     fn display_name(&self) -> &str {
-        "Inlay hints"
+        "Find supertypes"
     }
 
-    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        let Some(inlay_hint_provider) = &capabilities.server_capabilities.inlay_hint_provider
-        else {
-            return false;
-        };
-        match inlay_hint_provider {
-            lsp::OneOf::Left(enabled) => *enabled,
-            lsp::OneOf::Right(inlay_hint_capabilities) => match inlay_hint_capabilities {
-                lsp::InlayHintServerCapabilities::Options(_) => true,
-                lsp::InlayHintServerCapabilities::RegistrationOptions(_) => false,
-            },
-        }
+    fn status(&self) -> Option<String> {
+        Some("Finding supertypes...".to_owned())
     }
 
     fn to_lsp(
         &self,
-        path: &Path,
-        buffer: &Buffer,
+        _: &Path,
+        _: &Buffer,
         _: &Arc<LanguageServer>,
         _: &App,
-    ) -> Result<lsp::InlayHintParams> {
-        Ok(lsp::InlayHintParams {
-            text_document: lsp::TextDocumentIdentifier {
-                uri: file_path_to_lsp_url(path)?,
-            },
-            range: range_to_lsp(self.range.to_point_utf16(buffer))?,
+    ) -> Result<lsp::TypeHierarchySupertypesParams> {
+        Ok(lsp::TypeHierarchySupertypesParams {
+            item: self.item.clone(),
             work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         })
     }
 
     async fn response_from_lsp(
         self,
-        message: Option<Vec<lsp::InlayHint>>,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
         lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
         server_id: LanguageServerId,
-        mut cx: AsyncApp,
-    ) -> anyhow::Result<Vec<InlayHint>> {
-        let (lsp_adapter, lsp_server) =
-            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
-        // `typescript-language-server` adds padding to the left for type hints, turning
-        // `const foo: boolean` into `const foo : boolean` which looks odd.
-        // `rust-analyzer` does not have the padding for this case, and we have to accommodate both.
-        //
-        // We could trim the whole string, but being pessimistic on par with the situation above,
-        // there might be a hint with multiple whitespaces at the end(s) which we need to display properly.
-        // Hence let's use a heuristic first to handle the most awkward case and look for more.
-        let force_no_type_left_padding =
-            lsp_adapter.name.0.as_ref() == "typescript-language-server";
-
-        let hints = message.unwrap_or_default().into_iter().map(|lsp_hint| {
-            let resolve_state = if InlayHints::can_resolve_inlays(&lsp_server.capabilities()) {
-                ResolveState::CanResolve(lsp_server.server_id(), lsp_hint.data.clone())
-            } else {
-                ResolveState::Resolved
-            };
-
-            let buffer = buffer.clone();
-            cx.spawn(move |mut cx| async move {
-                InlayHints::lsp_to_project_hint(
-                    lsp_hint,
-                    &buffer,
-                    server_id,
-                    resolve_state,
-                    force_no_type_left_padding,
-                    &mut cx,
-                )
-                .await
-            })
-        });
-        future::join_all(hints)
-            .await
-            .into_iter()
-            .collect::<anyhow::Result<_>>()
-            .context("lsp to project inlay hints conversion")
+        cx: AsyncApp,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, lsp_store, buffer, server_id, cx).await
     }
 
-    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::InlayHints {
-        proto::InlayHints {
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
             project_id,
             buffer_id: buffer.remote_id().into(),
-            start: Some(language::proto::serialize_anchor(&self.range.start)),
-            end: Some(language::proto::serialize_anchor(&self.range.end)),
-            version: serialize_version(&buffer.version()),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
         }
     }
 
     async fn from_proto(
-        message: proto::InlayHints,
+        message: proto::GetSupertypes,
         _: Entity<LspStore>,
-        buffer: Entity<Buffer>,
-        mut cx: AsyncApp,
+        _: Entity<Buffer>,
+        _: AsyncApp,
     ) -> Result<Self> {
-        let start = message
-            .start
-            .and_then(language::proto::deserialize_anchor)
-            .context("invalid start")?;
-        let end = message
-            .end
-            .and_then(language::proto::deserialize_anchor)
-            .context("invalid end")?;
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&message.version))
-            })?
-            .await?;
-
-        Ok(Self { range: start..end })
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
     }
 
     fn response_to_proto(
-        response: Vec<InlayHint>,
-        _: &mut LspStore,
-        _: PeerId,
-        buffer_version: &clock::Global,
-        _: &mut App,
-    ) -> proto::InlayHintsResponse {
-        proto::InlayHintsResponse {
-            hints: response
-                .into_iter()
-                .map(InlayHints::project_to_proto_hint)
-                .collect(),
-            version: serialize_version(buffer_version),
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetSupertypesResponse {
+        proto::GetSupertypesResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
         }
     }
 
     async fn response_from_proto(
         self,
-        message: proto::InlayHintsResponse,
-        _: Entity<LspStore>,
-        buffer: Entity<Buffer>,
-        mut cx: AsyncApp,
-    ) -> anyhow::Result<Vec<InlayHint>> {
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&message.version))
-            })?
-            .await?;
-
-        let mut hints = Vec::new();
-        for message_hint in message.hints {
-            hints.push(InlayHints::proto_to_project_hint(message_hint)?);
-        }
-
-        Ok(hints)
+        message: proto::GetSupertypesResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        cx: AsyncApp,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
     }
 
-    fn buffer_id_from_proto(message: &proto::InlayHints) -> Result<BufferId> {
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
         BufferId::new(message.buffer_id)
     }
 }
 
 #[async_trait(?Send)]
-impl LspCommand for LinkedEditingRange {
-    type Response = Vec<Range<Anchor>>;
-    type LspRequest = lsp::request::LinkedEditingRange;
-    type ProtoRequest = proto::LinkedEditingRange;
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
 
     fn display_name(&self) -> &str {
-        "Linked editing range"
+        "Find subtypes"
     }
 
-    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        let Some(linked_editing_options) = &capabilities
-            .server_capabilities
-            .linked_editing_range_provider
-        else {
-            return false;
-        };
-        if let LinkedEditingRangeServerCapabilities::Simple(false) = linked_editing_options {
-            return false;
-        }
-        true
+    fn status(&self) -> Option<String> {
+        Some("Finding subtypes...".to_owned())
     }
 
     fn to_lsp(
         &self,
-        path: &Path,
-        buffer: &Buffer,
-        _server: &Arc<LanguageServer>,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
         _: &App,
-    ) -> Result<lsp::LinkedEditingRangeParams> {
-        let position = self.position.to_point_utf16(&buffer.snapshot());
-        Ok(lsp::LinkedEditingRangeParams {
-            text_document_position_params: make_lsp_text_document_position(path, position)?,
+    ) -> Result<lsp::TypeHierarchySubtypesParams> {
+        Ok(lsp::TypeHierarchySubtypesParams {
+            item: self.item.clone(),
             work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         })
     }
 
     async fn response_from_lsp(
         self,
-        message: Option<lsp::LinkedEditingRanges>,
-        _: Entity<LspStore>,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
         buffer: Entity<Buffer>,
-        _server_id: LanguageServerId,
+        server_id: LanguageServerId,
         cx: AsyncApp,
-    ) -> Result<Vec<Range<Anchor>>> {
-        if let Some(lsp::LinkedEditingRanges { mut ranges, .. }) = message {
-            ranges.sort_by_key(|range| range.start);
-
-            buffer.read_with(&cx, |buffer, _| {
-                ranges
-                    .into_iter()
-                    .map(|range| {
-                        let start =
-                            buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
-                        let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
-                        buffer.anchor_before(start)..buffer.anchor_after(end)
-                    })
-                    .collect()
-            })
-        } else {
-            Ok(vec![])
-        }
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, lsp_store, buffer, server_id, cx).await
     }
 
-    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::LinkedEditingRange {
-        proto::LinkedEditingRange {
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
             project_id,
-            buffer_id: buffer.remote_id().to_proto(),
-            position: Some(serialize_anchor(&self.position)),
-            version: serialize_version(&buffer.version()),
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
         }
     }
 
     async fn from_proto(
-        message: proto::LinkedEditingRange,
+        message: proto::GetSubtypes,
         _: Entity<LspStore>,
-        buffer: Entity<Buffer>,
-        mut cx: AsyncApp,
+        _: Entity<Buffer>,
+        _: AsyncApp,
     ) -> Result<Self> {
-        let position = message
-            .position
-            .ok_or_else(|| anyhow!("invalid position"))?;
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&message.version))
-            })?
-            .await?;
-        let position = deserialize_anchor(position).ok_or_else(|| anyhow!("invalid position"))?;
-        buffer
-            .update(&mut cx, |buffer, _| buffer.wait_for_anchors([position]))?
-            .await?;
-        Ok(Self { position })
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
     }
 
     fn response_to_proto(
-        response: Vec<Range<Anchor>>,
-        _: &mut LspStore,
-        _: PeerId,
-        buffer_version: &clock::Global,
-        _: &mut App,
-    ) -> proto::LinkedEditingRangeResponse {
-        proto::LinkedEditingRangeResponse {
-            items: response
-                .into_iter()
-                .map(|range| proto::AnchorRange {
-                    start: Some(serialize_anchor(&range.start)),
-                    end: Some(serialize_anchor(&range.end)),
-                })
-                .collect(),
-            version: serialize_version(buffer_version),
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetSubtypesResponse {
+        proto::GetSubtypesResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
         }
     }
 
     async fn response_from_proto(
         self,
-        message: proto::LinkedEditingRangeResponse,
-        _: Entity<LspStore>,
-        buffer: Entity<Buffer>,
-        mut cx: AsyncApp,
-    ) -> Result<Vec<Range<Anchor>>> {
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&message.version))
-            })?
-            .await?;
-        let items: Vec<Range<Anchor>> = message
-            .items
-            .into_iter()
-            .filter_map(|range| {
-                let start = deserialize_anchor(range.start?)?;
-                let end = deserialize_anchor(range.end?)?;
-                Some(start..end)
-            })
-            .collect();
-        for range in &items {
-            buffer
-                .update(&mut cx, |buffer, _| {
-                    buffer.wait_for_anchors([range.start, range.end])
-                })?
-                .await?;
-        }
-        Ok(items)
+        message: proto::GetSubtypesResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        cx: AsyncApp,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
     }
 
-    fn buffer_id_from_proto(message: &proto::LinkedEditingRange) -> Result<BufferId> {
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
         BufferId::new(message.buffer_id)
     }
 }
@@ -2,7 +2,7 @@ use crate::{lsp_command::LspCommand, lsp_store::LspStore, make_text_document_ide
 use anyhow::{Context as _, Result};
 use async_trait::async_trait;
 use gpui::{App, AsyncApp, Entity};
-use language::{point_to_lsp, proto::deserialize_anchor, Buffer};
+use language::{point_to_lsp_encoded, proto::deserialize_anchor, Buffer};
 use lsp::{LanguageServer, LanguageServerId};
 use rpc::proto::{self, PeerId};
 use serde::{Deserialize, Serialize};
@@ -54,13 +54,17 @@ impl LspCommand for ExpandMacro {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<ExpandMacroParams> {
         Ok(ExpandMacroParams {
             text_document: make_text_document_identifier(path)?,
-            position: point_to_lsp(self.position),
+            position: point_to_lsp_encoded(
+                self.position,
+                buffer.as_rope(),
+                language_server.position_encoding(),
+            ),
         })
     }
 
@@ -182,15 +186,19 @@ impl LspCommand for OpenDocs {
     fn to_lsp(
         &self,
         path: &Path,
-        _: &Buffer,
-        _: &Arc<LanguageServer>,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
         _: &App,
     ) -> Result<OpenDocsParams> {
         Ok(OpenDocsParams {
             text_document: lsp::TextDocumentIdentifier {
                 uri: lsp::Url::from_file_path(path).unwrap(),
             },
-            position: point_to_lsp(self.position),
+            position: point_to_lsp_encoded(
+                self.position,
+                buffer.as_rope(),
+                language_server.position_encoding(),
+            ),
         })
     }
 
@@ -11,8 +11,9 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    CodeAction, CodeLens, Completion, CoreCompletion, DocumentColor, DocumentLink, FoldingRange,
+    Hover, InlayHint, ProjectItem as _, ProjectPath, ProjectTransaction, ResolveState,
+    SemanticToken, SemanticTokens, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -35,12 +36,13 @@ use language::{
     language_settings::{
         language_settings, FormatOnSave, Formatter, LanguageSettings, SelectedFormatter,
     },
-    point_to_lsp,
+    point_from_lsp_encoded, point_to_lsp, point_to_lsp_encoded,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
-    range_from_lsp, range_to_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CodeLabel,
-    Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, File as _, Language, LanguageRegistry,
-    LanguageServerBinaryStatus, LanguageToolchainStore, LocalFile, LspAdapter, LspAdapterDelegate,
-    Patch, PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    range_from_lsp, range_from_lsp_encoded, range_to_lsp_encoded, Bias, Buffer, BufferSnapshot,
+    CachedLspAdapter, CodeLabel, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, File as _,
+    Language, LanguageRegistry, LanguageServerBinaryStatus, LanguageToolchainStore, LocalFile,
+    LspAdapter, LspAdapterDelegate, Patch, PointUtf16, Rope, TextBufferSnapshot, ToOffset,
+    ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     notification::DidRenameFiles, CodeActionKind, CompletionContext, DiagnosticSeverity,
@@ -48,8 +50,8 @@ use lsp::{
     FileOperationPatternKind, FileOperationRegistrationOptions, FileRename, FileSystemWatcher,
     InsertTextFormat, LanguageServer, LanguageServerBinary, LanguageServerBinaryOptions,
     LanguageServerId, LanguageServerName, LspRequestFuture, MessageActionItem, MessageType, OneOf,
-    RenameFilesParams, ServerHealthStatus, ServerStatus, SymbolKind, TextEdit, WillRenameFiles,
-    WorkDoneProgressCancelParams, WorkspaceFolder,
+    PositionEncoding, RenameFilesParams, ServerHealthStatus, ServerStatus, SymbolKind, TextEdit,
+    WillRenameFiles, WorkDoneProgressCancelParams, WorkspaceFolder,
 };
 use node_runtime::read_package_installed_version;
 use parking_lot::Mutex;
@@ -68,7 +70,7 @@ use snippet::Snippet;
 use std::{
     any::Any,
     cell::RefCell,
-    cmp::Ordering,
+    cmp::{Ordering, Reverse},
     convert::TryInto,
     ffi::OsStr,
     iter, mem,
@@ -96,6 +98,7 @@ pub use worktree::{
 };
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(100);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -163,6 +166,11 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, HashMap<LanguageServerId, CachedSemanticTokens>>,
+    /// The result ids of the last diagnostics pulled from each language server, per document.
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    buffer_pull_diagnostics_tasks: HashMap<BufferId, Task<()>>,
+    workspace_pull_diagnostics_tasks: HashMap<LanguageServerId, Task<()>>,
     _subscription: gpui::Subscription,
     lsp_tree: Entity<LanguageServerTree>,
     registered_buffers: HashMap<BufferId, usize>,
@@ -777,6 +785,63 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -1409,8 +1474,13 @@ impl LocalLspStore {
                 // TODO: Instead of using current snapshot, should use the latest snapshot sent to
                 // LSP.
                 let snapshot = buffer_handle.read(cx).snapshot();
+                let encoding = language_server.position_encoding();
                 for range in ranges {
-                    lsp_ranges.push(range_to_lsp(range.to_point_utf16(&snapshot))?);
+                    lsp_ranges.push(range_to_lsp_encoded(
+                        range.to_point_utf16(&snapshot),
+                        snapshot.as_rope(),
+                        encoding,
+                    )?);
                 }
                 anyhow::Ok(())
             })??;
@@ -1475,7 +1545,13 @@ impl LocalLspStore {
                 .await?
         } else if matches!(range_formatting_provider, Some(p) if *p != OneOf::Left(false)) {
             let buffer_start = lsp::Position::new(0, 0);
-            let buffer_end = buffer.update(cx, |b, _| point_to_lsp(b.max_point_utf16()))?;
+            let buffer_end = buffer.update(cx, |b, _| {
+                point_to_lsp_encoded(
+                    b.max_point_utf16(),
+                    b.as_rope(),
+                    language_server.position_encoding(),
+                )
+            })?;
             language_server
                 .request::<lsp::request::RangeFormatting>(lsp::DocumentRangeFormattingParams {
                     text_document: text_document.clone(),
@@ -1693,6 +1769,30 @@ impl LocalLspStore {
                 .then_with(|| a.message.cmp(&b.message))
         }
 
+        let snapshot = self.buffer_snapshot_for_lsp_version(buffer, server_id, version, cx)?;
+        let saved_version = buffer.read(cx).saved_version().clone();
+
+        // Diagnostic ranges are reported in the server's position encoding, against the text
+        // they were computed for: the saved file for disk-based diagnostics, and the reported
+        // document version otherwise.
+        let encoding = self
+            .running_language_server_for_id(server_id)
+            .map(|server| server.position_encoding())
+            .unwrap_or_default();
+        if encoding != PositionEncoding::Utf16 {
+            let saved_text = std::cell::LazyCell::new(|| snapshot.rope_for_version(&saved_version));
+            for entry in &mut diagnostics {
+                let text = if entry.diagnostic.is_disk_based {
+                    &*saved_text
+                } else {
+                    snapshot.as_rope()
+                };
+                for point in [&mut entry.range.start, &mut entry.range.end] {
+                    *point = point_from_lsp_encoded(point_to_lsp(point.0), text, encoding);
+                }
+            }
+        }
+
         diagnostics.sort_unstable_by(|a, b| {
             Ordering::Equal
                 .then_with(|| a.range.start.cmp(&b.range.start))
@@ -1700,11 +1800,8 @@ impl LocalLspStore {
                 .then_with(|| compare_diagnostics(&a.diagnostic, &b.diagnostic))
         });
 
-        let snapshot = self.buffer_snapshot_for_lsp_version(buffer, server_id, version, cx)?;
-
         let edits_since_save = std::cell::LazyCell::new(|| {
-            let saved_version = buffer.read(cx).saved_version();
-            Patch::new(snapshot.edits_since::<PointUtf16>(saved_version).collect())
+            Patch::new(snapshot.edits_since::<PointUtf16>(&saved_version).collect())
         });
 
         let mut sanitized_diagnostics = Vec::new();
@@ -1968,6 +2065,10 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             let _ = self.buffer_snapshots.remove(&buffer.remote_id());
+            let _ = self.semantic_tokens.remove(&buffer.remote_id());
+            let _ = self
+                .buffer_pull_diagnostics_tasks
+                .remove(&buffer.remote_id());
 
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server.unregister_buffer(file_url.clone());
@@ -2146,11 +2247,20 @@ impl LocalLspStore {
         cx: &mut Context<LspStore>,
     ) -> Task<Result<Vec<(Range<Anchor>, Arc<str>)>>> {
         let snapshot = self.buffer_snapshot_for_lsp_version(buffer, server_id, version, cx);
+        let encoding = self
+            .running_language_server_for_id(server_id)
+            .map(|server| server.position_encoding())
+            .unwrap_or_default();
         cx.background_spawn(async move {
             let snapshot = snapshot?;
             let mut lsp_edits = lsp_edits
                 .into_iter()
-                .map(|edit| (range_from_lsp(edit.range), edit.new_text))
+                .map(|edit| {
+                    (
+                        range_from_lsp_encoded(edit.range, snapshot.as_rope(), encoding),
+                        edit.new_text,
+                    )
+                })
                 .collect::<Vec<_>>();
             lsp_edits.sort_by_key(|(range, _)| range.start);
 
@@ -2359,7 +2469,17 @@ impl LocalLspStore {
                                         };
 
                                         if is_active_entry {
-                                            snippet_edits.push((edit.range, snippet));
+                                            // Editors expect snippet ranges in UTF-16.
+                                            let range = range_from_lsp_encoded(
+                                                edit.range,
+                                                buffer_to_edit.read(cx).as_rope(),
+                                                language_server.position_encoding(),
+                                            );
+                                            let range = lsp::Range::new(
+                                                point_to_lsp(range.start.0),
+                                                point_to_lsp(range.end.0),
+                                            );
+                                            snippet_edits.push((range, snippet));
                                         } else {
                                             // Since this buffer is not focused, apply a normal edit.
                                             let new_edit = TextEdit {
@@ -2809,6 +2929,9 @@ pub struct LspStore {
     _maintain_buffer_languages: Task<()>,
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
+    folding_ranges: HashMap<BufferId, (clock::Global, Vec<FoldingRange>)>,
+    document_links: HashMap<BufferId, (clock::Global, Vec<DocumentLink>)>,
+    document_colors: HashMap<BufferId, (clock::Global, Vec<DocumentColor>)>,
 }
 
 pub enum LspStoreEvent {
@@ -2826,6 +2949,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshCodeLens,
+    RefreshSemanticTokens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2875,16 +3000,27 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_format_buffers);
         client.add_entity_request_handler(Self::handle_resolve_completion_documentation);
         client.add_entity_request_handler(Self::handle_apply_code_action);
+        client.add_entity_request_handler(Self::handle_apply_code_lens);
+        client.add_entity_request_handler(Self::handle_resolve_code_lens);
+        client.add_entity_request_handler(Self::handle_resolve_document_link);
         client.add_entity_request_handler(Self::handle_inlay_hints);
         client.add_entity_request_handler(Self::handle_get_project_symbols);
         client.add_entity_request_handler(Self::handle_resolve_inlay_hint);
         client.add_entity_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_entity_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_entity_request_handler(Self::handle_refresh_code_lens);
+        client.add_entity_request_handler(Self::handle_get_semantic_tokens);
+        client.add_entity_request_handler(Self::handle_refresh_semantic_tokens);
         client.add_entity_request_handler(Self::handle_on_type_formatting);
         client.add_entity_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_entity_request_handler(Self::handle_register_buffer_with_language_servers);
         client.add_entity_request_handler(Self::handle_rename_project_entry);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetCodeActions>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentColors>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetColorPresentations>);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetHover>);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetDefinition>);
@@ -2896,6 +3032,12 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_entity_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSubtypes>);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -2982,6 +3124,10 @@ impl LspStore {
                 language_server_watcher_registrations: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                diagnostic_result_ids: Default::default(),
+                buffer_pull_diagnostics_tasks: Default::default(),
+                workspace_pull_diagnostics_tasks: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -3004,6 +3150,9 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            folding_ranges: Default::default(),
+            document_links: Default::default(),
+            document_colors: Default::default(),
             active_entry: None,
 
             _maintain_workspace_config,
@@ -3061,6 +3210,9 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            folding_ranges: Default::default(),
+            document_links: Default::default(),
+            document_colors: Default::default(),
             active_entry: None,
             toolchain_store,
             _maintain_workspace_config,
@@ -3098,7 +3250,11 @@ impl LspStore {
                     }
                 }
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.folding_ranges.remove(buffer_id);
+                self.document_links.remove(buffer_id);
+                self.document_colors.remove(buffer_id);
+            }
         }
     }
 
@@ -3188,11 +3344,13 @@ impl LspStore {
     ) {
         match event {
             language::BufferEvent::Edited { .. } => {
-                self.on_buffer_edited(buffer, cx);
+                self.on_buffer_edited(buffer.clone(), cx);
+                self.pull_buffer_diagnostics(&buffer, cx);
             }
 
             language::BufferEvent::Saved => {
-                self.on_buffer_saved(buffer, cx);
+                self.on_buffer_saved(buffer.clone(), cx);
+                self.pull_workspace_diagnostics_for_buffer(&buffer, cx);
             }
 
             _ => {}
@@ -3225,6 +3383,9 @@ impl LspStore {
     ) -> OpenLspBufferHandle {
         let buffer_id = buffer.read(cx).remote_id();
         let handle = cx.new(|_| buffer.clone());
+        if buffer.read(cx).is_large_file() {
+            return handle;
+        }
         if let Some(local) = self.as_local_mut() {
             let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
                 return handle;
@@ -3240,6 +3401,7 @@ impl LspStore {
 
             if !ignore_refcounts || *refcount == 1 {
                 local.register_buffer_with_language_servers(buffer, cx);
+                self.pull_buffer_diagnostics(buffer, cx);
             }
             if !ignore_refcounts {
                 cx.observe_release(&handle, move |this, buffer, cx| {
@@ -3364,6 +3526,10 @@ impl LspStore {
     ) -> Option<language::AvailableLanguage> {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
+        // Large files are shown as plain text, as parsing them would take too long.
+        if buffer.is_large_file() {
+            return None;
+        }
         let file = buffer.file()?;
 
         let content = buffer.as_rope();
@@ -3860,6 +4026,131 @@ impl LspStore {
         }
     }
 
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.is_resolved() {
+            return Task::ready(Ok(lens));
+        }
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client.request(request).await?;
+                Self::deserialize_code_lens(
+                    response.lens.ok_or_else(|| anyhow!("missing code lens"))?,
+                )
+            })
+        } else {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(lens));
+            };
+            let capabilities = lang_server.capabilities();
+            if !GetCodeLens::can_resolve_lens(&capabilities) {
+                return Task::ready(Ok(lens));
+            }
+            cx.background_spawn(async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                let mut resolved = CodeLens { lsp_lens, ..lens };
+                // A lens whose command can't be run is left unresolved, so it isn't shown.
+                if !GetCodeLens::can_execute_lens(&resolved, &capabilities) {
+                    resolved.lsp_lens.command = None;
+                }
+                Ok(resolved)
+            })
+        }
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ApplyCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            let buffer_store = self.buffer_store();
+            cx.spawn(move |_, mut cx| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+
+                buffer_store
+                    .update(&mut cx, |buffer_store, cx| {
+                        buffer_store.deserialize_project_transaction(response, true, cx)
+                    })?
+                    .await
+            })
+        } else if self.mode.is_local() {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let Some(command) = lens.lsp_lens.command else {
+                return Task::ready(Ok(Default::default()));
+            };
+            // Many lens commands (e.g. "Run test") are meant to be handled by the client, only
+            // forward the ones the server has declared it can execute.
+            let server_can_execute = lang_server
+                .capabilities()
+                .execute_command_provider
+                .is_some_and(|options| options.commands.contains(&command.command));
+            if !server_can_execute {
+                return Task::ready(Err(anyhow!(
+                    "language server cannot execute command {:?}",
+                    command.command
+                )));
+            }
+
+            cx.spawn(move |this, mut cx| async move {
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id());
+                })?;
+
+                lang_server
+                    .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                        command: command.command,
+                        arguments: command.arguments.unwrap_or_default(),
+                        ..Default::default()
+                    })
+                    .await?;
+
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id())
+                        .unwrap_or_default()
+                })
+            })
+        } else {
+            Task::ready(Err(anyhow!("no upstream client and not local")))
+        }
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -3896,9 +4187,10 @@ impl LspStore {
                 return Task::ready(Ok(hint));
             }
             let buffer_snapshot = buffer_handle.read(cx).snapshot();
+            let encoding = lang_server.position_encoding();
             cx.spawn(move |_, mut cx| async move {
                 let resolve_task = lang_server.request::<lsp::request::InlayHintResolveRequest>(
-                    InlayHints::project_to_lsp_hint(hint, &buffer_snapshot),
+                    InlayHints::project_to_lsp_hint(hint, &buffer_snapshot, encoding),
                 );
                 let resolved_hint = resolve_task
                     .await
@@ -3907,6 +4199,7 @@ impl LspStore {
                     resolved_hint,
                     &buffer_handle,
                     server_id,
+                    encoding,
                     ResolveState::Resolved,
                     false,
                     &mut cx,
@@ -4355,7 +4648,7 @@ impl LspStore {
             // language server we currently use that does update `text_edit` in `completionItem/resolve`
             // is `typescript-language-server` and they only update `text_edit.new_text`.
             // But we should not rely on that.
-            let edit = parse_completion_text_edit(text_edit, snapshot);
+            let edit = parse_completion_text_edit(text_edit, snapshot, server.position_encoding());
 
             if let Some((old_range, mut new_text)) = edit {
                 LineEnding::normalize(&mut new_text);
@@ -4676,100 +4969,407 @@ impl LspStore {
         }
     }
 
-    pub fn signature_help<T: ToPointUtf16>(
+    /// Returns the document links of the buffer, which are cached for each version of it.
+    pub fn document_links(
         &mut self,
-        buffer: &Entity<Buffer>,
-        position: T,
+        buffer_handle: Entity<Buffer>,
         cx: &mut Context<Self>,
-    ) -> Task<Vec<SignatureHelp>> {
-        let position = position.to_point_utf16(buffer.read(cx));
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let version = buffer.version();
+        if let Some((cached_version, links)) = self.document_links.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(links.clone()));
+            }
+        }
 
-        if let Some((client, upstream_project_id)) = self.upstream_client() {
-            let request_task = client.request(proto::MultiLspQuery {
-                buffer_id: buffer.read(cx).remote_id().into(),
-                version: serialize_version(&buffer.read(cx).version()),
-                project_id: upstream_project_id,
-                strategy: Some(proto::multi_lsp_query::Strategy::All(
-                    proto::AllLanguageServers {},
-                )),
-                request: Some(proto::multi_lsp_query::Request::GetSignatureHelp(
-                    GetSignatureHelp { position }.to_proto(upstream_project_id, buffer.read(cx)),
-                )),
-            });
-            let buffer = buffer.clone();
-            cx.spawn(|weak_project, cx| async move {
-                let Some(project) = weak_project.upgrade() else {
-                    return Vec::new();
-                };
-                join_all(
-                    request_task
-                        .await
-                        .log_err()
-                        .map(|response| response.responses)
-                        .unwrap_or_default()
-                        .into_iter()
-                        .filter_map(|lsp_response| match lsp_response.response? {
-                            proto::lsp_response::Response::GetSignatureHelpResponse(response) => {
-                                Some(response)
-                            }
-                            unexpected => {
-                                debug_panic!("Unexpected response: {unexpected:?}");
-                                None
-                            }
-                        })
-                        .map(|signature_response| {
-                            let response = GetSignatureHelp { position }.response_from_proto(
-                                signature_response,
-                                project.clone(),
-                                buffer.clone(),
-                                cx.clone(),
-                            );
-                            async move { response.await.log_err().flatten() }
-                        }),
-                )
-                .await
-                .into_iter()
-                .flatten()
-                .collect()
+        let request = self.request_lsp(
+            buffer_handle,
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let links = request.await?;
+            this.update(&mut cx, |this, _| {
+                this.document_links
+                    .insert(buffer_id, (version, links.clone()));
+            })?;
+            Ok(links)
+        })
+    }
+
+    /// Resolves the target of a document link, if the language server left it out.
+    pub fn resolve_document_link(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        link: DocumentLink,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        if link.target().is_some() {
+            return Task::ready(Ok(link));
+        }
+
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                link: Some(Self::serialize_document_link(&link)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client.request(request).await?;
+                match response.link {
+                    Some(link) => Self::deserialize_document_link(link),
+                    None => Ok(link),
+                }
             })
         } else {
-            let all_actions_task = self.request_multiple_lsp_locally(
-                buffer,
-                Some(position),
-                GetSignatureHelp { position },
-                cx,
-            );
-            cx.spawn(|_, _| async move {
-                all_actions_task
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, link.server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(link));
+            };
+            if !GetDocumentLinks::can_resolve_link(&lang_server.capabilities()) {
+                return Task::ready(Ok(link));
+            }
+            cx.spawn(move |_, _| async move {
+                let lsp_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
                     .await
-                    .into_iter()
-                    .flatten()
-                    .filter(|help| !help.label.is_empty())
-                    .collect::<Vec<_>>()
+                    .context("document link resolve LSP request")?;
+                // Keep the range of the original link, as the buffer may have changed since.
+                Ok(DocumentLink { lsp_link, ..link })
             })
         }
     }
 
-    pub fn hover(
+    /// Returns the color literals of the buffer, which are cached for each version of it.
+    pub fn document_colors(
         &mut self,
-        buffer: &Entity<Buffer>,
-        position: PointUtf16,
+        buffer_handle: Entity<Buffer>,
         cx: &mut Context<Self>,
-    ) -> Task<Vec<Hover>> {
-        if let Some((client, upstream_project_id)) = self.upstream_client() {
-            let request_task = client.request(proto::MultiLspQuery {
-                buffer_id: buffer.read(cx).remote_id().into(),
-                version: serialize_version(&buffer.read(cx).version()),
-                project_id: upstream_project_id,
-                strategy: Some(proto::multi_lsp_query::Strategy::All(
-                    proto::AllLanguageServers {},
-                )),
-                request: Some(proto::multi_lsp_query::Request::GetHover(
-                    GetHover { position }.to_proto(upstream_project_id, buffer.read(cx)),
-                )),
-            });
-            let buffer = buffer.clone();
-            cx.spawn(|weak_project, cx| async move {
+    ) -> Task<Result<Vec<DocumentColor>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let version = buffer.version();
+        if let Some((cached_version, colors)) = self.document_colors.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(colors.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer_handle,
+            LanguageServerToQuery::Primary,
+            GetDocumentColors,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let colors = request.await?;
+            this.update(&mut cx, |this, _| {
+                this.document_colors
+                    .insert(buffer_id, (version, colors.clone()));
+            })?;
+            Ok(colors)
+        })
+    }
+
+    /// Returns the folding ranges of the buffer, which are cached for each version of it.
+    pub fn folding_ranges(
+        &mut self,
+        buffer_handle: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let version = buffer.version();
+        if let Some((cached_version, ranges)) = self.folding_ranges.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(ranges.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer_handle,
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let ranges = request.await?;
+            // Servers that aren't running yet report no ranges, so only cache actual results.
+            if !ranges.is_empty() {
+                this.update(&mut cx, |this, _| {
+                    this.folding_ranges
+                        .insert(buffer_id, (version, ranges.clone()));
+                })?;
+            }
+            Ok(ranges)
+        })
+    }
+
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<SemanticTokens>> {
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+            };
+            return cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                Self::deserialize_semantic_tokens(response)
+            });
+        }
+
+        let Some(local) = self.as_local() else {
+            return Task::ready(Err(anyhow!("no upstream client and not local")));
+        };
+        let Some(abs_path) = File::from_dyn(buffer_handle.read(cx).file())
+            .and_then(File::as_local)
+            .map(|file| file.abs_path(cx))
+        else {
+            return Task::ready(Ok(SemanticTokens::default()));
+        };
+        let Some((language_server, options)) = buffer_handle.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .find_map(|(_, server)| {
+                    let options = match server.capabilities().semantic_tokens_provider? {
+                        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+                            options
+                        }
+                        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
+                            options,
+                        ) => options.semantic_tokens_options,
+                    };
+                    Some((server.clone(), options))
+                })
+        }) else {
+            return Task::ready(Ok(SemanticTokens::default()));
+        };
+        let supports_delta = match options.full {
+            Some(lsp::SemanticTokensFullOptions::Bool(true)) => false,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta }) => delta.unwrap_or(false),
+            Some(lsp::SemanticTokensFullOptions::Bool(false)) | None => {
+                return Task::ready(Ok(SemanticTokens::default()));
+            }
+        };
+        let text_document = match make_text_document_identifier(&abs_path) {
+            Ok(text_document) => text_document,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let server_id = language_server.server_id();
+        // Changes are sent to language servers as soon as they happen, so the tokens are
+        // computed against the current state of the buffer.
+        let snapshot = buffer.text_snapshot();
+        let previous_tokens = local
+            .semantic_tokens
+            .get(&buffer_id)
+            .and_then(|tokens| tokens.get(&server_id))
+            .filter(|_| supports_delta)
+            .cloned();
+        let token_types: Arc<[String]> = options
+            .legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect();
+        let token_modifiers: Arc<[String]> = options
+            .legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect();
+
+        cx.spawn(move |this, mut cx| async move {
+            let mut result = None;
+            if let Some(previous_tokens) = previous_tokens {
+                let delta = language_server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document: text_document.clone(),
+                            previous_result_id: previous_tokens.result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await
+                    .log_err()
+                    .flatten();
+                result = match delta {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                        Some((tokens.result_id, tokens.data))
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                        let mut data = previous_tokens.data;
+                        apply_semantic_token_edits(&mut data, delta.edits)
+                            .log_err()
+                            .map(|()| (delta.result_id, data))
+                    }
+                    // Partial results are never requested.
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { .. }) | None => {
+                        None
+                    }
+                };
+            }
+
+            let (result_id, data) = match result {
+                Some(result) => result,
+                None => match language_server
+                    .request::<lsp::request::SemanticTokensFullRequest>(lsp::SemanticTokensParams {
+                        text_document,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    })
+                    .await?
+                {
+                    Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                        (tokens.result_id, tokens.data)
+                    }
+                    Some(lsp::SemanticTokensResult::Partial(tokens)) => (None, tokens.data),
+                    None => (None, Vec::new()),
+                },
+            };
+
+            let encoding = language_server.position_encoding();
+            let (tokens, data) = cx
+                .background_executor()
+                .spawn(async move { (semantic_tokens_from_lsp(&data, &snapshot, encoding), data) })
+                .await;
+            this.update(&mut cx, |this, _| {
+                if let Some(local) = this.as_local_mut() {
+                    let cached_tokens = local.semantic_tokens.entry(buffer_id).or_default();
+                    match result_id {
+                        Some(result_id) => {
+                            cached_tokens
+                                .insert(server_id, CachedSemanticTokens { result_id, data });
+                        }
+                        None => {
+                            cached_tokens.remove(&server_id);
+                        }
+                    }
+                }
+            })?;
+
+            Ok(SemanticTokens {
+                token_types,
+                token_modifiers,
+                tokens,
+            })
+        })
+    }
+
+    pub fn signature_help<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<SignatureHelp>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+
+        if let Some((client, upstream_project_id)) = self.upstream_client() {
+            let request_task = client.request(proto::MultiLspQuery {
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+                project_id: upstream_project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetSignatureHelp(
+                    GetSignatureHelp { position }.to_proto(upstream_project_id, buffer.read(cx)),
+                )),
+            });
+            let buffer = buffer.clone();
+            cx.spawn(|weak_project, cx| async move {
+                let Some(project) = weak_project.upgrade() else {
+                    return Vec::new();
+                };
+                join_all(
+                    request_task
+                        .await
+                        .log_err()
+                        .map(|response| response.responses)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|lsp_response| match lsp_response.response? {
+                            proto::lsp_response::Response::GetSignatureHelpResponse(response) => {
+                                Some(response)
+                            }
+                            unexpected => {
+                                debug_panic!("Unexpected response: {unexpected:?}");
+                                None
+                            }
+                        })
+                        .map(|signature_response| {
+                            let response = GetSignatureHelp { position }.response_from_proto(
+                                signature_response,
+                                project.clone(),
+                                buffer.clone(),
+                                cx.clone(),
+                            );
+                            async move { response.await.log_err().flatten() }
+                        }),
+                )
+                .await
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+        } else {
+            let all_actions_task = self.request_multiple_lsp_locally(
+                buffer,
+                Some(position),
+                GetSignatureHelp { position },
+                cx,
+            );
+            cx.spawn(|_, _| async move {
+                all_actions_task
+                    .await
+                    .into_iter()
+                    .flatten()
+                    .filter(|help| !help.label.is_empty())
+                    .collect::<Vec<_>>()
+            })
+        }
+    }
+
+    pub fn hover(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: PointUtf16,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Hover>> {
+        if let Some((client, upstream_project_id)) = self.upstream_client() {
+            let request_task = client.request(proto::MultiLspQuery {
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+                project_id: upstream_project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetHover(
+                    GetHover { position }.to_proto(upstream_project_id, buffer.read(cx)),
+                )),
+            });
+            let buffer = buffer.clone();
+            cx.spawn(|weak_project, cx| async move {
                 let Some(project) = weak_project.upgrade() else {
                     return Vec::new();
                 };
@@ -4853,6 +5453,7 @@ impl LspStore {
                 lsp_adapter: Arc<CachedLspAdapter>,
                 worktree: WeakEntity<Worktree>,
                 worktree_abs_path: Arc<Path>,
+                position_encoding: PositionEncoding,
                 lsp_symbols: Vec<(String, SymbolKind, lsp::Location)>,
             }
 
@@ -4886,6 +5487,7 @@ impl LspStore {
                     let worktree_abs_path = worktree.abs_path().clone();
                     let worktree_handle = worktree_handle.clone();
                     let server_id = server.server_id();
+                    let position_encoding = server.position_encoding();
                     requests.push(
                             server
                                 .request::<lsp::request::WorkspaceSymbolRequest>(
@@ -4921,6 +5523,7 @@ impl LspStore {
                                         lsp_adapter,
                                         worktree: worktree_handle.downgrade(),
                                         worktree_abs_path,
+                                        position_encoding,
                                         lsp_symbols,
                                     }
                                 }),
@@ -4929,6 +5532,7 @@ impl LspStore {
                 requested_servers.append(&mut servers_to_query);
             }
 
+            let fs = local.fs.clone();
             cx.spawn(move |this, mut cx| async move {
                 let responses = futures::future::join_all(requests).await;
                 let this = match this.upgrade() {
@@ -4938,6 +5542,7 @@ impl LspStore {
 
                 let mut symbols = Vec::new();
                 for result in responses {
+                    let encoding = result.position_encoding;
                     let core_symbols = this.update(&mut cx, |this, cx| {
                         result
                             .lsp_symbols
@@ -4965,20 +5570,59 @@ impl LspStore {
                                     path: path.into(),
                                 };
                                 let signature = this.symbol_signature(&project_path);
-                                Some(CoreSymbol {
+
+                                // Ranges from servers that don't use UTF-16 are converted
+                                // against the file's text: the buffer's if it is open, or
+                                // the file on disk, loaded below.
+                                let mut unconverted_range = None;
+                                let range = if encoding == PositionEncoding::Utf16 {
+                                    range_from_lsp(symbol_location.range)
+                                } else if let Some(buffer) =
+                                    this.buffer_store.read(cx).get_by_path(&project_path, cx)
+                                {
+                                    range_from_lsp_encoded(
+                                        symbol_location.range,
+                                        buffer.read(cx).as_rope(),
+                                        encoding,
+                                    )
+                                } else {
+                                    unconverted_range = Some((abs_path, symbol_location.range));
+                                    range_from_lsp(symbol_location.range)
+                                };
+                                let symbol = CoreSymbol {
                                     source_language_server_id: result.server_id,
                                     language_server_name: result.lsp_adapter.name.clone(),
                                     source_worktree_id,
                                     path: project_path,
                                     kind: symbol_kind,
                                     name: symbol_name,
-                                    range: range_from_lsp(symbol_location.range),
+                                    range,
                                     signature,
-                                })
+                                };
+                                Some((symbol, unconverted_range))
                             })
-                            .collect()
+                            .collect::<Vec<_>>()
                     })?;
 
+                    let mut file_texts = HashMap::<PathBuf, Option<Rope>>::default();
+                    let mut converted_symbols = Vec::with_capacity(core_symbols.len());
+                    for (mut symbol, unconverted_range) in core_symbols {
+                        if let Some((abs_path, lsp_range)) = unconverted_range {
+                            if !file_texts.contains_key(&abs_path) {
+                                let text = fs.load(&abs_path).await.log_err();
+                                file_texts.insert(
+                                    abs_path.clone(),
+                                    text.map(|text| Rope::from(text.as_str())),
+                                );
+                            }
+                            if let Some(text) = &file_texts[&abs_path] {
+                                symbol.range = range_from_lsp_encoded(lsp_range, text, encoding);
+                            }
+                        }
+                        converted_symbols.push(symbol);
+                    }
+                    let core_symbols = converted_symbols;
+
                     populate_labels_for_symbols(
                         core_symbols,
                         &language_registry,
@@ -5072,19 +5716,37 @@ impl LspStore {
             let previous_snapshot = buffer_snapshots.last()?;
 
             let build_incremental_change = || {
+                let encoding = language_server.position_encoding();
+                let previous_text = previous_snapshot.snapshot.as_rope();
                 buffer
                     .edits_since::<(PointUtf16, usize)>(previous_snapshot.snapshot.version())
                     .map(|edit| {
-                        let edit_start = edit.new.start.0;
-                        let edit_end = edit_start + (edit.old.end.0 - edit.old.start.0);
+                        // Each change applies on top of the previous ones, so the edit starts at
+                        // its new position and spans the extent of the replaced text.
+                        let edit_start = point_to_lsp_encoded(
+                            edit.new.start.0,
+                            next_snapshot.as_rope(),
+                            encoding,
+                        );
+                        let old_start =
+                            point_to_lsp_encoded(edit.old.start.0, previous_text, encoding);
+                        let old_end = point_to_lsp_encoded(edit.old.end.0, previous_text, encoding);
+                        let edit_end = if old_start.line == old_end.line {
+                            lsp::Position::new(
+                                edit_start.line,
+                                edit_start.character + old_end.character - old_start.character,
+                            )
+                        } else {
+                            lsp::Position::new(
+                                edit_start.line + old_end.line - old_start.line,
+                                old_end.character,
+                            )
+                        };
                         let new_text = next_snapshot
                             .text_for_range(edit.new.start.1..edit.new.end.1)
                             .collect();
                         lsp::TextDocumentContentChangeEvent {
-                            range: Some(lsp::Range::new(
-                                point_to_lsp(edit_start),
-                                point_to_lsp(edit_end),
-                            )),
+                            range: Some(lsp::Range::new(edit_start, edit_end)),
                             range_length: None,
                             text: new_text,
                         }
@@ -5975,6 +6637,61 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_document_link(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = Self::deserialize_document_link(
+            envelope
+                .payload
+                .link
+                .ok_or_else(|| anyhow!("invalid document link"))?,
+        )?;
+        let resolve_document_link = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_document_link(buffer, link, cx))
+        })??;
+        let link = resolve_document_link.await?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(Self::serialize_document_link(&link)),
+        })
+    }
+
+    async fn handle_apply_code_lens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ApplyCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ApplyCodeLensResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let apply_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.apply_code_lens(buffer, lens, cx))
+        })??;
+
+        let project_transaction = apply_code_lens.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store.serialize_project_transaction_for_peer(
+                    project_transaction,
+                    sender_id,
+                    cx,
+                )
+            })
+        })?;
+        Ok(proto::ApplyCodeLensResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
     async fn handle_register_buffer_with_language_servers(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::RegisterBufferWithLanguageServers>,
@@ -6465,6 +7182,14 @@ impl LspStore {
         });
     }
 
+    /// The position encoding negotiated with the given language server, falling back to UTF-16
+    /// for servers that aren't running locally.
+    pub fn position_encoding(&self, id: LanguageServerId) -> PositionEncoding {
+        self.language_server_for_id(id)
+            .map(|server| server.position_encoding())
+            .unwrap_or_default()
+    }
+
     pub fn language_server_for_id(&self, id: LanguageServerId) -> Option<Arc<LanguageServer>> {
         let local_lsp_store = self.as_local()?;
         if let Some(LanguageServerState::Running { server, .. }) =
@@ -6639,6 +7364,8 @@ impl LspStore {
             if let Some(work) = status.pending_work.remove(&token) {
                 if !work.is_disk_based_diagnostics_progress {
                     cx.emit(LspStoreEvent::RefreshInlayHints);
+                    cx.emit(LspStoreEvent::RefreshCodeLens);
+                    cx.emit(LspStoreEvent::RefreshSemanticTokens);
                 }
             }
             cx.notify();
@@ -6707,7 +7434,12 @@ impl LspStore {
             })??;
 
             if let Some(text_edit) = completion.text_edit.as_ref() {
-                let edit = parse_completion_text_edit(text_edit, &buffer_snapshot);
+                let encoding = this.read_with(&cx, |this, _| {
+                    this.position_encoding(LanguageServerId(
+                        envelope.payload.language_server_id as usize,
+                    ))
+                })?;
+                let edit = parse_completion_text_edit(text_edit, &buffer_snapshot, encoding);
 
                 if let Some((old_range, mut text_edit_new_text)) = edit {
                     LineEnding::normalize(&mut text_edit_new_text);
@@ -6768,6 +7500,52 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_code_lens(
+        this: Entity<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_semantic_tokens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let buffer_version = buffer.update(&mut cx, |buffer, _| buffer.version())?;
+        let tokens = this
+            .update(&mut cx, |this, cx| this.semantic_tokens(buffer, cx))?
+            .await
+            .context("semantic tokens fetch")?;
+        Ok(Self::serialize_semantic_tokens(&tokens, &buffer_version))
+    }
+
+    async fn handle_refresh_semantic_tokens(
+        this: Entity<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_inlay_hints(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::InlayHints>,
@@ -6813,6 +7591,28 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let resolve_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_code_lens(buffer, lens, cx))
+        })??;
+        let lens = resolve_code_lens.await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&lens)),
+        })
+    }
+
     async fn handle_resolve_inlay_hint(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::ResolveInlayHint>,
@@ -7299,6 +8099,8 @@ impl LspStore {
         }
 
         let local = self.as_local_mut().unwrap();
+        local.diagnostic_result_ids.remove(&server_id);
+        local.workspace_pull_diagnostics_tasks.remove(&server_id);
         for diagnostics in local.diagnostics.values_mut() {
             diagnostics.retain(|_, diagnostics_by_server_id| {
                 if let Ok(ix) = diagnostics_by_server_id.binary_search_by_key(&server_id, |e| e.0) {
@@ -7373,6 +8175,297 @@ impl LspStore {
         }
     }
 
+    /// Pulls the diagnostics of a buffer from its language servers that support the pull model.
+    fn pull_buffer_diagnostics(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(uri) = File::from_dyn(buffer.read(cx).file())
+            .and_then(File::as_local)
+            .and_then(|file| lsp::Url::from_file_path(file.abs_path(cx)).ok())
+        else {
+            return;
+        };
+        let supports_pull = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .any(|(_, server)| pull_diagnostics_options(server).is_some())
+        });
+        if !supports_pull {
+            local.buffer_pull_diagnostics_tasks.remove(&buffer_id);
+            return;
+        }
+
+        let buffer = buffer.downgrade();
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                .await;
+
+            let Ok(requests) = this.update(&mut cx, |this, cx| {
+                let local = this.as_local()?;
+                let buffer = buffer.upgrade()?;
+                let requests = buffer.update(cx, |buffer, cx| {
+                    local
+                        .language_servers_for_buffer(buffer, cx)
+                        .filter_map(|(adapter, server)| {
+                            let options = pull_diagnostics_options(server)?;
+                            let server_id = server.server_id();
+                            // Diagnostics are reported for the latest version sent to the server.
+                            let version = local
+                                .buffer_snapshots
+                                .get(&buffer_id)?
+                                .get(&server_id)?
+                                .last()?
+                                .version;
+                            let previous_result_id = local
+                                .diagnostic_result_ids
+                                .get(&server_id)
+                                .and_then(|result_ids| result_ids.get(&uri))
+                                .cloned();
+                            let request = server
+                                .request::<lsp::request::DocumentDiagnosticRequest>(
+                                    lsp::DocumentDiagnosticParams {
+                                        text_document: lsp::TextDocumentIdentifier::new(
+                                            uri.clone(),
+                                        ),
+                                        identifier: options.identifier.clone(),
+                                        previous_result_id,
+                                        work_done_progress_params: Default::default(),
+                                        partial_result_params: Default::default(),
+                                    },
+                                );
+                            let adapter = adapter.clone();
+                            Some(async move { (adapter, server_id, version, request.await) })
+                        })
+                        .collect::<Vec<_>>()
+                });
+                Some(requests)
+            }) else {
+                return;
+            };
+
+            let responses = join_all(requests.into_iter().flatten()).await;
+            this.update(&mut cx, |this, cx| {
+                for (adapter, server_id, version, response) in responses {
+                    let Some(response) = response
+                        .with_context(|| format!("pulling diagnostics for {uri}"))
+                        .log_err()
+                    else {
+                        continue;
+                    };
+                    let (report, related_documents) = match response {
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Full(report),
+                        ) => (
+                            Some(lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            )),
+                            report.related_documents,
+                        ),
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Unchanged(report),
+                        ) => (
+                            Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            )),
+                            report.related_documents,
+                        ),
+                        lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                            (None, report.related_documents)
+                        }
+                    };
+                    if let Some(report) = report {
+                        this.apply_pulled_diagnostics(
+                            &adapter,
+                            server_id,
+                            uri.clone(),
+                            Some(version),
+                            report,
+                            cx,
+                        );
+                    }
+                    for (related_uri, report) in related_documents.into_iter().flatten() {
+                        this.apply_pulled_diagnostics(
+                            &adapter,
+                            server_id,
+                            related_uri,
+                            None,
+                            report,
+                            cx,
+                        );
+                    }
+                }
+            })
+            .ok();
+        });
+        if let Some(local) = self.as_local_mut() {
+            local.buffer_pull_diagnostics_tasks.insert(buffer_id, task);
+        }
+    }
+
+    /// Pulls the diagnostics of the whole workspace from a language server, if it supports it.
+    fn pull_workspace_diagnostics(&mut self, server_id: LanguageServerId, cx: &mut Context<Self>) {
+        let Some(adapter) = self.language_server_adapter_for_id(server_id) else {
+            return;
+        };
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let Some(server) = local.running_language_server_for_id(server_id).cloned() else {
+            return;
+        };
+        let Some(options) = pull_diagnostics_options(&server) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+
+        let previous_result_ids = local
+            .diagnostic_result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, result_id)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: result_id.clone(),
+            })
+            .collect();
+        let request = server.request::<lsp::request::WorkspaceDiagnosticRequest>(
+            lsp::WorkspaceDiagnosticParams {
+                identifier: options.identifier.clone(),
+                previous_result_ids,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        let task = cx.spawn(move |this, mut cx| async move {
+            let Some(response) = request
+                .await
+                .context("pulling workspace diagnostics")
+                .log_err()
+            else {
+                return;
+            };
+            let items = match response {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    this.apply_pulled_diagnostics(
+                        &adapter,
+                        server_id,
+                        uri,
+                        version.map(|version| version as i32),
+                        report,
+                        cx,
+                    );
+                }
+            })
+            .ok();
+        });
+        local
+            .workspace_pull_diagnostics_tasks
+            .insert(server_id, task);
+    }
+
+    fn pull_workspace_diagnostics_for_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let server_ids = buffer.update(cx, |buffer, cx| {
+            local.language_server_ids_for_buffer(buffer, cx)
+        });
+        for server_id in server_ids {
+            self.pull_workspace_diagnostics(server_id, cx);
+        }
+    }
+
+    /// Pulls the diagnostics of every open buffer and of the workspace from a language server again.
+    fn refresh_pulled_diagnostics(&mut self, server_id: LanguageServerId, cx: &mut Context<Self>) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                local
+                    .buffer_snapshots
+                    .get(&buffer.read(cx).remote_id())
+                    .map_or(false, |snapshots| snapshots.contains_key(&server_id))
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_buffer_diagnostics(&buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn apply_pulled_diagnostics(
+        &mut self,
+        adapter: &CachedLspAdapter,
+        server_id: LanguageServerId,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let result_ids = local.diagnostic_result_ids.entry(server_id).or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+            // The diagnostics we have for the document are still up to date.
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+            }
+        }
+    }
+
     pub fn update_diagnostics(
         &mut self,
         language_server_id: LanguageServerId,
@@ -7563,6 +8656,8 @@ impl LspStore {
             Some(key.0),
         ));
         cx.emit(LspStoreEvent::RefreshInlayHints);
+        cx.emit(LspStoreEvent::RefreshCodeLens);
+        cx.emit(LspStoreEvent::RefreshSemanticTokens);
 
         if let Some((downstream_client, project_id)) = self.downstream_client.as_ref() {
             downstream_client
@@ -7656,6 +8751,7 @@ impl LspStore {
                 });
             }
         });
+        self.refresh_pulled_diagnostics(server_id, cx);
 
         cx.notify();
     }
@@ -7991,6 +9087,141 @@ impl LspStore {
         })
     }
 
+    pub(crate) fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
+    pub(crate) fn serialize_semantic_tokens(
+        tokens: &SemanticTokens,
+        buffer_version: &clock::Global,
+    ) -> proto::GetSemanticTokensResponse {
+        proto::GetSemanticTokensResponse {
+            token_types: tokens.token_types.to_vec(),
+            token_modifiers: tokens.token_modifiers.to_vec(),
+            tokens: tokens
+                .tokens
+                .iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type,
+                    token_modifiers: token.token_modifiers,
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    pub(crate) fn deserialize_semantic_tokens(
+        response: proto::GetSemanticTokensResponse,
+    ) -> Result<SemanticTokens> {
+        let tokens = response
+            .tokens
+            .into_iter()
+            .map(|token| {
+                let start = token
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = token
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok(SemanticToken {
+                    range: start..end,
+                    token_type: token.token_type,
+                    token_modifiers: token.token_modifiers,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SemanticTokens {
+            token_types: response.token_types.into(),
+            token_modifiers: response.token_modifiers.into(),
+            tokens,
+        })
+    }
+
+    pub(crate) fn serialize_document_link(link: &DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            server_id: link.server_id.0 as u64,
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_document_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_link = serde_json::from_slice(&link.lsp_link)?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId(link.server_id as usize),
+            range: start..end,
+            lsp_link,
+        })
+    }
+
+    pub(crate) fn serialize_document_color(color: &DocumentColor) -> proto::DocumentColor {
+        proto::DocumentColor {
+            server_id: color.server_id.0 as u64,
+            start: Some(serialize_anchor(&color.range.start)),
+            end: Some(serialize_anchor(&color.range.end)),
+            red: color.color.red,
+            green: color.color.green,
+            blue: color.color.blue,
+            alpha: color.color.alpha,
+        }
+    }
+
+    pub(crate) fn deserialize_document_color(color: proto::DocumentColor) -> Result<DocumentColor> {
+        let start = color
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = color
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(DocumentColor {
+            server_id: LanguageServerId(color.server_id as usize),
+            range: start..end,
+            color: lsp::Color {
+                red: color.red,
+                green: color.green,
+                blue: color.blue,
+                alpha: color.alpha,
+            },
+        })
+    }
+
     fn update_last_formatting_failure<T>(&mut self, formatting_result: &anyhow::Result<T>) {
         match &formatting_result {
             Ok(_) => self.last_formatting_failure = None,
@@ -8006,6 +9237,86 @@ impl LspStore {
 
 impl EventEmitter<LspStoreEvent> for LspStore {}
 
+/// Applies the edits of a `textDocument/semanticTokens/full/delta` response to the
+/// previously reported tokens. Edits address the flat integer encoding of the tokens, where
+/// each token takes five integers.
+fn apply_semantic_token_edits(
+    data: &mut Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Result<()> {
+    const TOKEN_LEN: u32 = 5;
+
+    // Apply the edits back to front, so that the offsets of the remaining ones stay valid.
+    edits.sort_by_key(|edit| Reverse(edit.start));
+    for edit in edits {
+        if edit.start % TOKEN_LEN != 0 || edit.delete_count % TOKEN_LEN != 0 {
+            return Err(anyhow!(
+                "semantic token edit is not aligned to a token boundary"
+            ));
+        }
+        let start = (edit.start / TOKEN_LEN) as usize;
+        let end = start + (edit.delete_count / TOKEN_LEN) as usize;
+        if end > data.len() {
+            return Err(anyhow!("semantic token edit is out of bounds"));
+        }
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+    Ok(())
+}
+
+fn pull_diagnostics_options(server: &LanguageServer) -> Option<lsp::DiagnosticOptions> {
+    match server.capabilities().diagnostic_provider? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options)
+        }
+    }
+}
+
+/// Decodes the relative positions of semantic tokens into anchors in the given snapshot.
+fn semantic_tokens_from_lsp(
+    data: &[lsp::SemanticToken],
+    snapshot: &TextBufferSnapshot,
+    encoding: PositionEncoding,
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut row = 0;
+    let mut column = 0;
+    for token in data {
+        if token.delta_line > 0 {
+            row += token.delta_line;
+            column = token.delta_start;
+        } else {
+            column += token.delta_start;
+        }
+        let start = snapshot.clip_point_utf16(
+            point_from_lsp_encoded(
+                lsp::Position::new(row, column),
+                snapshot.as_rope(),
+                encoding,
+            ),
+            Bias::Left,
+        );
+        let end = snapshot.clip_point_utf16(
+            point_from_lsp_encoded(
+                lsp::Position::new(row, column + token.length),
+                snapshot.as_rope(),
+                encoding,
+            ),
+            Bias::Right,
+        );
+        if start == end {
+            continue;
+        }
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+            token_type: token.token_type,
+            token_modifiers: token.token_modifiers_bitset,
+        });
+    }
+    tokens
+}
+
 fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     hover
         .contents
@@ -8242,6 +9553,14 @@ struct LspBufferSnapshot {
     snapshot: TextBufferSnapshot,
 }
 
+/// The last semantic tokens a language server reported for a buffer, kept around so that
+/// subsequent requests can ask for a delta.
+#[derive(Clone, Debug)]
+struct CachedSemanticTokens {
+    result_id: String,
+    data: Vec<lsp::SemanticToken>,
+}
+
 /// A prompt requested by LSP server.
 #[derive(Clone, Debug)]
 pub struct LanguageServerPromptRequest {
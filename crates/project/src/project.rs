@@ -53,13 +53,13 @@ use ::git::{
 };
 use gpui::{
     AnyEntity, App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, EventEmitter,
-    Hsla, SharedString, Task, WeakEntity, Window,
+    Hsla, Rgba, SharedString, Task, WeakEntity, Window,
 };
 use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent, Capability,
-    CodeLabel, File as _, Language, LanguageName, LanguageRegistry, PointUtf16, ToOffset,
-    ToPointUtf16, Toolchain, ToolchainList, Transaction, Unclipped,
+    CodeLabel, Encoding, File as _, Language, LanguageName, LanguageRegistry, ParseStatus,
+    PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServerId,
@@ -138,6 +138,20 @@ pub trait ProjectItem {
     fn is_dirty(&self) -> bool;
 }
 
+/// The error a [`ProjectItem::try_open`] task fails with when the file turns out not to be one
+/// the item can open, once its content has been looked at. The file is then offered to the
+/// other project items.
+#[derive(Debug)]
+pub struct UnsupportedProjectItem;
+
+impl std::fmt::Display for UnsupportedProjectItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the file isn't supported by this item")
+    }
+}
+
+impl std::error::Error for UnsupportedProjectItem {}
+
 #[derive(Clone)]
 pub enum OpenedBufferEvent {
     Disconnected,
@@ -285,6 +299,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshCodeLens,
+    RefreshSemanticTokens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
     ExpandedAllForEntry(WorktreeId, ProjectEntryId),
@@ -418,6 +434,154 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server, e.g. "Run test" or "5 references".
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens is attached to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+impl CodeLens {
+    pub fn title(&self) -> Option<&str> {
+        self.lsp_lens
+            .command
+            .as_ref()
+            .map(|command| command.title.as_str())
+    }
+
+    /// Whether the lens has a command, servers may leave it out until the lens is resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.lsp_lens.command.is_some()
+    }
+
+    /// The client-side command this lens runs, if any.
+    pub fn client_command(&self) -> Option<CodeLensClientCommand> {
+        let command = self.lsp_lens.command.as_ref()?;
+        match command.command.as_str() {
+            "rust-analyzer.runSingle" => Some(CodeLensClientCommand::Run),
+            "rust-analyzer.showReferences" | "editor.action.showReferences" => {
+                Some(CodeLensClientCommand::ShowReferences)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A code lens command that servers expect the client to implement, rather than executing it
+/// through `workspace/executeCommand`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeLensClientCommand {
+    /// Run the runnable the lens is attached to.
+    Run,
+    /// Show the references to the symbol the lens is attached to.
+    ShowReferences,
+}
+
+/// A link in a buffer provided by a language server, e.g. an `#include` path or a dependency in
+/// a manifest.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    /// The id of the language server that produced this link.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the link is attached to.
+    pub range: Range<Anchor>,
+    /// The raw document link provided by the language server.
+    pub lsp_link: lsp::DocumentLink,
+}
+
+impl DocumentLink {
+    /// The target of the link, which may only be known after the link is resolved.
+    pub fn target(&self) -> Option<&lsp::Url> {
+        self.lsp_link.target.as_ref()
+    }
+
+    pub fn tooltip(&self) -> Option<&str> {
+        self.lsp_link.tooltip.as_deref()
+    }
+}
+
+/// A color literal in a buffer, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct DocumentColor {
+    /// The id of the language server that reported this color.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the color literal spans.
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
+
+impl DocumentColor {
+    pub fn hsla(&self) -> Hsla {
+        Rgba {
+            r: self.color.red,
+            g: self.color.green,
+            b: self.color.blue,
+            a: self.color.alpha,
+        }
+        .into()
+    }
+}
+
+/// A way of writing a color in a buffer, as proposed by a language server.
+#[derive(Clone, Debug)]
+pub struct ColorPresentation {
+    pub label: String,
+    /// The edits to apply to write the color this way, the first of which replaces the color
+    /// literal itself.
+    pub edits: Vec<(Range<Anchor>, String)>,
+}
+
+/// A range of a buffer that can be folded, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// The folded range, from the end of its first line to the end of its last line.
+    pub range: Range<Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// Semantic tokens provided by a language server for a whole buffer.
+#[derive(Clone, Debug, Default)]
+pub struct SemanticTokens {
+    /// The token types of the server's legend, indexed by [`SemanticToken::token_type`].
+    pub token_types: Arc<[String]>,
+    /// The token modifiers of the server's legend, indexed by the bits of
+    /// [`SemanticToken::token_modifiers`].
+    pub token_modifiers: Arc<[String]>,
+    /// The tokens, ordered by their position in the buffer.
+    pub tokens: Vec<SemanticToken>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl SemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn token_modifiers<'a>(
+        &'a self,
+        token: &SemanticToken,
+    ) -> impl Iterator<Item = &'a str> + Clone + 'a {
+        let bits = token.token_modifiers;
+        self.token_modifiers
+            .iter()
+            .enumerate()
+            .filter(move |(ix, _)| *ix < 32 && bits & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -471,6 +635,58 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol that can be queried for its incoming and outgoing calls.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyItem {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The buffer containing the symbol.
+    pub buffer: Entity<Buffer>,
+    /// The range enclosing the symbol, including its body.
+    pub range: Range<Anchor>,
+    /// The range that should be selected when navigating to the symbol, e.g. its name.
+    pub selection_range: Range<Anchor>,
+    /// The raw item provided by the language server.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+impl CallHierarchyItem {
+    pub fn name(&self) -> &str {
+        &self.lsp_item.name
+    }
+}
+
+/// A call from or to a [`CallHierarchyItem`].
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The places where the call happens. For incoming calls these are in the caller's buffer,
+    /// for outgoing calls they are in the buffer of the item that was queried.
+    pub call_sites: Vec<Location>,
+}
+
+/// A type that can be queried for its supertypes and subtypes.
+#[derive(Clone, Debug)]
+pub struct TypeHierarchyItem {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The buffer containing the type.
+    pub buffer: Entity<Buffer>,
+    /// The range enclosing the type, including its body.
+    pub range: Range<Anchor>,
+    /// The range that should be selected when navigating to the type, e.g. its name.
+    pub selection_range: Range<Anchor>,
+    /// The raw item provided by the language server.
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+impl TypeHierarchyItem {
+    pub fn name(&self) -> &str {
+        &self.lsp_item.name
+    }
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -2331,6 +2547,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2746,6 +2964,18 @@ impl Project {
         })
     }
 
+    pub fn reload_buffers_with_encoding(
+        &self,
+        buffers: HashSet<Entity<Buffer>>,
+        encoding: Encoding,
+        push_to_history: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.reload_buffers_with_encoding(buffers, Some(encoding), push_to_history, cx)
+        })
+    }
+
     pub fn reload_images(
         &self,
         images: HashSet<Entity<ImageItem>>,
@@ -2870,6 +3100,96 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
     fn document_highlights_impl(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -3029,6 +3349,114 @@ impl Project {
         })
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(buffer_handle, lens, cx)
+        })
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer_handle, lens, cx)
+        })
+    }
+
+    pub fn document_links(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.document_links(buffer_handle.clone(), cx)
+        })
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        link: DocumentLink,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(buffer_handle, link, cx)
+        })
+    }
+
+    pub fn document_colors(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DocumentColor>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.document_colors(buffer_handle.clone(), cx)
+        })
+    }
+
+    /// Returns the ways the language server proposes to replace the given color literal with
+    /// `new_color`, the preferred one first.
+    pub fn color_presentations(
+        &self,
+        buffer_handle: &Entity<Buffer>,
+        color: &DocumentColor,
+        new_color: lsp::Color,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<ColorPresentation>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Other(color.server_id),
+            GetColorPresentations {
+                color: DocumentColor {
+                    color: new_color,
+                    ..color.clone()
+                },
+            },
+            cx,
+        )
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.folding_ranges(buffer_handle.clone(), cx)
+        })
+    }
+
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<SemanticTokens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.semantic_tokens(buffer_handle.clone(), cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Entity<Buffer>,
@@ -3137,6 +3565,17 @@ impl Project {
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
+                    if query.is_structural() {
+                        // Structural matches are found in the syntax tree, which buffers that
+                        // were just opened may still be parsing.
+                        let mut parse_status =
+                            buffer.read_with(&cx, |buffer, _| buffer.parse_status())?;
+                        while *parse_status.borrow() != ParseStatus::Idle {
+                            if parse_status.changed().await.is_err() {
+                                break;
+                            }
+                        }
+                    }
                     let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                     chunk_results.push(cx.background_spawn(async move {
                         let ranges = query
@@ -3155,6 +3594,11 @@ impl Project {
                 let chunk_results = futures::future::join_all(chunk_results).await;
                 for result in chunk_results {
                     if let Some((buffer, ranges)) = result.log_err() {
+                        // Files are searched for structural patterns when they contain the
+                        // pattern's words, which doesn't mean that they contain a match.
+                        if ranges.is_empty() && query.is_structural() {
+                            continue;
+                        }
                         range_count += ranges.len();
                         buffer_count += 1;
                         result_tx
@@ -3213,6 +3657,15 @@ impl Project {
                     if !search_query.file_matches(file.path()) {
                         return false;
                     }
+                    let in_scope = search_query.scope().contains(file.path(), || {
+                        worktree_store
+                            .worktree_for_id(file.worktree_id(cx), cx)?
+                            .read(cx)
+                            .status_for_file(file.path())
+                    });
+                    if !in_scope {
+                        return false;
+                    }
                     if let Some(entry) = b
                         .entry_id(cx)
                         .and_then(|entry_id| worktree_store.entry_for_id(entry_id, cx))
@@ -3221,8 +3674,10 @@ impl Project {
                             return false;
                         }
                     }
+                    true
+                } else {
+                    search_query.scope().contains_unnamed(b)
                 }
-                true
             })
             .collect::<Vec<_>>();
         let (tx, rx) = smol::channel::unbounded();
@@ -1,4 +1,4 @@
-use crate::{Event, *};
+use crate::{search::SearchScope, Event, *};
 use buffer_diff::{assert_hunks, DiffHunkSecondaryStatus, DiffHunkStatus};
 use fs::FakeFs;
 use futures::{future, StreamExt};
@@ -19,7 +19,13 @@ use pretty_assertions::{assert_eq, assert_matches};
 use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
-use std::{str::FromStr, sync::OnceLock};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{self, AtomicUsize},
+        OnceLock,
+    },
+};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
 use task::{ResolvedTask, TaskContext};
@@ -1102,6 +1108,127 @@ async fn test_single_file_worktrees_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "let a = 1;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: None,
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: false,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document.uri,
+                Url::from_file_path(path!("/dir/a.ts")).unwrap()
+            );
+            let report = match params.previous_result_id.as_deref() {
+                None => {
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some("1".to_string()),
+                            items: vec![lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 4),
+                                    lsp::Position::new(0, 5),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                message: "error 1".to_string(),
+                                ..Default::default()
+                            }],
+                        },
+                    })
+                }
+                // The document didn't change in a way that affects its diagnostics.
+                Some(result_id) => lsp::DocumentDiagnosticReport::Unchanged(
+                    lsp::RelatedUnchangedDocumentDiagnosticReport {
+                        related_documents: None,
+                        unchanged_document_diagnostic_report:
+                            lsp::UnchangedDocumentDiagnosticReport {
+                                result_id: result_id.to_string(),
+                            },
+                    },
+                ),
+            };
+            Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+        },
+    );
+
+    cx.executor().advance_clock(Duration::from_secs(1));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("let ", None),
+                ("a", Some(DiagnosticSeverity::ERROR)),
+                (" = 1;", None),
+            ]
+        );
+    });
+
+    // Edits pull the diagnostics again, and the server reports them as unchanged.
+    buffer.update(cx, |buffer, cx| buffer.edit([(10..10, "\n")], None, cx));
+    cx.executor().advance_clock(Duration::from_secs(1));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![(Point::new(0, 4)..Point::new(0, 5), "error 1".to_string())]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2674,6 +2801,266 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() { b() }\nfn b() { c() }\nfn c() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn function_item(name: &str, row: u32, len: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.into(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path!("/dir/a.rs")).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, len)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, 3),
+                lsp::Position::new(row, 4),
+            ),
+            data: None,
+        }
+    }
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 3)
+            );
+            Ok(Some(vec![function_item("b", 1, 14)]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: function_item("a", 0, 14),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 9),
+                    lsp::Position::new(0, 10),
+                )],
+            }]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: function_item("c", 2, 9),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(1, 9),
+                    lsp::Position::new(1, 10),
+                )],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, PointUtf16::new(1, 3), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.into_iter().next().unwrap();
+    assert_eq!(item.name(), "b");
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(item.selection_range.to_offset(buffer), 18..19);
+    });
+
+    let calls_summary = |calls: Vec<CallHierarchyCall>, cx: &gpui::TestAppContext| {
+        calls
+            .into_iter()
+            .map(|call| {
+                let call_sites = call
+                    .call_sites
+                    .iter()
+                    .map(|site| {
+                        site.buffer
+                            .read_with(cx, |buffer, _| site.range.to_offset(buffer))
+                    })
+                    .collect::<Vec<_>>();
+                (call.item.name().to_string(), call_sites)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Incoming call sites are in the caller, and outgoing ones in the queried item.
+    let incoming = project
+        .update(cx, |project, cx| project.incoming_calls(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        calls_summary(incoming, cx),
+        [("a".to_string(), vec![9..10])]
+    );
+    let outgoing = project
+        .update(cx, |project, cx| project.outgoing_calls(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        calls_summary(outgoing, cx),
+        [("c".to_string(), vec![24..25])]
+    );
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "trait Shape {}\nstruct Square;\nimpl Shape for Square {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn type_item(
+        name: &str,
+        kind: lsp::SymbolKind,
+        row: u32,
+        column: u32,
+    ) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.into(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path!("/dir/a.rs")).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 14)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, column),
+                lsp::Position::new(row, column + name.len() as u32),
+            ),
+            data: None,
+        }
+    }
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 7)
+            );
+            Ok(Some(vec![type_item(
+                "Square",
+                lsp::SymbolKind::STRUCT,
+                1,
+                7,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Square");
+            Ok(Some(vec![type_item(
+                "Shape",
+                lsp::SymbolKind::INTERFACE,
+                0,
+                6,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Shape");
+            Ok(Some(vec![type_item(
+                "Square",
+                lsp::SymbolKind::STRUCT,
+                1,
+                7,
+            )]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, PointUtf16::new(1, 7), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let square = items.into_iter().next().unwrap();
+    assert_eq!(square.name(), "Square");
+
+    let items_summary = |items: &[TypeHierarchyItem], cx: &gpui::TestAppContext| {
+        items
+            .iter()
+            .map(|item| {
+                let range = item
+                    .buffer
+                    .read_with(cx, |buffer, _| item.selection_range.to_offset(buffer));
+                (item.name().to_string(), range)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&square, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        items_summary(&supertypes, cx),
+        [("Shape".to_string(), 6..11)]
+    );
+
+    // Querying the subtypes of a supertype leads back to the original type.
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&supertypes[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        items_summary(&subtypes, cx),
+        [("Square".to_string(), 22..28)]
+    );
+}
+
 #[gpui::test]
 async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3004,60 +3391,259 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
-#[gpui::test(iterations = 30)]
-async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_save_file_with_encoding(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let fs = FakeFs::new(cx.executor().clone());
-    fs.insert_tree(
-        path!("/dir"),
-        json!({
-            "file1": "the original contents",
-        }),
-    )
-    .await;
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({})).await;
+    fs.insert_file(path!("/dir/latin1.txt"), b"caf\xE9".to_vec())
+        .await;
 
     let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
-    let worktree = project.read_with(cx, |project, cx| project.worktrees(cx).next().unwrap());
     let buffer = project
-        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/file1"), cx))
+        .update(cx, |p, cx| {
+            p.open_local_buffer(path!("/dir/latin1.txt"), cx)
+        })
         .await
         .unwrap();
-
-    // Simulate buffer diffs being slow, so that they don't complete before
-    // the next file change occurs.
-    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
-
-    // Change the buffer's file on disk, and then wait for the file change
-    // to be detected by the worktree, so that the buffer starts reloading.
-    fs.save(
-        path!("/dir/file1").as_ref(),
-        &"the first contents".into(),
-        Default::default(),
-    )
-    .await
-    .unwrap();
-    worktree.next_event(cx).await;
-
-    // Change the buffer's file again. Depending on the random seed, the
-    // previous file change may still be in progress.
-    fs.save(
-        path!("/dir/file1").as_ref(),
-        &"the second contents".into(),
-        Default::default(),
-    )
-    .await
-    .unwrap();
-    worktree.next_event(cx).await;
-
-    cx.executor().run_until_parked();
-    let on_disk_text = fs.load(Path::new(path!("/dir/file1"))).await.unwrap();
-    buffer.read_with(cx, |buffer, _| {
-        assert_eq!(buffer.text(), on_disk_text);
-        assert!(!buffer.is_dirty(), "buffer should not be dirty");
-        assert!(!buffer.has_conflict(), "buffer should not be dirty");
+    // Files that aren't valid UTF-8 are decoded with the legacy encoding they're detected as.
+    let windows_1252 = Encoding::for_label("windows-1252").unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "café");
+        assert_eq!(buffer.encoding(), windows_1252);
+        assert!(!buffer.is_lossy());
     });
-}
+
+    // Reopened with an encoding that can't decode it, the file is decoded lossily, and can't be
+    // saved over until it's reopened with the right encoding.
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffers_with_encoding(
+                HashSet::from_iter([buffer.clone()]),
+                Encoding::default(),
+                true,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "caf\u{FFFD}");
+        assert_eq!(buffer.encoding(), Encoding::default());
+        assert!(buffer.is_lossy());
+    });
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/latin1.txt")))
+            .await
+            .unwrap(),
+        b"caf\xE9"
+    );
+
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffers_with_encoding(
+                HashSet::from_iter([buffer.clone()]),
+                windows_1252,
+                true,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "café");
+        assert_eq!(buffer.encoding(), windows_1252);
+        assert!(!buffer.is_lossy());
+        buffer.edit([(buffer.len()..buffer.len(), " crème")], None, cx);
+    });
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/latin1.txt")))
+            .await
+            .unwrap(),
+        b"caf\xE9 cr\xE8me"
+    );
+
+    // Characters that the encoding can't represent fail the save, leaving the file untouched.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(buffer.len()..buffer.len(), " 🍐")], None, cx);
+    });
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/latin1.txt")))
+            .await
+            .unwrap(),
+        b"caf\xE9 cr\xE8me"
+    );
+
+    // Reopening the file with another encoding decodes it again.
+    let encoding = Encoding::for_label("iso-8859-5").unwrap();
+    buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffers_with_encoding(
+                HashSet::from_iter([buffer.clone()]),
+                encoding,
+                true,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "cafщ crшme");
+        assert_eq!(buffer.encoding(), encoding);
+    });
+}
+
+#[gpui::test]
+async fn test_open_large_file(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.large_file_threshold_mb = Some(1);
+                settings.file_scan_exclusions = Some(vec!["**/excluded".to_string()]);
+            });
+        })
+    });
+
+    // The file spans several of the chunks it's read in.
+    let large_text = "fn main() {\r\n    println!(\"🍐\");\r\n}\r\n".repeat(100_000);
+    let excluded_text = "excluded\n".repeat(200_000);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "large.rs": large_text,
+            "empty.rs": "",
+            "excluded": {
+                "large.txt": excluded_text,
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/large.rs"), cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.capability(), Capability::ReadOnly);
+        assert_eq!(buffer.text(), large_text.replace("\r\n", "\n"));
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert!(buffer.language().is_none());
+    });
+
+    // Changes on disk aren't reloaded, but mark the buffer as conflicting.
+    fs.save(
+        path!("/dir/large.rs").as_ref(),
+        &"fn main() {}\n".into(),
+        LineEnding::Unix,
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), large_text.replace("\r\n", "\n"));
+        assert!(buffer.has_conflict());
+    });
+
+    // Excluded files have no worktree entry, but are still measured against the threshold.
+    let buffer = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer(path!("/dir/excluded/large.txt"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.text(), excluded_text);
+    });
+
+    // Files under the threshold open normally.
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/empty.rs"), cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert_eq!(buffer.capability(), Capability::ReadWrite);
+        assert_eq!(buffer.language().unwrap().name(), "Rust".into());
+    });
+}
+
+#[gpui::test(iterations = 30)]
+async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "file1": "the original contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let worktree = project.read_with(cx, |project, cx| project.worktrees(cx).next().unwrap());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/file1"), cx))
+        .await
+        .unwrap();
+
+    // Simulate buffer diffs being slow, so that they don't complete before
+    // the next file change occurs.
+    cx.executor().deprioritize(*language::BUFFER_DIFF_TASK);
+
+    // Change the buffer's file on disk, and then wait for the file change
+    // to be detected by the worktree, so that the buffer starts reloading.
+    fs.save(
+        path!("/dir/file1").as_ref(),
+        &"the first contents".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    worktree.next_event(cx).await;
+
+    // Change the buffer's file again. Depending on the random seed, the
+    // previous file change may still be in progress.
+    fs.save(
+        path!("/dir/file1").as_ref(),
+        &"the second contents".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    worktree.next_event(cx).await;
+
+    cx.executor().run_until_parked();
+    let on_disk_text = fs.load(Path::new(path!("/dir/file1"))).await.unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), on_disk_text);
+        assert!(!buffer.is_dirty(), "buffer should not be dirty");
+        assert!(!buffer.has_conflict(), "buffer should not be dirty");
+    });
+}
 
 #[gpui::test(iterations = 30)]
 async fn test_edit_buffer_while_it_reloads(cx: &mut gpui::TestAppContext) {
@@ -4357,6 +4943,122 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn one() { let a = foo.unwrap(); bar(a).unwrap(); }",
+            "two.rs": "fn unwrap() { unwrap(); }",
+            "three.txt": "foo.unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let query = SearchQuery::structural(
+        "$X.unwrap()",
+        false,
+        Default::default(),
+        Default::default(),
+        None,
+    )
+    .unwrap()
+    .with_replacement("$X.expect(\"$X\")".to_string());
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([(separator!("dir/one.rs").to_string(), vec![19..31, 33..48])])
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/one.rs"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        assert_eq!(
+            query.structural_replacement_for(&snapshot, 33..48),
+            Some("bar(a).expect(\"bar(a)\")".to_string())
+        );
+        assert_eq!(query.structural_replacement_for(&snapshot, 33..40), None);
+    });
+}
+
+#[gpui::test]
+async fn test_search_scopes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            ".git": {},
+            "one.rs": "// Rust file one",
+            "two.rs": "// Rust file two",
+            "three.ts": "// TypeScript file three",
+        }),
+    )
+    .await;
+    fs.set_status_for_repo_via_git_operation(
+        Path::new(path!("/dir/.git")),
+        &[
+            (
+                Path::new("two.rs"),
+                git::status::StatusCode::Modified.worktree(),
+            ),
+            (Path::new("three.ts"), FileStatus::Untracked),
+        ],
+    );
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let query = |scope| {
+        SearchQuery::text(
+            "file",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+        .with_scope(scope)
+    };
+    assert_eq!(
+        search(&project, query(SearchScope::GitChanged), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/two.rs").to_string(), vec![8..12]),
+            (separator!("dir/three.ts").to_string(), vec![14..18]),
+        ]),
+        "Only files with changes are searched"
+    );
+    let rust = SearchScope::Language {
+        name: "Rust".to_string(),
+        path_suffixes: vec!["rs".to_string()],
+    };
+    assert_eq!(
+        search(&project, query(rust.clone()), cx).await.unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/one.rs").to_string(), vec![8..12]),
+            (separator!("dir/two.rs").to_string(), vec![8..12]),
+        ]),
+        "Only files with the language's path suffixes are searched"
+    );
+
+    let message = query(rust.clone()).to_proto();
+    assert_eq!(SearchQuery::from_proto(message).unwrap().scope(), &rust);
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5285,14 +5987,14 @@ async fn test_code_actions_only_kinds(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
+async fn test_semantic_tokens_delta(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         path!("/dir"),
         json!({
-            "a.tsx": "a",
+            "a.ts": "let a = b;\nlet c = a;",
         }),
     )
     .await;
@@ -5300,18 +6002,708 @@ async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(tsx_lang());
-    let language_server_names = [
-        "TypeScriptServer",
-        "TailwindServer",
-        "ESLintServer",
-        "NoActionsCapabilitiesServer",
-    ];
-
-    let mut language_server_rxs = [
-        language_registry.register_fake_lsp(
-            "tsx",
-            FakeLspAdapter {
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::KEYWORD,
+                                    lsp::SemanticTokenType::VARIABLE,
+                                ],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::READONLY],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..lsp::SemanticTokensOptions::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        token_type: u32,
+        token_modifiers_bitset: u32,
+    ) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset,
+        }
+    }
+
+    fn describe(
+        tokens: &SemanticTokens,
+        buffer: &Buffer,
+    ) -> Vec<(Range<Point>, String, Vec<String>)> {
+        tokens
+            .tokens
+            .iter()
+            .map(|token| {
+                (
+                    token.range.to_point(buffer),
+                    tokens.token_type(token).unwrap().to_string(),
+                    tokens
+                        .token_modifiers(token)
+                        .map(ToString::to_string)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".to_string()),
+                    data: vec![
+                        token(0, 0, 3, 0, 0),
+                        token(0, 4, 1, 1, 1),
+                        token(0, 4, 1, 1, 0),
+                    ],
+                },
+            )))
+        },
+    );
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".to_string()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 15,
+                        delete_count: 0,
+                        data: Some(vec![token(1, 0, 3, 0, 0)]),
+                    }],
+                },
+            )))
+        },
+    );
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| describe(&tokens, buffer)),
+        vec![
+            (
+                Point::new(0, 0)..Point::new(0, 3),
+                "keyword".to_string(),
+                vec![]
+            ),
+            (
+                Point::new(0, 4)..Point::new(0, 5),
+                "variable".to_string(),
+                vec!["readonly".to_string()]
+            ),
+            (
+                Point::new(0, 8)..Point::new(0, 9),
+                "variable".to_string(),
+                vec![]
+            ),
+        ]
+    );
+
+    // The second request only asks for the changes since the first one.
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(tokens.tokens.len(), 4);
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| describe(&tokens, buffer))[3],
+        (
+            Point::new(1, 0)..Point::new(1, 3),
+            "keyword".to_string(),
+            vec![]
+        )
+    );
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "// a\n// b\nfunction a() {\n    if (b) {\n        c();\n    }\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let request_count = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>({
+        let request_count = request_count.clone();
+        move |_, _| {
+            request_count.fetch_add(1, atomic::Ordering::SeqCst);
+            async move {
+                let range = |start_line, end_line, kind| lsp::FoldingRange {
+                    start_line,
+                    end_line,
+                    kind,
+                    ..lsp::FoldingRange::default()
+                };
+                Ok(Some(vec![
+                    range(3, 5, None),
+                    range(2, 6, None),
+                    range(0, 1, Some(lsp::FoldingRangeKind::Comment)),
+                    // Ranges that can't be folded are ignored.
+                    range(4, 4, None),
+                    range(6, 100, None),
+                ]))
+            }
+        }
+    });
+
+    let folding_ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| {
+            folding_ranges
+                .iter()
+                .map(|folding_range| {
+                    (
+                        folding_range.range.to_point(buffer),
+                        folding_range.kind.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        }),
+        vec![
+            (
+                Point::new(0, 4)..Point::new(1, 4),
+                Some(lsp::FoldingRangeKind::Comment)
+            ),
+            (Point::new(2, 14)..Point::new(6, 1), None),
+            (Point::new(3, 12)..Point::new(5, 5), None),
+        ]
+    );
+
+    // Ranges are cached until the buffer changes.
+    project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    let folding_ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(request_count.load(atomic::Ordering::SeqCst), 2);
+    assert_eq!(folding_ranges.len(), 3);
+}
+
+#[gpui::test]
+async fn test_code_lens_resolution(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "function one() {}\nfunction two() {}\nfunction three() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["server.command".to_string()],
+                    ..Default::default()
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let command = |title: &str, command: &str| lsp::Command {
+        title: title.to_string(),
+        command: command.to_string(),
+        arguments: None,
+    };
+    let line = |row| lsp::Range::new(lsp::Position::new(row, 9), lsp::Position::new(row, 12));
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(move |_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: line(0),
+                command: Some(command("Run on server", "server.command")),
+                data: None,
+            },
+            lsp::CodeLens {
+                range: line(1),
+                command: Some(command("Unknown", "client.unknownCommand")),
+                data: None,
+            },
+            lsp::CodeLens {
+                range: line(2),
+                command: None,
+                data: Some(json!("references")),
+            },
+        ]))
+    });
+    let resolve_count = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>({
+        let resolve_count = resolve_count.clone();
+        move |lens, _| {
+            resolve_count.fetch_add(1, atomic::Ordering::SeqCst);
+            async move {
+                Ok(lsp::CodeLens {
+                    command: Some(command("3 references", "editor.action.showReferences")),
+                    ..lens
+                })
+            }
+        }
+    });
+
+    // Lenses are not resolved when they are fetched, and lenses whose command neither the
+    // client nor the server can run are left out.
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        lenses.iter().map(|lens| lens.title()).collect::<Vec<_>>(),
+        [Some("Run on server"), None]
+    );
+    assert_eq!(resolve_count.load(atomic::Ordering::SeqCst), 0);
+
+    let resolved = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(buffer.clone(), lenses[1].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(resolved.title(), Some("3 references"));
+    assert_eq!(
+        resolved.client_command(),
+        Some(CodeLensClientCommand::ShowReferences)
+    );
+    assert_eq!(resolve_count.load(atomic::Ordering::SeqCst), 1);
+
+    // Resolving a lens that already has a command doesn't reach the server.
+    project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(buffer.clone(), resolved, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(resolve_count.load(atomic::Ordering::SeqCst), 1);
+}
+
+#[gpui::test]
+async fn test_document_colors(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "const red = \"#ff0000\";\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let literal_range = lsp::Range::new(lsp::Position::new(0, 13), lsp::Position::new(0, 20));
+    let red = lsp::Color {
+        red: 1.,
+        green: 0.,
+        blue: 0.,
+        alpha: 1.,
+    };
+    let green = lsp::Color {
+        red: 0.,
+        green: 1.,
+        blue: 0.,
+        alpha: 1.,
+    };
+    fake_server.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _| async move {
+        Ok(vec![lsp::ColorInformation {
+            range: literal_range,
+            color: red,
+        }])
+    });
+    fake_server.handle_request::<lsp::request::ColorPresentationRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.range, literal_range);
+            assert_eq!(params.color, green);
+            Ok(vec![
+                lsp::ColorPresentation {
+                    label: "#00ff00".to_string(),
+                    text_edit: None,
+                    additional_text_edits: None,
+                },
+                lsp::ColorPresentation {
+                    label: "rgb(0, 255, 0)".to_string(),
+                    text_edit: Some(lsp::TextEdit::new(
+                        literal_range,
+                        "rgb(0, 255, 0)".to_string(),
+                    )),
+                    additional_text_edits: Some(vec![lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+                        "// green\n".to_string(),
+                    )]),
+                },
+            ])
+        },
+    );
+
+    let colors = project
+        .update(cx, |project, cx| project.document_colors(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0].color, red);
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| colors[0].range.to_point(buffer)),
+        Point::new(0, 13)..Point::new(0, 20)
+    );
+
+    let presentations = project
+        .update(cx, |project, cx| {
+            project.color_presentations(&buffer, &colors[0], green, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| {
+            presentations
+                .iter()
+                .map(|presentation| {
+                    (
+                        presentation.label.as_str(),
+                        presentation
+                            .edits
+                            .iter()
+                            .map(|(range, new_text)| (range.to_point(buffer), new_text.as_str()))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        }),
+        vec![
+            (
+                "#00ff00",
+                vec![(Point::new(0, 13)..Point::new(0, 20), "#00ff00")]
+            ),
+            (
+                "rgb(0, 255, 0)",
+                vec![
+                    (Point::new(0, 13)..Point::new(0, 20), "rgb(0, 255, 0)"),
+                    (Point::new(0, 0)..Point::new(0, 0), "// green\n"),
+                ]
+            ),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_utf8_position_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "let a = \"é\"; a;\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                position_encoding: Some(lsp::PositionEncodingKind::UTF8),
+                document_highlight_provider: Some(lsp::OneOf::Left(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    // The second `a` is at UTF-16 column 13, but at byte 14 in UTF-8.
+    fake_server.handle_request::<lsp::request::DocumentHighlightRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 14)
+            );
+            Ok(Some(vec![
+                lsp::DocumentHighlight {
+                    range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 5)),
+                    kind: Some(lsp::DocumentHighlightKind::WRITE),
+                },
+                lsp::DocumentHighlight {
+                    range: lsp::Range::new(lsp::Position::new(0, 14), lsp::Position::new(0, 15)),
+                    kind: Some(lsp::DocumentHighlightKind::READ),
+                },
+            ]))
+        },
+    );
+
+    let highlights = project
+        .update(cx, |project, cx| {
+            project.document_highlights(&buffer, PointUtf16::new(0, 13), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| {
+            highlights
+                .iter()
+                .map(|highlight| highlight.range.to_point_utf16(buffer))
+                .collect::<Vec<_>>()
+        }),
+        vec![
+            PointUtf16::new(0, 4)..PointUtf16::new(0, 5),
+            PointUtf16::new(0, 13)..PointUtf16::new(0, 14),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_utf8_position_encoding_for_workspace_symbols(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.ts": "let é = 1; let a = 2;\n",
+            "b.ts": "let é = 1; let b = 2;\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                position_encoding: Some(lsp::PositionEncodingKind::UTF8),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    // Only `a.ts` is open, so `b.ts` has to be converted against its contents on disk.
+    let (_buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.ts"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    // Both symbols are at UTF-16 column 15, but at byte 16 in UTF-8.
+    fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(|_, _| async move {
+        #[allow(deprecated)]
+        Ok(Some(lsp::WorkspaceSymbolResponse::Flat(
+            ["a", "b"]
+                .into_iter()
+                .map(|name| lsp::SymbolInformation {
+                    name: name.to_string(),
+                    kind: lsp::SymbolKind::VARIABLE,
+                    tags: None,
+                    deprecated: None,
+                    container_name: None,
+                    location: lsp::Location::new(
+                        lsp::Url::from_file_path(path!("/dir/").to_string() + name + ".ts")
+                            .unwrap(),
+                        lsp::Range::new(lsp::Position::new(0, 16), lsp::Position::new(0, 17)),
+                    ),
+                })
+                .collect(),
+        )))
+    });
+
+    let symbols = project
+        .update(cx, |project, cx| project.symbols("", cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        symbols
+            .iter()
+            .map(|symbol| (
+                symbol.name.as_str(),
+                symbol.range.start.0..symbol.range.end.0
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("a", PointUtf16::new(0, 15)..PointUtf16::new(0, 16)),
+            ("b", PointUtf16::new(0, 15)..PointUtf16::new(0, 16)),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.tsx": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(tsx_lang());
+    let language_server_names = [
+        "TypeScriptServer",
+        "TailwindServer",
+        "ESLintServer",
+        "NoActionsCapabilitiesServer",
+    ];
+
+    let mut language_server_rxs = [
+        language_registry.register_fake_lsp(
+            "tsx",
+            FakeLspAdapter {
                 name: language_server_names[0],
                 capabilities: lsp::ServerCapabilities {
                     code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
@@ -5928,6 +7320,116 @@ async fn test_single_file_diffs(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_stash(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            ".git": {},
+            "a.txt": "one\n",
+            "b.txt": "two\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new(path!("/dir/.git"));
+    fs.set_head_for_repo(dot_git, &[("a.txt".into(), "zero\n".into())]);
+    fs.set_index_for_repo(dot_git, &[("a.txt".into(), "one\n".into())]);
+    fs.set_status_for_repo_via_git_operation(
+        dot_git,
+        &[(
+            Path::new("a.txt"),
+            git::status::StatusCode::Modified.worktree(),
+        )],
+    );
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.run_until_parked();
+    let repository = project
+        .read_with(cx, |project, cx| project.active_repository(cx))
+        .unwrap();
+
+    // Unsaved changes are saved before stashing, so that they're stashed too.
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/b.txt"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "edited ")], None, cx));
+    repository
+        .update(cx, |repository, cx| {
+            repository.stash(Some("message".into()), false, cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    assert!(!buffer.read_with(cx, |buffer, _| buffer.is_dirty()));
+    assert_eq!(
+        fs.load(path!("/dir/b.txt").as_ref()).await.unwrap(),
+        "edited two\n"
+    );
+    repository.read_with(cx, |repository, _| {
+        assert_eq!(repository.repository_entry.status_len(), 0)
+    });
+
+    let entries = repository
+        .read_with(cx, |repository, cx| repository.stash_entries(cx))
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "On (no branch): message");
+    let files = repository
+        .read_with(cx, |repository, cx| repository.stash_diff(0, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        files,
+        [git::repository::StashFileDiff {
+            path: "a.txt".into(),
+            old_text: Some("zero\n".into()),
+            new_text: Some("one\n".into()),
+        }]
+    );
+
+    // The entry is only popped if it's still the one that was listed.
+    let result = repository
+        .update(cx, |repository, cx| {
+            repository.stash_pop(0, "0".repeat(40).into(), cx)
+        })
+        .await;
+    assert!(result.is_err());
+    repository
+        .update(cx, |repository, cx| {
+            repository.stash_pop(0, entries[0].sha.clone(), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    let entries = repository
+        .read_with(cx, |repository, cx| repository.stash_entries(cx))
+        .await
+        .unwrap();
+    assert!(entries.is_empty());
+    repository.read_with(cx, |repository, _| {
+        let statuses = repository
+            .repository_entry
+            .status()
+            .map(|entry| (entry.repo_path, entry.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [(
+                RepoPath::from("a.txt"),
+                git::status::StatusCode::Modified.worktree()
+            )]
+        );
+    });
+}
+
 async fn search(
     project: &Entity<Project>,
     query: SearchQuery,
@@ -1,7 +1,10 @@
+mod structural;
+
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
 use fancy_regex::{Captures, Regex, RegexBuilder};
+use git::status::FileStatus;
 use gpui::Entity;
 use language::{Buffer, BufferSnapshot, CharKind};
 use smol::future::yield_now;
@@ -15,6 +18,8 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+pub use structural::StructuralPattern;
+
 pub enum SearchResult {
     Buffer {
         buffer: Entity<Buffer>,
@@ -36,6 +41,7 @@ pub struct SearchInputs {
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Entity<Buffer>>>,
+    scope: SearchScope,
 }
 
 impl SearchInputs {
@@ -51,6 +57,85 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Entity<Buffer>>> {
         &self.buffers
     }
+    pub fn scope(&self) -> &SearchScope {
+        &self.scope
+    }
+}
+
+/// Narrows a search down to some of the files that its include and exclude filters allow.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    #[default]
+    All,
+    /// Files that have changed since the last commit, including untracked files.
+    GitChanged,
+    /// Files of a language, recognized by the path suffixes the language is registered with, so
+    /// that files can be matched without loading them.
+    Language {
+        name: String,
+        path_suffixes: Vec<String>,
+    },
+}
+
+impl SearchScope {
+    /// Returns whether a file at the given path is in the scope. The file's git status is only
+    /// computed when the scope needs it.
+    pub fn contains(&self, path: &Path, git_status: impl FnOnce() -> Option<FileStatus>) -> bool {
+        match self {
+            Self::All => true,
+            Self::GitChanged => git_status().is_some_and(|status| status.has_changes()),
+            Self::Language { path_suffixes, .. } => {
+                let file_name = path.file_name().and_then(|name| name.to_str());
+                let extension = file_name.and_then(|file_name| file_name.split('.').last());
+                let candidates = [extension, file_name, path.to_str()];
+                path_suffixes
+                    .iter()
+                    .any(|suffix| candidates.contains(&Some(suffix.as_str())))
+            }
+        }
+    }
+
+    /// Returns whether a buffer without a file is in the scope, which can only be decided by its
+    /// language.
+    pub fn contains_unnamed(&self, buffer: &Buffer) -> bool {
+        match self {
+            Self::All => true,
+            Self::GitChanged => false,
+            Self::Language { name, .. } => buffer
+                .language()
+                .is_some_and(|language| language.name().as_ref() == name.as_str()),
+        }
+    }
+
+    pub fn from_proto(message: Option<proto::SearchScope>) -> Self {
+        match message.and_then(|message| message.variant) {
+            None => Self::All,
+            Some(proto::search_scope::Variant::GitChanged(_)) => Self::GitChanged,
+            Some(proto::search_scope::Variant::Language(language)) => Self::Language {
+                name: language.name,
+                path_suffixes: language.path_suffixes,
+            },
+        }
+    }
+
+    pub fn to_proto(&self) -> Option<proto::SearchScope> {
+        let variant = match self {
+            Self::All => return None,
+            Self::GitChanged => {
+                proto::search_scope::Variant::GitChanged(proto::search_scope::GitChanged {})
+            }
+            Self::Language {
+                name,
+                path_suffixes,
+            } => proto::search_scope::Variant::Language(proto::search_scope::Language {
+                name: name.clone(),
+                path_suffixes: path_suffixes.clone(),
+            }),
+        };
+        Some(proto::SearchScope {
+            variant: Some(variant),
+        })
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -72,6 +157,14 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    /// Matches code with the same syntax tree as a pattern, such as `$X.unwrap()`.
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -99,6 +192,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            scope: SearchScope::All,
         };
         Ok(Self::Text {
             search,
@@ -146,6 +240,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            scope: SearchScope::All,
         };
         Ok(Self::Regex {
             regex,
@@ -158,8 +253,41 @@ impl SearchQuery {
         })
     }
 
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+            scope: SearchScope::All,
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let scope = SearchScope::from_proto(message.scope.clone());
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -179,7 +307,8 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
-        }
+        };
+        Ok(query?.with_scope(scope))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -191,6 +320,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -198,15 +331,28 @@ impl SearchQuery {
         }
     }
 
+    pub fn with_scope(mut self, scope: SearchScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.scope = scope;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: self.files_to_include().sources().join(","),
             files_to_exclude: self.files_to_exclude().sources().join(","),
+            scope: self.scope().to_proto(),
         }
     }
 
@@ -247,19 +393,26 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(pattern.may_match(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    /// Structural replacements depend on the code around the match, so they come from [`Self::structural_replacement_for`] instead.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
+            SearchQuery::Structural { .. } => None,
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
             SearchQuery::Regex {
                 regex, replacement, ..
@@ -284,6 +437,23 @@ impl SearchQuery {
         }
     }
 
+    /// Replaces a structural search hit at `range` in `buffer` if replacement is set, substituting
+    /// the text that the pattern's metavariables matched for the ones in the replacement.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacement_for(buffer, range, replacement),
+            _ => None,
+        }
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -295,6 +465,15 @@ impl SearchQuery {
             return Default::default();
         }
 
+        if let Self::Structural { pattern, .. } = self {
+            let range = subrange.unwrap_or(0..buffer.len());
+            return pattern
+                .find_matches(buffer, range.clone())
+                .into_iter()
+                .map(|mat| mat.start - range.start..mat.end - range.start)
+                .collect();
+        }
+
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
@@ -376,6 +555,7 @@ impl SearchQuery {
                     }
                 }
             }
+            Self::Structural { .. } => unreachable!("structural queries are searched above"),
         }
 
         matches
@@ -393,6 +573,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -400,6 +581,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -411,6 +593,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -418,6 +603,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -430,6 +619,10 @@ impl SearchQuery {
         self.as_inner().buffers.as_ref()
     }
 
+    pub fn scope(&self) -> &SearchScope {
+        self.as_inner().scope()
+    }
+
     pub fn is_opened_only(&self) -> bool {
         self.as_inner().buffers.is_some()
     }
@@ -455,7 +648,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
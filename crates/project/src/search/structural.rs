@@ -0,0 +1,293 @@
+//! Structural search, which matches code whose syntax tree has the same shape as a pattern such
+//! as `$X.unwrap()`, in which metavariables like `$X` stand for any single syntax node.
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{with_parser, BufferSnapshot, Language, LanguageName, Node, Tree};
+use std::{ops::Range, sync::Arc};
+
+/// The identifier that metavariables are replaced with, followed by their name, so that patterns
+/// parse as code.
+const PLACEHOLDER_PREFIX: &str = "zed_metavariable_";
+
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    /// The pattern with its metavariables replaced by placeholder identifiers.
+    source: String,
+    /// The words outside of metavariables, which any text that matches the pattern contains.
+    words: Vec<String>,
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut source = String::new();
+        let mut code = String::new();
+        let mut last_end = 0;
+        for (range, name) in metavariables(pattern) {
+            code.push_str(&pattern[last_end..range.start]);
+            source.push_str(&pattern[last_end..range.start]);
+            source.push_str(PLACEHOLDER_PREFIX);
+            source.push_str(name);
+            last_end = range.end;
+        }
+        code.push_str(&pattern[last_end..]);
+        source.push_str(&pattern[last_end..]);
+
+        if code.trim().is_empty() {
+            return Err(anyhow!(
+                "a structural pattern must contain code besides metavariables"
+            ));
+        }
+        let words = text_words(&code).collect();
+        Ok(Self { source, words })
+    }
+
+    /// Returns whether text might contain a match, without parsing it.
+    pub fn may_match(&self, text: &str) -> bool {
+        self.words.iter().all(|word| text.contains(word.as_str()))
+    }
+
+    /// Returns the ranges of the syntax nodes in `range` that match the pattern. Matches can't
+    /// overlap, so the nodes within a match aren't matched themselves.
+    pub fn find_matches(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut patterns = HashMap::<LanguageName, Option<Tree>>::default();
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let pattern = patterns
+                .entry(layer.language.name())
+                .or_insert_with(|| self.parse(layer.language));
+            let Some(pattern) = pattern.as_ref().map(|tree| self.root(tree)) else {
+                continue;
+            };
+
+            let mut cursor = layer.node().walk();
+            'walk: loop {
+                let node = cursor.node();
+                let node_range = node.byte_range();
+                let mut descend = node_range.start < range.end && node_range.end > range.start;
+                if descend
+                    && node_range.start >= range.start
+                    && node_range.end <= range.end
+                    && self.matcher(buffer).match_node(pattern, node)
+                {
+                    matches.push(node_range);
+                    descend = false;
+                }
+
+                if descend && cursor.goto_first_child() {
+                    continue;
+                }
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'walk;
+                    }
+                }
+            }
+        }
+
+        // Matches in injected languages can be inside matches in the languages around them.
+        matches.sort_by_key(|range| (range.start, usize::MAX - range.end));
+        let mut last_end = 0;
+        matches.retain(|range| {
+            let keep = range.start >= last_end;
+            if keep {
+                last_end = range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Returns `replacement` with the metavariables in it replaced by the text they matched in the
+    /// match at `range`.
+    pub fn replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        for layer in buffer.syntax_layers() {
+            let Some(pattern) = self.parse(layer.language) else {
+                continue;
+            };
+            let pattern = self.root(&pattern);
+
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end);
+            while let Some(candidate) = node.filter(|node| node.byte_range() == range) {
+                let mut matcher = self.matcher(buffer);
+                if matcher.match_node(pattern, candidate) {
+                    let mut result = String::new();
+                    let mut last_end = 0;
+                    for (range, name) in metavariables(replacement) {
+                        result.push_str(&replacement[last_end..range.start]);
+                        match matcher.bindings.get(name) {
+                            Some(bound) => result.extend(buffer.text_for_range(bound.clone())),
+                            None => result.push_str(&replacement[range.clone()]),
+                        }
+                        last_end = range.end;
+                    }
+                    result.push_str(&replacement[last_end..]);
+                    return Some(result);
+                }
+                node = candidate.parent();
+            }
+        }
+        None
+    }
+
+    /// Parses the pattern as code in the given language, returning `None` if it isn't valid code
+    /// in that language.
+    fn parse(&self, language: &Arc<Language>) -> Option<Tree> {
+        let grammar = language.grammar()?;
+        let tree = with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(&self.source, None)
+        })?;
+
+        // Missing tokens, such as the semicolon after an expression, are fine in a pattern.
+        let mut cursor = tree.walk();
+        loop {
+            if cursor.node().is_error() {
+                return None;
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return Some(tree);
+                }
+            }
+        }
+    }
+
+    /// Returns the smallest node in the parsed pattern that contains all of it, which is what
+    /// code has to match.
+    fn root<'a>(&self, tree: &'a Tree) -> Node<'a> {
+        let start = self.source.len() - self.source.trim_start().len();
+        let end = self.source.trim_end().len();
+        let root = tree.root_node();
+        root.descendant_for_byte_range(start, end).unwrap_or(root)
+    }
+
+    fn matcher<'a>(&'a self, buffer: &'a BufferSnapshot) -> Matcher<'a> {
+        Matcher {
+            pattern_source: &self.source,
+            buffer,
+            bindings: HashMap::default(),
+        }
+    }
+}
+
+/// Matches code against a parsed pattern, keeping track of what its metavariables stand for.
+struct Matcher<'a> {
+    pattern_source: &'a str,
+    buffer: &'a BufferSnapshot,
+    bindings: HashMap<&'a str, Range<usize>>,
+}
+
+impl<'a> Matcher<'a> {
+    fn match_node(&mut self, pattern: Node, node: Node) -> bool {
+        let pattern_source = self.pattern_source;
+        let pattern_text = &pattern_source[pattern.byte_range()];
+        let metavariable = pattern_text
+            .strip_prefix(PLACEHOLDER_PREFIX)
+            .filter(|name| !name.is_empty() && name.chars().all(is_metavariable_char));
+        if let Some(name) = metavariable {
+            if !node.is_named() {
+                return false;
+            }
+            // A metavariable that appears more than once has to match the same text each time.
+            return match self.bindings.get(name) {
+                Some(bound) => self.text_eq(bound.clone(), &self.text(node.byte_range())),
+                None => {
+                    self.bindings.insert(name, node.byte_range());
+                    true
+                }
+            };
+        }
+
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+        let pattern_children = significant_children(pattern);
+        let children = significant_children(node);
+        if pattern_children.is_empty() {
+            return children.is_empty() && self.text_eq(node.byte_range(), pattern_text);
+        }
+        pattern_children.len() == children.len()
+            && pattern_children
+                .into_iter()
+                .zip(children)
+                .all(|(pattern_child, child)| self.match_node(pattern_child, child))
+    }
+
+    fn text(&self, range: Range<usize>) -> String {
+        self.buffer.text_for_range(range).collect()
+    }
+
+    fn text_eq(&self, range: Range<usize>, text: &str) -> bool {
+        range.len() == text.len() && self.text(range) == text
+    }
+}
+
+/// Returns the children of a node, without comments and the tokens that tree-sitter inserted to
+/// recover from errors.
+fn significant_children<'a>(node: Node<'a>) -> Vec<Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn is_metavariable_char(c: char) -> bool {
+    c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'
+}
+
+/// Returns the ranges and names of metavariables such as `$X` or `$VALUE_2` in text.
+fn metavariables(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut metavariables = Vec::new();
+    let mut offset = 0;
+    while let Some(dollar) = text[offset..].find('$') {
+        let start = offset + dollar;
+        let name_start = start + 1;
+        let name_len = text[name_start..]
+            .find(|c| !is_metavariable_char(c))
+            .unwrap_or(text.len() - name_start);
+        let name = &text[name_start..name_start + name_len];
+        if name.starts_with(|c: char| c.is_ascii_uppercase() || c == '_') {
+            metavariables.push((start..name_start + name_len, name));
+        }
+        offset = name_start + name_len;
+    }
+    metavariables
+}
+
+fn text_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metavariables() {
+        assert_eq!(
+            metavariables("$X.foo($Y_2, $lower, $)"),
+            [(0..2, "X"), (7..11, "Y_2")]
+        );
+    }
+
+    #[test]
+    fn test_pattern_words() {
+        let pattern = StructuralPattern::new("$X.unwrap_or($DEFAULT)").unwrap();
+        assert!(pattern.may_match("let a = b.unwrap_or(c);"));
+        assert!(!pattern.may_match("let a = b.unwrap();"));
+        assert!(StructuralPattern::new(" $X ").is_err());
+    }
+}
@@ -32,7 +32,10 @@ use worktree::{
     WorktreeSettings,
 };
 
-use crate::{search::SearchQuery, ProjectPath};
+use crate::{
+    search::{SearchQuery, SearchScope},
+    ProjectPath,
+};
 
 struct MatchingEntry {
     worktree_path: Arc<Path>,
@@ -777,6 +780,10 @@ impl WorktreeStore {
             results.sort_by(|(a_path, _), (b_path, _)| a_path.cmp(b_path));
             for (path, is_file) in results {
                 if is_file {
+                    // Ignored files have no git status.
+                    if !query.scope().contains(&path, || None) {
+                        continue;
+                    }
                     if query.filters_path() {
                         let matched_path = if include_root {
                             let mut full_path = PathBuf::from(snapshot.root_name());
@@ -831,7 +838,10 @@ impl WorktreeStore {
         for (snapshot, settings) in snapshots {
             for entry in snapshot.entries(query.include_ignored(), 0) {
                 if entry.is_dir() && entry.is_ignored {
-                    if !settings.is_path_excluded(&entry.path) {
+                    // Ignored files are never changed as far as git is concerned.
+                    if !settings.is_path_excluded(&entry.path)
+                        && *query.scope() != SearchScope::GitChanged
+                    {
                         Self::scan_ignored_dir(
                             &fs,
                             &snapshot,
@@ -850,6 +860,13 @@ impl WorktreeStore {
                     continue;
                 }
 
+                if !query
+                    .scope()
+                    .contains(&entry.path, || snapshot.status_for_file(&entry.path))
+                {
+                    continue;
+                }
+
                 if query.filters_path() {
                     let matched_path = if include_root {
                         let mut full_path = PathBuf::from(snapshot.root_name());
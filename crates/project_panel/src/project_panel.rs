@@ -6,6 +6,7 @@ use client::{ErrorCode, ErrorExt};
 use collections::{hash_map, BTreeSet, HashMap};
 use command_palette_hooks::CommandPaletteFilter;
 use db::kvp::KEY_VALUE_STORE;
+use diff_view::{DiffSide, DiffView};
 use editor::{
     items::{
         entry_diagnostic_aware_icon_decoration_and_color,
@@ -92,6 +93,8 @@ pub struct ProjectPanel {
     edit_state: Option<EditState>,
     filename_editor: Entity<Editor>,
     clipboard: Option<ClipboardEntry>,
+    /// The file chosen with "Select for Compare", which "Compare with Selected" compares with.
+    compare_source: Option<ProjectPath>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakEntity<Workspace>,
     width: Option<Pixels>,
@@ -206,6 +209,8 @@ actions!(
         SelectPrevDiagnostic,
         SelectNextDirectory,
         SelectPrevDirectory,
+        SelectForCompare,
+        CompareWithSelected,
     ]
 );
 
@@ -440,6 +445,7 @@ impl ProjectPanel {
                 context_menu: None,
                 filename_editor,
                 clipboard: None,
+                compare_source: None,
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
                 width: None,
@@ -685,6 +691,9 @@ impl ProjectPanel {
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_via_collab();
             let is_local = project.is_local();
+            let can_compare = self.compare_source.as_ref().is_some_and(|source| {
+                source.worktree_id != worktree.id() || source.path != entry.path
+            });
 
             let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -706,6 +715,16 @@ impl ProjectPanel {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
+                            .when(!is_dir, |menu| {
+                                menu.separator()
+                                    .action("Select for Compare", Box::new(SelectForCompare))
+                                    .when(can_compare, |menu| {
+                                        menu.action(
+                                            "Compare with Selected",
+                                            Box::new(CompareWithSelected),
+                                        )
+                                    })
+                            })
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
@@ -2275,6 +2294,56 @@ impl ProjectPanel {
         }
     }
 
+    fn select_for_compare(&mut self, _: &SelectForCompare, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some((worktree, entry)) = self.selected_sub_entry(cx) {
+            if entry.is_file() {
+                self.compare_source = Some(ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: entry.path.clone(),
+                });
+            }
+        }
+    }
+
+    fn compare_with_selected(
+        &mut self,
+        _: &CompareWithSelected,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(source) = self.compare_source.clone() else {
+            return;
+        };
+        let Some(target) = self
+            .selected_sub_entry(cx)
+            .filter(|(_, entry)| entry.is_file())
+            .map(|(worktree, entry)| ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: entry.path.clone(),
+            })
+        else {
+            return;
+        };
+
+        let open_source = self
+            .project
+            .update(cx, |project, cx| project.open_buffer(source, cx));
+        let open_target = self
+            .project
+            .update(cx, |project, cx| project.open_buffer(target, cx));
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            let source = open_source.await?;
+            let target = open_target.await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let left = DiffSide::for_buffer(source, cx);
+                let right = DiffSide::for_buffer(target, cx);
+                DiffView::open(left, right, workspace, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn new_search_in_directory(
         &mut self,
         _: &NewSearchInDirectory,
@@ -4437,6 +4506,8 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::select_for_compare))
+                .on_action(cx.listener(Self::compare_with_selected))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
                 .on_action(cx.listener(Self::remove_from_project))
@@ -444,12 +444,54 @@ messages!(
     (GitCheckoutFiles, Background),
     (GitShow, Background),
     (GitCommitDetails, Background),
+    (GitLoadConflictTexts, Background),
+    (GitConflictTexts, Background),
+    (GitStash, Background),
+    (GitStashList, Background),
+    (GitStashEntries, Background),
+    (GitStashApply, Background),
+    (GitStashPop, Background),
+    (GitStashDrop, Background),
+    (GitLoadStashDiff, Background),
+    (GitStashDiff, Background),
     (SetIndexText, Background),
     (Push, Background),
     (Fetch, Background),
     (GetRemotes, Background),
     (GetRemotesResponse, Background),
     (Pull, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ApplyCodeLens, Background),
+    (ApplyCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (RefreshCodeLens, Foreground),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GetDocumentColors, Background),
+    (GetDocumentColorsResponse, Background),
+    (GetColorPresentations, Background),
+    (GetColorPresentationsResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
 );
 
 request_messages!(
@@ -497,6 +539,23 @@ request_messages!(
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (ApplyCodeLens, ApplyCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (RefreshCodeLens, Ack),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (RefreshSemanticTokens, Ack),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (GetDocumentColors, GetDocumentColorsResponse),
+    (GetColorPresentations, GetColorPresentationsResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
     (ListRemoteDirectory, ListRemoteDirectoryResponse),
     (GetUsers, UsersResponse),
     (IncomingCall, Ack),
@@ -584,6 +643,13 @@ request_messages!(
     (InstallExtension, Ack),
     (RegisterBufferWithLanguageServers, Ack),
     (GitShow, GitCommitDetails),
+    (GitLoadConflictTexts, GitConflictTexts),
+    (GitStash, Ack),
+    (GitStashList, GitStashEntries),
+    (GitStashApply, Ack),
+    (GitStashPop, Ack),
+    (GitStashDrop, Ack),
+    (GitLoadStashDiff, GitStashDiff),
     (GitReset, Ack),
     (GitCheckoutFiles, Ack),
     (SetIndexText, Ack),
@@ -684,6 +750,13 @@ entity_messages!(
     CancelLanguageServerWork,
     RegisterBufferWithLanguageServers,
     GitShow,
+    GitLoadConflictTexts,
+    GitStash,
+    GitStashList,
+    GitStashApply,
+    GitStashPop,
+    GitStashDrop,
+    GitLoadStashDiff,
     GitReset,
     GitCheckoutFiles,
     SetIndexText,
@@ -691,6 +764,23 @@ entity_messages!(
     Fetch,
     GetRemotes,
     Pull,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetCodeLens,
+    ApplyCodeLens,
+    ResolveCodeLens,
+    RefreshCodeLens,
+    GetSemanticTokens,
+    RefreshSemanticTokens,
+    GetFoldingRanges,
+    GetDocumentLinks,
+    GetDocumentColors,
+    GetColorPresentations,
+    ResolveDocumentLink,
 );
 
 entity_messages!(
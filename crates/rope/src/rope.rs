@@ -390,6 +390,28 @@ impl Rope {
             })
     }
 
+    /// Returns the number of chars between the start of the point's row and the point.
+    pub fn point_to_char_column(&self, point: Point) -> u32 {
+        let row_start = self.point_to_offset(Point::new(point.row, 0));
+        let offset = self.point_to_offset(point);
+        self.chunks_in_range(row_start..offset)
+            .map(|chunk| chunk.chars().count() as u32)
+            .sum()
+    }
+
+    /// Returns the point that is the given number of chars past the start of the row, clipped to
+    /// the end of the row.
+    pub fn char_column_to_point(&self, row: u32, column: u32) -> Point {
+        let row_start = self.point_to_offset(Point::new(row, 0));
+        let column_len = self
+            .chars_at(row_start)
+            .take(column as usize)
+            .take_while(|ch| *ch != '\n')
+            .map(char::len_utf8)
+            .sum::<usize>();
+        self.offset_to_point(row_start + column_len)
+    }
+
     pub fn clip_offset(&self, mut offset: usize, bias: Bias) -> usize {
         let mut cursor = self.chunks.cursor::<usize>(&());
         cursor.seek(&offset, Bias::Left, &());
@@ -1461,6 +1483,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_char_columns() {
+        let rope = Rope::from("a🧘b\n𠀀c\n");
+
+        assert_eq!(rope.point_to_char_column(Point::new(0, 0)), 0);
+        assert_eq!(rope.point_to_char_column(Point::new(0, 5)), 2);
+        assert_eq!(rope.point_to_char_column(Point::new(0, 6)), 3);
+        assert_eq!(rope.point_to_char_column(Point::new(1, 4)), 1);
+        assert_eq!(rope.point_to_char_column(Point::new(2, 0)), 0);
+
+        assert_eq!(rope.char_column_to_point(0, 1), Point::new(0, 1));
+        assert_eq!(rope.char_column_to_point(0, 2), Point::new(0, 5));
+        assert_eq!(rope.char_column_to_point(1, 1), Point::new(1, 4));
+        // Columns past the end of the row are clipped.
+        assert_eq!(rope.char_column_to_point(1, 10), Point::new(1, 5));
+        assert_eq!(rope.char_column_to_point(5, 1), Point::new(2, 0));
+    }
+
     #[test]
     fn test_prev_next_line() {
         let rope = Rope::from("abc\ndef\nghi\njkl");
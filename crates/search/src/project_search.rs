@@ -1,7 +1,7 @@
 use crate::{
     buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
     ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
@@ -11,15 +11,15 @@ use editor::{
 };
 use futures::StreamExt;
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, App, Axis, Context, Entity, EntityId, EventEmitter,
-    FocusHandle, Focusable, Global, Hsla, InteractiveElement, IntoElement, KeyContext,
-    ParentElement, Point, Render, SharedString, Styled, Subscription, Task, TextStyle,
+    actions, div, Action, AnyElement, AnyView, App, Axis, Context, Corner, Entity, EntityId,
+    EventEmitter, FocusHandle, Focusable, Global, Hsla, InteractiveElement, IntoElement,
+    KeyContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task, TextStyle,
     UpdateGlobal, WeakEntity, Window,
 };
 use language::{Buffer, Language};
 use menu::Confirm;
 use project::{
-    search::{SearchInputKind, SearchQuery},
+    search::{SearchInputKind, SearchQuery, SearchScope},
     search_history::SearchHistoryCursor,
     Project, ProjectPath,
 };
@@ -34,8 +34,9 @@ use std::{
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, utils::SearchInputWidth, v_flex, Icon, IconButton, IconButtonShape,
-    IconName, KeyBinding, Label, LabelCommon, LabelSize, Toggleable, Tooltip,
+    h_flex, prelude::*, utils::SearchInputWidth, v_flex, ContextMenu, Icon, IconButton,
+    IconButtonShape, IconName, IconPosition, KeyBinding, Label, LabelCommon, LabelSize,
+    PopoverMenu, Toggleable, Tooltip,
 };
 use util::paths::PathMatcher;
 use workspace::{
@@ -82,6 +83,9 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, _, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, _, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -169,6 +173,7 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     included_opened_only: bool,
+    search_scope: SearchScope,
     regex_language: Option<Arc<Language>>,
     _subscriptions: Vec<Subscription>,
 }
@@ -610,6 +615,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
         self.search_options.toggle(option);
+        // A query is either a regex or a syntax pattern, so turning one on turns the other off.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -623,6 +636,14 @@ impl ProjectSearchView {
         self.included_opened_only = !self.included_opened_only;
     }
 
+    fn set_search_scope(&mut self, scope: SearchScope, cx: &mut Context<Self>) {
+        self.search_scope = scope;
+        if self.entity.read(cx).active_query.is_some() {
+            self.search(cx);
+        }
+        cx.notify();
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
         if self.entity.read(cx).match_ranges.is_empty() {
             return;
@@ -739,6 +760,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited { .. } = event {
+                    this.refresh_replacement_previews(cx);
+                }
+            },
+        ));
         let results_editor = cx.new(|cx| {
             let mut editor =
                 Editor::for_multibuffer(excerpts, Some(project.clone()), true, window, cx);
@@ -828,6 +857,7 @@ impl ProjectSearchView {
             filters_enabled,
             replace_enabled: false,
             included_opened_only: false,
+            search_scope: SearchScope::All,
             regex_language: None,
             _subscriptions: subscriptions,
         };
@@ -1040,7 +1070,32 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1101,7 +1156,7 @@ impl ProjectSearchView {
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
-        query
+        query.map(|query| query.with_scope(self.search_scope.clone()))
     }
 
     fn open_buffers(&self, cx: &mut Context<Self>) -> Vec<Entity<Buffer>> {
@@ -1230,11 +1285,30 @@ impl ProjectSearchView {
                 self.focus_results_editor(window, cx);
             }
         }
+        self.refresh_replacement_previews(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
 
+    /// Shows what structural matches will be replaced with next to them, since the metavariables
+    /// in the replacement make it different for each match.
+    fn refresh_replacement_previews(&mut self, cx: &mut Context<Self>) {
+        let replacement = self.replacement(cx);
+        let query = self.entity.read(cx).active_query.clone().filter(|query| {
+            self.replace_enabled && query.is_structural() && !replacement.is_empty()
+        });
+        let match_ranges = self.entity.read(cx).match_ranges.clone();
+        self.results_editor.update(cx, |editor, cx| match query {
+            Some(query) => editor.show_replacement_previews(
+                &match_ranges,
+                &query.with_replacement(replacement),
+                cx,
+            ),
+            None => editor.clear_replacement_previews(cx),
+        });
+    }
+
     fn update_match_index(&mut self, cx: &mut Context<Self>) {
         let results_editor = self.results_editor.read(cx);
         let new_index = active_match_index(
@@ -1515,6 +1589,7 @@ impl ProjectSearchBar {
         if let Some(search) = &self.active_project_search {
             search.update(cx, |this, cx| {
                 this.replace_enabled = !this.replace_enabled;
+                this.refresh_replacement_previews(cx);
                 let editor_to_focus = if this.replace_enabled {
                     this.replacement_editor.focus_handle(cx)
                 } else {
@@ -1562,6 +1637,119 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_git_changed_only(&mut self, cx: &mut Context<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                let scope = if search_view.search_scope == SearchScope::GitChanged {
+                    SearchScope::All
+                } else {
+                    SearchScope::GitChanged
+                };
+                search_view.set_search_scope(scope, cx);
+            });
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn search_scope(&self, cx: &App) -> SearchScope {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.read(cx).search_scope.clone()
+        } else {
+            SearchScope::All
+        }
+    }
+
+    fn render_language_scope_menu(&self, cx: &App) -> Option<impl IntoElement> {
+        let search_view = self.active_project_search.as_ref()?;
+        let languages = search_view
+            .read(cx)
+            .entity
+            .read(cx)
+            .project
+            .read(cx)
+            .languages()
+            .clone();
+        let selected_language = match self.search_scope(cx) {
+            SearchScope::Language { name, .. } => Some(name),
+            _ => None,
+        };
+        let tooltip = match &selected_language {
+            Some(name) => format!("Only Searching {name} Files"),
+            None => "Only Search Files of a Language".to_string(),
+        };
+        let search_view = search_view.downgrade();
+
+        Some(
+            PopoverMenu::new("project-search-language-menu")
+                .trigger_with_tooltip(
+                    IconButton::new("project-search-language", IconName::FileCode)
+                        .shape(IconButtonShape::Square)
+                        .toggle_state(selected_language.is_some()),
+                    Tooltip::text(tooltip),
+                )
+                .anchor(Corner::TopRight)
+                .menu(move |window, cx| {
+                    let set_scope = {
+                        let search_view = search_view.clone();
+                        move |scope: SearchScope, cx: &mut App| {
+                            search_view
+                                .update(cx, |search_view, cx| {
+                                    search_view.set_search_scope(scope, cx)
+                                })
+                                .ok();
+                        }
+                    };
+                    let languages = languages.clone();
+                    let selected_language = selected_language.clone();
+                    Some(ContextMenu::build(window, cx, move |menu, _, _| {
+                        let all_languages = {
+                            let set_scope = set_scope.clone();
+                            move |_: &mut Window, cx: &mut App| set_scope(SearchScope::All, cx)
+                        };
+                        let menu = menu.toggleable_entry(
+                            "All Languages",
+                            selected_language.is_none(),
+                            IconPosition::Start,
+                            None,
+                            all_languages,
+                        );
+                        languages
+                            .language_names()
+                            .into_iter()
+                            .fold(menu, |menu, name| {
+                                let selected = selected_language.as_ref() == Some(&name);
+                                let set_scope = set_scope.clone();
+                                let languages = languages.clone();
+                                menu.toggleable_entry(
+                                    name.clone(),
+                                    selected,
+                                    IconPosition::Start,
+                                    None,
+                                    move |_, cx| {
+                                        let path_suffixes = languages
+                                            .available_language_for_name(&name)
+                                            .map(|language| {
+                                                language.matcher().path_suffixes.clone()
+                                            })
+                                            .unwrap_or_default();
+                                        set_scope(
+                                            SearchScope::Language {
+                                                name: name.clone(),
+                                                path_suffixes,
+                                            },
+                                            cx,
+                                        );
+                                    },
+                                )
+                            })
+                    }))
+                }),
+        )
+    }
+
     fn is_opened_only_enabled(&self, cx: &App) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.read(cx).included_opened_only
@@ -1795,6 +1983,13 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
@@ -2054,6 +2249,16 @@ impl Render for ProjectSearchBar {
                                     this.toggle_opened_only(window, cx);
                                 })),
                         )
+                        .child(
+                            IconButton::new("project-search-git-changed-only", IconName::FileGit)
+                                .shape(IconButtonShape::Square)
+                                .toggle_state(self.search_scope(cx) == SearchScope::GitChanged)
+                                .tooltip(Tooltip::text("Only Search Files Changed Since HEAD"))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.toggle_git_changed_only(cx);
+                                })),
+                        )
+                        .children(self.render_language_scope_menu(cx))
                         .child(
                             SearchOptions::INCLUDE_IGNORED.as_button(
                                 search
@@ -1,11 +1,18 @@
+mod transform;
+
 use anyhow::{anyhow, Context as _, Result};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
+pub use transform::Transform;
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// Text derived from tabstops by regex transforms, which follows the tabstops as they're
+    /// edited.
+    pub transformed_tabstops: Vec<TransformedTabStop>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -14,18 +21,110 @@ pub struct TabStop {
     pub choices: Option<Vec<String>>,
 }
 
+/// The text of a tabstop after a transform, such as `${1/(.*)/${1:/upcase}/}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformedTabStop {
+    /// The index in [`Snippet::tabstops`] of the tabstop that the text is derived from.
+    pub tabstop: usize,
+    pub range: Range<isize>,
+    pub transform: Transform,
+}
+
 impl Snippet {
+    /// Parses a snippet, inserting variables such as `$TM_FILENAME` as placeholders containing
+    /// their name.
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &|_| None)
+    }
 
-        let len = text.len() as isize;
-        let final_tabstop = tabstops.remove(&0);
-        let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+    /// Parses a snippet, inserting the values that `resolve_variable` returns for variables such
+    /// as `$TM_FILENAME`. Variables it returns `None` for are inserted as placeholders containing
+    /// their name, unless they have a default.
+    pub fn parse_with_variables(
+        source: &str,
+        resolve_variable: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut parser = SnippetParser::new(resolve_variable, BTreeMap::new());
+        parser.parse(source).context("failed to parse snippet")?;
+
+        // Transforms can refer to tabstops that are only defined after them, so parse again once
+        // the text of every tabstop is known.
+        if !parser.transformations.is_empty() {
+            let tabstop_texts = parser.tabstop_texts();
+            parser = SnippetParser::new(resolve_variable, tabstop_texts);
+            parser.parse(source).context("failed to parse snippet")?;
+        }
+
+        Ok(parser.finish())
+    }
+}
+
+struct SnippetParser<'a> {
+    resolve_variable: &'a dyn Fn(&str) -> Option<String>,
+    /// The text of each tabstop, which transforms of it are applied to.
+    tabstop_texts: BTreeMap<usize, String>,
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    transformations: Vec<(usize, Range<isize>, Transform)>,
+    /// Variables that couldn't be resolved, which become placeholders containing their name.
+    unresolved_variables: Vec<Range<isize>>,
+}
+
+impl<'a> SnippetParser<'a> {
+    fn new(
+        resolve_variable: &'a dyn Fn(&str) -> Option<String>,
+        tabstop_texts: BTreeMap<usize, String>,
+    ) -> Self {
+        Self {
+            resolve_variable,
+            tabstop_texts,
+            text: String::new(),
+            tabstops: BTreeMap::new(),
+            transformations: Vec::new(),
+            unresolved_variables: Vec::new(),
+        }
+    }
+
+    fn parse(&mut self, source: &str) -> Result<()> {
+        self.text.reserve(source.len());
+        self.parse_snippet(source, false)?;
+        Ok(())
+    }
+
+    /// The text of the first non-empty range of each tabstop.
+    fn tabstop_texts(&self) -> BTreeMap<usize, String> {
+        self.tabstops
+            .iter()
+            .map(|(index, tabstop)| {
+                let text = tabstop
+                    .ranges
+                    .iter()
+                    .find(|range| !range.is_empty())
+                    .map_or(String::new(), |range| {
+                        self.text[range.start as usize..range.end as usize].to_string()
+                    });
+                (*index, text)
+            })
+            .collect()
+    }
+
+    fn finish(mut self) -> Snippet {
+        let len = self.text.len() as isize;
+        let final_tabstop = self.tabstops.remove(&0);
+
+        let mut tabstop_indices = BTreeMap::new();
+        let mut tabstops = Vec::new();
+        for (index, tabstop) in self.tabstops {
+            tabstop_indices.insert(index, tabstops.len());
+            tabstops.push(tabstop);
+        }
+        tabstops.extend(self.unresolved_variables.into_iter().map(|range| TabStop {
+            ranges: [range].into_iter().collect(),
+            choices: None,
+        }));
 
         if let Some(final_tabstop) = final_tabstop {
+            tabstop_indices.insert(0, tabstops.len());
             tabstops.push(final_tabstop);
         } else {
             let end_tabstop = TabStop {
@@ -38,99 +137,201 @@ impl Snippet {
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        // Transforms of tabstops that don't appear anywhere else have nothing to follow.
+        let transformed_tabstops = self
+            .transformations
+            .into_iter()
+            .filter_map(|(index, range, transform)| {
+                Some(TransformedTabStop {
+                    tabstop: *tabstop_indices.get(&index)?,
+                    range,
+                    transform,
+                })
+            })
+            .collect();
+
+        Snippet {
+            text: self.text,
+            tabstops,
+            transformed_tabstops,
+        }
     }
-}
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    loop {
-        match source.chars().next() {
-            None => return Ok(""),
-            Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
-            }
-            Some('\\') => {
-                // As specified in the LSP spec (`Grammar` section),
-                // backslashes can escape some characters:
-                // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
-                source = &source[1..];
-                if let Some(c) = source.chars().next() {
-                    if c == '$' || c == '\\' || c == '}' {
-                        text.push(c);
-                        // All escapable characters are 1 byte long:
-                        source = &source[1..];
+    fn parse_snippet<'b>(&mut self, mut source: &'b str, nested: bool) -> Result<&'b str> {
+        loop {
+            match source.chars().next() {
+                None => return Ok(""),
+                Some('$') => {
+                    source = self.parse_dollar(&source[1..])?;
+                }
+                Some('\\') => {
+                    // As specified in the LSP spec (`Grammar` section),
+                    // backslashes can escape some characters:
+                    // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
+                    source = &source[1..];
+                    if let Some(c) = source.chars().next() {
+                        if c == '$' || c == '\\' || c == '}' {
+                            self.text.push(c);
+                            // All escapable characters are 1 byte long:
+                            source = &source[1..];
+                        } else {
+                            self.text.push('\\');
+                        }
                     } else {
-                        text.push('\\');
+                        self.text.push('\\');
                     }
-                } else {
-                    text.push('\\');
                 }
-            }
-            Some('}') => {
-                if nested {
-                    return Ok(source);
-                } else {
-                    text.push('}');
-                    source = &source[1..];
+                Some('}') => {
+                    if nested {
+                        return Ok(source);
+                    } else {
+                        self.text.push('}');
+                        source = &source[1..];
+                    }
+                }
+                Some(_) => {
+                    let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
+                    let (chunk, rest) = source.split_at(chunk_end);
+                    self.text.push_str(chunk);
+                    source = rest;
                 }
             }
-            Some(_) => {
-                let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
-                let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
-                source = rest;
+        }
+    }
+
+    /// Parses the tabstop or variable after a `$`.
+    fn parse_dollar<'b>(&mut self, source: &'b str) -> Result<&'b str> {
+        let braced = source.strip_prefix('{');
+        let name_source = braced.unwrap_or(source);
+        if name_source.starts_with(|c: char| c.is_ascii_digit()) {
+            self.parse_tabstop(source)
+        } else if let Some((name, rest)) = parse_variable_name(name_source) {
+            self.parse_variable(name, rest, braced.is_some())
+        } else if braced.is_some() {
+            Err(anyhow!("expected a tabstop or a variable"))
+        } else {
+            // A `$` that doesn't start a tabstop or a variable is just text.
+            self.text.push('$');
+            Ok(source)
+        }
+    }
+
+    fn parse_tabstop<'b>(&mut self, mut source: &'b str) -> Result<&'b str> {
+        let tabstop_start = self.text.len();
+        let tabstop_index;
+        let mut choices = None;
+
+        if source.starts_with('{') {
+            let (index, rest) = parse_int(&source[1..])?;
+            tabstop_index = index;
+            source = rest;
+
+            if let Some(rest) = source.strip_prefix('/') {
+                let (transform, rest) = Transform::parse(rest)?;
+                let tabstop_text = self
+                    .tabstop_texts
+                    .get(&tabstop_index)
+                    .map_or("", String::as_str);
+                self.text.push_str(&transform.apply(tabstop_text));
+                self.transformations.push((
+                    tabstop_index,
+                    tabstop_start as isize..self.text.len() as isize,
+                    transform,
+                ));
+                return rest
+                    .strip_prefix('}')
+                    .ok_or_else(|| anyhow!("expected a closing brace"));
             }
+
+            if source.starts_with("|") {
+                (source, choices) = parse_choices(&source[1..], &mut self.text)?;
+            }
+
+            if source.starts_with(':') {
+                source = self.parse_snippet(&source[1..], true)?;
+            }
+
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        } else {
+            let (index, rest) = parse_int(source)?;
+            tabstop_index = index;
+            source = rest;
         }
+
+        self.tabstops
+            .entry(tabstop_index)
+            .or_insert_with(|| TabStop {
+                ranges: Default::default(),
+                choices,
+            })
+            .ranges
+            .push(tabstop_start as isize..self.text.len() as isize);
+        Ok(source)
     }
-}
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    let mut choices = None;
-
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
-
-        if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
+    /// Parses the rest of a variable after its name, which is followed by a default, a transform
+    /// or the closing brace when the variable is `braced`.
+    fn parse_variable<'b>(
+        &mut self,
+        name: &str,
+        mut source: &'b str,
+        braced: bool,
+    ) -> Result<&'b str> {
+        let value = (self.resolve_variable)(name);
+        if !braced {
+            self.push_variable(name, value);
+            return Ok(source);
         }
 
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+        if let Some(rest) = source.strip_prefix('/') {
+            let (transform, rest) = Transform::parse(rest)?;
+            source = rest;
+            let value = value.map(|value| transform.apply(&value));
+            self.push_variable(name, value);
+        } else if let Some(default) = source.strip_prefix(':') {
+            // The default is used when the variable is empty too.
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                // The default still needs parsing to find where it ends.
+                let mut discarded = SnippetParser::new(self.resolve_variable, BTreeMap::new());
+                source = discarded.parse_snippet(default, true)?;
+                self.text.push_str(&value);
+            } else {
+                source = self.parse_snippet(default, true)?;
+            }
+        } else {
+            self.push_variable(name, value);
         }
 
-        if source.starts_with('}') {
-            source = &source[1..];
+        source
+            .strip_prefix('}')
+            .ok_or_else(|| anyhow!("expected a closing brace"))
+    }
+
+    fn push_variable(&mut self, name: &str, value: Option<String>) {
+        if let Some(value) = value {
+            self.text.push_str(&value);
         } else {
-            return Err(anyhow!("expected a closing brace"));
+            let start = self.text.len() as isize;
+            self.text.push_str(name);
+            self.unresolved_variables
+                .push(start..self.text.len() as isize);
         }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
-        source = rest;
     }
+}
 
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+/// Parses a variable name, which starts with a letter or an underscore.
+fn parse_variable_name(source: &str) -> Option<(&str, &str)> {
+    if !source.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return None;
+    }
+    let len = source
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(source.len());
+    Some(source.split_at(len))
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -327,6 +528,68 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let resolve_variable = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+        let parse = |source| Snippet::parse_with_variables(source, &resolve_variable).unwrap();
+
+        let snippet = parse("// $TM_FILENAME: ${TM_FILENAME}$1");
+        assert_eq!(snippet.text, "// main.rs: main.rs");
+        assert_eq!(tabstops(&snippet), &[vec![19..19]]);
+
+        // Defaults are used for empty and unknown variables, and can contain tabstops.
+        let snippet = parse("${TM_SELECTED_TEXT:${1:none}} ${UNKNOWN:x} ${TM_FILENAME:${2:y}}");
+        assert_eq!(snippet.text, "none x main.rs");
+        assert_eq!(tabstops(&snippet), &[vec![0..4], vec![14..14]]);
+
+        // Unknown variables become placeholders with their name, after the numbered tabstops.
+        let snippet = parse("$1 $UNKNOWN $0");
+        assert_eq!(snippet.text, " UNKNOWN ");
+        assert_eq!(tabstops(&snippet), &[vec![0..0], vec![1..8], vec![9..9]]);
+        let snippet = Snippet::parse("$TM_FILENAME").unwrap();
+        assert_eq!(snippet.text, "TM_FILENAME");
+        assert_eq!(tabstops(&snippet), &[vec![0..11], vec![11..11]]);
+
+        let snippet = parse("${TM_FILENAME/(.*)\\.rs/${1:/upcase}/}");
+        assert_eq!(snippet.text, "MAIN");
+        assert!(snippet.transformed_tabstops.is_empty());
+
+        // A `$` that doesn't start a tabstop or a variable is just text.
+        let snippet = parse("$ 5 $-");
+        assert_eq!(snippet.text, "$ 5 $-");
+        assert!(Snippet::parse("${-}").is_err());
+    }
+
+    #[test]
+    fn test_snippet_with_transformed_tabstops() {
+        let snippet = Snippet::parse("${1/(.*)/${1:/upcase}/} = ${1:value}; ${2/x/y/g}$2").unwrap();
+        assert_eq!(snippet.text, "VALUE = value; ");
+        assert_eq!(tabstops(&snippet), &[vec![8..13], vec![15..15]]);
+        assert_eq!(
+            snippet
+                .transformed_tabstops
+                .iter()
+                .map(|transformed| (transformed.tabstop, transformed.range.clone()))
+                .collect::<Vec<_>>(),
+            &[(0, 0..5), (1, 15..15)]
+        );
+        assert_eq!(
+            snippet.transformed_tabstops[0].transform.apply("abc"),
+            "ABC"
+        );
+        assert_eq!(
+            snippet.transformed_tabstops[1].transform.apply("xox"),
+            "yoy"
+        );
+
+        assert!(Snippet::parse("${1/(.*)/$1}").is_err());
+        assert!(Snippet::parse("${1/(/$1/}").is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
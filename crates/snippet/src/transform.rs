@@ -0,0 +1,342 @@
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// A regex transform, such as the `/(.*)/${1:/upcase}/g` in `${1/(.*)/${1:/upcase}/g}`, which
+/// replaces matches of the regex with the format.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    options: String,
+    format: Vec<FormatItem>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    /// `$1` or `${1}`.
+    Group(usize),
+    /// `${1:/upcase}` and the other case modifiers.
+    ChangeCase(usize, Case),
+    /// `${1:+if}`, `${1:?if:else}`, `${1:-else}` and `${1:else}`. When the group matched, it's
+    /// replaced with `if_matched`, or kept if there's none.
+    Conditional {
+        group: usize,
+        if_matched: Option<String>,
+        otherwise: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    CamelCase,
+    PascalCase,
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.options == other.options
+            && self.format == other.format
+    }
+}
+
+impl Transform {
+    /// Parses a transform from the text after its first `/`, returning the rest of the source,
+    /// starting at the closing brace.
+    pub(crate) fn parse(source: &str) -> Result<(Self, &str)> {
+        let (pattern, source) = parse_regex(source)?;
+        let (format, source) = parse_format(source)?;
+        let options_len = source
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(source.len());
+        let (options, source) = source.split_at(options_len);
+
+        let mut regex = RegexBuilder::new(&pattern);
+        for option in options.chars() {
+            match option {
+                'i' => {
+                    regex.case_insensitive(true);
+                }
+                'm' => {
+                    regex.multi_line(true);
+                }
+                's' => {
+                    regex.dot_matches_new_line(true);
+                }
+                // Global matching is handled when the transform is applied, and Unicode and
+                // sticky matching make no difference to it.
+                'g' | 'u' | 'y' => {}
+                _ => return Err(anyhow!("unknown regex option '{option}'")),
+            }
+        }
+
+        let transform = Self {
+            regex: regex.build()?,
+            options: options.to_string(),
+            format,
+        };
+        Ok((transform, source))
+    }
+
+    /// Replaces the first match of the regex in `text`, or all of them with the `g` option.
+    pub fn apply(&self, text: &str) -> String {
+        let global = self.options.contains('g');
+        let mut result = String::with_capacity(text.len());
+        let mut last_match_end = 0;
+        for captures in self.regex.captures_iter(text) {
+            let Some(matched) = captures.get(0) else {
+                continue;
+            };
+            result.push_str(&text[last_match_end..matched.start()]);
+            for item in &self.format {
+                item.append_to(&mut result, &captures);
+            }
+            last_match_end = matched.end();
+            if !global {
+                break;
+            }
+        }
+        result.push_str(&text[last_match_end..]);
+        result
+    }
+}
+
+impl FormatItem {
+    fn append_to(&self, result: &mut String, captures: &Captures) {
+        let group = |ix: usize| captures.get(ix).map_or("", |group| group.as_str());
+        match self {
+            Self::Text(text) => result.push_str(text),
+            Self::Group(ix) => result.push_str(group(*ix)),
+            Self::ChangeCase(ix, case) => result.push_str(&case.apply(group(*ix))),
+            Self::Conditional {
+                group: ix,
+                if_matched,
+                otherwise,
+            } => {
+                let group = group(*ix);
+                if group.is_empty() {
+                    result.push_str(otherwise);
+                } else {
+                    result.push_str(if_matched.as_deref().unwrap_or(group));
+                }
+            }
+        }
+    }
+}
+
+impl Case {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "upcase" => Ok(Self::Upcase),
+            "downcase" => Ok(Self::Downcase),
+            "capitalize" => Ok(Self::Capitalize),
+            "camelcase" => Ok(Self::CamelCase),
+            "pascalcase" => Ok(Self::PascalCase),
+            _ => Err(anyhow!("unknown case modifier '{name}'")),
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        let words = || {
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+        };
+        match self {
+            Self::Upcase => text.to_uppercase(),
+            Self::Downcase => text.to_lowercase(),
+            Self::Capitalize => capitalize(text),
+            Self::CamelCase => words()
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 {
+                        let mut chars = word.chars();
+                        chars
+                            .next()
+                            .map_or(String::new(), |c| c.to_lowercase().chain(chars).collect())
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            Self::PascalCase => words().map(capitalize).collect(),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+}
+
+/// Parses the regex up to the unescaped `/` that ends it.
+fn parse_regex(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected a '/' after the regex")),
+            Some('/') => return Ok((pattern, chars.as_str())),
+            Some('\\') => match chars.next() {
+                Some('/') => pattern.push('/'),
+                Some(c) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                None => return Err(anyhow!("expected a '/' after the regex")),
+            },
+            Some(c) => pattern.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
+/// Parses the format up to the unescaped `/` that ends it.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut format = Vec::new();
+    loop {
+        if let Some(rest) = source.strip_prefix('/') {
+            return Ok((format, rest));
+        } else if let Some(rest) = source.strip_prefix("${") {
+            let (item, rest) = parse_format_group(rest)?;
+            format.push(item);
+            source = rest;
+        } else if source.starts_with('$') && source[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            let (group, rest) = crate::parse_int(&source[1..])?;
+            format.push(FormatItem::Group(group));
+            source = rest;
+        } else {
+            let (text, rest) = parse_format_text(source, &['/', '$'])?;
+            // A `$` that doesn't start a group is just text.
+            let (text, rest) = match rest.strip_prefix('$') {
+                Some(rest) if !rest.starts_with(|c: char| c == '{' || c.is_ascii_digit()) => {
+                    (text + "$", rest)
+                }
+                _ => (text, rest),
+            };
+            if !text.is_empty() {
+                match format.last_mut() {
+                    Some(FormatItem::Text(previous)) => previous.push_str(&text),
+                    _ => format.push(FormatItem::Text(text)),
+                }
+            }
+            source = rest;
+        }
+    }
+}
+
+/// Parses a group in the format from the text after its `${`.
+fn parse_format_group(source: &str) -> Result<(FormatItem, &str)> {
+    let (group, source) = crate::parse_int(source)?;
+    let (item, source) = if let Some(rest) = source.strip_prefix(":/") {
+        let name_len = rest.find('}').unwrap_or(rest.len());
+        let (name, rest) = rest.split_at(name_len);
+        (FormatItem::ChangeCase(group, Case::parse(name)?), rest)
+    } else if let Some(rest) = source.strip_prefix(":+") {
+        let (if_matched, rest) = parse_format_text(rest, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: Some(if_matched),
+            otherwise: String::new(),
+        };
+        (item, rest)
+    } else if let Some(rest) = source.strip_prefix(":?") {
+        let (if_matched, rest) = parse_format_text(rest, &[':'])?;
+        let (otherwise, rest) = parse_format_text(&rest[1..], &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: Some(if_matched),
+            otherwise,
+        };
+        (item, rest)
+    } else if let Some(rest) = source
+        .strip_prefix(":-")
+        .or_else(|| source.strip_prefix(':'))
+    {
+        let (otherwise, rest) = parse_format_text(rest, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: None,
+            otherwise,
+        };
+        (item, rest)
+    } else {
+        (FormatItem::Group(group), source)
+    };
+
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    Ok((item, source))
+}
+
+/// Parses text in the format up to one of the `terminators`, which is left in the returned source.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected a '/' after the format")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => match chars.next() {
+                Some(c @ ('$' | '\\' | '/' | '}' | ':')) => text.push(c),
+                Some(c) => {
+                    text.push('\\');
+                    text.push(c);
+                }
+                None => text.push('\\'),
+            },
+            Some(c) => text.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(source: &str) -> Transform {
+        let (transform, rest) = Transform::parse(source).unwrap();
+        assert_eq!(rest, "");
+        transform
+    }
+
+    #[test]
+    fn test_transform_groups_and_options() {
+        assert_eq!(transform("(.*)/<$1>/").apply("abc"), "<abc>");
+        assert_eq!(transform("b/X/").apply("abcb"), "aXcb");
+        assert_eq!(transform("b/X/g").apply("abcb"), "aXcX");
+        assert_eq!(transform("B/X/gi").apply("abcb"), "aXcX");
+        assert_eq!(transform("\\//-/g").apply("a/b/c"), "a-b-c");
+        assert_eq!(transform("(a)(z)?/${2}$1\\$/").apply("a"), "a$");
+        assert!(Transform::parse("(.*)/$1/q").is_err());
+        assert!(Transform::parse("(.*)/$1").is_err());
+    }
+
+    #[test]
+    fn test_transform_case_modifiers() {
+        let apply = |format: &str, text: &str| transform(&format!("(.*)/{format}/")).apply(text);
+        assert_eq!(apply("${1:/upcase}", "foo bar"), "FOO BAR");
+        assert_eq!(apply("${1:/downcase}", "Foo BAR"), "foo bar");
+        assert_eq!(apply("${1:/capitalize}", "foo bar"), "Foo bar");
+        assert_eq!(apply("${1:/camelcase}", "foo_bar-baz"), "fooBarBaz");
+        assert_eq!(apply("${1:/pascalcase}", "foo_bar-baz"), "FooBarBaz");
+        assert!(Transform::parse("(.*)/${1:/shout}/").is_err());
+    }
+
+    #[test]
+    fn test_transform_conditionals() {
+        let apply = |format: &str, text: &str| transform(&format!("(a)?b/{format}/")).apply(text);
+        assert_eq!(apply("${1:+yes}", "ab"), "yes");
+        assert_eq!(apply("${1:+yes}", "b"), "");
+        assert_eq!(apply("${1:?yes:no}", "ab"), "yes");
+        assert_eq!(apply("${1:?yes:no}", "b"), "no");
+        assert_eq!(apply("${1:-no}", "ab"), "a");
+        assert_eq!(apply("${1:no}", "b"), "no");
+    }
+}
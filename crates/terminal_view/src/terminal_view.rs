@@ -1570,6 +1570,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal output has no syntax tree to match patterns against.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
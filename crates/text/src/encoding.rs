@@ -0,0 +1,244 @@
+use std::{borrow::Cow, fmt};
+
+use anyhow::{anyhow, Result};
+
+/// The number of bytes inspected when guessing whether a file without a byte order mark is
+/// encoded in UTF-16.
+const UTF16_DETECTION_LEN: usize = 4096;
+
+/// The character encoding of a file on disk.
+///
+/// Buffers always hold UTF-8 text. The encoding a file was decoded from is kept alongside its
+/// [`LineEnding`](crate::LineEnding), so that it can be written back the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    has_bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::new(encoding_rs::UTF_8, false)
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.has_bom && self.is_utf8() {
+            write!(f, "{} with BOM", self.name())
+        } else {
+            write!(f, "{}", self.name())
+        }
+    }
+}
+
+impl Encoding {
+    pub fn new(encoding: &'static encoding_rs::Encoding, has_bom: bool) -> Self {
+        // Only the Unicode encodings have a byte order mark.
+        let has_bom = has_bom && Self::bom_for(encoding).is_some();
+        Self { encoding, has_bom }
+    }
+
+    /// Looks up an encoding by one of its [WHATWG labels](https://encoding.spec.whatwg.org/#names-and-labels).
+    /// UTF-16 files are given a byte order mark, as most tools expect one.
+    pub fn for_label(label: &str) -> Option<Self> {
+        let encoding = encoding_rs::Encoding::for_label(label.as_bytes())?;
+        Some(Self::new(encoding, encoding != encoding_rs::UTF_8))
+    }
+
+    /// The encodings that files can be reopened or saved with.
+    pub fn all() -> Vec<Self> {
+        [
+            (encoding_rs::UTF_8, false),
+            (encoding_rs::UTF_8, true),
+            (encoding_rs::UTF_16LE, true),
+            (encoding_rs::UTF_16BE, true),
+            (encoding_rs::WINDOWS_1252, false),
+            (encoding_rs::WINDOWS_1250, false),
+            (encoding_rs::WINDOWS_1251, false),
+            (encoding_rs::WINDOWS_1253, false),
+            (encoding_rs::WINDOWS_1254, false),
+            (encoding_rs::WINDOWS_1255, false),
+            (encoding_rs::WINDOWS_1256, false),
+            (encoding_rs::WINDOWS_1257, false),
+            (encoding_rs::WINDOWS_1258, false),
+            (encoding_rs::WINDOWS_874, false),
+            (encoding_rs::ISO_8859_2, false),
+            (encoding_rs::ISO_8859_5, false),
+            (encoding_rs::ISO_8859_7, false),
+            (encoding_rs::ISO_8859_15, false),
+            (encoding_rs::KOI8_R, false),
+            (encoding_rs::KOI8_U, false),
+            (encoding_rs::SHIFT_JIS, false),
+            (encoding_rs::EUC_JP, false),
+            (encoding_rs::GBK, false),
+            (encoding_rs::GB18030, false),
+            (encoding_rs::BIG5, false),
+            (encoding_rs::EUC_KR, false),
+        ]
+        .into_iter()
+        .map(|(encoding, has_bom)| Self::new(encoding, has_bom))
+        .collect()
+    }
+
+    /// Returns this encoding with or without a byte order mark.
+    pub fn with_bom(self, has_bom: bool) -> Self {
+        Self::new(self.encoding, has_bom)
+    }
+
+    /// The name of the encoding, without regard for its byte order mark.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.has_bom
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    /// The bytes written at the start of files in this encoding.
+    pub fn bom(&self) -> &'static [u8] {
+        if self.has_bom {
+            Self::bom_for(self.encoding).unwrap_or_default()
+        } else {
+            &[]
+        }
+    }
+
+    fn bom_for(encoding: &'static encoding_rs::Encoding) -> Option<&'static [u8]> {
+        if encoding == encoding_rs::UTF_8 {
+            Some(b"\xEF\xBB\xBF")
+        } else if encoding == encoding_rs::UTF_16LE {
+            Some(b"\xFF\xFE")
+        } else if encoding == encoding_rs::UTF_16BE {
+            Some(b"\xFE\xFF")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the encoding indicated by the byte order mark at the start of `bytes`, if any.
+    pub fn from_bom(bytes: &[u8]) -> Option<Self> {
+        let (encoding, _) = encoding_rs::Encoding::for_bom(bytes)?;
+        Some(Self::new(encoding, true))
+    }
+
+    /// Guesses the encoding of a file's content.
+    ///
+    /// Byte order marks are trusted, then UTF-16 without a byte order mark is recognized by its
+    /// zero bytes. Content that's valid UTF-8 is treated as UTF-8, and anything else is matched
+    /// against the legacy encodings by the frequency of its characters.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if let Some(encoding) = Self::from_bom(bytes).or_else(|| Self::detect_utf16(bytes)) {
+            return encoding;
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Self::default();
+        }
+
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        Self::new(detector.guess(None, false), false)
+    }
+
+    /// Text that's mostly ASCII has a zero in every other byte when encoded in UTF-16, which
+    /// never happens in text files in other encodings.
+    fn detect_utf16(bytes: &[u8]) -> Option<Self> {
+        let sample = &bytes[..bytes.len().min(UTF16_DETECTION_LEN) & !1];
+        if sample.is_empty() {
+            return None;
+        }
+        let pairs = sample.len() / 2;
+        let (mut even_zeros, mut odd_zeros) = (0, 0);
+        for pair in sample.chunks_exact(2) {
+            even_zeros += (pair[0] == 0) as usize;
+            odd_zeros += (pair[1] == 0) as usize;
+        }
+
+        if odd_zeros * 2 > pairs && even_zeros * 10 < pairs {
+            Some(Self::new(encoding_rs::UTF_16LE, false))
+        } else if even_zeros * 2 > pairs && odd_zeros * 10 < pairs {
+            Some(Self::new(encoding_rs::UTF_16BE, false))
+        } else {
+            None
+        }
+    }
+
+    /// Decodes a file's content, skipping its byte order mark. Malformed sequences are replaced
+    /// with U+FFFD, and whether there were any is returned along with the text.
+    pub fn decode(&self, bytes: Vec<u8>) -> (String, bool) {
+        let own_bom = Self::bom_for(self.encoding).unwrap_or_default();
+        if self.is_utf8() && !bytes.starts_with(own_bom) {
+            return match String::from_utf8(bytes) {
+                Ok(text) => (text, false),
+                Err(error) => (String::from_utf8_lossy(error.as_bytes()).into_owned(), true),
+            };
+        }
+
+        let content = bytes.strip_prefix(own_bom).unwrap_or(&bytes);
+        let (text, had_errors) = self.encoding.decode_without_bom_handling(content);
+        (text.into_owned(), had_errors)
+    }
+
+    /// Returns a decoder for a file's content that's read a chunk at a time, skipping its byte
+    /// order mark.
+    pub fn chunk_decoder(&self) -> ChunkDecoder {
+        ChunkDecoder(self.encoding.new_decoder_with_bom_removal())
+    }
+
+    /// Encodes text to be written to a file, including the byte order mark if there is one.
+    /// Fails if the text contains characters that can't be represented in this encoding.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let bom = self.bom();
+        if self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE {
+            let little_endian = self.encoding == encoding_rs::UTF_16LE;
+            let mut bytes = Vec::with_capacity(bom.len() + text.len() * 2);
+            bytes.extend_from_slice(bom);
+            for unit in text.encode_utf16() {
+                if little_endian {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        let (encoded, _, had_unmappable_characters) = self.encoding.encode(text);
+        if had_unmappable_characters {
+            return Err(anyhow!("the text can't be represented in {}", self.name()));
+        }
+        if bom.is_empty() {
+            Ok(encoded)
+        } else {
+            let mut bytes = bom.to_vec();
+            bytes.extend_from_slice(&encoded);
+            Ok(Cow::Owned(bytes))
+        }
+    }
+}
+
+/// Decodes a file's content a chunk at a time, for files too large to decode in one piece.
+pub struct ChunkDecoder(encoding_rs::Decoder);
+
+impl ChunkDecoder {
+    /// Decodes the next chunk of content, appending it to `text`. A character split across chunks
+    /// is completed by the next call. Malformed sequences are replaced with U+FFFD, and whether
+    /// there were any in this chunk is returned.
+    pub fn decode(&mut self, mut bytes: &[u8], text: &mut String, is_last: bool) -> bool {
+        let mut had_errors = false;
+        loop {
+            let max_len = self.0.max_utf8_buffer_length(bytes.len());
+            text.reserve(max_len.unwrap_or(bytes.len()));
+            let (result, read, chunk_had_errors) = self.0.decode_to_string(bytes, text, is_last);
+            had_errors |= chunk_had_errors;
+            bytes = &bytes[read..];
+            if result == encoding_rs::CoderResult::InputEmpty {
+                return had_errors;
+            }
+        }
+    }
+}
@@ -189,6 +189,65 @@ fn test_line_endings() {
     buffer.check_invariants();
 }
 
+#[test]
+fn test_encodings() {
+    let utf8 = Encoding::default();
+    assert_eq!(Encoding::detect("🍐✅\n".as_bytes()), utf8);
+    assert_eq!(utf8.decode("🍐✅\n".into()), ("🍐✅\n".into(), false));
+
+    let utf8_bom = Encoding::detect(b"\xEF\xBB\xBFabc");
+    assert!(utf8_bom.is_utf8() && utf8_bom.has_bom());
+    assert_eq!(
+        utf8_bom.decode(b"\xEF\xBB\xBFabc".to_vec()),
+        ("abc".into(), false)
+    );
+    assert_eq!(utf8_bom.encode("abc").unwrap().as_ref(), b"\xEF\xBB\xBFabc");
+
+    let utf16 = Encoding::detect(b"\xFF\xFEa\0b\0");
+    assert_eq!(utf16.to_string(), "UTF-16LE");
+    assert_eq!(
+        utf16.decode(b"\xFF\xFEa\0b\0".to_vec()),
+        ("ab".into(), false)
+    );
+    assert_eq!(utf16.encode("ab").unwrap().as_ref(), b"\xFF\xFEa\0b\0");
+    // UTF-16 without a byte order mark is recognized by its zero bytes.
+    assert_eq!(Encoding::detect(b"\0a\0b\0\n").name(), "UTF-16BE");
+
+    // Content that isn't valid UTF-8 is matched against the legacy encodings.
+    let shift_jis =
+        Encoding::detect(b"\x93\xFA\x96\x7B\x8C\xEA\x82\xCC\x83\x65\x83\x4C\x83\x58\x83\x67");
+    assert_eq!(shift_jis.name(), "Shift_JIS");
+    let gbk = Encoding::detect(b"\xD6\xD0\xCE\xC4\xCE\xC4\xB1\xBE\xCE\xC4\xBC\xFE");
+    assert_eq!(gbk.name(), "GBK");
+    assert_eq!(
+        gbk.decode(b"\xD6\xD0\xCE\xC4".to_vec()),
+        ("中文".into(), false)
+    );
+    assert_eq!(
+        shift_jis.decode(b"\x93\xFA\x96\x7B\x8C\xEA".to_vec()),
+        ("日本語".into(), false)
+    );
+    assert_eq!(
+        shift_jis.encode("日本語").unwrap().as_ref(),
+        b"\x93\xFA\x96\x7B\x8C\xEA"
+    );
+    assert!(shift_jis.encode("🍐").is_err());
+
+    let windows_1252 = Encoding::detect(b"caf\xE9 cr\xE8me");
+    assert_eq!(windows_1252.name(), "windows-1252");
+    assert_eq!(Encoding::for_label("latin1"), Some(windows_1252));
+    assert_eq!(
+        windows_1252.decode(b"caf\xE9".to_vec()),
+        ("café".into(), false)
+    );
+
+    // Decoding with the wrong encoding replaces malformed sequences, and reports them.
+    assert_eq!(
+        utf8.decode(b"caf\xE9".to_vec()),
+        ("caf\u{FFFD}".into(), true)
+    );
+}
+
 #[test]
 fn test_line_len() {
     let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
@@ -51,6 +51,38 @@ impl SyntaxTheme {
         Some(ix as u32)
     }
 
+    /// Returns the style to use for an LSP semantic token, if the theme has one.
+    ///
+    /// Token types are mapped to the keys used for tree-sitter captures, e.g. `enumMember` is
+    /// styled as `variant`. Modifiers can be styled by adding a `.{modifier}` suffix to the key,
+    /// e.g. `variable.mutable`, and keys fall back to their parent, e.g. `function.macro` to
+    /// `function`.
+    pub fn semantic_token_style<'a>(
+        &self,
+        token_type: &str,
+        token_modifiers: impl IntoIterator<Item = &'a str>,
+    ) -> Option<HighlightStyle> {
+        let key = semantic_token_key(token_type)?;
+        let find = |name: &str| {
+            self.highlights
+                .iter()
+                .find_map(|(key, style)| (key == name).then_some(*style))
+        };
+
+        for modifier in token_modifiers {
+            if let Some(style) = find(&format!("{key}.{modifier}")) {
+                return Some(style);
+            }
+        }
+        let mut key = key;
+        loop {
+            if let Some(style) = find(key) {
+                return Some(style);
+            }
+            key = key.rsplit_once('.')?.0;
+        }
+    }
+
     /// Returns a new [`Arc<SyntaxTheme>`] with the given syntax styles merged in.
     pub fn merge(base: Arc<Self>, user_syntax_styles: Vec<(String, HighlightStyle)>) -> Arc<Self> {
         if user_syntax_styles.is_empty() {
@@ -87,12 +119,66 @@ impl SyntaxTheme {
     }
 }
 
+/// Maps the standard LSP semantic token types, and some commonly used custom ones, to syntax
+/// theme keys. Tokens without a key keep their tree-sitter highlighting.
+fn semantic_token_key(token_type: &str) -> Option<&'static str> {
+    Some(match token_type {
+        "namespace" => "namespace",
+        "type" | "class" | "interface" | "struct" | "typeParameter" | "typeAlias" | "union" => {
+            "type"
+        }
+        "builtinType" => "type.builtin",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" => "variable",
+        "property" | "event" => "property",
+        "function" | "method" => "function",
+        "macro" => "function.macro",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "escapeSequence" => "string.escape",
+        "formatSpecifier" => "string.special",
+        "regexp" => "string.regex",
+        "number" => "number",
+        "boolean" => "boolean",
+        "operator" => "operator",
+        "decorator" | "attribute" | "builtinAttribute" | "derive" => "attribute",
+        "label" | "lifetime" => "label",
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use gpui::FontStyle;
 
     use super::*;
 
+    #[test]
+    fn test_semantic_token_style() {
+        let syntax_theme = SyntaxTheme::new_test([
+            ("type", gpui::red()),
+            ("variable", gpui::green()),
+            ("variable.mutable", gpui::blue()),
+            ("function", gpui::yellow()),
+        ]);
+        let color = |token_type: &str, modifiers: &[&'static str]| {
+            syntax_theme
+                .semantic_token_style(token_type, modifiers.iter().copied())
+                .and_then(|style| style.color)
+        };
+
+        assert_eq!(color("struct", &[]), Some(gpui::red()));
+        assert_eq!(color("variable", &["declaration"]), Some(gpui::green()));
+        assert_eq!(color("variable", &["mutable"]), Some(gpui::blue()));
+        assert_eq!(color("parameter", &[]), Some(gpui::green()));
+        assert_eq!(color("macro", &[]), Some(gpui::yellow()));
+        assert_eq!(color("enumMember", &[]), None);
+        assert_eq!(color("unknownType", &[]), None);
+    }
+
     #[test]
     fn test_syntax_theme_merge() {
         // Merging into an empty `SyntaxTheme` keeps all the user-defined styles.
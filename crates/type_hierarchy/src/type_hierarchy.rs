@@ -0,0 +1,130 @@
+use anyhow::Result;
+use editor::{Editor, EditorMode};
+use gpui::{actions, App, Context, Entity, Task, Window};
+use hierarchy_view::{HierarchyDelegate, HierarchySymbol, HierarchyView};
+use project::{Project, TypeHierarchyItem};
+
+actions!(type_hierarchy, [ShowSupertypes, ShowSubtypes]);
+
+pub type TypeHierarchyView = HierarchyView<TypeHierarchyDelegate>;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(register).detach();
+}
+
+fn register(editor: &mut Editor, _: Option<&mut Window>, cx: &mut Context<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+
+    let handle = cx.entity().downgrade();
+    editor
+        .register_action({
+            let handle = handle.clone();
+            move |_: &ShowSupertypes, window, cx| {
+                if let Some(editor) = handle.upgrade() {
+                    deploy(editor, TypeHierarchyDirection::Supertypes, window, cx);
+                }
+            }
+        })
+        .detach();
+    editor
+        .register_action(move |_: &ShowSubtypes, window, cx| {
+            if let Some(editor) = handle.upgrade() {
+                deploy(editor, TypeHierarchyDirection::Subtypes, window, cx);
+            }
+        })
+        .detach();
+}
+
+fn deploy(
+    editor: Entity<Editor>,
+    direction: TypeHierarchyDirection,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    hierarchy_view::deploy::<TypeHierarchyDelegate>(
+        editor,
+        |project, buffer, position, cx| project.prepare_type_hierarchy(buffer, position, cx),
+        move |project, _, _| TypeHierarchyDelegate { project, direction },
+        window,
+        cx,
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeHierarchyDirection {
+    /// Show the types the symbol extends or implements.
+    Supertypes,
+    /// Show the types extending or implementing the symbol.
+    Subtypes,
+}
+
+impl TypeHierarchyDirection {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+}
+
+/// Loads the supertypes (or subtypes) of a type from the language server.
+pub struct TypeHierarchyDelegate {
+    project: Entity<Project>,
+    direction: TypeHierarchyDirection,
+}
+
+impl HierarchyDelegate for TypeHierarchyDelegate {
+    type Node = TypeHierarchyItem;
+
+    fn direction_label(&self) -> &'static str {
+        self.direction.label()
+    }
+
+    fn toggle_direction(&mut self) {
+        self.direction = self.direction.opposite();
+    }
+
+    fn load_children(
+        &self,
+        item: &TypeHierarchyItem,
+        cx: &mut App,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.project.update(cx, |project, cx| match self.direction {
+            TypeHierarchyDirection::Supertypes => project.supertypes(item, cx),
+            TypeHierarchyDirection::Subtypes => project.subtypes(item, cx),
+        })
+    }
+
+    fn symbol(&self, item: &TypeHierarchyItem, _: &App) -> HierarchySymbol {
+        HierarchySymbol {
+            name: item.name().to_string().into(),
+            detail: item.lsp_item.detail.clone().map(Into::into),
+            buffer: item.buffer.clone(),
+            selection_range: item.selection_range.clone(),
+        }
+    }
+
+    fn key_context(&self) -> &'static str {
+        "TypeHierarchy"
+    }
+
+    fn telemetry_event_text(&self) -> &'static str {
+        "Type Hierarchy Opened"
+    }
+
+    fn clone_on_split(&self, _: &mut Window, _: &mut App) -> Self {
+        Self {
+            project: self.project.clone(),
+            direction: self.direction,
+        }
+    }
+}
@@ -60,7 +60,8 @@ use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
+    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, UnsupportedProjectItem,
+    Worktree, WorktreeId,
 };
 use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnectionOptions};
 use schemars::JsonSchema;
@@ -2907,14 +2908,35 @@ impl Workspace {
     ) -> Task<Result<(Option<ProjectEntryId>, WorkspaceItemBuilder)>> {
         let project = self.project().clone();
         let project_item_builders = cx.default_global::<ProjectItemOpeners>().clone();
+        let mut project_item_builders = project_item_builders.0.into_iter().rev();
         let Some(open_project_item) = project_item_builders
-            .iter()
-            .rev()
+            .by_ref()
             .find_map(|open_project_item| open_project_item(&project, &path, window, cx))
         else {
             return Task::ready(Err(anyhow!("cannot open file {:?}", path.path)));
         };
-        open_project_item
+
+        window.spawn(cx, |mut cx| async move {
+            let mut open_project_item = open_project_item;
+            loop {
+                match open_project_item.await {
+                    // Items that can only tell whether they support a file by loading it hand
+                    // it over to the items registered before them.
+                    Err(error) if error.is::<UnsupportedProjectItem>() => {
+                        open_project_item = cx
+                            .update(|window, cx| {
+                                project_item_builders
+                                    .by_ref()
+                                    .find_map(|open_project_item| {
+                                        open_project_item(&project, &path, window, cx)
+                                    })
+                            })?
+                            .ok_or_else(|| anyhow!("cannot open file {:?}", path.path))?;
+                    }
+                    result => return result,
+                }
+            }
+        })
     }
 
     pub fn find_project_item<T>(
@@ -8696,6 +8718,73 @@ mod tests {
             }
         }
 
+        // View
+        struct TestDecliningItemView {
+            focus_handle: FocusHandle,
+        }
+        // Model
+        struct TestDecliningItem {}
+
+        impl project::ProjectItem for TestDecliningItem {
+            fn try_open(
+                _project: &Entity<Project>,
+                _path: &ProjectPath,
+                cx: &mut App,
+            ) -> Option<Task<gpui::Result<Entity<Self>>>> {
+                Some(cx.spawn(|_| async move { Err(UnsupportedProjectItem.into()) }))
+            }
+
+            fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+                None
+            }
+
+            fn project_path(&self, _: &App) -> Option<ProjectPath> {
+                None
+            }
+
+            fn is_dirty(&self) -> bool {
+                false
+            }
+        }
+
+        impl Item for TestDecliningItemView {
+            type Event = ();
+        }
+        impl EventEmitter<()> for TestDecliningItemView {}
+        impl Focusable for TestDecliningItemView {
+            fn focus_handle(&self, _cx: &App) -> FocusHandle {
+                self.focus_handle.clone()
+            }
+        }
+
+        impl Render for TestDecliningItemView {
+            fn render(
+                &mut self,
+                _window: &mut Window,
+                _cx: &mut Context<Self>,
+            ) -> impl IntoElement {
+                Empty
+            }
+        }
+
+        impl ProjectItem for TestDecliningItemView {
+            type Item = TestDecliningItem;
+
+            fn for_project_item(
+                _project: Entity<Project>,
+                _item: Entity<Self::Item>,
+                _: &mut Window,
+                cx: &mut Context<Self>,
+            ) -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    focus_handle: cx.focus_handle(),
+                }
+            }
+        }
+
         #[gpui::test]
         async fn test_register_project_item(cx: &mut TestAppContext) {
             init_test(cx);
@@ -8808,6 +8897,53 @@ mod tests {
                 .await;
             assert!(handle.is_err());
         }
+
+        #[gpui::test]
+        async fn test_register_project_item_declining_file(cx: &mut TestAppContext) {
+            init_test(cx);
+
+            cx.update(|cx| {
+                register_project_item::<TestPngItemView>(cx);
+                register_project_item::<TestDecliningItemView>(cx);
+            });
+
+            let fs = FakeFs::new(cx.executor());
+            fs.insert_tree(
+                "/root1",
+                json!({
+                    "one.png": "BINARYDATAHERE",
+                    "three.txt": "editing text, sure why not?"
+                }),
+            )
+            .await;
+            let project = Project::test(fs, ["root1".as_ref()], cx).await;
+            let (workspace, cx) =
+                cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+            let worktree_id = project.update(cx, |project, cx| {
+                project.worktrees(cx).next().unwrap().read(cx).id()
+            });
+
+            // The item registered last declines the file once it has looked at it.
+            let handle = workspace
+                .update_in(cx, |workspace, window, cx| {
+                    let project_path = (worktree_id, "one.png");
+                    workspace.open_path(project_path, None, true, window, cx)
+                })
+                .await
+                .unwrap();
+            assert_eq!(
+                handle.to_any().entity_type(),
+                TypeId::of::<TestPngItemView>()
+            );
+
+            let handle = workspace
+                .update_in(cx, |workspace, window, cx| {
+                    let project_path = (worktree_id, "three.txt");
+                    workspace.open_path(project_path, None, true, window, cx)
+                })
+                .await;
+            assert!(handle.is_err());
+        }
     }
 
     pub fn init_test(cx: &mut TestAppContext) {
@@ -55,6 +55,7 @@ use std::{
     ffi::OsStr,
     fmt,
     future::Future,
+    io,
     mem::{self},
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
@@ -68,7 +69,7 @@ use std::{
 use sum_tree::{
     Bias, Cursor, Edit, KeyedItem, SeekTarget, SumTree, Summary, TreeMap, TreeSet, Unit,
 };
-use text::{LineEnding, Rope};
+use text::{ChunkDecoder, Encoding, LineEnding, Rope};
 use util::{
     paths::{home_dir, PathMatcher, SanitizedPath},
     ResultExt,
@@ -108,6 +109,19 @@ pub enum CreatedEntry {
 pub struct LoadedFile {
     pub file: Arc<File>,
     pub text: String,
+    pub encoding: Encoding,
+    /// Whether the file wasn't valid in its encoding, and malformed sequences were replaced.
+    pub lossy: bool,
+}
+
+/// A file opened in large-file mode, read into a [`Rope`] a chunk at a time.
+pub struct LoadedLargeFile {
+    pub file: Arc<File>,
+    pub text: Rope,
+    pub line_ending: LineEnding,
+    pub encoding: Encoding,
+    /// Whether the file wasn't valid in its encoding, and malformed sequences were replaced.
+    pub lossy: bool,
 }
 
 pub struct LoadedBinaryFile {
@@ -670,6 +684,13 @@ pub enum Event {
 
 const EMPTY_PATH: &str = "";
 
+/// The number of bytes read at a time when loading a file in large-file mode.
+const LARGE_FILE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The number of bytes at the start of a file in large-file mode that its encoding is guessed
+/// from.
+const ENCODING_SAMPLE_LEN: usize = 64 * 1024;
+
 impl EventEmitter<Event> for Worktree {}
 
 impl Worktree {
@@ -999,6 +1020,46 @@ impl Worktree {
         }
     }
 
+    /// Whether the file at the given path should be opened in large-file mode, according to
+    /// the `large_file_threshold_mb` setting.
+    pub fn is_large_file(&self, path: &Path, cx: &App) -> Task<bool> {
+        match self {
+            Worktree::Local(this) => {
+                if let Some(entry) = this.entry_for_path(path) {
+                    return Task::ready(this.settings.is_large_file(entry.size));
+                }
+
+                // Excluded files have no entry, so their size is read from disk.
+                let Ok(abs_path) = this.absolutize(path) else {
+                    return Task::ready(false);
+                };
+                let fs = this.fs.clone();
+                let settings = this.settings.clone();
+                cx.background_spawn(async move {
+                    fs.metadata(&abs_path)
+                        .await
+                        .ok()
+                        .flatten()
+                        .is_some_and(|metadata| settings.is_large_file(metadata.len))
+                })
+            }
+            Worktree::Remote(_) => Task::ready(false),
+        }
+    }
+
+    pub fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &Context<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        match self {
+            Worktree::Local(this) => this.load_large_file(path, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load large files")))
+            }
+        }
+    }
+
     pub fn load_staged_file(&self, path: &Path, cx: &App) -> Task<Result<Option<String>>> {
         match self {
             Worktree::Local(this) => {
@@ -1065,10 +1126,11 @@ impl Worktree {
         path: &Path,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, encoding, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1726,7 +1788,7 @@ impl LocalWorktree {
 
         cx.spawn(|this, _cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let (text, encoding, lossy) = fs.load_with_encoding(&abs_path).await?;
 
             let worktree = this
                 .upgrade()
@@ -1756,7 +1818,72 @@ impl LocalWorktree {
                 }
             };
 
-            Ok(LoadedFile { file, text })
+            Ok(LoadedFile {
+                file,
+                text,
+                encoding,
+                lossy,
+            })
+        })
+    }
+
+    fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &Context<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        let path = Arc::from(path);
+        let abs_path = self.absolutize(&path);
+        let fs = self.fs.clone();
+        let entry = self.refresh_entry(path.clone(), None, cx);
+        let is_private = self.is_path_private(path.as_ref());
+
+        let worktree = cx.weak_entity();
+        cx.background_spawn(async move {
+            let abs_path = abs_path?;
+            let reader = fs.open_sync(&abs_path).await?;
+            let ChunkedText {
+                text,
+                line_ending,
+                encoding,
+                lossy,
+            } = read_in_chunks(reader, LARGE_FILE_CHUNK_SIZE)?;
+
+            let worktree = worktree
+                .upgrade()
+                .ok_or_else(|| anyhow!("worktree was dropped"))?;
+            let file = match entry.await? {
+                Some(entry) => File::for_entry(entry, worktree),
+                None => {
+                    let metadata = fs
+                        .metadata(&abs_path)
+                        .await
+                        .with_context(|| {
+                            format!("Loading metadata for excluded file {abs_path:?}")
+                        })?
+                        .with_context(|| {
+                            format!("Excluded file {abs_path:?} got removed during loading")
+                        })?;
+                    Arc::new(File {
+                        entry_id: None,
+                        worktree,
+                        path,
+                        disk_state: DiskState::Present {
+                            mtime: metadata.mtime,
+                        },
+                        is_local: true,
+                        is_private,
+                    })
+                }
+            };
+
+            Ok(LoadedLargeFile {
+                file,
+                text,
+                line_ending,
+                encoding,
+                lossy,
+            })
         })
     }
 
@@ -1842,6 +1969,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1854,7 +1982,10 @@ impl LocalWorktree {
         let write = cx.background_spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move {
+                fs.save_with_encoding(&abs_path, &text, line_ending, encoding)
+                    .await
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -3574,6 +3705,103 @@ async fn build_gitignore(abs_path: &Path, fs: &dyn Fs) -> Result<Gitignore> {
     Ok(builder.build()?)
 }
 
+/// Text read by [`read_in_chunks`].
+struct ChunkedText {
+    text: Rope,
+    line_ending: LineEnding,
+    encoding: Encoding,
+    /// Whether malformed sequences were replaced with U+FFFD.
+    lossy: bool,
+}
+
+/// Reads text into a [`Rope`] with normalized line endings, `chunk_size` bytes at a time, so
+/// that the whole file is never held in a single string. The encoding is guessed from the start
+/// of the file, and malformed sequences are replaced with U+FFFD.
+///
+/// This only avoids holding the file's bytes and its text at once: the whole text still ends up
+/// in the rope, so memory use and the time to open the file grow with its size.
+fn read_in_chunks(mut reader: impl io::Read, chunk_size: usize) -> Result<ChunkedText> {
+    let mut rope = Rope::new();
+    let mut line_ending = None;
+    let mut lossy = false;
+    let mut buffer = vec![0; chunk_size];
+    let mut sample = Vec::new();
+    let mut decoder: Option<(Encoding, ChunkDecoder)> = None;
+    let mut text = String::new();
+    loop {
+        let len = match reader.read(&mut buffer) {
+            Ok(len) => len,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        let is_last = len == 0;
+        let mut bytes = &buffer[..len];
+
+        // Read enough of the file to guess its encoding, ignoring a character that's split at
+        // the end of the sample, before decoding anything.
+        if decoder.is_none() {
+            sample.extend_from_slice(bytes);
+            if sample.len() < ENCODING_SAMPLE_LEN && !is_last {
+                continue;
+            }
+            let mut sample_len = sample.len();
+            if !is_last {
+                sample_len -= incomplete_utf8_suffix_len(&sample);
+            }
+            let encoding = Encoding::detect(&sample[..sample_len]);
+            decoder = Some((encoding, encoding.chunk_decoder()));
+            bytes = &sample;
+        }
+        let Some((encoding, decoder)) = decoder.as_mut() else {
+            continue;
+        };
+        lossy |= decoder.decode(bytes, &mut text, is_last);
+
+        // Hold back a `\r` that may begin a `\r\n` until the next chunk is read.
+        let ends_with_cr = !is_last && text.ends_with('\r');
+        if ends_with_cr {
+            text.pop();
+        }
+        if line_ending.is_none() && text.contains('\n') {
+            line_ending = Some(LineEnding::detect(&text));
+        }
+        LineEnding::normalize(&mut text);
+        rope.push(&text);
+        text.clear();
+        if ends_with_cr {
+            text.push('\r');
+        }
+
+        if is_last {
+            return Ok(ChunkedText {
+                text: rope,
+                line_ending: line_ending.unwrap_or_default(),
+                encoding: *encoding,
+                lossy,
+            });
+        }
+    }
+}
+
+/// Returns the number of bytes at the end of `bytes` that begin a UTF-8 character without
+/// completing it.
+fn incomplete_utf8_suffix_len(bytes: &[u8]) -> usize {
+    for (ix, byte) in bytes.iter().rev().copied().take(3).enumerate() {
+        // Skip over continuation bytes to find the first byte of the last character.
+        if byte & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let char_len = match byte {
+            0xF0..=0xFF => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+        return if char_len > ix + 1 { ix + 1 } else { 0 };
+    }
+    0
+}
+
 impl Deref for Worktree {
     type Target = Snapshot;
 
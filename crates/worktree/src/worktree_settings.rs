@@ -12,6 +12,9 @@ pub struct WorktreeSettings {
     pub file_scan_inclusions: PathMatcher,
     pub file_scan_exclusions: PathMatcher,
     pub private_files: PathMatcher,
+    /// The size in bytes above which files are opened in large-file mode, or `None` when
+    /// large-file mode is turned off.
+    pub large_file_threshold: Option<u64>,
 }
 
 impl WorktreeSettings {
@@ -29,6 +32,11 @@ impl WorktreeSettings {
         path.ancestors()
             .any(|ancestor| self.file_scan_inclusions.is_match(&ancestor))
     }
+
+    pub fn is_large_file(&self, size: u64) -> bool {
+        self.large_file_threshold
+            .is_some_and(|threshold| size > threshold)
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -62,6 +70,13 @@ pub struct WorktreeSettingsContent {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Open files larger than this many megabytes in large-file mode: read-only, and without
+    /// syntax highlighting, language servers or inlay hints. The whole file is still loaded into
+    /// memory. Set to `0` to open every file normally.
+    ///
+    /// Default: 50
+    pub large_file_threshold_mb: Option<u64>,
 }
 
 impl Settings for WorktreeSettings {
@@ -94,6 +109,10 @@ impl Settings for WorktreeSettings {
                 &parsed_file_scan_inclusions,
                 "file_scan_inclusions",
             )?,
+            large_file_threshold: result
+                .large_file_threshold_mb
+                .filter(|&threshold| threshold > 0)
+                .map(|threshold| threshold * 1024 * 1024),
         })
     }
 }
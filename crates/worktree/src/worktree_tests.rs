@@ -1,6 +1,6 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot,
-    WorkDirectory, Worktree, WorktreeModelHandle,
+    read_in_chunks, worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange,
+    Snapshot, WorkDirectory, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
@@ -26,6 +26,7 @@ use std::{
     sync::Arc,
     time::Duration,
 };
+use text::{Encoding, LineEnding};
 use util::{test::TempTree, ResultExt};
 
 #[gpui::test]
@@ -843,6 +844,54 @@ async fn test_update_gitignore(cx: &mut TestAppContext) {
     });
 }
 
+#[test]
+fn test_read_in_chunks() {
+    let text = "one\r\ntwo 🍐\r\nthree ✅\r\n".repeat(10);
+    // Chunks of every size split both characters and line endings.
+    for chunk_size in 1..8 {
+        let chunked = read_in_chunks(text.as_bytes(), chunk_size).unwrap();
+        assert_eq!(chunked.text.to_string(), text.replace("\r\n", "\n"));
+        assert_eq!(chunked.line_ending, LineEnding::Windows);
+        assert_eq!(chunked.encoding, Encoding::default());
+        assert!(!chunked.lossy);
+    }
+
+    let utf16 = Encoding::for_label("utf-16le").unwrap();
+    let bytes = utf16.encode(&text).unwrap();
+    for chunk_size in 1..8 {
+        let chunked = read_in_chunks(bytes.as_ref(), chunk_size).unwrap();
+        assert_eq!(chunked.text.to_string(), text.replace("\r\n", "\n"));
+        assert_eq!(chunked.line_ending, LineEnding::Windows);
+        assert_eq!(chunked.encoding, utf16);
+    }
+
+    let chunked = read_in_chunks(b"a\xFFb\nc".as_slice(), 2).unwrap();
+    assert_eq!(chunked.text.to_string(), "a\u{FF}b\nc");
+    assert_eq!(chunked.line_ending, LineEnding::Unix);
+    assert_eq!(chunked.encoding.name(), "windows-1252");
+    assert!(!chunked.lossy);
+}
+
+#[gpui::test]
+fn test_large_file_threshold(cx: &mut TestAppContext) {
+    init_test(cx);
+    let is_large_file = |size_mb: u64, cx: &mut TestAppContext| {
+        cx.read(|cx| WorktreeSettings::get_global(cx).is_large_file(size_mb * 1024 * 1024))
+    };
+    assert!(!is_large_file(50, cx));
+    assert!(is_large_file(51, cx));
+
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(r#"{"large_file_threshold_mb": 0}"#, cx)
+                .unwrap();
+        });
+    });
+    assert!(!is_large_file(51, cx));
+    assert!(!is_large_file(u64::MAX / (1024 * 1024), cx));
+}
+
 #[gpui::test]
 async fn test_write_file(cx: &mut TestAppContext) {
     init_test(cx);
@@ -876,6 +925,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -886,6 +936,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -1975,8 +2026,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_spawn(async move {
                     task.await?;
                     Ok(())
@@ -480,17 +480,24 @@ fn main() {
 
         app_state.languages.set_theme(cx.theme().clone());
         editor::init(cx);
+        hex_viewer::init(cx);
         image_viewer::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
+        diff_view::init(cx);
 
         audio::init(Assets, cx);
         workspace::init(app_state.clone(), cx);
 
         go_to_line::init(cx);
         file_finder::init(cx);
+        file_history::init(cx);
+        keyboard_macros::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        bookmarks::init(cx);
+        call_hierarchy::init(cx);
+        type_hierarchy::init(cx);
         project_symbols::init(cx);
         project_panel::init(cx);
         git_ui::git_panel::init(cx);
@@ -504,6 +511,7 @@ fn main() {
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);